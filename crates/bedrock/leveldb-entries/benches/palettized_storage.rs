@@ -0,0 +1,230 @@
+//! Compares the specialized packing and unpacking of `PalettizedSubchunk` indices against
+//! the previous implementation, which handled one index at a time for every bits-per-index,
+//! and which built palettes by inserting each value into a `BTreeSet`.
+//!
+//! Run with `cargo bench --package prismarine-anchor-leveldb-entries --bench palettized_storage`.
+
+use std::array;
+use std::collections::BTreeSet;
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use prismarine_anchor_leveldb_entries::entries::helpers::palettized_storage::{
+    PaletteBitsPerIndex, PalettizedSubchunk,
+};
+
+
+const ALL_BITS_PER_INDEX: [u8; 8] = [1, 2, 3, 4, 5, 6, 8, 16];
+
+/// A subchunk whose palette has exactly `2^bits_per_index` values (at most 4096), which are
+/// scattered pseudo-randomly, so that `bits_per_index` bits are used per index.
+fn sample_subchunk(bits_per_index: u8) -> PalettizedSubchunk<u32> {
+    let palette_len = 1_u32 << bits_per_index.min(12);
+
+    let mut state = 0x2545_F491_u32;
+    let values: [u32; 4096] = array::from_fn(|i| {
+        // Make sure that every palette value is used at least once.
+        if let Ok(i) = u32::try_from(i) {
+            if i < palette_len {
+                return i;
+            }
+        }
+        // A xorshift generator
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state % palette_len
+    });
+
+    let subchunk = PalettizedSubchunk::new_unpacked_flattened_copy(values);
+    assert_eq!(u8::from(subchunk.packed().0), bits_per_index, "the palette has 2^bits values");
+    subchunk
+}
+
+fn unpack(c: &mut Criterion) {
+    let mut group = c.benchmark_group("unpacked_indices");
+    for bits in ALL_BITS_PER_INDEX {
+        let subchunk = sample_subchunk(bits);
+
+        group.bench_with_input(BenchmarkId::new("previous", bits), &subchunk, |b, subchunk| {
+            b.iter(|| previous::unpacked_indices(black_box(subchunk)));
+        });
+        group.bench_with_input(BenchmarkId::new("specialized", bits), &subchunk, |b, subchunk| {
+            b.iter(|| black_box(subchunk).unpacked_indices());
+        });
+        group.bench_with_input(BenchmarkId::new("iterator", bits), &subchunk, |b, subchunk| {
+            b.iter(|| {
+                black_box(subchunk)
+                    .palette_indices()
+                    .fold(0_u32, |sum, index| sum.wrapping_add(u32::from(index)))
+            });
+        });
+    }
+    group.finish();
+}
+
+fn unpack_values(c: &mut Criterion) {
+    let mut group = c.benchmark_group("unpacked_flattened");
+    for bits in ALL_BITS_PER_INDEX {
+        let subchunk = sample_subchunk(bits);
+
+        group.bench_with_input(BenchmarkId::new("previous", bits), &subchunk, |b, subchunk| {
+            b.iter(|| {
+                let (_, _, palette) = subchunk.packed();
+                previous::unpacked_indices(black_box(subchunk))
+                    .map(|index| palette[usize::from(index)])
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("specialized", bits), &subchunk, |b, subchunk| {
+            b.iter(|| black_box(subchunk).unpacked_flattened());
+        });
+    }
+    group.finish();
+}
+
+fn check(c: &mut Criterion) {
+    let mut group = c.benchmark_group("new_packed_checked");
+    for bits in ALL_BITS_PER_INDEX {
+        let (bits_per_index, packed_indices, palette) = sample_subchunk(bits).into_packed();
+
+        group.bench_function(BenchmarkId::new("previous", bits), |b| {
+            // Clone the data like `new_packed_checked` requires, for a fair comparison.
+            b.iter(|| {
+                let packed_indices = packed_indices.clone();
+                let palette = palette.clone();
+                previous::check_indices(black_box(bits_per_index), &packed_indices, palette.len())
+            });
+        });
+        group.bench_function(BenchmarkId::new("specialized", bits), |b| {
+            b.iter(|| {
+                PalettizedSubchunk::new_packed_checked(
+                    black_box(bits_per_index),
+                    packed_indices.clone(),
+                    palette.clone(),
+                )
+            });
+        });
+    }
+    group.finish();
+}
+
+fn pack(c: &mut Criterion) {
+    let mut group = c.benchmark_group("new_unpacked_flattened_copy");
+    for bits in ALL_BITS_PER_INDEX {
+        let values = sample_subchunk(bits).unpacked_flattened();
+
+        group.bench_with_input(BenchmarkId::new("previous", bits), &values, |b, values| {
+            b.iter(|| previous::new_unpacked_flattened_copy(black_box(values)));
+        });
+        group.bench_with_input(BenchmarkId::new("specialized", bits), &values, |b, values| {
+            b.iter(|| PalettizedSubchunk::new_unpacked_flattened_copy(black_box(*values)));
+        });
+    }
+    group.finish();
+}
+
+/// Copies of the previous implementation.
+mod previous {
+    use super::{array, BTreeSet, PaletteBitsPerIndex, PalettizedSubchunk};
+
+
+    pub(super) fn unpacked_indices(subchunk: &PalettizedSubchunk<u32>) -> [u16; 4096] {
+        let (bits_per_index, packed_indices, _) = subchunk.packed();
+        let index_mask = (1_u32 << u8::from(bits_per_index)) - 1;
+
+        let mut packed_ids = packed_indices.iter();
+        let mut u32_block = 0;
+        let mut num_indices_in_block = 0;
+
+        array::from_fn(|_| {
+            if num_indices_in_block == 0 {
+                u32_block = *packed_ids.next().expect("there are enough u32s for 4096 indices");
+                num_indices_in_block = bits_per_index.indices_per_u32();
+            }
+
+            let index = u32_block & index_mask;
+            u32_block >>= u8::from(bits_per_index);
+            num_indices_in_block -= 1;
+
+            index as u16
+        })
+    }
+
+    pub(super) fn pack_indices(
+        bits_per_index: PaletteBitsPerIndex,
+        indices:        &[u16; 4096],
+    ) -> Vec<u32> {
+        let bits = u8::from(bits_per_index);
+        let index_mask = (1_u32 << bits) - 1;
+
+        indices
+            .chunks(usize::from(bits_per_index.indices_per_u32()))
+            .map(|block_indices| {
+                block_indices
+                    .iter()
+                    .rev()
+                    .fold(0, |u32_block, &index| {
+                        (u32_block << bits) | (u32::from(index) & index_mask)
+                    })
+            })
+            .collect()
+    }
+
+    pub(super) fn check_indices(
+        bits_per_index: PaletteBitsPerIndex,
+        packed_indices: &[u32],
+        palette_len:    usize,
+    ) -> bool {
+        let max_permissible_index = palette_len as u32 - 1;
+        let index_mask = (1_u32 << u8::from(bits_per_index)) - 1;
+
+        let mut packed_index_iter = packed_indices.iter();
+        if let Some(&last_dword) = packed_index_iter.next_back() {
+            let mut last_dword = last_dword;
+            for _ in 0..bits_per_index.indices_in_last_u32() {
+                if last_dword & index_mask > max_permissible_index {
+                    return false;
+                }
+                last_dword >>= u8::from(bits_per_index);
+            }
+        }
+
+        for &dword in packed_index_iter {
+            let mut dword = dword;
+            for _ in 0..bits_per_index.indices_per_u32() {
+                if dword & index_mask > max_permissible_index {
+                    return false;
+                }
+                dword >>= u8::from(bits_per_index);
+            }
+        }
+
+        true
+    }
+
+    pub(super) fn new_unpacked_flattened_copy(
+        unpacked_data: &[u32; 4096],
+    ) -> PalettizedSubchunk<u32> {
+        let mut palette = BTreeSet::new();
+        for &value in unpacked_data {
+            palette.insert(value);
+        }
+        let palette: Vec<u32> = palette.into_iter().collect();
+        let bits_per_index = PaletteBitsPerIndex::new_from_usize(palette.len())
+            .unwrap_or(PaletteBitsPerIndex::One);
+
+        let indices = unpacked_data.map(|value| {
+            palette.binary_search(&value).expect("every value is in the palette") as u16
+        });
+
+        PalettizedSubchunk::new_packed_unchecked(
+            bits_per_index,
+            pack_indices(bits_per_index, &indices),
+            palette,
+        )
+    }
+}
+
+criterion_group!(benches, unpack, unpack_values, check, pack);
+criterion_main!(benches);
@@ -0,0 +1,102 @@
+use crate::{interface::KeyToBytesOptions, key::DBKey};
+use super::DimensionedChunkPos;
+
+
+/// The tag byte which follows a `DimensionedChunkPos` in `SubchunkBlocks` keys, and which is
+/// itself followed by the subchunk index.
+const SUBCHUNK_TAG: u8 = 47;
+
+
+/// A range of raw LevelDB keys, consisting of every key which begins with a certain prefix.
+#[cfg_attr(feature = "derive_standard", derive(PartialEq, Eq, PartialOrd, Ord, Hash))]
+#[derive(Debug, Clone)]
+pub struct KeyPrefixRange {
+    prefix: Vec<u8>,
+}
+
+impl KeyPrefixRange {
+    #[inline]
+    pub fn new(prefix: Vec<u8>) -> Self {
+        Self { prefix }
+    }
+
+    /// The prefix of every key in the range, which is also the first key of the range
+    /// in bytewise order (the order used by Minecraft's LevelDB).
+    #[inline]
+    pub fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    /// The first key after every key in the range, in bytewise order, which is an exclusive
+    /// upper bound of the range. Returns `None` if the range has no upper bound, which occurs
+    /// when the prefix is empty or consists only of `0xFF` bytes.
+    pub fn end(&self) -> Option<Vec<u8>> {
+        let mut end = self.prefix.clone();
+        while let Some(last) = end.pop() {
+            if last != u8::MAX {
+                end.push(last + 1);
+                return Some(end);
+            }
+        }
+        None
+    }
+
+    #[inline]
+    pub fn contains(&self, key: &[u8]) -> bool {
+        key.starts_with(&self.prefix)
+    }
+}
+
+/// The raw LevelDB keys which cover every record of one chunk, including every subchunk.
+#[cfg_attr(feature = "derive_standard", derive(PartialEq, Eq, PartialOrd, Ord, Hash))]
+#[derive(Debug, Clone)]
+pub struct ChunkKeyRanges {
+    /// Every key which begins with the serialized `DimensionedChunkPos` of the chunk.
+    /// This includes the keys of every chunk record except for `ActorDigest`.
+    ///
+    /// If the dimension ID of the chunk is elided, which is usual for the Overworld,
+    /// then this range also includes the keys of the chunks at the same position in every
+    /// other dimension. Use [`is_record_key`] to exclude them.
+    ///
+    /// [`is_record_key`]: ChunkKeyRanges::is_record_key
+    pub records:      KeyPrefixRange,
+    /// The key of the chunk's `ActorDigest` record, which begins with `digp` instead of
+    /// the `DimensionedChunkPos`.
+    pub actor_digest: Vec<u8>,
+}
+
+impl ChunkKeyRanges {
+    /// Get the keys of the chunk at the given position, serialized with the provided options.
+    /// In particular, `opts.write_overworld_id` determines whether the dimension ID of
+    /// an Overworld chunk is written.
+    pub fn new(chunk_pos: DimensionedChunkPos, opts: KeyToBytesOptions) -> Self {
+        Self {
+            records:      KeyPrefixRange::new(chunk_pos.to_bytes(opts.write_overworld_id)),
+            actor_digest: DBKey::ActorDigest(chunk_pos).to_bytes(opts),
+        }
+    }
+
+    /// Whether the key is in `self.records` and has the length of a chunk record's key,
+    /// followed by either a single tag byte or a subchunk tag and index.
+    /// Keys of chunks in other dimensions, which may share the same prefix, are excluded.
+    ///
+    /// Note that the tag byte is not checked to be a recognized tag.
+    pub fn is_record_key(&self, key: &[u8]) -> bool {
+        if !self.records.contains(key) {
+            return false;
+        }
+
+        let prefix_len = self.records.prefix().len();
+        match key.len() - prefix_len {
+            1 => true,
+            2 => key[prefix_len] == SUBCHUNK_TAG,
+            _ => false,
+        }
+    }
+
+    /// Whether the key is the key of any record of this chunk, including `ActorDigest`.
+    #[inline]
+    pub fn contains(&self, key: &[u8]) -> bool {
+        self.is_record_key(key) || key == self.actor_digest.as_slice()
+    }
+}
@@ -1,7 +1,4 @@
-use std::io::Cursor;
-
 use prismarine_anchor_nbt::io::NbtIoError;
-use prismarine_anchor_util::u64_equals_usize;
 
 use crate::interface::{ValueParseOptions, ValueToBytesOptions};
 use super::NamedCompound;
@@ -11,15 +8,24 @@ use super::NamedCompound;
 pub struct ConcatenatedNbtCompounds(pub Vec<NamedCompound>);
 
 impl ConcatenatedNbtCompounds {
+    /// Parses compounds until the input is exhausted.
+    ///
+    /// If `opts.salvage_nbt` is set, a compound which is truncated or corrupted is salvaged
+    /// and becomes the last compound, since the rest of the input cannot be split into compounds.
     pub fn parse(input: &[u8], opts: ValueParseOptions) -> Result<Self, NbtIoError> {
         let mut compounds = Vec::new();
-
-        let input_len = input.len();
-        let mut reader = Cursor::new(input);
-
-        while !u64_equals_usize(reader.position(), input_len) {
-            let nbt = NamedCompound::read(&mut reader, opts)?;
-            compounds.push(nbt);
+        let mut remaining = input;
+
+        while !remaining.is_empty() {
+            let start = remaining;
+            match NamedCompound::read(&mut remaining, opts) {
+                Ok(nbt) => compounds.push(nbt),
+                Err(err) if opts.salvage_nbt => {
+                    compounds.push(NamedCompound::salvage(start, opts, err)?);
+                    break;
+                }
+                Err(err) => return Err(err),
+            }
         }
 
         Ok(Self(compounds))
@@ -7,6 +7,7 @@ use prismarine_anchor_util::declare_and_pub_use;
 declare_and_pub_use! {
     actor_id;
     block_volume;
+    chunk_key_ranges;
     concatenated_nbt_compounds;
     dimensioned_chunk_pos;
     heightmap;
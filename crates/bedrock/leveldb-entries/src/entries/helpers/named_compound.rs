@@ -1,9 +1,15 @@
+use std::borrow::Cow;
 use std::io::{Cursor, Read};
 
 use thiserror::Error;
 
 use prismarine_anchor_nbt::{NbtCompound, IoOptions};
-use prismarine_anchor_nbt::io::{NbtIoError, read_compound, write_compound};
+use prismarine_anchor_nbt::salvage::salvage_compound;
+use prismarine_anchor_nbt::schema::{CompoundSchema, SchemaViolation};
+use prismarine_anchor_nbt::{
+    io::{NbtIoError, read_compound, write_compound},
+    view::{NbtCompoundRef, read_compound_ref},
+};
 use prismarine_anchor_util::u64_equals_usize;
 
 use crate::interface::{DataFidelity, ValueParseOptions, ValueToBytesOptions};
@@ -19,9 +25,17 @@ pub struct NamedCompound {
 }
 
 impl NamedCompound {
+    /// Parses a single compound which should take up the entire value.
+    ///
+    /// If `opts.salvage_nbt` is set, a compound which is truncated or corrupted is salvaged;
+    /// see [`NamedCompound::salvage`].
     pub fn parse(value: &[u8], opts: ValueParseOptions) -> Result<Self, NamedCompoundParseError> {
         let mut reader = Cursor::new(value);
-        let nbt = Self::read(&mut reader, opts)?;
+        let nbt = match Self::read(&mut reader, opts) {
+            Ok(nbt) => nbt,
+            Err(err) if opts.salvage_nbt => return Ok(Self::salvage(value, opts, err)?),
+            Err(err) => return Err(err.into()),
+        };
 
         if u64_equals_usize(reader.position(), value.len()) {
             Ok(nbt)
@@ -36,16 +50,50 @@ impl NamedCompound {
             IoOptions::bedrock_uncompressed(),
         )?;
 
+        Ok(Self::new(nbt, name, opts))
+    }
+
+    /// Recovers every tag of the compound at the start of `value` which could be read before
+    /// reading it failed with `err`. Returns `err` if not even the start of the root compound
+    /// could be read.
+    ///
+    /// Salvaging discards the data which could not be read, so with `DataFidelity::BitPerfect`,
+    /// nothing is salvaged and `err` is returned.
+    pub fn salvage(
+        value: &[u8],
+        opts:  ValueParseOptions,
+        err:   NbtIoError,
+    ) -> Result<Self, NbtIoError> {
+        if matches!(opts.data_fidelity, DataFidelity::BitPerfect) {
+            return Err(err);
+        }
+
+        let salvaged = salvage_compound(value, IoOptions::bedrock_uncompressed());
+        if salvaged.consumed == 0 {
+            return Err(err);
+        }
+
+        if let Some(failure) = &salvaged.failure {
+            log::warn!(
+                "Salvaged NBT data which could not be read at '{}' (offset {}): {}",
+                failure.path, failure.offset, failure.reason,
+            );
+        }
+
+        Ok(Self::new(salvaged.compound, salvaged.root_name, opts))
+    }
+
+    fn new(compound: NbtCompound, name: String, opts: ValueParseOptions) -> Self {
         let root_name = if matches!(opts.data_fidelity, DataFidelity::BitPerfect) {
             name
         } else {
             String::new()
         };
 
-        Ok(Self {
-            compound: nbt,
+        Self {
+            compound,
             root_name,
-        })
+        }
     }
 
     #[inline]
@@ -74,6 +122,54 @@ impl NamedCompound {
         self.extend_serialized(&mut bytes, opts)?;
         Ok(bytes)
     }
+
+    /// Checks whether the compound matches the given schema, returning every violation if not.
+    /// See [`crate::schemas`] for schemas of common data.
+    #[inline]
+    pub fn validate(&self, schema: &CompoundSchema) -> Result<(), Vec<SchemaViolation>> {
+        self.compound.validate(schema)
+    }
+}
+
+/// A borrowed view of a [`NamedCompound`], whose tags are only parsed when accessed.
+#[derive(Debug, Clone)]
+pub struct NamedCompoundRef<'a> {
+    pub compound:  NbtCompoundRef<'a>,
+    /// If using `DataFidelity::Semantic`, the empty string is used in place of the compound's
+    /// root name.
+    pub root_name: Cow<'a, str>,
+}
+
+impl<'a> NamedCompoundRef<'a> {
+    pub fn parse(value: &'a [u8], opts: ValueParseOptions) -> Result<Self, NamedCompoundParseError> {
+        let mut remaining = value;
+        let (compound, name) = read_compound_ref(
+            &mut remaining,
+            IoOptions::bedrock_uncompressed(),
+        )?;
+
+        if compound.raw_bytes()?.len() != remaining.len() {
+            return Err(NamedCompoundParseError::ExcessData);
+        }
+
+        let root_name = if matches!(opts.data_fidelity, DataFidelity::BitPerfect) {
+            name
+        } else {
+            Cow::Borrowed("")
+        };
+
+        Ok(Self {
+            compound,
+            root_name,
+        })
+    }
+
+    pub fn to_owned_compound(&self) -> Result<NamedCompound, NbtIoError> {
+        Ok(NamedCompound {
+            compound:  self.compound.to_compound()?,
+            root_name: self.root_name.clone().into_owned(),
+        })
+    }
 }
 
 #[derive(Error, Debug)]
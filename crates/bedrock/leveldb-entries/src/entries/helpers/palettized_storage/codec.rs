@@ -0,0 +1,184 @@
+//! Packing and unpacking of the palette indices of a subchunk.
+//!
+//! Each `PaletteBitsPerIndex` gets its own copy of the packing and unpacking loops, in which the
+//! number of bits per index and the number of indices per `u32` are constants. This lets the
+//! compiler fully unroll the inner loops and vectorize the outer loops.
+
+use std::iter::FusedIterator;
+use std::slice;
+
+use super::PaletteBitsPerIndex;
+
+
+/// Unpacks the palette indices of a subchunk, in YZX order (Y increments first).
+/// The first index in each `u32` uses its least significant bits.
+///
+/// # Panics
+/// Panics if `packed_indices` is shorter than `bits_per_index.num_u32s_for_4096_indices()`.
+pub(super) fn unpack_indices(
+    bits_per_index: PaletteBitsPerIndex,
+    packed_indices: &[u32],
+) -> [u16; 4096] {
+    let mut indices = [0; 4096];
+    match bits_per_index {
+        PaletteBitsPerIndex::One     => unpack::<1>(packed_indices, &mut indices),
+        PaletteBitsPerIndex::Two     => unpack::<2>(packed_indices, &mut indices),
+        PaletteBitsPerIndex::Three   => unpack::<3>(packed_indices, &mut indices),
+        PaletteBitsPerIndex::Four    => unpack::<4>(packed_indices, &mut indices),
+        PaletteBitsPerIndex::Five    => unpack::<5>(packed_indices, &mut indices),
+        PaletteBitsPerIndex::Six     => unpack::<6>(packed_indices, &mut indices),
+        PaletteBitsPerIndex::Eight   => unpack::<8>(packed_indices, &mut indices),
+        PaletteBitsPerIndex::Sixteen => unpack::<16>(packed_indices, &mut indices),
+    }
+    indices
+}
+
+/// Packs palette indices, given in YZX order (Y increments first), into `u32`s. The first index
+/// in each `u32` uses its least significant bits. Any needed padding bits will be zeroes.
+///
+/// Only the least significant `bits_per_index` bits of each index are used.
+pub(super) fn pack_indices(
+    bits_per_index: PaletteBitsPerIndex,
+    indices:        &[u16; 4096],
+) -> Vec<u32> {
+    match bits_per_index {
+        PaletteBitsPerIndex::One     => pack::<1>(indices),
+        PaletteBitsPerIndex::Two     => pack::<2>(indices),
+        PaletteBitsPerIndex::Three   => pack::<3>(indices),
+        PaletteBitsPerIndex::Four    => pack::<4>(indices),
+        PaletteBitsPerIndex::Five    => pack::<5>(indices),
+        PaletteBitsPerIndex::Six     => pack::<6>(indices),
+        PaletteBitsPerIndex::Eight   => pack::<8>(indices),
+        PaletteBitsPerIndex::Sixteen => pack::<16>(indices),
+    }
+}
+
+#[inline]
+fn unpack<const BITS: usize>(packed_indices: &[u32], indices: &mut [u16; 4096]) {
+    let indices_per_u32 = 32 / BITS;
+    // `2^BITS - 1` has the least-significant `BITS` bits set.
+    let index_mask = (1_u32 << BITS) - 1;
+
+    let num_full_u32s = 4096 / indices_per_u32;
+    let (full_u32s, last_u32) = packed_indices.split_at(num_full_u32s);
+
+    let mut chunks = indices.chunks_exact_mut(indices_per_u32);
+    for (chunk, &u32_block) in (&mut chunks).zip(full_u32s) {
+        for (i, index) in chunk.iter_mut().enumerate() {
+            // BITS is at most 16, so this does not overflow.
+            *index = ((u32_block >> (i * BITS)) & index_mask) as u16;
+        }
+    }
+
+    // Unless `indices_per_u32` divides 4096, the final `u32` is only partially filled.
+    let remainder = chunks.into_remainder();
+    if !remainder.is_empty() {
+        let u32_block = last_u32[0];
+        for (i, index) in remainder.iter_mut().enumerate() {
+            *index = ((u32_block >> (i * BITS)) & index_mask) as u16;
+        }
+    }
+}
+
+#[inline]
+fn pack<const BITS: usize>(indices: &[u16; 4096]) -> Vec<u32> {
+    let indices_per_u32 = 32 / BITS;
+    // `2^BITS - 1` has the least-significant `BITS` bits set.
+    let index_mask = (1_u32 << BITS) - 1;
+
+    let pack_block = |block_indices: &[u16]| {
+        block_indices
+            .iter()
+            .enumerate()
+            .fold(0, |u32_block, (i, &index)| {
+                u32_block | ((u32::from(index) & index_mask) << (i * BITS))
+            })
+    };
+
+    let chunks = indices.chunks_exact(indices_per_u32);
+    let remainder = chunks.remainder();
+
+    let mut packed_indices = Vec::with_capacity(4096_usize.div_ceil(indices_per_u32));
+    packed_indices.extend(chunks.map(pack_block));
+    if !remainder.is_empty() {
+        packed_indices.push(pack_block(remainder));
+    }
+    packed_indices
+}
+
+/// An iterator over the palette indices of a [`PalettizedSubchunk`], in YZX order
+/// (Y increments first), which unpacks them one `u32` at a time.
+///
+/// [`PalettizedSubchunk`]: super::PalettizedSubchunk
+#[derive(Debug, Clone)]
+pub struct PaletteIndices<'a> {
+    packed_indices:  slice::Iter<'a, u32>,
+    bits_per_index:  u8,
+    index_mask:      u32,
+    indices_per_u32: u8,
+    u32_block:       u32,
+    left_in_block:   u8,
+    /// At most 4096.
+    remaining:       u16,
+}
+
+impl<'a> PaletteIndices<'a> {
+    #[inline]
+    pub(super) fn new(bits_per_index: PaletteBitsPerIndex, packed_indices: &'a [u32]) -> Self {
+        let bits = u8::from(bits_per_index);
+        let indices_per_u32 = bits_per_index.indices_per_u32();
+        // If too few `u32`s were provided for all 4096 indices, only yield the indices they hold.
+        let provided = packed_indices.len().saturating_mul(usize::from(indices_per_u32));
+        #[expect(clippy::cast_possible_truncation, reason = "the value is at most 4096")]
+        let remaining = provided.min(4096) as u16;
+        Self {
+            packed_indices:  packed_indices.iter(),
+            bits_per_index:  bits,
+            // `2^bits_per_index - 1` has the least-significant `bits_per_index` bits set.
+            index_mask:      (1_u32 << bits) - 1,
+            indices_per_u32,
+            u32_block:       0,
+            left_in_block:   0,
+            remaining,
+        }
+    }
+}
+
+impl Iterator for PaletteIndices<'_> {
+    type Item = u16;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        if self.left_in_block == 0 {
+            let Some(&u32_block) = self.packed_indices.next() else {
+                // Unreachable, since `remaining` accounts for the number of `u32`s provided.
+                self.remaining = 0;
+                return None;
+            };
+            self.u32_block = u32_block;
+            self.left_in_block = self.indices_per_u32;
+        }
+
+        let index = self.u32_block & self.index_mask;
+        self.u32_block >>= self.bits_per_index;
+        self.left_in_block -= 1;
+        self.remaining -= 1;
+
+        // Note that self.bits_per_index is at most 16, so this does not overflow.
+        Some(index as u16)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = usize::from(self.remaining);
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for PaletteIndices<'_> {}
+
+impl FusedIterator for PaletteIndices<'_> {}
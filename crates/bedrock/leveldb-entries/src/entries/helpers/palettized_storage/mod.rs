@@ -1,10 +1,23 @@
-use std::{array, slice};
+mod codec;
+
+
+use std::{array, mem, slice};
 use std::{collections::BTreeSet, convert::Infallible, error::Error as StdError};
 use std::io::{Error as IoError, Read};
 
 use thiserror::Error;
 use zerocopy::transmute;
 
+use prismarine_anchor_mc_datatypes::BlockPosInSubchunk;
+
+use self::codec::{pack_indices, unpack_indices};
+pub use self::codec::PaletteIndices;
+
+
+/// The maximum length of the palette of a `PalettizedSubchunk`, as there are only 4096
+/// values in a subchunk.
+const MAX_PALETTE_LEN: usize = 4096;
+
 
 // ================================
 //  Structs
@@ -147,6 +160,15 @@ pub fn write_le_u32s(u32s: &[u32], bytes: &mut Vec<u8>) -> Result<(), Infallible
     Ok(())
 }
 
+/// The index of the position in a subchunk's data, in YZX order (Y increments first).
+#[inline]
+fn flattened_index(pos: BlockPosInSubchunk) -> usize {
+    let x = usize::from(pos.x());
+    let z = usize::from(pos.z());
+    let y = usize::from(pos.y());
+    (x << 8) + (z << 4) + y
+}
+
 // ================================
 //  Impls
 // ================================
@@ -217,6 +239,69 @@ impl<T> PalettizedStorage<T> {
         }
     }
 
+    /// Returns the value at the given position, or `None` if this storage is `Empty`.
+    #[inline]
+    pub fn get(&self, pos: BlockPosInSubchunk) -> Option<&T> {
+        match self {
+            Self::Empty                  => None,
+            Self::Uniform(value)         => Some(value),
+            Self::Palettized(palettized) => Some(palettized.get(pos)),
+        }
+    }
+
+    /// Sets the value at the given position. See [`PalettizedSubchunk::set`].
+    ///
+    /// `Empty` and `Uniform` storage is converted into `Palettized` storage if needed. Every
+    /// other position of `Empty` storage is then given the value returned by `empty_value`.
+    pub fn set<F>(&mut self, pos: BlockPosInSubchunk, value: T, empty_value: F)
+    where
+        T: PartialEq,
+        F: FnOnce() -> T,
+    {
+        let other_value = match self {
+            Self::Empty => empty_value(),
+            Self::Uniform(uniform) => {
+                if *uniform == value {
+                    return;
+                }
+                let Self::Uniform(uniform) = mem::replace(self, Self::Empty) else {
+                    unreachable!("`self` was just matched as `Uniform`");
+                };
+                uniform
+            }
+            Self::Palettized(palettized) => {
+                palettized.set(pos, value);
+                return;
+            }
+        };
+
+        // Every index is `0`, the index of `other_value`, except the index at `pos`.
+        let bits_per_index = PaletteBitsPerIndex::One;
+        let mut indices = [0; 4096];
+        indices[flattened_index(pos)] = 1;
+
+        *self = Self::Palettized(PalettizedSubchunk {
+            bits_per_index,
+            packed_indices: pack_indices(bits_per_index, &indices),
+            palette:        vec![other_value, value],
+        });
+    }
+
+    /// Compacts `Palettized` storage with [`PalettizedSubchunk::compact`], and converts it into
+    /// `Uniform` storage if only one value remains.
+    pub fn compact(&mut self) {
+        let Self::Palettized(palettized) = self else {
+            return;
+        };
+
+        palettized.compact();
+        if palettized.palette.len() == 1 {
+            if let Some(value) = palettized.palette.pop() {
+                *self = Self::Uniform(value);
+            }
+        }
+    }
+
     pub fn extend_serialized<E, F>(
         &self,
         bytes:                    &mut Vec<u8>,
@@ -325,13 +410,10 @@ impl<T> PalettizedSubchunk<T> {
     where
         T: Ord + Copy,
     {
-        let mut palette = BTreeSet::new();
-
-        for &value in &unpacked_data {
-            palette.insert(value);
-        }
-
-        let palette: Vec<T> = palette.into_iter().collect();
+        // Sorting a copy of the data is much faster than inserting each value into a BTreeSet.
+        let mut palette = unpacked_data.to_vec();
+        palette.sort_unstable();
+        palette.dedup();
 
         if palette.len() == 1 {
             // We can, as a special case, do this very quickly.
@@ -359,20 +441,9 @@ impl<T> PalettizedSubchunk<T> {
         )]
         let bits_per_index = PaletteBitsPerIndex::new_from_usize(palette.len()).unwrap();
 
-        let num_u32s = bits_per_index.num_u32s_for_4096_indices();
-        // `2^bits_per_index - 1` has the least-significant `bits_per_index` bits set.
-        // This fits in a u32, which is used below.
-        let index_mask = (1_usize << u8::from(bits_per_index)) - 1;
-
-        let mut packed_indices = Vec::with_capacity(num_u32s);
-        let mut u32_block = 0_u32;
-        let mut num_indices_in_block = 0;
-
-        for value in unpacked_data {
-            u32_block <<= u8::from(bits_per_index);
-
-            // This unwrapping does not panic since we added every value
-            // to the BTreeSet which was then converted to a sorted Vec.
+        let indices = unpacked_data.map(|value| {
+            // This unwrapping does not panic since every value is in the
+            // sorted and deduplicated palette.
             // Therefore every attempt to search for a value succeeds.
             #[expect(
                 clippy::unwrap_used,
@@ -380,22 +451,13 @@ impl<T> PalettizedSubchunk<T> {
             )]
             let index = palette.binary_search(&value).unwrap();
 
-            // index_mask fits in a u32, so this doesn't overflow.
-            let index = (index & index_mask) as u32;
-
-            u32_block |= index;
-
-            num_indices_in_block += 1;
-            if num_indices_in_block >= bits_per_index.indices_per_u32() {
-                packed_indices.push(u32_block);
-                u32_block = 0;
-                num_indices_in_block = 0;
-            }
-        }
+            // The palette has length at most 4096, so this doesn't overflow.
+            index as u16
+        });
 
         Self {
             bits_per_index,
-            packed_indices,
+            packed_indices: pack_indices(bits_per_index, &indices),
             palette,
         }
     }
@@ -450,38 +512,19 @@ impl<T> PalettizedSubchunk<T> {
         )]
         let bits_per_index = PaletteBitsPerIndex::new_from_usize(palette.len()).unwrap();
 
-        let num_u32s = bits_per_index.num_u32s_for_4096_indices();
-        // `2^bits_per_index - 1` has the least-significant `bits_per_index` bits set.
-        // This fits in a u32, which is used below.
-        let index_mask = (1_usize << u8::from(bits_per_index)) - 1;
-
-        let mut packed_indices = Vec::with_capacity(num_u32s);
-        let mut u32_block = 0_u32;
-        let mut num_indices_in_block = 0;
-
-        for value in &unpacked_data {
-            u32_block <<= u8::from(bits_per_index);
-
+        let indices = array::from_fn(|i| {
             // This unwrapping does not panic since we added every value
             // to the BTreeSet which was then converted to a sorted Vec.
             #[expect(
                 clippy::unwrap_used,
                 reason = "we inserted everything in `unpacked_data` into `palette`",
             )]
-            let index = palette.binary_search(&value).unwrap();
-
-            // index_mask fits in a u32, so this doesn't overflow.
-            let index = (index & index_mask) as u32;
+            let index = palette.binary_search(&&unpacked_data[i]).unwrap();
 
-            u32_block |= index;
-
-            num_indices_in_block += 1;
-            if num_indices_in_block >= bits_per_index.indices_per_u32() {
-                packed_indices.push(u32_block);
-                u32_block = 0;
-                num_indices_in_block = 0;
-            }
-        }
+            // The palette has length at most 4096, so this doesn't overflow.
+            index as u16
+        });
+        let packed_indices = pack_indices(bits_per_index, &indices);
 
         let mut actual_palette = BTreeSet::new();
         for value in unpacked_data {
@@ -520,50 +563,15 @@ impl<T> PalettizedSubchunk<T> {
             return Err(PalettizedSubchunkCheckError::InvalidPaletteLen(palette_len));
         }
 
-        let max_permissible_index = palette_len - 1;
-        // This unwrap does not panic, by the above checks on palette_len.
-        #[expect(
-            clippy::unwrap_used,
-            reason = "`palette.len() <= 4096 = (1 << 12)`, so it fits in 32 bits",
-        )]
-        let max_permissible_index = u32::try_from(max_permissible_index).unwrap();
-
-        let indices_per_u32 = bits_per_index.indices_per_u32();
-        // let padding_bits = bits_per_index.padding_bits();
-        // `2^bits_per_index - 1` has the least-significant `bits_per_index` bits set.
-        let index_mask = (1_u32 << u8::from(bits_per_index)) - 1;
-
-        let mut packed_index_iter = packed_indices.iter();
-        if let Some(&last_dword) = packed_index_iter.next_back() {
-            let mut last_dword = last_dword;
-
-            for _ in 0..bits_per_index.indices_in_last_u32() {
-                let index = last_dword & index_mask;
-                if index > max_permissible_index {
-                    return Err(PalettizedSubchunkCheckError::IndexTooLarge {
-                        palette_len,
-                        index,
-                    });
-                }
-
-                last_dword >>= u8::from(bits_per_index);
-            }
-        }
-
-        for &dword in packed_index_iter {
-            let mut dword = dword;
-
-            for _ in 0..indices_per_u32 {
-                let index = dword & index_mask;
-                if index > max_permissible_index {
-                    return Err(PalettizedSubchunkCheckError::IndexTooLarge {
-                        palette_len,
-                        index,
-                    });
-                }
-
-                dword >>= u8::from(bits_per_index);
-            }
+        // Unpacking every index at once and then finding the largest is much faster
+        // than checking each index as it is unpacked.
+        let indices = unpack_indices(bits_per_index, &packed_indices);
+        let max_index = indices.iter().copied().max().unwrap_or(0);
+        if usize::from(max_index) >= palette_len {
+            return Err(PalettizedSubchunkCheckError::IndexTooLarge {
+                palette_len,
+                index: u32::from(max_index),
+            });
         }
 
         // All the checks are done.
@@ -636,43 +644,161 @@ impl<T> PalettizedSubchunk<T> {
     where
         T: Clone,
     {
-        // `2^bits_per_index - 1` has the least-significant `bits_per_index` bits set.
-        let index_mask = (1_u32 << u8::from(self.bits_per_index)) - 1;
+        self.unpacked_indices()
+            .map(|index| self.palette[usize::from(index)].clone())
+    }
 
-        let mut packed_ids = self.packed_indices.iter();
-        let mut u32_block = 0;
-        let mut num_indices_in_block = 0;
+    /// Returns the value at the given position.
+    #[inline]
+    pub fn get(&self, pos: BlockPosInSubchunk) -> &T {
+        &self.palette[usize::from(self.palette_index(pos))]
+    }
 
-        array::from_fn(|_| {
-            if num_indices_in_block == 0 {
-                // Since there must be enough blocks for all 4096 indices, this does not panic.
-                #[expect(
-                    clippy::unwrap_used,
-                    reason = "we should have \
-                    `packed_ids.len() == self.bits_per_index.num_u32s_for_4096_indices()`",
-                )]
-                // The extra block is because the compiler
-                // complained about applying an attribute to an expression
-                {
-                    u32_block = *packed_ids.next().unwrap();
-                };
+    /// Returns the index into the palette of the value at the given position.
+    #[inline]
+    pub fn palette_index(&self, pos: BlockPosInSubchunk) -> u16 {
+        self.index_at(flattened_index(pos))
+    }
+
+    /// Sets the value at the given position, in place.
+    ///
+    /// If the value is not already in the palette, it is added to the end of the palette,
+    /// and `bits_per_index` is increased if needed. If the palette is full, unused entries
+    /// are first removed with [`compact`].
+    ///
+    /// Palette entries which are no longer used are not removed; see [`compact`].
+    ///
+    /// [`compact`]: PalettizedSubchunk::compact
+    pub fn set(&mut self, pos: BlockPosInSubchunk, value: T)
+    where
+        T: PartialEq,
+    {
+        let flat_index = flattened_index(pos);
 
-                // Note that this value is not accurate for the final block, but that's fine,
-                // since the final block is stopped by the 4096-index-limit of array::from_fn
-                // (That is to say, the callback won't be called more than the correct
-                // number of times in the final block.)
-                num_indices_in_block = self.bits_per_index.indices_per_u32();
+        if let Some(index) = self.palette.iter().position(|entry| *entry == value) {
+            // The palette has length at most 4096, so this doesn't overflow.
+            self.set_index_at(flat_index, index as u16);
+            return;
+        }
+
+        if self.palette.len() >= MAX_PALETTE_LEN {
+            self.compact();
+        }
+
+        if self.palette.len() >= MAX_PALETTE_LEN {
+            // Every one of the 4096 values is distinct and used exactly once,
+            // so the entry of the overwritten value can be reused.
+            let index = self.index_at(flat_index);
+            self.palette[usize::from(index)] = value;
+            return;
+        }
+
+        self.palette.push(value);
+        let index = self.palette.len() - 1;
+
+        #[expect(
+            clippy::unwrap_used,
+            reason = "`new_from_usize` returns `Some` since 1 < palette.len() <= 4096 == (1 << 12)",
+        )]
+        let needed_bits = PaletteBitsPerIndex::new_from_usize(self.palette.len()).unwrap();
+        if u8::from(needed_bits) > u8::from(self.bits_per_index) {
+            let mut indices = self.unpacked_indices();
+            // The palette has length at most 4096, so this doesn't overflow.
+            indices[flat_index] = index as u16;
+
+            self.bits_per_index = needed_bits;
+            self.packed_indices = pack_indices(needed_bits, &indices);
+        } else {
+            // The palette has length at most 4096, so this doesn't overflow.
+            self.set_index_at(flat_index, index as u16);
+        }
+    }
+
+    /// Removes palette entries which are not used by any position, and decreases
+    /// `bits_per_index` to the smallest value which fits the remaining palette.
+    /// The order of the remaining palette entries is preserved.
+    pub fn compact(&mut self) {
+        let indices = self.unpacked_indices();
+
+        let mut used = vec![false; self.palette.len()];
+        for &index in &indices {
+            used[usize::from(index)] = true;
+        }
+
+        // Maps each old palette index to its new index.
+        let mut new_indices = Vec::with_capacity(used.len());
+        let mut num_used = 0_u16;
+        for &is_used in &used {
+            new_indices.push(num_used);
+            if is_used {
+                num_used += 1;
             }
+        }
 
-            let index = u32_block & index_mask;
-            u32_block >>= u8::from(self.bits_per_index);
-            num_indices_in_block -= 1;
+        let mut used = used.into_iter();
+        self.palette.retain(|_| used.next().unwrap_or(false));
 
-            // Note that self.bits_per_index is at most 16, so this does not overflow.
-            let index = index as u16;
+        let bits_per_index = PaletteBitsPerIndex::new_from_usize(self.palette.len())
+            .unwrap_or(PaletteBitsPerIndex::One);
+        let indices = indices.map(|index| new_indices[usize::from(index)]);
 
-            self.palette[usize::from(index)].clone()
-        })
+        self.bits_per_index = bits_per_index;
+        self.packed_indices = pack_indices(bits_per_index, &indices);
+    }
+
+    /// Compute the palette index of each value in the subchunk.
+    /// The output data is in YZX order (Y increments first).
+    #[inline]
+    pub fn unpacked_indices(&self) -> [u16; 4096] {
+        unpack_indices(self.bits_per_index, &self.packed_indices)
+    }
+
+    /// Returns an iterator over the palette index of each value in the subchunk, in YZX order
+    /// (Y increments first). Unlike [`unpacked_indices`], this does not unpack every index
+    /// at once.
+    ///
+    /// [`unpacked_indices`]: PalettizedSubchunk::unpacked_indices
+    #[inline]
+    pub fn palette_indices(&self) -> PaletteIndices<'_> {
+        PaletteIndices::new(self.bits_per_index, &self.packed_indices)
+    }
+
+    /// Returns an iterator over the value at each position in the subchunk, in YZX order
+    /// (Y increments first).
+    #[inline]
+    pub fn values(&self) -> impl Iterator<Item = &T> + '_ {
+        self.palette_indices().map(|index| &self.palette[usize::from(index)])
+    }
+
+    /// Returns the palette index at the given index in YZX order (Y increments first).
+    #[inline]
+    fn index_at(&self, flat_index: usize) -> u16 {
+        let bits = u8::from(self.bits_per_index);
+        let indices_per_u32 = usize::from(self.bits_per_index.indices_per_u32());
+        // `2^bits_per_index - 1` has the least-significant `bits_per_index` bits set.
+        let index_mask = (1_u32 << bits) - 1;
+
+        let u32_block = self.packed_indices[flat_index / indices_per_u32];
+        let shift = (flat_index % indices_per_u32) * usize::from(bits);
+
+        // Note that self.bits_per_index is at most 16, so this does not overflow.
+        ((u32_block >> shift) & index_mask) as u16
+    }
+
+    /// Overwrites the palette index at the given index in YZX order (Y increments first),
+    /// leaving every other index and any padding bits unchanged.
+    #[inline]
+    fn set_index_at(&mut self, flat_index: usize, index: u16) {
+        let bits = u8::from(self.bits_per_index);
+        let indices_per_u32 = usize::from(self.bits_per_index.indices_per_u32());
+        // `2^bits_per_index - 1` has the least-significant `bits_per_index` bits set.
+        let index_mask = (1_u32 << bits) - 1;
+
+        let u32_block = &mut self.packed_indices[flat_index / indices_per_u32];
+        let shift = (flat_index % indices_per_u32) * usize::from(bits);
+
+        *u32_block &= !(index_mask << shift);
+        *u32_block |= (u32::from(index) & index_mask) << shift;
     }
 
     /// The provided data should be for one subchunk,
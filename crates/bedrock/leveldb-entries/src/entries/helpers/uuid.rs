@@ -1,4 +1,4 @@
-use std::fmt;
+use std::{array, fmt};
 use std::fmt::{Display, Formatter};
 
 use subslice_to_array::SubsliceToArray as _;
@@ -16,23 +16,29 @@ impl Uuid {
     /// Parse a 128-bit UUID in the 8-4-4-4-12 hex digit format,
     /// such as `002494ea-22dc-4fec-b590-4ea523338c20`.
     pub fn parse(uuid: &str) -> Option<Self> {
-        // Based on the slightly-more-complicated UUID implementation in
-        // prismarine-anchor-nbt's lexer
-
-        // Four hyphens, 32 hex digits which are ASCII and are one byte each
-        if uuid.len() != 36 {
+        if uuid.len() != 36 || !uuid.is_ascii() {
             log::info!("A presumed 8-4-4-4-12 hex digit UUID was not the correct length");
             return None;
         }
 
-        let uuid_chars: Vec<char> = uuid.chars().collect();
+        Self::parse_quietly(uuid).inspect_none(|| log::info!("Failed to parse UUID: {uuid}"))
+    }
 
-        // The above check doesn't exclude the chance of multibyte chars
-        if uuid_chars.len() != 36 {
-            log::info!("A presumed 8-4-4-4-12 hex digit UUID was not the correct length");
+    /// Parse a 128-bit UUID in the 8-4-4-4-12 hex digit format, without logging anything
+    /// on failure and without allocating, so that keys can be classified cheaply.
+    pub(crate) fn parse_quietly(uuid: &str) -> Option<Self> {
+        // Based on the slightly-more-complicated UUID implementation in
+        // prismarine-anchor-nbt's lexer
+
+        // Four hyphens, 32 hex digits which are ASCII and are one byte each.
+        // The ASCII check excludes the chance of multibyte chars.
+        if uuid.len() != 36 || !uuid.is_ascii() {
             return None;
         }
 
+        let uuid_bytes = uuid.as_bytes();
+        let uuid_chars: [char; 36] = array::from_fn(|i| char::from(uuid_bytes[i]));
+
         // Split the UUID into its parts
         let first:       [char; 8] = uuid_chars.subslice_to_array::< 0,  8>();
         let second:      [char; 4] = uuid_chars.subslice_to_array::< 9, 13>();
@@ -59,7 +65,6 @@ impl Uuid {
         }
 
         convert(first, second, third, fourth, fifth_start, fifth_end)
-            .inspect_none(|| log::info!("Failed to parse UUID: {uuid}"))
     }
 
     /// Extend the provided bytes with this UUID serialized into a byte string in the
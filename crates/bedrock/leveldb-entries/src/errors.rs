@@ -100,3 +100,14 @@ pub struct EntryToBytesError {
     pub key:         Vec<u8>,
     pub value_error: ValueToBytesError,
 }
+
+/// An error from parsing the text form of a [`DBKey`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum KeyTextParseError {
+    #[error("unrecognized kind of key: `{0}`")]
+    UnknownKind(String),
+    #[error("a `{0}` key had missing, excess, or invalid arguments")]
+    InvalidArguments(&'static str),
+    #[error("a raw key was not a quoted and escaped string or hex digits")]
+    InvalidRawKey,
+}
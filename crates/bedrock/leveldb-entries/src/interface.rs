@@ -184,18 +184,48 @@ impl From<EntryToBytesOptions> for ValueToBytesOptions {
 pub struct EntryParseOptions {
     /// The data fidelity of entry values; does not affect keys.
     pub value_fidelity: DataFidelity,
+    /// Whether NBT values which are truncated or corrupted should be salvaged, keeping every
+    /// tag read before the first error, instead of the entry becoming a `RawValue`.
+    /// See [`prismarine_anchor_nbt::salvage`].
+    ///
+    /// Salvaged entries are lossy; the data which could not be read is discarded. For that
+    /// reason, nothing is salvaged if `value_fidelity` is `DataFidelity::BitPerfect`.
+    pub salvage_nbt:    bool,
+}
+
+impl EntryParseOptions {
+    /// Options with the given value fidelity, which do not salvage NBT values.
+    pub fn new(value_fidelity: DataFidelity) -> Self {
+        Self {
+            value_fidelity,
+            salvage_nbt: false,
+        }
+    }
 }
 
 #[cfg_attr(feature = "derive_standard", derive(PartialEq, Eq, PartialOrd, Ord, Hash))]
 #[derive(Debug, Clone, Copy)]
 pub struct ValueParseOptions {
     pub data_fidelity: DataFidelity,
+    /// See [`EntryParseOptions::salvage_nbt`].
+    pub salvage_nbt:   bool,
+}
+
+impl ValueParseOptions {
+    /// Options with the given data fidelity, which do not salvage NBT values.
+    pub fn new(data_fidelity: DataFidelity) -> Self {
+        Self {
+            data_fidelity,
+            salvage_nbt: false,
+        }
+    }
 }
 
 impl From<EntryParseOptions> for ValueParseOptions {
     fn from(opts: EntryParseOptions) -> Self {
         Self {
             data_fidelity: opts.value_fidelity,
+            salvage_nbt:   opts.salvage_nbt,
         }
     }
 }
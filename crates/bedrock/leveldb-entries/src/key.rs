@@ -3,8 +3,8 @@ use std::str;
 use subslice_to_array::SubsliceToArray as _;
 
 use prismarine_anchor_mc_datatypes::{
-    IdentifierParseOptions, NamedDimension, NamespacedIdentifier,
-    OverworldElision, VanillaDimension,
+    CustomDimensionNumber, IdentifierParseOptions, NamedDimension, NamespacedIdentifier,
+    NumericDimension, OverworldElision, VanillaDimension,
 };
 
 
@@ -199,6 +199,96 @@ pub enum DBKey {
     RawKey(Vec<u8>),
 }
 
+/// The variant of a [`DBKey`], without any of its data.
+///
+/// Returned by [`DBKey::classify`], which determines the variant of a raw key
+/// without allocating.
+#[cfg_attr(feature = "derive_standard", derive(PartialEq, Eq, PartialOrd, Ord, Hash))]
+#[derive(Debug, Clone, Copy)]
+pub enum KeyKind {
+    // ================================
+    //  Chunk-specific data
+    // ================================
+
+    Version,
+    LegacyVersion,
+    ActorDigestVersion,
+    Data3D,
+    Data2D,
+    LegacyData2D,
+    SubchunkBlocks,
+    LegacyTerrain,
+    LegacyExtraBlockData,
+    BlockEntities,
+    Entities,
+    PendingTicks,
+    RandomTicks,
+    BorderBlocks,
+    HardcodedSpawners,
+    AabbVolumes,
+    Checksums,
+    MetaDataHash,
+    GenerationSeed,
+    FinalizedState,
+    BiomeState,
+    ConversionData,
+    CavesAndCliffsBlending,
+    BlendingBiomeHeight,
+    BlendingData,
+    ActorDigest,
+
+    // ================================
+    //  Data not specific to a chunk
+    // ================================
+
+    Actor,
+    LevelChunkMetaDataDictionary,
+    AutonomousEntities,
+    LocalPlayer,
+    Player,
+    LegacyPlayer,
+    PlayerServer,
+    VillageDwellers,
+    VillageInfo,
+    VillagePOI,
+    VillagePlayers,
+    VillageRaid,
+    Map,
+    StructureTemplate,
+    Scoreboard,
+    TickingArea,
+    BiomeData,
+    BiomeIdsTable,
+    MobEvents,
+    Portals,
+    PositionTrackingDB,
+    PositionTrackingLastId,
+    WanderingTraderScheduler,
+    Overworld,
+    Nether,
+    TheEnd,
+    FlatWorldLayers,
+    LevelSpawnWasFixed,
+    MVillages,
+    Villages,
+    Dimension0,
+    Dimension1,
+    Dimension2,
+
+    RawKey,
+}
+
+/// The dimension which a raw key refers to, returned by [`DBKey::parse_dimension`].
+/// An elided Overworld dimension ID or name is returned as the vanilla Overworld.
+#[cfg_attr(feature = "derive_standard", derive(PartialEq, Eq, PartialOrd, Ord, Hash))]
+#[derive(Debug, Clone, Copy)]
+pub enum KeyDimension<'a> {
+    Vanilla(VanillaDimension),
+    CustomNumeric(CustomDimensionNumber),
+    /// The name of a custom dimension, borrowed from the raw key.
+    CustomNamed(&'a str),
+}
+
 impl DBKey {
     pub fn parse_key(raw_key: &[u8]) -> Self {
         Self::parse_recognized_key(raw_key).unwrap_or_else(|| Self::RawKey(raw_key.to_owned()))
@@ -213,111 +303,70 @@ impl DBKey {
         reason = "best to contain where raw keys are handled",
     )]
     pub fn parse_recognized_key(raw_key: &[u8]) -> Option<Self> {
-        // Try some common prefixes first
-        if (raw_key.len() == 12 || raw_key.len() == 16) && raw_key.starts_with(b"digp") {
-            if let Some(dimensioned_pos) = DimensionedChunkPos::parse(&raw_key[4..]) {
-                return Some(Self::ActorDigest(dimensioned_pos));
-            }
-        } else if raw_key.len() == 19 && raw_key.starts_with(b"actorprefix") {
-            let actorid_bytes: [u8; 8] = raw_key.subslice_to_array::<11, 19>();
-
-            return Some(Self::Actor(ActorID::parse(actorid_bytes)));
+        // Most data is chunk data, so we want to match against that before rarer keys.
+        if let Some((kind, dimensioned_pos)) = parse_chunk_key(raw_key) {
+            return Some(match kind {
+                KeyKind::Data3D                 => Self::Data3D                 (dimensioned_pos),
+                KeyKind::Version                => Self::Version                (dimensioned_pos),
+                KeyKind::Data2D                 => Self::Data2D                 (dimensioned_pos),
+                KeyKind::LegacyData2D           => Self::LegacyData2D           (dimensioned_pos),
+                KeyKind::LegacyTerrain          => Self::LegacyTerrain          (dimensioned_pos),
+                KeyKind::BlockEntities          => Self::BlockEntities          (dimensioned_pos),
+                KeyKind::Entities               => Self::Entities               (dimensioned_pos),
+                KeyKind::PendingTicks           => Self::PendingTicks           (dimensioned_pos),
+                KeyKind::LegacyExtraBlockData   => Self::LegacyExtraBlockData   (dimensioned_pos),
+                KeyKind::BiomeState             => Self::BiomeState             (dimensioned_pos),
+                KeyKind::FinalizedState         => Self::FinalizedState         (dimensioned_pos),
+                KeyKind::ConversionData         => Self::ConversionData         (dimensioned_pos),
+                KeyKind::BorderBlocks           => Self::BorderBlocks           (dimensioned_pos),
+                KeyKind::HardcodedSpawners      => Self::HardcodedSpawners      (dimensioned_pos),
+                KeyKind::RandomTicks            => Self::RandomTicks            (dimensioned_pos),
+                KeyKind::Checksums              => Self::Checksums              (dimensioned_pos),
+                KeyKind::GenerationSeed         => Self::GenerationSeed         (dimensioned_pos),
+                KeyKind::CavesAndCliffsBlending => Self::CavesAndCliffsBlending (dimensioned_pos),
+                KeyKind::BlendingBiomeHeight    => Self::BlendingBiomeHeight    (dimensioned_pos),
+                KeyKind::MetaDataHash           => Self::MetaDataHash           (dimensioned_pos),
+                KeyKind::BlendingData           => Self::BlendingData           (dimensioned_pos),
+                KeyKind::ActorDigestVersion     => Self::ActorDigestVersion     (dimensioned_pos),
+                KeyKind::LegacyVersion          => Self::LegacyVersion          (dimensioned_pos),
+                KeyKind::AabbVolumes            => Self::AabbVolumes            (dimensioned_pos),
+                KeyKind::ActorDigest            => Self::ActorDigest            (dimensioned_pos),
+                KeyKind::SubchunkBlocks         => Self::SubchunkBlocks(
+                    dimensioned_pos,
+                    raw_key[raw_key.len() - 1] as i8,
+                ),
+                _ => unreachable!("parse_chunk_key only returns chunk keys"),
+            });
         }
 
-        // Next, most data is chunk data, so we want to match against that before rarer keys.
-        // AFAIK only "map_######" can collide with these keys (and would end up interpreted as a
-        // chunk in an impossibly far-from-origin position), and a legacy "player_[ID]" plausibly
-        // could have the same issue, too.
-        let not_a_chunk_key = raw_key.starts_with(b"map") || raw_key.starts_with(b"player_");
-
-        if (raw_key.len() == 9 || raw_key.len() == 13) && !not_a_chunk_key {
-            // b'd' (Overworld), b'a' (BiomeData), b's' (mobevents / mVillages),
-            // b'r' (~local_player), and b'e' (BiomeIdsTable)
-            // should never be allowed as tags here, to avoid a collision. We don't check for
-            // them above, since they're a colder path, and we don't strictly need to.
-            // Note that:
-            //    b'a' == 97
-            //    b'd' == 100
-            //    b'e' == 101
-            //    b'r' == 114
-            //    b's' == 115
-            let tag = raw_key[raw_key.len() - 1];
-            if ((43 <= tag && tag <= 65) && tag != 47) || tag == 118 || tag == 119 {
-                if let Some(dimensioned_pos) =
-                    DimensionedChunkPos::parse(&raw_key[..raw_key.len() - 1])
-                {
-                    // These chunk key numeric values are hardcoded twice in this file,
-                    // and a few are also in prismarine-anchor-leveldb-values/src/checksums.rs
-                    return Some(match tag {
-                        43  => Self::Data3D                 (dimensioned_pos),
-                        44  => Self::Version                (dimensioned_pos),
-                        45  => Self::Data2D                 (dimensioned_pos),
-                        46  => Self::LegacyData2D           (dimensioned_pos),
-                        // 47 is subchunk block data, handled below
-                        48  => Self::LegacyTerrain          (dimensioned_pos),
-                        49  => Self::BlockEntities          (dimensioned_pos),
-                        50  => Self::Entities               (dimensioned_pos),
-                        51  => Self::PendingTicks           (dimensioned_pos),
-                        52  => Self::LegacyExtraBlockData   (dimensioned_pos),
-                        53  => Self::BiomeState             (dimensioned_pos),
-                        54  => Self::FinalizedState         (dimensioned_pos),
-                        55  => Self::ConversionData         (dimensioned_pos),
-                        56  => Self::BorderBlocks           (dimensioned_pos),
-                        57  => Self::HardcodedSpawners      (dimensioned_pos),
-                        58  => Self::RandomTicks            (dimensioned_pos),
-                        59  => Self::Checksums              (dimensioned_pos),
-                        60  => Self::GenerationSeed         (dimensioned_pos),
-                        61  => Self::CavesAndCliffsBlending (dimensioned_pos),
-                        62  => Self::BlendingBiomeHeight    (dimensioned_pos),
-                        63  => Self::MetaDataHash           (dimensioned_pos),
-                        64  => Self::BlendingData           (dimensioned_pos),
-                        65  => Self::ActorDigestVersion     (dimensioned_pos),
-                        118 => Self::LegacyVersion          (dimensioned_pos),
-                        119 => Self::AabbVolumes            (dimensioned_pos),
-                        _ => unreachable!()
-                    });
-                }
-            }
-
-        } else if (raw_key.len() == 10 || raw_key.len() == 14) && !not_a_chunk_key {
-            // Subchunk keys are slightly different from the others
+        if raw_key.len() == 19 && raw_key.starts_with(b"actorprefix") {
+            let actorid_bytes: [u8; 8] = raw_key.subslice_to_array::<11, 19>();
 
-            // Note that 47 is b'/', and that `scoreboard`, `dimension0`, and `dimension1`
-            // would enter this `else if` block before failing this check.
-            if raw_key[raw_key.len() - 2] == 47 {
-                if let Some(dimensioned_pos) =
-                    DimensionedChunkPos::parse(&raw_key[..raw_key.len() - 2])
-                {
-                    return Some(Self::SubchunkBlocks(
-                        dimensioned_pos,
-                        raw_key[raw_key.len() - 1] as i8,
-                    ));
-                }
-            }
+            return Some(Self::Actor(ActorID::parse(actorid_bytes)));
         }
 
-        // NOT else if: some of these keys may be 9, 10, 13, or 14 bytes long.
+        // Some of the remaining keys may be 9, 10, 13, or 14 bytes long.
         if let Ok(key_string) = str::from_utf8(raw_key) {
-            let parts: Vec<&str> = key_string.split('_').collect();
+            let (parts, num_parts) = split_key_parts(key_string);
 
             // The majority of the rest of the keys will likely be villages and maps
 
             // VILLAGE_[DIMENSION]?_[UUID]_[VARIANT]
-            if (parts.len() == 3 || parts.len() == 4)
+            if (num_parts == 3 || num_parts == 4)
                 && parts[0] == "VILLAGE"
                 && ["DWELLERS", "INFO", "PLAYERS", "POI", "RAID"]
-                    .contains(&parts[parts.len() - 1])
+                    .contains(&parts[num_parts - 1])
             {
-                // Note that len is 3 or 4, so this doesn't overflow or panic
-                if let Some(uuid) = Uuid::parse(parts[parts.len() - 2]) {
-                    let dimension = if parts.len() == 4 {
+                // Note that num_parts is 3 or 4, so this doesn't overflow or panic
+                if let Some(uuid) = Uuid::parse(parts[num_parts - 2]) {
+                    let dimension = if num_parts == 4 {
                         // Dimension included
                         Some(NamedDimension::from_bedrock_name(parts[1]))
                     } else {
                         None
                     };
 
-                    return Some(match parts[parts.len() - 1] {
+                    return Some(match parts[num_parts - 1] {
                         "DWELLERS" => Self::VillageDwellers(dimension, uuid),
                         "INFO"     => Self::VillageInfo(dimension, uuid),
                         "PLAYERS"  => Self::VillagePlayers(dimension, uuid),
@@ -327,13 +376,13 @@ impl DBKey {
                     });
                 }
 
-            } else if parts.len() == 2 && parts[0] == "map" {
+            } else if num_parts == 2 && parts[0] == "map" {
                 // Maps
                 if let Ok(map_id) = i64::from_str_radix(parts[1], 10) {
                     return Some(Self::Map(map_id));
                 }
 
-            } else if parts.len() == 2 && parts[0] == "player" {
+            } else if num_parts == 2 && parts[0] == "player" {
                 // A remote player
                 if let Some(uuid) = Uuid::parse(parts[1]) {
                     return Some(Self::Player(uuid));
@@ -342,13 +391,13 @@ impl DBKey {
                     return Some(Self::LegacyPlayer(id));
                 }
 
-            } else if parts.len() == 3 && parts[0] == "player" && parts[1] == "server" {
+            } else if num_parts == 3 && parts[0] == "player" && parts[1] == "server" {
                 // A player, probably the local player?
                 if let Some(uuid) = Uuid::parse(parts[2]) {
                     return Some(Self::PlayerServer(uuid));
                 }
 
-            } else if parts.len() == 2 && parts[0] == "tickingarea" {
+            } else if num_parts == 2 && parts[0] == "tickingarea" {
                 // A ticking area, could be in any dimension.
                 if let Some(uuid) = Uuid::parse(parts[1]) {
                     return Some(Self::TickingArea(uuid));
@@ -416,6 +465,136 @@ impl DBKey {
         None
     }
 
+    /// Determine which variant of `DBKey` the raw key would be parsed as, without allocating
+    /// and without logging anything.
+    ///
+    /// This is useful for filtering keys before parsing them or their values.
+    pub fn classify(raw_key: &[u8]) -> KeyKind {
+        if let Some((kind, _)) = parse_chunk_key(raw_key) {
+            return kind;
+        }
+        if raw_key.len() == 19 && raw_key.starts_with(b"actorprefix") {
+            return KeyKind::Actor;
+        }
+
+        // The remaining keys are all strings
+        str::from_utf8(raw_key).map_or(KeyKind::RawKey, classify_string_key)
+    }
+
+    /// If the raw key is the key of a chunk record, including `SubchunkBlocks` and
+    /// `ActorDigest`, parse the position and dimension of the chunk without allocating.
+    #[inline]
+    pub fn parse_chunk_pos(raw_key: &[u8]) -> Option<DimensionedChunkPos> {
+        parse_chunk_key(raw_key).map(|(_, chunk_pos)| chunk_pos)
+    }
+
+    /// Parse the dimension which the raw key refers to, if any, without allocating.
+    ///
+    /// Chunk records, village records, and the `Overworld`, `Nether`, and `TheEnd` records
+    /// refer to a dimension.
+    pub fn parse_dimension(raw_key: &[u8]) -> Option<KeyDimension<'_>> {
+        if let Some(DimensionedChunkPos(_, dimension)) = Self::parse_chunk_pos(raw_key) {
+            return Some(match dimension {
+                None                                    => {
+                    KeyDimension::Vanilla(VanillaDimension::Overworld)
+                }
+                Some(NumericDimension::Vanilla(vanilla)) => KeyDimension::Vanilla(vanilla),
+                Some(NumericDimension::CustomNumeric(n)) => KeyDimension::CustomNumeric(n),
+            });
+        }
+
+        match Self::classify(raw_key) {
+            KeyKind::VillageDwellers
+            | KeyKind::VillageInfo
+            | KeyKind::VillagePOI
+            | KeyKind::VillagePlayers
+            | KeyKind::VillageRaid => {
+                // VILLAGE_[DIMENSION]?_[UUID]_[VARIANT]
+                let mut parts = str::from_utf8(raw_key).ok()?.split('_').skip(1);
+                let dimension = parts.next()?;
+                // If there are only three parts, the dimension was elided
+                if parts.nth(1).is_none() {
+                    return Some(KeyDimension::Vanilla(VanillaDimension::Overworld));
+                }
+
+                Some(match VanillaDimension::try_from_bedrock_name(dimension) {
+                    Some(vanilla) => KeyDimension::Vanilla(vanilla),
+                    None          => KeyDimension::CustomNamed(dimension),
+                })
+            }
+            KeyKind::Overworld => Some(KeyDimension::Vanilla(VanillaDimension::Overworld)),
+            KeyKind::Nether    => Some(KeyDimension::Vanilla(VanillaDimension::Nether)),
+            KeyKind::TheEnd    => Some(KeyDimension::Vanilla(VanillaDimension::End)),
+            _ => None,
+        }
+    }
+
+    /// Get the variant of this `DBKey`, without any of its data.
+    pub fn kind(&self) -> KeyKind {
+        match self {
+            Self::Version(..)                    => KeyKind::Version,
+            Self::LegacyVersion(..)              => KeyKind::LegacyVersion,
+            Self::ActorDigestVersion(..)         => KeyKind::ActorDigestVersion,
+            Self::Data3D(..)                     => KeyKind::Data3D,
+            Self::Data2D(..)                     => KeyKind::Data2D,
+            Self::LegacyData2D(..)               => KeyKind::LegacyData2D,
+            Self::SubchunkBlocks(..)             => KeyKind::SubchunkBlocks,
+            Self::LegacyTerrain(..)              => KeyKind::LegacyTerrain,
+            Self::LegacyExtraBlockData(..)       => KeyKind::LegacyExtraBlockData,
+            Self::BlockEntities(..)              => KeyKind::BlockEntities,
+            Self::Entities(..)                   => KeyKind::Entities,
+            Self::PendingTicks(..)               => KeyKind::PendingTicks,
+            Self::RandomTicks(..)                => KeyKind::RandomTicks,
+            Self::BorderBlocks(..)               => KeyKind::BorderBlocks,
+            Self::HardcodedSpawners(..)          => KeyKind::HardcodedSpawners,
+            Self::AabbVolumes(..)                => KeyKind::AabbVolumes,
+            Self::Checksums(..)                  => KeyKind::Checksums,
+            Self::MetaDataHash(..)               => KeyKind::MetaDataHash,
+            Self::GenerationSeed(..)             => KeyKind::GenerationSeed,
+            Self::FinalizedState(..)             => KeyKind::FinalizedState,
+            Self::BiomeState(..)                 => KeyKind::BiomeState,
+            Self::ConversionData(..)             => KeyKind::ConversionData,
+            Self::CavesAndCliffsBlending(..)     => KeyKind::CavesAndCliffsBlending,
+            Self::BlendingBiomeHeight(..)        => KeyKind::BlendingBiomeHeight,
+            Self::BlendingData(..)               => KeyKind::BlendingData,
+            Self::ActorDigest(..)                => KeyKind::ActorDigest,
+            Self::Actor(..)                      => KeyKind::Actor,
+            Self::LevelChunkMetaDataDictionary   => KeyKind::LevelChunkMetaDataDictionary,
+            Self::AutonomousEntities             => KeyKind::AutonomousEntities,
+            Self::LocalPlayer                    => KeyKind::LocalPlayer,
+            Self::Player(..)                     => KeyKind::Player,
+            Self::LegacyPlayer(..)               => KeyKind::LegacyPlayer,
+            Self::PlayerServer(..)               => KeyKind::PlayerServer,
+            Self::VillageDwellers(..)            => KeyKind::VillageDwellers,
+            Self::VillageInfo(..)                => KeyKind::VillageInfo,
+            Self::VillagePOI(..)                 => KeyKind::VillagePOI,
+            Self::VillagePlayers(..)             => KeyKind::VillagePlayers,
+            Self::VillageRaid(..)                => KeyKind::VillageRaid,
+            Self::Map(..)                        => KeyKind::Map,
+            Self::StructureTemplate(..)          => KeyKind::StructureTemplate,
+            Self::Scoreboard                     => KeyKind::Scoreboard,
+            Self::TickingArea(..)                => KeyKind::TickingArea,
+            Self::BiomeData                      => KeyKind::BiomeData,
+            Self::BiomeIdsTable                  => KeyKind::BiomeIdsTable,
+            Self::MobEvents                      => KeyKind::MobEvents,
+            Self::Portals                        => KeyKind::Portals,
+            Self::PositionTrackingDB(..)         => KeyKind::PositionTrackingDB,
+            Self::PositionTrackingLastId         => KeyKind::PositionTrackingLastId,
+            Self::WanderingTraderScheduler       => KeyKind::WanderingTraderScheduler,
+            Self::Overworld                      => KeyKind::Overworld,
+            Self::Nether                         => KeyKind::Nether,
+            Self::TheEnd                         => KeyKind::TheEnd,
+            Self::FlatWorldLayers                => KeyKind::FlatWorldLayers,
+            Self::LevelSpawnWasFixed             => KeyKind::LevelSpawnWasFixed,
+            Self::MVillages                      => KeyKind::MVillages,
+            Self::Villages                       => KeyKind::Villages,
+            Self::Dimension0                     => KeyKind::Dimension0,
+            Self::Dimension1                     => KeyKind::Dimension1,
+            Self::Dimension2                     => KeyKind::Dimension2,
+            Self::RawKey(..)                     => KeyKind::RawKey,
+        }
+    }
+
     /// Extend the provided `Vec` with the raw key bytes of a `DBKey`,
     /// using the provided serialization settings.
     #[expect(
@@ -686,3 +865,343 @@ impl From<Vec<u8>> for DBKey {
         Self::parse_key_vec(raw_key)
     }
 }
+
+impl KeyKind {
+    /// Whether keys of this kind are the keys of chunk records, which begin with a
+    /// `DimensionedChunkPos` (or, for `ActorDigest`, with `digp` and a `DimensionedChunkPos`).
+    pub fn is_chunk_key(self) -> bool {
+        matches!(
+            self,
+            Self::Version
+            | Self::LegacyVersion
+            | Self::ActorDigestVersion
+            | Self::Data3D
+            | Self::Data2D
+            | Self::LegacyData2D
+            | Self::SubchunkBlocks
+            | Self::LegacyTerrain
+            | Self::LegacyExtraBlockData
+            | Self::BlockEntities
+            | Self::Entities
+            | Self::PendingTicks
+            | Self::RandomTicks
+            | Self::BorderBlocks
+            | Self::HardcodedSpawners
+            | Self::AabbVolumes
+            | Self::Checksums
+            | Self::MetaDataHash
+            | Self::GenerationSeed
+            | Self::FinalizedState
+            | Self::BiomeState
+            | Self::ConversionData
+            | Self::CavesAndCliffsBlending
+            | Self::BlendingBiomeHeight
+            | Self::BlendingData
+            | Self::ActorDigest,
+        )
+    }
+
+    /// The name of this kind of key in the text form of a [`DBKey`], such as `subchunk` in
+    /// `subchunk(12,-4,nether,y=-2)`.
+    pub fn text_name(self) -> &'static str {
+        match self {
+            Self::Version                      => "version",
+            Self::LegacyVersion                => "legacy_version",
+            Self::ActorDigestVersion           => "actor_digest_version",
+            Self::Data3D                       => "data_3d",
+            Self::Data2D                       => "data_2d",
+            Self::LegacyData2D                 => "legacy_data_2d",
+            Self::SubchunkBlocks               => "subchunk",
+            Self::LegacyTerrain                => "legacy_terrain",
+            Self::LegacyExtraBlockData         => "legacy_extra_block_data",
+            Self::BlockEntities                => "block_entities",
+            Self::Entities                     => "entities",
+            Self::PendingTicks                 => "pending_ticks",
+            Self::RandomTicks                  => "random_ticks",
+            Self::BorderBlocks                 => "border_blocks",
+            Self::HardcodedSpawners            => "hardcoded_spawners",
+            Self::AabbVolumes                  => "aabb_volumes",
+            Self::Checksums                    => "checksums",
+            Self::MetaDataHash                 => "meta_data_hash",
+            Self::GenerationSeed               => "generation_seed",
+            Self::FinalizedState               => "finalized_state",
+            Self::BiomeState                   => "biome_state",
+            Self::ConversionData               => "conversion_data",
+            Self::CavesAndCliffsBlending       => "caves_and_cliffs_blending",
+            Self::BlendingBiomeHeight          => "blending_biome_height",
+            Self::BlendingData                 => "blending_data",
+            Self::ActorDigest                  => "actor_digest",
+            Self::Actor                        => "actor",
+            Self::LevelChunkMetaDataDictionary => "level_chunk_meta_data_dictionary",
+            Self::AutonomousEntities           => "autonomous_entities",
+            Self::LocalPlayer                  => "local_player",
+            Self::Player                       => "player",
+            Self::LegacyPlayer                 => "legacy_player",
+            Self::PlayerServer                 => "player_server",
+            Self::VillageDwellers              => "village_dwellers",
+            Self::VillageInfo                  => "village_info",
+            Self::VillagePOI                   => "village_poi",
+            Self::VillagePlayers               => "village_players",
+            Self::VillageRaid                  => "village_raid",
+            Self::Map                          => "map",
+            Self::StructureTemplate            => "structure_template",
+            Self::Scoreboard                   => "scoreboard",
+            Self::TickingArea                  => "ticking_area",
+            Self::BiomeData                    => "biome_data",
+            Self::BiomeIdsTable                => "biome_ids_table",
+            Self::MobEvents                    => "mob_events",
+            Self::Portals                      => "portals",
+            Self::PositionTrackingDB           => "position_tracking_db",
+            Self::PositionTrackingLastId       => "position_tracking_last_id",
+            Self::WanderingTraderScheduler     => "wandering_trader_scheduler",
+            Self::Overworld                    => "overworld",
+            Self::Nether                       => "nether",
+            Self::TheEnd                       => "the_end",
+            Self::FlatWorldLayers              => "flat_world_layers",
+            Self::LevelSpawnWasFixed           => "level_spawn_was_fixed",
+            Self::MVillages                    => "m_villages",
+            Self::Villages                     => "villages",
+            Self::Dimension0                   => "dimension0",
+            Self::Dimension1                   => "dimension1",
+            Self::Dimension2                   => "dimension2",
+            Self::RawKey                       => "raw",
+        }
+    }
+
+    /// The kind of key with the given name in the text form of a [`DBKey`].
+    /// The inverse of [`KeyKind::text_name`].
+    pub fn from_text_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "version"                          => Self::Version,
+            "legacy_version"                   => Self::LegacyVersion,
+            "actor_digest_version"             => Self::ActorDigestVersion,
+            "data_3d"                          => Self::Data3D,
+            "data_2d"                          => Self::Data2D,
+            "legacy_data_2d"                   => Self::LegacyData2D,
+            "subchunk"                         => Self::SubchunkBlocks,
+            "legacy_terrain"                   => Self::LegacyTerrain,
+            "legacy_extra_block_data"          => Self::LegacyExtraBlockData,
+            "block_entities"                   => Self::BlockEntities,
+            "entities"                         => Self::Entities,
+            "pending_ticks"                    => Self::PendingTicks,
+            "random_ticks"                     => Self::RandomTicks,
+            "border_blocks"                    => Self::BorderBlocks,
+            "hardcoded_spawners"               => Self::HardcodedSpawners,
+            "aabb_volumes"                     => Self::AabbVolumes,
+            "checksums"                        => Self::Checksums,
+            "meta_data_hash"                   => Self::MetaDataHash,
+            "generation_seed"                  => Self::GenerationSeed,
+            "finalized_state"                  => Self::FinalizedState,
+            "biome_state"                      => Self::BiomeState,
+            "conversion_data"                  => Self::ConversionData,
+            "caves_and_cliffs_blending"        => Self::CavesAndCliffsBlending,
+            "blending_biome_height"            => Self::BlendingBiomeHeight,
+            "blending_data"                    => Self::BlendingData,
+            "actor_digest"                     => Self::ActorDigest,
+            "actor"                            => Self::Actor,
+            "level_chunk_meta_data_dictionary" => Self::LevelChunkMetaDataDictionary,
+            "autonomous_entities"              => Self::AutonomousEntities,
+            "local_player"                     => Self::LocalPlayer,
+            "player"                           => Self::Player,
+            "legacy_player"                    => Self::LegacyPlayer,
+            "player_server"                    => Self::PlayerServer,
+            "village_dwellers"                 => Self::VillageDwellers,
+            "village_info"                     => Self::VillageInfo,
+            "village_poi"                      => Self::VillagePOI,
+            "village_players"                  => Self::VillagePlayers,
+            "village_raid"                     => Self::VillageRaid,
+            "map"                              => Self::Map,
+            "structure_template"               => Self::StructureTemplate,
+            "scoreboard"                       => Self::Scoreboard,
+            "ticking_area"                     => Self::TickingArea,
+            "biome_data"                       => Self::BiomeData,
+            "biome_ids_table"                  => Self::BiomeIdsTable,
+            "mob_events"                       => Self::MobEvents,
+            "portals"                          => Self::Portals,
+            "position_tracking_db"             => Self::PositionTrackingDB,
+            "position_tracking_last_id"        => Self::PositionTrackingLastId,
+            "wandering_trader_scheduler"       => Self::WanderingTraderScheduler,
+            "overworld"                        => Self::Overworld,
+            "nether"                           => Self::Nether,
+            "the_end"                          => Self::TheEnd,
+            "flat_world_layers"                => Self::FlatWorldLayers,
+            "level_spawn_was_fixed"            => Self::LevelSpawnWasFixed,
+            "m_villages"                       => Self::MVillages,
+            "villages"                         => Self::Villages,
+            "dimension0"                       => Self::Dimension0,
+            "dimension1"                       => Self::Dimension1,
+            "dimension2"                       => Self::Dimension2,
+            "raw"                              => Self::RawKey,
+            _ => return None,
+        })
+    }
+}
+
+/// Classifies a raw key as a chunk record, including `SubchunkBlocks` and `ActorDigest`,
+/// and parses its position and dimension.
+///
+/// `DBKey::parse_recognized_key` relies on this returning only chunk keys.
+fn parse_chunk_key(raw_key: &[u8]) -> Option<(KeyKind, DimensionedChunkPos)> {
+    if (raw_key.len() == 12 || raw_key.len() == 16) && raw_key.starts_with(b"digp") {
+        return DimensionedChunkPos::parse(&raw_key[4..])
+            .map(|dimensioned_pos| (KeyKind::ActorDigest, dimensioned_pos));
+    }
+
+    // AFAIK only "map_######" can collide with these keys (and would end up interpreted as a
+    // chunk in an impossibly far-from-origin position), and a legacy "player_[ID]" plausibly
+    // could have the same issue, too.
+    let not_a_chunk_key = raw_key.starts_with(b"map") || raw_key.starts_with(b"player_");
+    if not_a_chunk_key {
+        return None;
+    }
+
+    if raw_key.len() == 9 || raw_key.len() == 13 {
+        // b'd' (Overworld), b'a' (BiomeData), b's' (mobevents / mVillages),
+        // b'r' (~local_player), and b'e' (BiomeIdsTable)
+        // should never be allowed as tags here, to avoid a collision.
+        // These chunk key numeric values are hardcoded twice in this file,
+        // and a few are also in prismarine-anchor-leveldb-values/src/checksums.rs
+        let kind = match raw_key[raw_key.len() - 1] {
+            43  => KeyKind::Data3D,
+            44  => KeyKind::Version,
+            45  => KeyKind::Data2D,
+            46  => KeyKind::LegacyData2D,
+            // 47 is subchunk block data, handled below
+            48  => KeyKind::LegacyTerrain,
+            49  => KeyKind::BlockEntities,
+            50  => KeyKind::Entities,
+            51  => KeyKind::PendingTicks,
+            52  => KeyKind::LegacyExtraBlockData,
+            53  => KeyKind::BiomeState,
+            54  => KeyKind::FinalizedState,
+            55  => KeyKind::ConversionData,
+            56  => KeyKind::BorderBlocks,
+            57  => KeyKind::HardcodedSpawners,
+            58  => KeyKind::RandomTicks,
+            59  => KeyKind::Checksums,
+            60  => KeyKind::GenerationSeed,
+            61  => KeyKind::CavesAndCliffsBlending,
+            62  => KeyKind::BlendingBiomeHeight,
+            63  => KeyKind::MetaDataHash,
+            64  => KeyKind::BlendingData,
+            65  => KeyKind::ActorDigestVersion,
+            118 => KeyKind::LegacyVersion,
+            119 => KeyKind::AabbVolumes,
+            _ => return None,
+        };
+        DimensionedChunkPos::parse(&raw_key[..raw_key.len() - 1])
+            .map(|dimensioned_pos| (kind, dimensioned_pos))
+
+    } else if (raw_key.len() == 10 || raw_key.len() == 14) && raw_key[raw_key.len() - 2] == 47 {
+        // Subchunk keys are slightly different from the others. Note that 47 is b'/', and that
+        // `scoreboard`, `dimension0`, and `dimension1` are 10 bytes long but fail this check.
+        DimensionedChunkPos::parse(&raw_key[..raw_key.len() - 2])
+            .map(|dimensioned_pos| (KeyKind::SubchunkBlocks, dimensioned_pos))
+
+    } else {
+        None
+    }
+}
+
+/// Splits a key at underscores, without allocating.
+///
+/// Returns the first four parts and the total number of parts; the keys which are split
+/// by `DBKey::parse_recognized_key` and `classify_string_key` have at most four parts.
+fn split_key_parts(key_string: &str) -> ([&str; 4], usize) {
+    let mut parts = [""; 4];
+    let mut num_parts = 0;
+    for part in key_string.split('_') {
+        if let Some(slot) = parts.get_mut(num_parts) {
+            *slot = part;
+        }
+        num_parts += 1;
+    }
+    (parts, num_parts)
+}
+
+/// Classifies a key which is not a chunk or actor key, with the same checks as
+/// `DBKey::parse_recognized_key`, but without allocating.
+fn classify_string_key(key_string: &str) -> KeyKind {
+    let (parts, num_parts) = split_key_parts(key_string);
+    let is_uuid = |part: &str| Uuid::parse_quietly(part).is_some();
+
+    if (num_parts == 3 || num_parts == 4) && parts[0] == "VILLAGE" {
+        let kind = match parts[num_parts - 1] {
+            "DWELLERS" => Some(KeyKind::VillageDwellers),
+            "INFO"     => Some(KeyKind::VillageInfo),
+            "PLAYERS"  => Some(KeyKind::VillagePlayers),
+            "POI"      => Some(KeyKind::VillagePOI),
+            "RAID"     => Some(KeyKind::VillageRaid),
+            _          => None,
+        };
+        if let Some(kind) = kind {
+            if is_uuid(parts[num_parts - 2]) {
+                return kind;
+            }
+        }
+
+    } else if num_parts == 2 && parts[0] == "map" {
+        if parts[1].parse::<i64>().is_ok() {
+            return KeyKind::Map;
+        }
+
+    } else if num_parts == 2 && parts[0] == "player" {
+        if is_uuid(parts[1]) {
+            return KeyKind::Player;
+        } else if parts[1].parse::<u64>().is_ok() {
+            return KeyKind::LegacyPlayer;
+        }
+
+    } else if num_parts == 3 && parts[0] == "player" && parts[1] == "server" {
+        if is_uuid(parts[2]) {
+            return KeyKind::PlayerServer;
+        }
+
+    } else if num_parts == 2 && parts[0] == "tickingarea" && is_uuid(parts[1]) {
+        return KeyKind::TickingArea;
+    }
+
+    if let Some(structure_identifier) = key_string.strip_prefix("structuretemplate_") {
+        // The same checks as `NamespacedIdentifier::parse_string` with Bedrock's constraints
+        if let Some((namespace, path)) = structure_identifier.split_once(':') {
+            if !namespace.contains('/') && !path.contains(':') {
+                return KeyKind::StructureTemplate;
+            }
+        }
+    }
+
+    if let Some(num) = key_string.strip_prefix("PosTrackDB-0x") {
+        if u32::from_str_radix(num, 16).is_ok() {
+            return KeyKind::PositionTrackingDB;
+        }
+    }
+
+    if let Some(dimension) = VanillaDimension::try_from_bedrock_name(key_string) {
+        return match dimension {
+            VanillaDimension::Overworld => KeyKind::Overworld,
+            VanillaDimension::Nether    => KeyKind::Nether,
+            VanillaDimension::End       => KeyKind::TheEnd,
+        };
+    }
+
+    match key_string {
+        "~local_player"                 => KeyKind::LocalPlayer,
+        "LevelChunkMetaDataDictionary"  => KeyKind::LevelChunkMetaDataDictionary,
+        "AutonomousEntities"            => KeyKind::AutonomousEntities,
+        "scoreboard"                    => KeyKind::Scoreboard,
+        "BiomeData"                     => KeyKind::BiomeData,
+        "BiomeIdsTable"                 => KeyKind::BiomeIdsTable,
+        "mobevents"                     => KeyKind::MobEvents,
+        "portals"                       => KeyKind::Portals,
+        "PositionTrackDB-LastId"        => KeyKind::PositionTrackingLastId,
+        "schedulerWT"                   => KeyKind::WanderingTraderScheduler,
+        "game_flatworldlayers"          => KeyKind::FlatWorldLayers,
+        "LevelSpawnWasFixed"            => KeyKind::LevelSpawnWasFixed,
+        "mVillages"                     => KeyKind::MVillages,
+        "villages"                      => KeyKind::Villages,
+        "dimension0"                    => KeyKind::Dimension0,
+        "dimension1"                    => KeyKind::Dimension1,
+        "dimension2"                    => KeyKind::Dimension2,
+        _                               => KeyKind::RawKey,
+    }
+}
@@ -0,0 +1,467 @@
+//! A human-readable text form of [`DBKey`]s, for debugging and command-line tools.
+//!
+//! Keys are written as the [`KeyKind::text_name`] of the key, followed by any data of the key
+//! in parentheses:
+//! - Chunk records: `data_3d(12,-4)` or `subchunk(12,-4,nether,y=-2)`. The dimension is omitted
+//!   when it is elided, and is otherwise `overworld`, `nether`, `end`, or a custom numeric ID.
+//! - Village records: `village_info(<uuid>)` or `village_info(nether,<uuid>)`. A custom dimension
+//!   name is a quoted string.
+//! - Actors: `actor(0x1234)`, with the 64-bit ID in hexadecimal.
+//! - Players and ticking areas: `player(<uuid>)`, `legacy_player(123)`, `ticking_area(<uuid>)`.
+//! - Others: `map(-5)`, `position_tracking_db(0x1f)`, `structure_template("mystructure:house")`.
+//! - Keys without data, such as `scoreboard` or `local_player`, have no parentheses.
+//! - Raw keys: `raw:"text"`, where `"` and `\` are escaped with a backslash and other bytes
+//!   may be escaped as `\xNN`, or `raw:0a0b`, with two hex digits per byte.
+//!
+//! Parsing the text form of a key results in a key with the same bytes under any
+//! `KeyToBytesOptions`.
+
+use std::{fmt, str};
+use std::fmt::{Display, Formatter, Write as _};
+use std::str::FromStr;
+
+use prismarine_anchor_mc_datatypes::{
+    ChunkPosition, CustomDimensionName, CustomDimensionNumber, IdentifierParseOptions,
+    NamedDimension, NamespacedIdentifier, NumericDimension, VanillaDimension,
+};
+
+use crate::{DBKey, KeyKind, KeyTextParseError};
+use crate::entries::helpers::{ActorID, DimensionedChunkPos, Uuid};
+
+
+impl Display for DBKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = self.kind().text_name();
+
+        if let Some(dimensioned_pos) = chunk_pos(self) {
+            write!(f, "{name}(")?;
+            write_chunk_pos(f, dimensioned_pos)?;
+            if let Self::SubchunkBlocks(_, y_index) = self {
+                write!(f, ",y={y_index}")?;
+            }
+            return f.write_char(')');
+        }
+
+        match self {
+            Self::Actor(actor_id) => {
+                let actor_id = (u64::from(actor_id.upper) << 32) | u64::from(actor_id.lower);
+                write!(f, "{name}({actor_id:#x})")
+            }
+            Self::Player(uuid) | Self::PlayerServer(uuid) | Self::TickingArea(uuid) => {
+                write!(f, "{name}({uuid})")
+            }
+            Self::LegacyPlayer(id) => write!(f, "{name}({id})"),
+            Self::VillageDwellers(dimension, uuid)
+            | Self::VillageInfo(dimension, uuid)
+            | Self::VillagePOI(dimension, uuid)
+            | Self::VillagePlayers(dimension, uuid)
+            | Self::VillageRaid(dimension, uuid) => {
+                write!(f, "{name}(")?;
+                if let Some(dimension) = dimension {
+                    write_named_dimension(f, dimension)?;
+                    f.write_char(',')?;
+                }
+                write!(f, "{uuid})")
+            }
+            Self::Map(map_id) => write!(f, "{name}({map_id})"),
+            Self::StructureTemplate(identifier) => {
+                write!(f, "{name}(")?;
+                write_quoted(f, identifier.to_string().as_bytes())?;
+                f.write_char(')')
+            }
+            Self::PositionTrackingDB(id) => write!(f, "{name}({id:#x})"),
+            Self::RawKey(raw_key) => {
+                write!(f, "{name}:")?;
+                // Prefer the readable form, unless the key looks like binary data
+                let readable = str::from_utf8(raw_key)
+                    .is_ok_and(|raw_key| !raw_key.chars().any(char::is_control));
+                if readable {
+                    write_quoted(f, raw_key)
+                } else {
+                    raw_key.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+                }
+            }
+            // Every other key has no data
+            _ => f.write_str(name),
+        }
+    }
+}
+
+impl FromStr for DBKey {
+    type Err = KeyTextParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if let Some(raw_key) = text.strip_prefix("raw:") {
+            return parse_raw_key(raw_key)
+                .map(Self::RawKey)
+                .ok_or(KeyTextParseError::InvalidRawKey);
+        }
+
+        let (name, args) = match text.split_once('(') {
+            Some((name, args)) => (name, Some(args)),
+            None               => (text, None),
+        };
+        let kind = KeyKind::from_text_name(name)
+            .filter(|kind| !matches!(kind, KeyKind::RawKey))
+            .ok_or_else(|| KeyTextParseError::UnknownKind(name.to_owned()))?;
+
+        let invalid = || KeyTextParseError::InvalidArguments(kind.text_name());
+        let args = match args {
+            // Keys without data have no parentheses, so empty parentheses are invalid
+            Some(args) => match args.strip_suffix(')') {
+                Some("") | None => return Err(invalid()),
+                Some(args)      => split_args(args),
+            },
+            None => Vec::new(),
+        };
+
+        parse_key(kind, &args).ok_or_else(invalid)
+    }
+}
+
+/// Parse a key of the given kind from its arguments, which must be exactly the arguments
+/// which are written for that kind of key.
+#[expect(clippy::too_many_lines, reason = "it's a giant match over every kind of key")]
+fn parse_key(kind: KeyKind, args: &[&str]) -> Option<DBKey> {
+    if kind.is_chunk_key() {
+        let (args, y_index) = match (kind, args) {
+            (KeyKind::SubchunkBlocks, [args @ .., y_index]) => {
+                (args, Some(y_index.strip_prefix("y=")?.parse::<i8>().ok()?))
+            }
+            (KeyKind::SubchunkBlocks, []) => return None,
+            _ => (args, None),
+        };
+        let dimensioned_pos = parse_chunk_pos(args)?;
+
+        return Some(match kind {
+            KeyKind::Version                => DBKey::Version(dimensioned_pos),
+            KeyKind::LegacyVersion          => DBKey::LegacyVersion(dimensioned_pos),
+            KeyKind::ActorDigestVersion     => DBKey::ActorDigestVersion(dimensioned_pos),
+            KeyKind::Data3D                 => DBKey::Data3D(dimensioned_pos),
+            KeyKind::Data2D                 => DBKey::Data2D(dimensioned_pos),
+            KeyKind::LegacyData2D           => DBKey::LegacyData2D(dimensioned_pos),
+            KeyKind::SubchunkBlocks         => DBKey::SubchunkBlocks(dimensioned_pos, y_index?),
+            KeyKind::LegacyTerrain          => DBKey::LegacyTerrain(dimensioned_pos),
+            KeyKind::LegacyExtraBlockData   => DBKey::LegacyExtraBlockData(dimensioned_pos),
+            KeyKind::BlockEntities          => DBKey::BlockEntities(dimensioned_pos),
+            KeyKind::Entities               => DBKey::Entities(dimensioned_pos),
+            KeyKind::PendingTicks           => DBKey::PendingTicks(dimensioned_pos),
+            KeyKind::RandomTicks            => DBKey::RandomTicks(dimensioned_pos),
+            KeyKind::BorderBlocks           => DBKey::BorderBlocks(dimensioned_pos),
+            KeyKind::HardcodedSpawners      => DBKey::HardcodedSpawners(dimensioned_pos),
+            KeyKind::AabbVolumes            => DBKey::AabbVolumes(dimensioned_pos),
+            KeyKind::Checksums              => DBKey::Checksums(dimensioned_pos),
+            KeyKind::MetaDataHash           => DBKey::MetaDataHash(dimensioned_pos),
+            KeyKind::GenerationSeed         => DBKey::GenerationSeed(dimensioned_pos),
+            KeyKind::FinalizedState         => DBKey::FinalizedState(dimensioned_pos),
+            KeyKind::BiomeState             => DBKey::BiomeState(dimensioned_pos),
+            KeyKind::ConversionData         => DBKey::ConversionData(dimensioned_pos),
+            KeyKind::CavesAndCliffsBlending => DBKey::CavesAndCliffsBlending(dimensioned_pos),
+            KeyKind::BlendingBiomeHeight    => DBKey::BlendingBiomeHeight(dimensioned_pos),
+            KeyKind::BlendingData           => DBKey::BlendingData(dimensioned_pos),
+            KeyKind::ActorDigest            => DBKey::ActorDigest(dimensioned_pos),
+            _ => return None,
+        });
+    }
+
+    let village = |args: &[&str]| -> Option<(Option<NamedDimension>, Uuid)> {
+        match args {
+            [uuid]            => Some((None, Uuid::parse_quietly(uuid)?)),
+            [dimension, uuid] => {
+                Some((Some(parse_named_dimension(dimension)?), Uuid::parse_quietly(uuid)?))
+            }
+            _ => None,
+        }
+    };
+
+    let key = match (kind, args) {
+        (KeyKind::Actor, [actor_id]) => {
+            let actor_id = u64::from_str_radix(actor_id.strip_prefix("0x")?, 16).ok()?;
+            DBKey::Actor(ActorID {
+                upper: (actor_id >> 32) as u32,
+                lower: actor_id as u32,
+            })
+        }
+        (KeyKind::Player,       [uuid]) => DBKey::Player(Uuid::parse_quietly(uuid)?),
+        (KeyKind::LegacyPlayer, [id])   => DBKey::LegacyPlayer(id.parse().ok()?),
+        (KeyKind::PlayerServer, [uuid]) => DBKey::PlayerServer(Uuid::parse_quietly(uuid)?),
+        (KeyKind::TickingArea,  [uuid]) => DBKey::TickingArea(Uuid::parse_quietly(uuid)?),
+        (KeyKind::VillageDwellers, args) => {
+            let (dimension, uuid) = village(args)?;
+            DBKey::VillageDwellers(dimension, uuid)
+        }
+        (KeyKind::VillageInfo, args) => {
+            let (dimension, uuid) = village(args)?;
+            DBKey::VillageInfo(dimension, uuid)
+        }
+        (KeyKind::VillagePOI, args) => {
+            let (dimension, uuid) = village(args)?;
+            DBKey::VillagePOI(dimension, uuid)
+        }
+        (KeyKind::VillagePlayers, args) => {
+            let (dimension, uuid) = village(args)?;
+            DBKey::VillagePlayers(dimension, uuid)
+        }
+        (KeyKind::VillageRaid, args) => {
+            let (dimension, uuid) = village(args)?;
+            DBKey::VillageRaid(dimension, uuid)
+        }
+        (KeyKind::Map, [map_id]) => DBKey::Map(map_id.parse().ok()?),
+        (KeyKind::StructureTemplate, [identifier]) => {
+            let identifier = String::from_utf8(parse_quoted(identifier)?).ok()?;
+            // The same checks as when parsing the key from bytes
+            let identifier = NamespacedIdentifier::parse_string(
+                identifier,
+                IdentifierParseOptions {
+                    default_namespace:          None,
+                    java_character_constraints: false,
+                },
+            ).ok()?;
+            DBKey::StructureTemplate(identifier)
+        }
+        (KeyKind::PositionTrackingDB, [id]) => {
+            DBKey::PositionTrackingDB(u32::from_str_radix(id.strip_prefix("0x")?, 16).ok()?)
+        }
+        (KeyKind::LevelChunkMetaDataDictionary, []) => DBKey::LevelChunkMetaDataDictionary,
+        (KeyKind::AutonomousEntities,           []) => DBKey::AutonomousEntities,
+        (KeyKind::LocalPlayer,                  []) => DBKey::LocalPlayer,
+        (KeyKind::Scoreboard,                   []) => DBKey::Scoreboard,
+        (KeyKind::BiomeData,                    []) => DBKey::BiomeData,
+        (KeyKind::BiomeIdsTable,                []) => DBKey::BiomeIdsTable,
+        (KeyKind::MobEvents,                    []) => DBKey::MobEvents,
+        (KeyKind::Portals,                      []) => DBKey::Portals,
+        (KeyKind::PositionTrackingLastId,       []) => DBKey::PositionTrackingLastId,
+        (KeyKind::WanderingTraderScheduler,     []) => DBKey::WanderingTraderScheduler,
+        (KeyKind::Overworld,                    []) => DBKey::Overworld,
+        (KeyKind::Nether,                       []) => DBKey::Nether,
+        (KeyKind::TheEnd,                       []) => DBKey::TheEnd,
+        (KeyKind::FlatWorldLayers,              []) => DBKey::FlatWorldLayers,
+        (KeyKind::LevelSpawnWasFixed,           []) => DBKey::LevelSpawnWasFixed,
+        (KeyKind::MVillages,                    []) => DBKey::MVillages,
+        (KeyKind::Villages,                     []) => DBKey::Villages,
+        (KeyKind::Dimension0,                   []) => DBKey::Dimension0,
+        (KeyKind::Dimension1,                   []) => DBKey::Dimension1,
+        (KeyKind::Dimension2,                   []) => DBKey::Dimension2,
+        _ => return None,
+    };
+    Some(key)
+}
+
+// ================================
+//  Chunk positions and dimensions
+// ================================
+
+fn chunk_pos(key: &DBKey) -> Option<DimensionedChunkPos> {
+    match key {
+        &DBKey::Version(dimensioned_pos)
+        | &DBKey::LegacyVersion(dimensioned_pos)
+        | &DBKey::ActorDigestVersion(dimensioned_pos)
+        | &DBKey::Data3D(dimensioned_pos)
+        | &DBKey::Data2D(dimensioned_pos)
+        | &DBKey::LegacyData2D(dimensioned_pos)
+        | &DBKey::SubchunkBlocks(dimensioned_pos, _)
+        | &DBKey::LegacyTerrain(dimensioned_pos)
+        | &DBKey::LegacyExtraBlockData(dimensioned_pos)
+        | &DBKey::BlockEntities(dimensioned_pos)
+        | &DBKey::Entities(dimensioned_pos)
+        | &DBKey::PendingTicks(dimensioned_pos)
+        | &DBKey::RandomTicks(dimensioned_pos)
+        | &DBKey::BorderBlocks(dimensioned_pos)
+        | &DBKey::HardcodedSpawners(dimensioned_pos)
+        | &DBKey::AabbVolumes(dimensioned_pos)
+        | &DBKey::Checksums(dimensioned_pos)
+        | &DBKey::MetaDataHash(dimensioned_pos)
+        | &DBKey::GenerationSeed(dimensioned_pos)
+        | &DBKey::FinalizedState(dimensioned_pos)
+        | &DBKey::BiomeState(dimensioned_pos)
+        | &DBKey::ConversionData(dimensioned_pos)
+        | &DBKey::CavesAndCliffsBlending(dimensioned_pos)
+        | &DBKey::BlendingBiomeHeight(dimensioned_pos)
+        | &DBKey::BlendingData(dimensioned_pos)
+        | &DBKey::ActorDigest(dimensioned_pos) => Some(dimensioned_pos),
+        _ => None,
+    }
+}
+
+fn write_chunk_pos(f: &mut Formatter<'_>, dimensioned_pos: DimensionedChunkPos) -> fmt::Result {
+    let DimensionedChunkPos(ChunkPosition { x, z }, dimension) = dimensioned_pos;
+    write!(f, "{x},{z}")?;
+
+    match dimension {
+        None                                      => Ok(()),
+        Some(NumericDimension::Vanilla(vanilla))  => write!(f, ",{}", vanilla_name(vanilla)),
+        Some(NumericDimension::CustomNumeric(id)) => write!(f, ",{}", id.0),
+    }
+}
+
+fn parse_chunk_pos(args: &[&str]) -> Option<DimensionedChunkPos> {
+    let (x, z, dimension) = match *args {
+        [x, z]            => (x, z, None),
+        [x, z, dimension] => (x, z, Some(dimension)),
+        _ => return None,
+    };
+
+    let dimension = match dimension {
+        Some(dimension) => Some(match parse_vanilla_name(dimension) {
+            Some(vanilla) => NumericDimension::Vanilla(vanilla),
+            None          => {
+                NumericDimension::CustomNumeric(CustomDimensionNumber(dimension.parse().ok()?))
+            }
+        }),
+        None => None,
+    };
+
+    Some(DimensionedChunkPos(
+        ChunkPosition {
+            x: x.parse().ok()?,
+            z: z.parse().ok()?,
+        },
+        dimension,
+    ))
+}
+
+fn write_named_dimension(f: &mut Formatter<'_>, dimension: &NamedDimension) -> fmt::Result {
+    match dimension {
+        NamedDimension::Vanilla(vanilla)  => f.write_str(vanilla_name(*vanilla)),
+        NamedDimension::CustomNamed(name) => write_quoted(f, name.0.as_bytes()),
+    }
+}
+
+fn parse_named_dimension(dimension: &str) -> Option<NamedDimension> {
+    if let Some(vanilla) = parse_vanilla_name(dimension) {
+        return Some(NamedDimension::Vanilla(vanilla));
+    }
+
+    let name = String::from_utf8(parse_quoted(dimension)?).ok()?;
+    Some(NamedDimension::CustomNamed(CustomDimensionName(name.into_boxed_str())))
+}
+
+#[inline]
+fn vanilla_name(dimension: VanillaDimension) -> &'static str {
+    match dimension {
+        VanillaDimension::Overworld => "overworld",
+        VanillaDimension::Nether    => "nether",
+        VanillaDimension::End       => "end",
+    }
+}
+
+#[inline]
+fn parse_vanilla_name(name: &str) -> Option<VanillaDimension> {
+    match name {
+        "overworld" => Some(VanillaDimension::Overworld),
+        "nether"    => Some(VanillaDimension::Nether),
+        "end"       => Some(VanillaDimension::End),
+        _ => None,
+    }
+}
+
+// ================================
+//  Quoted strings and raw bytes
+// ================================
+
+/// Split arguments at commas which are not inside a quoted string.
+fn split_args(args: &str) -> Vec<&str> {
+    if args.is_empty() {
+        return Vec::new();
+    }
+
+    let mut split = Vec::new();
+    let mut arg_start = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for (idx, ch) in args.char_indices() {
+        match ch {
+            _ if escaped           => escaped = false,
+            '\\' if in_quotes      => escaped = true,
+            '"'                    => in_quotes = !in_quotes,
+            ',' if !in_quotes      => {
+                split.push(&args[arg_start..idx]);
+                // The UTF-8 byte length of ',' is 1
+                arg_start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    split.push(&args[arg_start..]);
+
+    split
+}
+
+/// Write the bytes as a quoted string. Any quotes or backslashes are escaped with
+/// a backslash, and any control characters or bytes which are not valid UTF-8 are
+/// escaped as `\xNN`.
+fn write_quoted(f: &mut Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    f.write_char('"')?;
+
+    for chunk in bytes.utf8_chunks() {
+        for ch in chunk.valid().chars() {
+            match ch {
+                '"' | '\\' => write!(f, "\\{ch}")?,
+                _ if ch.is_control() => {
+                    let mut utf8 = [0; 4];
+                    ch.encode_utf8(&mut utf8)
+                        .bytes()
+                        .try_for_each(|byte| write!(f, "\\x{byte:02x}"))?;
+                }
+                _ => f.write_char(ch)?,
+            }
+        }
+        chunk.invalid().iter().try_for_each(|byte| write!(f, "\\x{byte:02x}"))?;
+    }
+
+    f.write_char('"')
+}
+
+/// Parse a quoted string written by `write_quoted`.
+fn parse_quoted(quoted: &str) -> Option<Vec<u8>> {
+    let quoted = quoted.strip_prefix('"')?.strip_suffix('"')?;
+
+    let mut bytes = Vec::with_capacity(quoted.len());
+    let mut rest = quoted;
+    while let Some(escape_pos) = rest.find(['\\', '"']) {
+        bytes.extend(&rest.as_bytes()[..escape_pos]);
+        // An unescaped quote would end the string early
+        let escaped = rest[escape_pos..].strip_prefix('\\')?;
+
+        rest = if let Some(hex) = escaped.strip_prefix('x') {
+            bytes.push(parse_hex_byte(hex.get(..2)?)?);
+            &hex[2..]
+        } else if let Some(escaped) = escaped.strip_prefix(['"', '\\']) {
+            // The escaped character is one byte
+            bytes.push(rest.as_bytes()[escape_pos + 1]);
+            escaped
+        } else {
+            return None;
+        };
+    }
+    bytes.extend(rest.as_bytes());
+
+    Some(bytes)
+}
+
+fn parse_raw_key(raw_key: &str) -> Option<Vec<u8>> {
+    if raw_key.starts_with('"') {
+        return parse_quoted(raw_key);
+    }
+
+    if raw_key.len() % 2 != 0 || !raw_key.is_ascii() {
+        return None;
+    }
+    raw_key
+        .as_bytes()
+        .chunks_exact(2)
+        // The bytes are ASCII, so this does not fail.
+        .map(|hex| parse_hex_byte(str::from_utf8(hex).ok()?))
+        .collect()
+}
+
+#[inline]
+fn parse_hex_byte(hex: &str) -> Option<u8> {
+    // `from_str_radix` would also accept a leading `+`
+    if hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        u8::from_str_radix(hex, 16).ok()
+    } else {
+        None
+    }
+}
@@ -0,0 +1,76 @@
+use std::cell::OnceCell;
+
+use crate::{DBEntry, DBKey, EntryParseOptions, KeyDimension, KeyKind};
+use crate::entries::helpers::DimensionedChunkPos;
+
+
+/// A raw LevelDB entry which is only parsed into a [`DBEntry`] when first accessed.
+///
+/// The kind, chunk position, and dimension of the key can be determined without parsing the
+/// entry or allocating, so that entries can be filtered before their values are parsed.
+#[derive(Debug, Clone)]
+pub struct LazyDBEntry {
+    key:   Vec<u8>,
+    value: Vec<u8>,
+    opts:  EntryParseOptions,
+    entry: OnceCell<DBEntry>,
+}
+
+impl LazyDBEntry {
+    #[inline]
+    pub fn new(key: Vec<u8>, value: Vec<u8>, opts: EntryParseOptions) -> Self {
+        Self {
+            key,
+            value,
+            opts,
+            entry: OnceCell::new(),
+        }
+    }
+
+    #[inline]
+    pub fn raw_key(&self) -> &[u8] {
+        &self.key
+    }
+
+    #[inline]
+    pub fn raw_value(&self) -> &[u8] {
+        &self.value
+    }
+
+    /// The variant of [`DBKey`] which the raw key would be parsed as. See [`DBKey::classify`].
+    #[inline]
+    pub fn kind(&self) -> KeyKind {
+        DBKey::classify(&self.key)
+    }
+
+    /// See [`DBKey::parse_chunk_pos`].
+    #[inline]
+    pub fn chunk_pos(&self) -> Option<DimensionedChunkPos> {
+        DBKey::parse_chunk_pos(&self.key)
+    }
+
+    /// See [`DBKey::parse_dimension`].
+    #[inline]
+    pub fn dimension(&self) -> Option<KeyDimension<'_>> {
+        DBKey::parse_dimension(&self.key)
+    }
+
+    /// Get the parsed entry, parsing it if it has not yet been parsed.
+    pub fn entry(&self) -> &DBEntry {
+        self.entry.get_or_init(|| DBEntry::parse_entry(&self.key, &self.value, self.opts))
+    }
+
+    /// Whether the entry has already been parsed.
+    #[inline]
+    pub fn is_parsed(&self) -> bool {
+        self.entry.get().is_some()
+    }
+
+    /// Get the parsed entry, parsing it if it has not yet been parsed.
+    pub fn into_entry(self) -> DBEntry {
+        match self.entry.into_inner() {
+            Some(entry) => entry,
+            None        => DBEntry::parse_entry_vec(self.key, self.value, self.opts),
+        }
+    }
+}
@@ -1,12 +1,16 @@
 pub mod entries;
+pub mod schemas;
 mod interface;
 
 mod entry;
 mod key;
+mod key_text;
+mod lazy_entry;
 mod errors;
 
 
-pub use self::{entry::DBEntry, key::DBKey};
+pub use self::{entry::DBEntry, lazy_entry::LazyDBEntry};
+pub use self::key::{DBKey, KeyDimension, KeyKind};
 pub use self::{errors::*, interface::*};
 
 // Note in case the LevelDB part didn't make it obvious: this is for Minecraft Bedrock.
@@ -0,0 +1,123 @@
+//! Schemas for common NBT data in Bedrock worlds, which can be used to check that edited data
+//! is still well-formed before it is written back.
+//!
+//! The schemas are deliberately loose: they permit unknown keys, and only require the keys
+//! which are present in data written by every recent version of the game.
+
+use prismarine_anchor_nbt::schema::{CompoundSchema, NbtSchema};
+
+
+/// Keys shared by every block entity.
+const BLOCK_ENTITY_BASE: &str = r#"{type: "compound", fields: {
+    id:         "string",
+    x:          "int",
+    y:          "int",
+    z:          "int",
+    isMovable:  "bool?",
+    CustomName: "string?"
+}}"#;
+
+/// A schema for the root compound of `level.dat`.
+pub fn level_dat() -> CompoundSchema {
+    parse(r#"{type: "compound", fields: {
+        LevelName:             "string",
+        StorageVersion:        "int",
+        GameType:              {type: "int", min: 0, max: 6, optional: true},
+        Generator:             {type: "int", min: 0, optional: true},
+        Difficulty:            {type: "int", min: 0, max: 3, optional: true},
+        LastPlayed:            "long?",
+        RandomSeed:            "long?",
+        SpawnX:                "int?",
+        SpawnY:                "int?",
+        SpawnZ:                "int?",
+        Time:                  "long?",
+        NetworkVersion:        "int?",
+        lastOpenedWithVersion: {type: "list", element: "int", optional: true},
+        abilities:             "compound?"
+    }}"#)
+}
+
+/// A schema for an item stack, as stored in inventories and containers.
+pub fn item_stack() -> CompoundSchema {
+    parse(r#"{type: "compound", fields: {
+        Name:        "string",
+        Count:       {type: "byte", min: 0},
+        Damage:      "short",
+        WasPickedUp: "bool?",
+        Block:       "compound?",
+        tag:         "compound?",
+        CanDestroy:  {type: "list", element: "string", optional: true},
+        CanPlaceOn:  {type: "list", element: "string", optional: true}
+    }}"#)
+}
+
+/// A schema for the block entity with the given `id`, such as `"Chest"`, if a schema
+/// for that kind of block entity is known.
+pub fn block_entity(id: &str) -> Option<CompoundSchema> {
+    let base = parse(BLOCK_ENTITY_BASE);
+    let items = || NbtSchema::list(NbtSchema::Compound(
+        item_stack().required("Slot", NbtSchema::byte()),
+    ));
+
+    let schema = match id {
+        "Chest" | "Barrel" | "ShulkerBox" | "Hopper" => base.optional("Items", items()),
+        "Furnace" | "BlastFurnace" | "Smoker" => {
+            base.optional("BurnTime", NbtSchema::short())
+                .optional("CookTime", NbtSchema::short())
+                .optional("BurnDuration", NbtSchema::short())
+                .optional("Items", items())
+        }
+        "Sign" | "HangingSign" => {
+            let text = NbtSchema::Compound(parse(r#"{type: "compound", fields: {
+                Text:              "string",
+                SignTextColor:     "int?",
+                IgnoreLighting:    "bool?",
+                HideGlowOutline:   "bool?",
+                PersistFormatting: "bool?",
+                TextOwner:         "string?"
+            }}"#));
+            base.optional("FrontText", text.clone())
+                .optional("BackText", text)
+                .optional("IsWaxed", NbtSchema::bool())
+        }
+        "MobSpawner" => {
+            base.optional("EntityIdentifier", NbtSchema::String)
+                .optional("Delay", NbtSchema::short())
+                .optional("MinSpawnDelay", NbtSchema::short())
+                .optional("MaxSpawnDelay", NbtSchema::short())
+                .optional("SpawnCount", NbtSchema::short())
+                .optional("RequiredPlayerRange", NbtSchema::short())
+        }
+        _ => return None,
+    };
+
+    Some(schema)
+}
+
+fn parse(description: &str) -> CompoundSchema {
+    CompoundSchema::from_snbt(description).expect("built-in schema descriptions are valid")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn bundled_schemas_load() {
+        let _level_dat = level_dat();
+        let _item_stack = item_stack();
+
+        let ids = [
+            "Chest", "Barrel", "ShulkerBox", "Hopper",
+            "Furnace", "BlastFurnace", "Smoker",
+            "Sign", "HangingSign",
+            "MobSpawner",
+        ];
+        for id in ids {
+            assert!(block_entity(id).is_some(), "there should be a schema for `{id}`");
+        }
+        assert!(block_entity("Unknown").is_none(), "there should be no schema for `Unknown`");
+    }
+}
@@ -7,5 +7,10 @@ pub use identifier::*;
 mod positions;
 pub use positions::*;
 
+#[cfg(feature = "text_component")]
+mod text_component;
+#[cfg(feature = "text_component")]
+pub use text_component::*;
+
 mod version;
 pub use version::*;
@@ -0,0 +1,275 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+
+/// The color of a [`TextComponent`].
+///
+/// [`TextComponent`]: super::TextComponent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextColor {
+    Named(NamedColor),
+    /// A color in the form `0xRRGGBB`.
+    Rgb(u32),
+}
+
+impl TextColor {
+    /// Parses either the name of a color, such as `dark_red`, or a hex color in the form
+    /// `#RRGGBB`.
+    pub fn parse(color: &str) -> Option<Self> {
+        if let Some(hex) = color.strip_prefix('#') {
+            if hex.is_empty() || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+                return None;
+            }
+            u32::from_str_radix(hex, 16)
+                .ok()
+                .filter(|&rgb| rgb <= 0xFF_FF_FF)
+                .map(Self::Rgb)
+        } else {
+            NamedColor::from_name(color).map(Self::Named)
+        }
+    }
+
+    /// The color in the form `0xRRGGBB`.
+    #[inline]
+    pub fn rgb(self) -> u32 {
+        match self {
+            Self::Named(named) => named.rgb(),
+            Self::Rgb(rgb)     => rgb,
+        }
+    }
+
+    /// The named color which is closest to this color.
+    pub fn nearest_named(self) -> NamedColor {
+        match self {
+            Self::Named(named) => named,
+            Self::Rgb(rgb)     => {
+                let [first, rest @ ..] = NamedColor::ALL.map(|named| (named, named.rgb()));
+                nearest(rgb, first, rest)
+            }
+        }
+    }
+}
+
+impl Display for TextColor {
+    /// Writes the name of a named color, or `#RRGGBB` for any other color.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Named(named) => f.write_str(named.name()),
+            Self::Rgb(rgb)     => write!(f, "#{rgb:06X}"),
+        }
+    }
+}
+
+/// The sixteen colors which have names and legacy formatting codes in both editions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NamedColor {
+    Black,
+    DarkBlue,
+    DarkGreen,
+    DarkAqua,
+    DarkRed,
+    DarkPurple,
+    Gold,
+    Gray,
+    DarkGray,
+    Blue,
+    Green,
+    Aqua,
+    Red,
+    LightPurple,
+    Yellow,
+    White,
+}
+
+impl NamedColor {
+    /// Every named color, ordered by formatting code.
+    pub const ALL: [Self; 16] = [
+        Self::Black,
+        Self::DarkBlue,
+        Self::DarkGreen,
+        Self::DarkAqua,
+        Self::DarkRed,
+        Self::DarkPurple,
+        Self::Gold,
+        Self::Gray,
+        Self::DarkGray,
+        Self::Blue,
+        Self::Green,
+        Self::Aqua,
+        Self::Red,
+        Self::LightPurple,
+        Self::Yellow,
+        Self::White,
+    ];
+
+    /// The name used for this color in text components, such as `dark_red`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Black       => "black",
+            Self::DarkBlue    => "dark_blue",
+            Self::DarkGreen   => "dark_green",
+            Self::DarkAqua    => "dark_aqua",
+            Self::DarkRed     => "dark_red",
+            Self::DarkPurple  => "dark_purple",
+            Self::Gold        => "gold",
+            Self::Gray        => "gray",
+            Self::DarkGray    => "dark_gray",
+            Self::Blue        => "blue",
+            Self::Green       => "green",
+            Self::Aqua        => "aqua",
+            Self::Red         => "red",
+            Self::LightPurple => "light_purple",
+            Self::Yellow      => "yellow",
+            Self::White       => "white",
+        }
+    }
+
+    /// The character which follows `§` in the formatting code of this color.
+    pub fn legacy_code(self) -> char {
+        match self {
+            Self::Black       => '0',
+            Self::DarkBlue    => '1',
+            Self::DarkGreen   => '2',
+            Self::DarkAqua    => '3',
+            Self::DarkRed     => '4',
+            Self::DarkPurple  => '5',
+            Self::Gold        => '6',
+            Self::Gray        => '7',
+            Self::DarkGray    => '8',
+            Self::Blue        => '9',
+            Self::Green       => 'a',
+            Self::Aqua        => 'b',
+            Self::Red         => 'c',
+            Self::LightPurple => 'd',
+            Self::Yellow      => 'e',
+            Self::White       => 'f',
+        }
+    }
+
+    /// The color in the form `0xRRGGBB`.
+    pub fn rgb(self) -> u32 {
+        match self {
+            Self::Black       => 0x00_00_00,
+            Self::DarkBlue    => 0x00_00_AA,
+            Self::DarkGreen   => 0x00_AA_00,
+            Self::DarkAqua    => 0x00_AA_AA,
+            Self::DarkRed     => 0xAA_00_00,
+            Self::DarkPurple  => 0xAA_00_AA,
+            Self::Gold        => 0xFF_AA_00,
+            Self::Gray        => 0xAA_AA_AA,
+            Self::DarkGray    => 0x55_55_55,
+            Self::Blue        => 0x55_55_FF,
+            Self::Green       => 0x55_FF_55,
+            Self::Aqua        => 0x55_FF_FF,
+            Self::Red         => 0xFF_55_55,
+            Self::LightPurple => 0xFF_55_FF,
+            Self::Yellow      => 0xFF_FF_55,
+            Self::White       => 0xFF_FF_FF,
+        }
+    }
+
+    #[inline]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|color| color.name() == name)
+    }
+
+    /// Case-insensitive.
+    #[inline]
+    pub fn from_legacy_code(code: char) -> Option<Self> {
+        let code = code.to_ascii_lowercase();
+        Self::ALL.into_iter().find(|color| color.legacy_code() == code)
+    }
+}
+
+impl Display for NamedColor {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Which set of legacy `§` formatting codes is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FormattingCodes {
+    /// Color codes reset all formatting, and `§m` and `§n` are strikethrough and underline.
+    Java,
+    /// Color codes do not reset formatting, there is no strikethrough or underline, and
+    /// `§g` through `§v` are additional colors, including `§m` and `§n`.
+    Bedrock,
+}
+
+impl FormattingCodes {
+    /// The colors which are only available in Bedrock Edition, and their formatting codes.
+    const BEDROCK_COLORS: [(char, u32); 12] = [
+        ('g', 0xDD_D6_05), // minecoin_gold
+        ('h', 0xE3_D4_D1), // material_quartz
+        ('i', 0xCE_CA_CA), // material_iron
+        ('j', 0x44_3A_3B), // material_netherite
+        ('m', 0x97_16_07), // material_redstone
+        ('n', 0xB4_68_4D), // material_copper
+        ('p', 0xDE_B1_2D), // material_gold
+        ('q', 0x47_A0_36), // material_emerald
+        ('s', 0x2C_BA_A8), // material_diamond
+        ('t', 0x21_49_7B), // material_lapis
+        ('u', 0x9A_5C_C6), // material_amethyst
+        ('v', 0xEB_71_14), // material_resin
+    ];
+
+    /// The color of a formatting code, if it is a color code. Case-insensitive.
+    pub fn color(self, code: char) -> Option<TextColor> {
+        if let Some(named) = NamedColor::from_legacy_code(code) {
+            return Some(TextColor::Named(named));
+        }
+
+        match self {
+            Self::Java    => None,
+            Self::Bedrock => {
+                let code = code.to_ascii_lowercase();
+                Self::BEDROCK_COLORS
+                    .into_iter()
+                    .find(|&(color_code, _)| color_code == code)
+                    .map(|(_, rgb)| TextColor::Rgb(rgb))
+            }
+        }
+    }
+
+    /// The formatting code of the given color, or of the closest color which has a code.
+    pub fn color_code(self, color: TextColor) -> char {
+        match (self, color) {
+            (_, TextColor::Named(named))     => named.legacy_code(),
+            (Self::Java, TextColor::Rgb(_))  => color.nearest_named().legacy_code(),
+            (Self::Bedrock, TextColor::Rgb(rgb)) => {
+                let [first, rest @ ..] = NamedColor::ALL
+                    .map(|named| (named.legacy_code(), named.rgb()));
+                nearest(rgb, first, rest.into_iter().chain(Self::BEDROCK_COLORS))
+            }
+        }
+    }
+}
+
+/// Returns the key of the color closest to `rgb`, preferring earlier colors in case of a tie.
+fn nearest<K, I>(rgb: u32, first: (K, u32), rest: I) -> K
+where
+    I: IntoIterator<Item = (K, u32)>,
+{
+    let distance = |other: u32| {
+        [16, 8, 0]
+            .into_iter()
+            .map(|shift| {
+                let difference = ((rgb >> shift) & 0xFF).abs_diff((other >> shift) & 0xFF);
+                difference * difference
+            })
+            .sum::<u32>()
+    };
+
+    let mut nearest = first;
+    let mut nearest_distance = distance(nearest.1);
+    for color in rest {
+        let color_distance = distance(color.1);
+        if color_distance < nearest_distance {
+            nearest = color;
+            nearest_distance = color_distance;
+        }
+    }
+    nearest.0
+}
@@ -0,0 +1,167 @@
+use std::mem;
+
+use super::{ResolvedStyle, TextComponent, TextContent, TextStyle};
+use super::color::FormattingCodes;
+
+
+/// The character which begins a formatting code.
+const SECTION_SIGN: char = '§';
+
+
+pub(super) fn from_legacy_text(text: &str, codes: FormattingCodes) -> TextComponent {
+    let mut runs = Vec::new();
+    let mut run = String::new();
+    let mut style = TextStyle::default();
+
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch != SECTION_SIGN {
+            run.push(ch);
+            continue;
+        }
+        // A trailing section sign is removed, like an unrecognized code
+        let Some(code) = chars.next() else { break };
+
+        let mut next_style = style.clone();
+        if !apply_code(&mut next_style, code, codes) || next_style == style {
+            continue;
+        }
+
+        if !run.is_empty() {
+            runs.push(TextComponent {
+                content: TextContent::Text(mem::take(&mut run)),
+                style:   mem::replace(&mut style, next_style),
+                extra:   Vec::new(),
+            });
+        } else {
+            style = next_style;
+        }
+    }
+
+    if !run.is_empty() {
+        runs.push(TextComponent {
+            content: TextContent::Text(run),
+            style,
+            extra:   Vec::new(),
+        });
+    }
+
+    if runs.len() == 1 {
+        if let Some(only_run) = runs.pop() {
+            return only_run;
+        }
+    }
+
+    let mut component = TextComponent::text("");
+    component.extra = runs;
+    component
+}
+
+/// Applies the formatting code to the style, and returns whether the code was recognized.
+fn apply_code(style: &mut TextStyle, code: char, codes: FormattingCodes) -> bool {
+    if let Some(color) = codes.color(code) {
+        match codes {
+            FormattingCodes::Java => {
+                *style = TextStyle {
+                    color: Some(color),
+                    ..TextStyle::default()
+                };
+            }
+            FormattingCodes::Bedrock => style.color = Some(color),
+        }
+        return true;
+    }
+
+    let format = match (codes, code.to_ascii_lowercase()) {
+        (_, 'k')                     => &mut style.obfuscated,
+        (_, 'l')                     => &mut style.bold,
+        (FormattingCodes::Java, 'm') => &mut style.strikethrough,
+        (FormattingCodes::Java, 'n') => &mut style.underlined,
+        (_, 'o')                     => &mut style.italic,
+        (_, 'r')                     => {
+            *style = TextStyle::default();
+            return true;
+        }
+        _ => return false,
+    };
+    *format = Some(true);
+    true
+}
+
+pub(super) fn to_legacy_text(component: &TextComponent, codes: FormattingCodes) -> String {
+    let mut legacy = String::new();
+    let mut current = ResolvedStyle::default();
+
+    component.visit(&ResolvedStyle::default(), &|_| None, &mut |segment, style| {
+        if segment.is_empty() {
+            return;
+        }
+
+        let mut style = *style;
+        if codes == FormattingCodes::Bedrock {
+            style.strikethrough = false;
+            style.underlined = false;
+        }
+
+        if style != current {
+            push_codes(&mut legacy, &current, &style, codes);
+            current = style;
+        }
+        legacy.push_str(segment);
+    });
+
+    legacy
+}
+
+/// Pushes the formatting codes which change the current style into the given style.
+fn push_codes(
+    legacy:  &mut String,
+    current: &ResolvedStyle,
+    style:   &ResolvedStyle,
+    codes:   FormattingCodes,
+) {
+    let current_formats = formats(current);
+    let new_formats = formats(style);
+
+    // Formatting can only be removed by resetting it, and in Java, color codes reset it as well.
+    let reset = current_formats
+        .iter()
+        .zip(new_formats)
+        .any(|(&(was_enabled, _), (enabled, _))| was_enabled && !enabled)
+        || (current.color.is_some() && style.color.is_none())
+        || (codes == FormattingCodes::Java && current.color != style.color);
+
+    if reset {
+        if codes == FormattingCodes::Bedrock || style.color.is_none() {
+            push_code(legacy, 'r');
+        }
+        if let Some(color) = style.color {
+            push_code(legacy, codes.color_code(color));
+        }
+    } else if let Some(color) = style.color.filter(|&color| current.color != Some(color)) {
+        push_code(legacy, codes.color_code(color));
+    }
+
+    for ((was_enabled, _), (enabled, code)) in current_formats.into_iter().zip(new_formats) {
+        if enabled && (reset || !was_enabled) {
+            push_code(legacy, code);
+        }
+    }
+}
+
+/// Each format of the style, and its formatting code.
+fn formats(style: &ResolvedStyle) -> [(bool, char); 5] {
+    [
+        (style.obfuscated,    'k'),
+        (style.bold,          'l'),
+        (style.strikethrough, 'm'),
+        (style.underlined,    'n'),
+        (style.italic,        'o'),
+    ]
+}
+
+#[inline]
+fn push_code(legacy: &mut String, code: char) {
+    legacy.push(SECTION_SIGN);
+    legacy.push(code);
+}
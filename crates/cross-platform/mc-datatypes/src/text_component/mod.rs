@@ -0,0 +1,442 @@
+mod color;
+mod legacy;
+mod read;
+mod write;
+
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use serde_json::Value;
+use thiserror::Error;
+
+use prismarine_anchor_nbt::{NbtCompound, NbtTag};
+
+
+pub use self::color::{FormattingCodes, NamedColor, TextColor};
+
+
+/// The maximum depth that text components may be nested when they are parsed, counting
+/// each component that is contained in another component.
+const DEPTH_LIMIT: u32 = 512;
+
+
+/// A Minecraft text component, as used by Java Edition for signs, books, item names and lore,
+/// and most other text shown to players.
+///
+/// Java versions before 1.21.5 store text components as JSON strings, and later versions store
+/// them as NBT. In either case, unstyled text may be stored as a plain string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextComponent {
+    pub content: TextContent,
+    pub style:   TextStyle,
+    /// Components displayed after this one, which inherit its style.
+    pub extra:   Vec<Self>,
+}
+
+impl TextComponent {
+    /// A component containing the given text, without any styling.
+    #[inline]
+    pub fn text<S: Into<String>>(text: S) -> Self {
+        Self {
+            content: TextContent::Text(text.into()),
+            style:   TextStyle::default(),
+            extra:   Vec::new(),
+        }
+    }
+
+    /// Whether this component is only text, without any styling or extra components.
+    /// Such components are written as plain strings.
+    pub fn is_plain_text(&self) -> bool {
+        matches!(self.content, TextContent::Text(_))
+            && self.style.is_empty()
+            && self.extra.is_empty()
+    }
+
+    /// Parses a text component from a JSON string, as stored by Java versions before 1.21.5.
+    /// See [`from_json`] for details.
+    ///
+    /// [`from_json`]: TextComponent::from_json
+    pub fn from_json_str(json: &str) -> Result<Self, TextComponentError> {
+        let json: Value = serde_json::from_str(json)
+            .map_err(|err| TextComponentError::InvalidJson(err.to_string()))?;
+        Self::from_json(&json)
+    }
+
+    /// Parses a text component from JSON.
+    ///
+    /// Both the field names used before Java 1.21.5, such as `clickEvent` and `contents`, and
+    /// those used afterwards, such as `click_event` and `url`, are accepted. The formats used by
+    /// hover events before Java 1.16 are accepted as well.
+    #[inline]
+    pub fn from_json(json: &Value) -> Result<Self, TextComponentError> {
+        read::read_component(json, 0)
+    }
+
+    /// Parses a text component from NBT, as stored by Java 1.21.5 and later.
+    ///
+    /// Elements of heterogenous lists which are still wrapped in compounds are unwrapped;
+    /// see [`NbtList::unwrap_heterogenous`].
+    ///
+    /// Note that older versions store text components as JSON inside of a `String` tag,
+    /// which this function parses as plain text; use [`from_json_str`] for such strings.
+    ///
+    /// [`NbtList::unwrap_heterogenous`]: prismarine_anchor_nbt::NbtList::unwrap_heterogenous
+    /// [`from_json_str`]: TextComponent::from_json_str
+    #[inline]
+    pub fn from_nbt(tag: &NbtTag) -> Result<Self, TextComponentError> {
+        read::read_component(tag, 0)
+    }
+
+    /// Converts this component into JSON, using the field names of the given format.
+    #[inline]
+    pub fn to_json(&self, format: TextComponentFormat) -> Value {
+        write::json_value(write::component_output(self, format))
+    }
+
+    /// Converts this component into a JSON string, using the field names of the given format.
+    #[inline]
+    pub fn to_json_string(&self, format: TextComponentFormat) -> String {
+        self.to_json(format).to_string()
+    }
+
+    /// Converts this component into NBT, as stored by Java 1.21.5 and later.
+    ///
+    /// Lists of components may be heterogenous, as plain text is written as a `String` tag;
+    /// the `io` module of `prismarine-anchor-nbt` wraps their elements when they are written.
+    #[inline]
+    pub fn to_nbt(&self) -> NbtTag {
+        write::nbt_tag(write::component_output(self, TextComponentFormat::Modern))
+    }
+
+    /// Flattens this component and its extra components into unstyled text.
+    ///
+    /// No translations are available, so translatable components use their fallback if they
+    /// have one, and their translation key otherwise; see [`to_plain_text_with`].
+    /// Score and selector components cannot be resolved, so they use the stored value of the
+    /// score (if any) and the selector itself, respectively. NBT components are omitted.
+    ///
+    /// [`to_plain_text_with`]: TextComponent::to_plain_text_with
+    #[inline]
+    pub fn to_plain_text(&self) -> String {
+        self.to_plain_text_with(|_| None)
+    }
+
+    /// Flattens this component and its extra components into unstyled text, looking up
+    /// the translation of each translation key with `translate`.
+    ///
+    /// If a key has no translation, the fallback of the component is used if it has one,
+    /// and the key itself otherwise. See [`to_plain_text`] for more information.
+    ///
+    /// [`to_plain_text`]: TextComponent::to_plain_text
+    pub fn to_plain_text_with<F>(&self, translate: F) -> String
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut text = String::new();
+        self.visit(&ResolvedStyle::default(), &translate, &mut |segment, _| {
+            text.push_str(segment);
+        });
+        text
+    }
+
+    /// Parses text which uses legacy `§` formatting codes, such as `§4§lDanger`, into
+    /// a component whose extra components each have the style of one run of the text.
+    ///
+    /// Bedrock Edition still uses formatting codes to style text. Unrecognized codes are removed,
+    /// as is done when such text is displayed.
+    #[inline]
+    pub fn from_legacy_text(text: &str, codes: FormattingCodes) -> Self {
+        legacy::from_legacy_text(text, codes)
+    }
+
+    /// Flattens this component into text which uses legacy `§` formatting codes.
+    ///
+    /// Colors without a formatting code are replaced with the closest color that has one,
+    /// and styling which cannot be expressed with formatting codes is lost.
+    /// Text is flattened as in [`to_plain_text`].
+    ///
+    /// [`to_plain_text`]: TextComponent::to_plain_text
+    #[inline]
+    pub fn to_legacy_text(&self, codes: FormattingCodes) -> String {
+        legacy::to_legacy_text(self, codes)
+    }
+
+    /// Calls `output` on each segment of text in this component, in order, along with the style
+    /// which that segment has after inheriting the styles of its parent components.
+    fn visit(
+        &self,
+        parent:    &ResolvedStyle,
+        translate: &dyn Fn(&str) -> Option<String>,
+        output:    &mut dyn FnMut(&str, &ResolvedStyle),
+    ) {
+        let style = parent.inherit(&self.style);
+
+        match &self.content {
+            TextContent::Text(text) => output(text, &style),
+            TextContent::Translatable { key, fallback, with } => {
+                let format = translate(key)
+                    .or_else(|| fallback.clone())
+                    .unwrap_or_else(|| key.clone());
+                visit_translation(&format, with, &style, translate, output);
+            }
+            TextContent::Score { value, .. } => {
+                if let Some(value) = value {
+                    output(value, &style);
+                }
+            }
+            TextContent::Selector { selector, .. } => output(selector, &style),
+            TextContent::Keybind(keybind)          => output(keybind, &style),
+            TextContent::Nbt { .. }                => {}
+        }
+
+        for extra in &self.extra {
+            extra.visit(&style, translate, output);
+        }
+    }
+}
+
+impl From<String> for TextComponent {
+    #[inline]
+    fn from(text: String) -> Self {
+        Self::text(text)
+    }
+}
+
+impl From<&str> for TextComponent {
+    #[inline]
+    fn from(text: &str) -> Self {
+        Self::text(text)
+    }
+}
+
+impl Display for TextComponent {
+    /// Writes the text of this component, as given by [`TextComponent::to_plain_text`].
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_plain_text())
+    }
+}
+
+/// Formats a translation in the way Java's `String.format` does for `%s` and `%1$s`, visiting
+/// the arguments of the translation where they are used.
+fn visit_translation(
+    format:    &str,
+    with:      &[TextComponent],
+    style:     &ResolvedStyle,
+    translate: &dyn Fn(&str) -> Option<String>,
+    output:    &mut dyn FnMut(&str, &ResolvedStyle),
+) {
+    let mut next_arg = 0;
+    let mut rest = format;
+
+    while let Some(percent) = rest.find('%') {
+        if percent > 0 {
+            output(&rest[..percent], style);
+        }
+        // "+ 1" because the UTF-8 byte length of '%' is 1
+        let after = &rest[percent + 1..];
+
+        let digits = after.find(|ch: char| !ch.is_ascii_digit()).unwrap_or(after.len());
+        let (arg, remaining) = if let Some(remaining) = after.strip_prefix('%') {
+            output("%", style);
+            rest = remaining;
+            continue;
+        } else if let Some(remaining) = after.strip_prefix('s') {
+            next_arg += 1;
+            (Some(next_arg - 1), remaining)
+        } else if let Some(remaining) = after[digits..].strip_prefix("$s") {
+            // Indices are one-based, and `0$s` is not a valid argument
+            let index = after[..digits].parse::<usize>().ok().and_then(|n| n.checked_sub(1));
+            (index, remaining)
+        } else {
+            output("%", style);
+            rest = after;
+            continue;
+        };
+
+        if let Some(arg) = arg.and_then(|arg| with.get(arg)) {
+            arg.visit(style, translate, output);
+        }
+        rest = remaining;
+    }
+
+    if !rest.is_empty() {
+        output(rest, style);
+    }
+}
+
+/// The content of a [`TextComponent`], which is displayed before its extra components.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextContent {
+    /// Literal text.
+    Text(String),
+    /// Text from the translation of `key` in the player's language. The translation may contain
+    /// `%s` or `%1$s` placeholders, which are replaced by the components in `with`.
+    Translatable {
+        key:      String,
+        /// Used instead of `key` if the key has no translation.
+        fallback: Option<String>,
+        with:     Vec<TextComponent>,
+    },
+    /// The score of an entity or player in a scoreboard objective.
+    Score {
+        /// A player name or entity selector, or `*` for the player viewing the text.
+        name:      String,
+        objective: String,
+        /// The resolved value of the score, which was stored by Java versions before 1.20.3.
+        value:     Option<String>,
+    },
+    /// The names of the entities matched by an entity selector.
+    Selector {
+        selector:  String,
+        /// Displayed between the names. Defaults to `, ` in gray.
+        separator: Option<Box<TextComponent>>,
+    },
+    /// The name of the key bound to a control, such as `key.jump`.
+    Keybind(String),
+    /// Values from the NBT data of a block, entity, or command storage.
+    Nbt {
+        /// An NBT path, such as `Inventory[0].id`.
+        path:      String,
+        /// Whether the values are parsed as text components instead of displayed as SNBT.
+        interpret: Option<bool>,
+        /// Displayed between the values. Defaults to `, `.
+        separator: Option<Box<TextComponent>>,
+        source:    NbtSource,
+    },
+}
+
+/// Where the NBT data displayed by [`TextContent::Nbt`] comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum NbtSource {
+    /// The coordinates of a block entity.
+    Block(String),
+    /// An entity selector.
+    Entity(String),
+    /// The namespaced identifier of a command storage.
+    Storage(String),
+}
+
+/// The style of a [`TextComponent`]. Each `None` value is inherited from the parent component.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TextStyle {
+    pub color:         Option<TextColor>,
+    /// The namespaced identifier of a font, such as `minecraft:uniform`.
+    pub font:          Option<String>,
+    pub bold:          Option<bool>,
+    pub italic:        Option<bool>,
+    pub underlined:    Option<bool>,
+    pub strikethrough: Option<bool>,
+    pub obfuscated:    Option<bool>,
+    /// The color of the shadow of the text, as ARGB.
+    pub shadow_color:  Option<u32>,
+    /// Text which is inserted into chat when the text is shift-clicked.
+    pub insertion:     Option<String>,
+    pub click_event:   Option<ClickEvent>,
+    pub hover_event:   Option<HoverEvent>,
+}
+
+impl TextStyle {
+    /// Whether every part of this style is inherited.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// An action taken when the player clicks on a [`TextComponent`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClickEvent {
+    OpenUrl(String),
+    OpenFile(String),
+    RunCommand(String),
+    SuggestCommand(String),
+    /// Turns to the given page of a book.
+    ChangePage(i32),
+    CopyToClipboard(String),
+}
+
+/// Information shown when the player hovers over a [`TextComponent`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum HoverEvent {
+    ShowText(Box<TextComponent>),
+    ShowItem {
+        /// The namespaced identifier of the item.
+        id:         String,
+        count:      i32,
+        /// The data components of the item, used by Java 1.20.5 and later.
+        components: Option<NbtCompound>,
+        /// The NBT data of the item, used by Java versions before 1.20.5. This cannot be
+        /// written in the format used by Java 1.21.5 and later, and is omitted from it.
+        tag:        Option<NbtCompound>,
+    },
+    ShowEntity {
+        /// The namespaced identifier of the type of the entity.
+        entity_type: String,
+        uuid:        u128,
+        name:        Option<Box<TextComponent>>,
+    },
+}
+
+/// Which field names are used when a [`TextComponent`] is written as JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextComponentFormat {
+    /// The format read by Java 1.16 through 1.21.4, which uses `clickEvent` and `hoverEvent`,
+    /// stores the argument of a click event in `value`, and stores the data of a hover event
+    /// in `contents`.
+    Legacy,
+    /// The format used by Java 1.21.5 and later in both JSON and NBT, which uses `click_event`
+    /// and `hover_event`, and gives each argument of an event its own field.
+    Modern,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TextComponentError {
+    #[error("invalid JSON: {0}")]
+    InvalidJson(String),
+    #[error("expected {expected} for `{field}`, but found {found}")]
+    InvalidField {
+        field:    &'static str,
+        expected: &'static str,
+        found:    &'static str,
+    },
+    #[error("missing the required field `{0}`")]
+    MissingField(&'static str),
+    #[error("unknown value \"{value}\" for `{field}`")]
+    UnknownValue {
+        field: &'static str,
+        value: String,
+    },
+    #[error("a text component had no content")]
+    MissingContent,
+    #[error("a list of text components for `{0}` was empty")]
+    EmptyList(&'static str),
+    #[error("invalid NBT data in a hover event: {0}")]
+    InvalidNbt(String),
+    #[error("text components were nested more than {} levels deep", DEPTH_LIMIT)]
+    ExceededDepthLimit,
+}
+
+/// A style after inheritance, reduced to what can be expressed with formatting codes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct ResolvedStyle {
+    color:         Option<TextColor>,
+    bold:          bool,
+    italic:        bool,
+    underlined:    bool,
+    strikethrough: bool,
+    obfuscated:    bool,
+}
+
+impl ResolvedStyle {
+    fn inherit(&self, style: &TextStyle) -> Self {
+        Self {
+            color:         style.color.or(self.color),
+            bold:          style.bold.unwrap_or(self.bold),
+            italic:        style.italic.unwrap_or(self.italic),
+            underlined:    style.underlined.unwrap_or(self.underlined),
+            strikethrough: style.strikethrough.unwrap_or(self.strikethrough),
+            obfuscated:    style.obfuscated.unwrap_or(self.obfuscated),
+        }
+    }
+}
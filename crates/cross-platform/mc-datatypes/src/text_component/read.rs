@@ -0,0 +1,756 @@
+use serde_json::Value;
+
+use prismarine_anchor_nbt::{NbtCompound, NbtTag, PlainJsonOptions, SnbtParseOptions};
+use prismarine_anchor_nbt::json::compound_from_plain_json;
+
+use super::{
+    ClickEvent, DEPTH_LIMIT, HoverEvent, NbtSource, TextColor,
+    TextComponent, TextComponentError, TextContent, TextStyle,
+};
+
+
+/// The keys which identify each type of content when a component has no `type` field,
+/// in the order they are checked, along with the name of the type.
+const CONTENT_KEYS: [(&str, &str); 6] = [
+    ("text",      "text"),
+    ("translate", "translatable"),
+    ("score",     "score"),
+    ("selector",  "selector"),
+    ("keybind",   "keybind"),
+    ("nbt",       "nbt"),
+];
+
+
+/// JSON or NBT data which a [`TextComponent`] can be read from.
+pub(super) trait ComponentSource: Sized {
+    /// A description of the kind of value this is, used in errors.
+    fn kind(&self) -> &'static str;
+
+    fn as_str(&self) -> Option<&str>;
+
+    /// Strings, numbers, and booleans are converted to text, since Minecraft accepts any of them
+    /// in place of a text component or as the argument of a translation.
+    fn primitive_text(&self) -> Option<String>;
+
+    fn as_bool(&self) -> Option<bool>;
+
+    fn as_i64(&self) -> Option<i64>;
+
+    fn as_f64(&self) -> Option<f64>;
+
+    /// The elements of a list, if this is a list.
+    fn elements(&self) -> Option<Vec<&Self>>;
+
+    /// The integers of an int array, or of a list of integers.
+    fn int_array(&self) -> Option<Vec<i32>>;
+
+    fn is_object(&self) -> bool;
+
+    fn field(&self, key: &str) -> Option<&Self>;
+
+    /// Converts an object into NBT data.
+    fn to_compound(&self, field: &'static str) -> Result<NbtCompound, TextComponentError>;
+}
+
+impl ComponentSource for Value {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Null      => "null",
+            Self::Bool(_)   => "a boolean",
+            Self::Number(_) => "a number",
+            Self::String(_) => "a string",
+            Self::Array(_)  => "a list",
+            Self::Object(_) => "an object",
+        }
+    }
+
+    #[inline]
+    fn as_str(&self) -> Option<&str> {
+        self.as_str()
+    }
+
+    fn primitive_text(&self) -> Option<String> {
+        match self {
+            Self::Bool(value)   => Some(value.to_string()),
+            Self::Number(value) => Some(value.to_string()),
+            Self::String(value) => Some(value.clone()),
+            Self::Null | Self::Array(_) | Self::Object(_) => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value)   => Some(*value),
+            Self::Number(value) => value.as_i64().map(|value| value != 0),
+            Self::Null | Self::String(_) | Self::Array(_) | Self::Object(_) => None,
+        }
+    }
+
+    #[inline]
+    fn as_i64(&self) -> Option<i64> {
+        self.as_i64()
+    }
+
+    #[inline]
+    fn as_f64(&self) -> Option<f64> {
+        self.as_f64()
+    }
+
+    fn elements(&self) -> Option<Vec<&Self>> {
+        self.as_array().map(|elements| elements.iter().collect())
+    }
+
+    fn int_array(&self) -> Option<Vec<i32>> {
+        self.as_array()?
+            .iter()
+            .map(|element| element.as_i64().and_then(|value| i32::try_from(value).ok()))
+            .collect()
+    }
+
+    #[inline]
+    fn is_object(&self) -> bool {
+        self.is_object()
+    }
+
+    #[inline]
+    fn field(&self, key: &str) -> Option<&Self> {
+        self.get(key)
+    }
+
+    fn to_compound(&self, field: &'static str) -> Result<NbtCompound, TextComponentError> {
+        if !self.is_object() {
+            return Err(invalid(field, "an object", self));
+        }
+        compound_from_plain_json(self, PlainJsonOptions::default())
+            .map_err(|err| TextComponentError::InvalidNbt(err.to_string()))
+    }
+}
+
+impl ComponentSource for NbtTag {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Byte(_)       => "a byte",
+            Self::Short(_)      => "a short",
+            Self::Int(_)        => "an int",
+            Self::Long(_)       => "a long",
+            Self::Float(_)      => "a float",
+            Self::Double(_)     => "a double",
+            Self::ByteArray(_)  => "a byte array",
+            Self::String(_)     => "a string",
+            Self::ByteString(_) => "a byte string",
+            Self::List(_)       => "a list",
+            Self::Compound(_)   => "a compound",
+            Self::IntArray(_)   => "an int array",
+            Self::LongArray(_)  => "a long array",
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        if let Self::String(value) = self {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    fn primitive_text(&self) -> Option<String> {
+        match self {
+            Self::Byte(value)   => Some(value.to_string()),
+            Self::Short(value)  => Some(value.to_string()),
+            Self::Int(value)    => Some(value.to_string()),
+            Self::Long(value)   => Some(value.to_string()),
+            Self::Float(value)  => Some(value.to_string()),
+            Self::Double(value) => Some(value.to_string()),
+            Self::String(value) => Some(value.clone()),
+            Self::ByteArray(_) | Self::ByteString(_) | Self::List(_) | Self::Compound(_)
+                | Self::IntArray(_) | Self::LongArray(_) => None,
+        }
+    }
+
+    #[inline]
+    fn as_bool(&self) -> Option<bool> {
+        self.as_i64().map(|value| value != 0)
+    }
+
+    fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Byte(value)  => Some(i64::from(*value)),
+            Self::Short(value) => Some(i64::from(*value)),
+            Self::Int(value)   => Some(i64::from(*value)),
+            Self::Long(value)  => Some(*value),
+            Self::Float(_) | Self::Double(_) | Self::ByteArray(_) | Self::String(_)
+                | Self::ByteString(_) | Self::List(_) | Self::Compound(_)
+                | Self::IntArray(_) | Self::LongArray(_) => None,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Float(value)  => Some(f64::from(*value)),
+            Self::Double(value) => Some(*value),
+            _ => self.as_i64().map(|value| value as f64),
+        }
+    }
+
+    fn elements(&self) -> Option<Vec<&Self>> {
+        if let Self::List(list) = self {
+            Some(list.iter().map(unwrap_element).collect())
+        } else {
+            None
+        }
+    }
+
+    fn int_array(&self) -> Option<Vec<i32>> {
+        match self {
+            Self::IntArray(values) => Some(values.clone()),
+            Self::List(list)       => list
+                .iter()
+                .map(|element| match unwrap_element(element) {
+                    Self::Int(value) => Some(*value),
+                    _                => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn is_object(&self) -> bool {
+        matches!(self, Self::Compound(_))
+    }
+
+    fn field(&self, key: &str) -> Option<&Self> {
+        if let Self::Compound(compound) = self {
+            compound.get_tag(key)
+        } else {
+            None
+        }
+    }
+
+    fn to_compound(&self, field: &'static str) -> Result<NbtCompound, TextComponentError> {
+        if let Self::Compound(compound) = self {
+            Ok(compound.clone())
+        } else {
+            Err(invalid(field, "a compound", self))
+        }
+    }
+}
+
+/// Java 1.21.5 wraps the elements of heterogenous lists in compounds whose only key is `""`,
+/// which might not have been unwrapped when the NBT was read.
+fn unwrap_element(element: &NbtTag) -> &NbtTag {
+    if let NbtTag::Compound(compound) = element {
+        if compound.len() == 1 {
+            if let Some(tag) = compound.get_tag("") {
+                return tag;
+            }
+        }
+    }
+    element
+}
+
+// ================================
+//  Components
+// ================================
+
+pub(super) fn read_component<S: ComponentSource>(
+    source: &S,
+    depth:  u32,
+) -> Result<TextComponent, TextComponentError> {
+    if depth >= DEPTH_LIMIT {
+        return Err(TextComponentError::ExceededDepthLimit);
+    }
+
+    if let Some(text) = source.primitive_text() {
+        return Ok(TextComponent::text(text));
+    }
+
+    // The first element of a list is the parent of all the others.
+    if let Some(elements) = source.elements() {
+        let mut elements = elements.into_iter();
+        let Some(first) = elements.next() else {
+            return Err(TextComponentError::EmptyList("component"));
+        };
+
+        let mut component = read_component(first, depth + 1)?;
+        for element in elements {
+            component.extra.push(read_component(element, depth + 1)?);
+        }
+        return Ok(component);
+    }
+
+    if !source.is_object() {
+        return Err(invalid("component", "a string, list, or object", source));
+    }
+
+    Ok(TextComponent {
+        content: read_content(source, depth)?,
+        style:   read_style(source, depth)?,
+        extra:   component_list_field(source, "extra", depth)?,
+    })
+}
+
+fn read_content<S: ComponentSource>(
+    object: &S,
+    depth:  u32,
+) -> Result<TextContent, TextComponentError> {
+    let content_type = string_field(object, "type")?;
+    let content_type = match content_type.as_deref() {
+        Some(content_type) => content_type,
+        None => CONTENT_KEYS
+            .into_iter()
+            .find(|(key, _)| object.field(key).is_some())
+            .map(|(_, content_type)| content_type)
+            .ok_or(TextComponentError::MissingContent)?,
+    };
+
+    match content_type {
+        "text" => {
+            let text = object.field("text").ok_or(TextComponentError::MissingField("text"))?;
+            text.primitive_text()
+                .map(TextContent::Text)
+                .ok_or_else(|| invalid("text", "a string", text))
+        }
+        "translatable" => Ok(TextContent::Translatable {
+            key:      required_string_field(object, "translate")?,
+            fallback: string_field(object, "fallback")?,
+            with:     component_list_field(object, "with", depth)?,
+        }),
+        "score" => {
+            let score = object.field("score").ok_or(TextComponentError::MissingField("score"))?;
+            if !score.is_object() {
+                return Err(invalid("score", "an object", score));
+            }
+
+            Ok(TextContent::Score {
+                name:      required_string_field(score, "name")?,
+                objective: required_string_field(score, "objective")?,
+                value:     string_field(score, "value")?,
+            })
+        }
+        "selector" => Ok(TextContent::Selector {
+            selector:  required_string_field(object, "selector")?,
+            separator: component_field(object, "separator", depth)?.map(Box::new),
+        }),
+        "keybind" => Ok(TextContent::Keybind(required_string_field(object, "keybind")?)),
+        "nbt" => Ok(TextContent::Nbt {
+            path:      required_string_field(object, "nbt")?,
+            interpret: bool_field(object, "interpret")?,
+            separator: component_field(object, "separator", depth)?.map(Box::new),
+            source:    read_nbt_source(object)?,
+        }),
+        _ => Err(TextComponentError::UnknownValue {
+            field: "type",
+            value: content_type.to_owned(),
+        }),
+    }
+}
+
+fn read_nbt_source<S: ComponentSource>(object: &S) -> Result<NbtSource, TextComponentError> {
+    let source = string_field(object, "source")?;
+    let source = match source.as_deref() {
+        Some(source) => source,
+        None => ["block", "entity", "storage"]
+            .into_iter()
+            .find(|key| object.field(key).is_some())
+            .ok_or(TextComponentError::MissingField("source"))?,
+    };
+
+    match source {
+        "block"   => Ok(NbtSource::Block(required_string_field(object, "block")?)),
+        "entity"  => Ok(NbtSource::Entity(required_string_field(object, "entity")?)),
+        "storage" => Ok(NbtSource::Storage(required_string_field(object, "storage")?)),
+        _ => Err(TextComponentError::UnknownValue {
+            field: "source",
+            value: source.to_owned(),
+        }),
+    }
+}
+
+// ================================
+//  Styles
+// ================================
+
+fn read_style<S: ComponentSource>(
+    object: &S,
+    depth:  u32,
+) -> Result<TextStyle, TextComponentError> {
+    let color = match string_field(object, "color")? {
+        Some(color) => match TextColor::parse(&color) {
+            Some(parsed) => Some(parsed),
+            None => return Err(TextComponentError::UnknownValue {
+                field: "color",
+                value: color,
+            }),
+        },
+        None => None,
+    };
+
+    Ok(TextStyle {
+        color,
+        font:          string_field(object, "font")?,
+        bold:          bool_field(object, "bold")?,
+        italic:        bool_field(object, "italic")?,
+        underlined:    bool_field(object, "underlined")?,
+        strikethrough: bool_field(object, "strikethrough")?,
+        obfuscated:    bool_field(object, "obfuscated")?,
+        shadow_color:  read_shadow_color(object)?,
+        insertion:     string_field(object, "insertion")?,
+        click_event:   read_click_event(object)?,
+        hover_event:   read_hover_event(object, depth)?,
+    })
+}
+
+/// Shadow colors are either an ARGB integer, or a list of four floats from 0 to 1
+/// in RGBA order.
+fn read_shadow_color<S: ComponentSource>(object: &S) -> Result<Option<u32>, TextComponentError> {
+    let Some(color) = object.field("shadow_color") else {
+        return Ok(None);
+    };
+
+    if let Some(argb) = color.as_i64() {
+        // Minecraft writes the color as a signed integer
+        let argb = i32::try_from(argb)
+            .map(|argb| u32::from_be_bytes(argb.to_be_bytes()))
+            .or_else(|_err| u32::try_from(argb));
+        return argb.map(Some).map_err(|_err| invalid("shadow_color", "an ARGB color", color));
+    }
+
+    let channels = color
+        .elements()
+        .filter(|channels| channels.len() == 4)
+        .and_then(|channels| {
+            channels
+                .into_iter()
+                .map(ComponentSource::as_f64)
+                .collect::<Option<Vec<f64>>>()
+        })
+        .ok_or_else(|| invalid("shadow_color", "an ARGB color", color))?;
+
+    // The value is clamped to the range of a `u8`
+    let channel = |channel: f64| (channel.clamp(0., 1.) * 255.).round() as u32;
+    let [red, green, blue, alpha] = [0, 1, 2, 3].map(|index| channel(channels[index]));
+
+    Ok(Some(alpha << 24 | red << 16 | green << 8 | blue))
+}
+
+fn read_click_event<S: ComponentSource>(
+    object: &S,
+) -> Result<Option<ClickEvent>, TextComponentError> {
+    let Some(event) = object.field("click_event").or_else(|| object.field("clickEvent")) else {
+        return Ok(None);
+    };
+    if !event.is_object() {
+        return Err(invalid("click_event", "an object", event));
+    }
+
+    // Before Java 1.21.5, the argument of every action was stored in `value`.
+    let argument = |key: &'static str| {
+        if event.field("value").is_some() {
+            required_string_field(event, "value")
+        } else {
+            required_string_field(event, key)
+        }
+    };
+
+    let action = required_string_field(event, "action")?;
+    let click_event = match action.as_str() {
+        "open_url"          => ClickEvent::OpenUrl(argument("url")?),
+        "open_file"         => ClickEvent::OpenFile(argument("path")?),
+        "run_command"       => ClickEvent::RunCommand(argument("command")?),
+        "suggest_command"   => ClickEvent::SuggestCommand(argument("command")?),
+        "change_page"       => ClickEvent::ChangePage(read_page(event)?),
+        "copy_to_clipboard" => ClickEvent::CopyToClipboard(required_string_field(event, "value")?),
+        _ => return Err(TextComponentError::UnknownValue {
+            field: "action",
+            value: action,
+        }),
+    };
+
+    Ok(Some(click_event))
+}
+
+/// Before Java 1.21.5, the page was stored as a string in `value`.
+fn read_page<S: ComponentSource>(event: &S) -> Result<i32, TextComponentError> {
+    let (field, page) = match event.field("page") {
+        Some(page) => ("page", page),
+        None => (
+            "value",
+            event.field("value").ok_or(TextComponentError::MissingField("page"))?,
+        ),
+    };
+
+    page.as_i64()
+        .and_then(|page| i32::try_from(page).ok())
+        .or_else(|| page.as_str().and_then(|page| page.trim().parse().ok()))
+        .ok_or_else(|| invalid(field, "a page number", page))
+}
+
+fn read_hover_event<S: ComponentSource>(
+    object: &S,
+    depth:  u32,
+) -> Result<Option<HoverEvent>, TextComponentError> {
+    let Some(event) = object.field("hover_event").or_else(|| object.field("hoverEvent")) else {
+        return Ok(None);
+    };
+    if !event.is_object() {
+        return Err(invalid("hover_event", "an object", event));
+    }
+
+    let action = required_string_field(event, "action")?;
+    let hover_event = match action.as_str() {
+        "show_text" => {
+            // `contents` was used from Java 1.16 until 1.21.5, and `value` otherwise
+            let text = event
+                .field("contents")
+                .or_else(|| event.field("value"))
+                .ok_or(TextComponentError::MissingField("value"))?;
+            HoverEvent::ShowText(Box::new(read_component(text, depth + 1)?))
+        }
+        "show_item"   => read_show_item(event, depth)?,
+        "show_entity" => read_show_entity(event, depth)?,
+        _ => return Err(TextComponentError::UnknownValue {
+            field: "action",
+            value: action,
+        }),
+    };
+
+    Ok(Some(hover_event))
+}
+
+/// The data of the item is stored in the event itself since Java 1.21.5, in `contents` from
+/// Java 1.16 until then, and as SNBT in `value` before that.
+fn read_show_item<S: ComponentSource>(
+    event: &S,
+    depth: u32,
+) -> Result<HoverEvent, TextComponentError> {
+    let item = match event.field("contents") {
+        Some(contents) => {
+            // The contents may be only the ID of the item
+            if let Some(id) = contents.as_str() {
+                return Ok(HoverEvent::ShowItem {
+                    id:         id.to_owned(),
+                    count:      1,
+                    components: None,
+                    tag:        None,
+                });
+            }
+            contents
+        }
+        None => match event.field("value") {
+            Some(value) if event.field("id").is_none() => {
+                return read_snbt_show_item(value, depth);
+            }
+            _ => event,
+        },
+    };
+
+    let count = match item.field("count") {
+        Some(count) => count
+            .as_i64()
+            .and_then(|count| i32::try_from(count).ok())
+            .ok_or_else(|| invalid("count", "an int", count))?,
+        None => 1,
+    };
+    let tag = match string_field(item, "tag")? {
+        Some(tag) => Some(parse_snbt(&tag)?),
+        None      => None,
+    };
+
+    Ok(HoverEvent::ShowItem {
+        id:         required_string_field(item, "id")?,
+        count,
+        components: item
+            .field("components")
+            .map(|components| components.to_compound("components"))
+            .transpose()?,
+        tag,
+    })
+}
+
+/// Before Java 1.16, the value of a `show_item` event was a component whose text was
+/// the SNBT of the item.
+fn read_snbt_show_item<S: ComponentSource>(
+    value: &S,
+    depth: u32,
+) -> Result<HoverEvent, TextComponentError> {
+    let item = parse_snbt(&read_component(value, depth + 1)?.to_plain_text())?;
+
+    let Some(NbtTag::String(id)) = item.get_tag("id") else {
+        return Err(TextComponentError::MissingField("id"));
+    };
+    let count = match item.get_tag("Count") {
+        Some(NbtTag::Byte(count)) => i32::from(*count),
+        _                         => 1,
+    };
+    let tag = match item.get_tag("tag") {
+        Some(NbtTag::Compound(tag)) => Some(tag.clone()),
+        _                           => None,
+    };
+
+    Ok(HoverEvent::ShowItem {
+        id: id.clone(),
+        count,
+        components: None,
+        tag,
+    })
+}
+
+/// The data of the entity is stored in the event itself since Java 1.21.5, in `contents` from
+/// Java 1.16 until then, and as SNBT in `value` before that.
+fn read_show_entity<S: ComponentSource>(
+    event: &S,
+    depth: u32,
+) -> Result<HoverEvent, TextComponentError> {
+    let (entity, type_field, uuid_field) = match event.field("contents") {
+        Some(contents) => (contents, "type", "id"),
+        None => match event.field("value") {
+            Some(value) if event.field("uuid").is_none() => {
+                return read_snbt_show_entity(value, depth);
+            }
+            _ => (event, "id", "uuid"),
+        },
+    };
+
+    let uuid_value = entity
+        .field(uuid_field)
+        .ok_or(TextComponentError::MissingField(uuid_field))?;
+    let uuid = match uuid_value.as_str() {
+        Some(uuid) => parse_uuid(uuid),
+        None       => uuid_value.int_array().as_deref().and_then(uuid_from_ints),
+    };
+
+    Ok(HoverEvent::ShowEntity {
+        entity_type: required_string_field(entity, type_field)?,
+        uuid:        uuid.ok_or_else(|| invalid(uuid_field, "a UUID", uuid_value))?,
+        name:        component_field(entity, "name", depth)?.map(Box::new),
+    })
+}
+
+/// Before Java 1.16, the value of a `show_entity` event was a component whose text was
+/// SNBT containing the type, UUID string, and JSON name of the entity.
+fn read_snbt_show_entity<S: ComponentSource>(
+    value: &S,
+    depth: u32,
+) -> Result<HoverEvent, TextComponentError> {
+    let entity = parse_snbt(&read_component(value, depth + 1)?.to_plain_text())?;
+
+    let Some(NbtTag::String(entity_type)) = entity.get_tag("type") else {
+        return Err(TextComponentError::MissingField("type"));
+    };
+    let Some(NbtTag::String(uuid)) = entity.get_tag("id") else {
+        return Err(TextComponentError::MissingField("id"));
+    };
+    let name = match entity.get_tag("name") {
+        Some(NbtTag::String(name)) => Some(Box::new(TextComponent::from_json_str(name)?)),
+        _                          => None,
+    };
+
+    Ok(HoverEvent::ShowEntity {
+        entity_type: entity_type.clone(),
+        uuid:        parse_uuid(uuid).ok_or_else(|| TextComponentError::UnknownValue {
+            field: "id",
+            value: uuid.clone(),
+        })?,
+        name,
+    })
+}
+
+fn parse_snbt(snbt: &str) -> Result<NbtCompound, TextComponentError> {
+    NbtCompound::from_snbt(snbt, SnbtParseOptions::default_original())
+        .map_err(|err| TextComponentError::InvalidNbt(err.to_string()))
+}
+
+/// Parses a UUID in its usual hyphenated form, or as 32 hex digits.
+fn parse_uuid(uuid: &str) -> Option<u128> {
+    let hex = uuid.replace('-', "");
+    if hex.len() != 32 || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    u128::from_str_radix(&hex, 16).ok()
+}
+
+/// Parses a UUID stored as four ints, from most to least significant.
+fn uuid_from_ints(ints: &[i32]) -> Option<u128> {
+    let &[a, b, c, d] = ints else {
+        return None;
+    };
+    Some([a, b, c, d].into_iter().fold(0, |uuid, int| {
+        uuid << 32 | u128::from(u32::from_be_bytes(int.to_be_bytes()))
+    }))
+}
+
+// ================================
+//  Fields
+// ================================
+
+fn invalid<S: ComponentSource>(
+    field:    &'static str,
+    expected: &'static str,
+    found:    &S,
+) -> TextComponentError {
+    TextComponentError::InvalidField {
+        field,
+        expected,
+        found: found.kind(),
+    }
+}
+
+fn string_field<S: ComponentSource>(
+    object: &S,
+    field:  &'static str,
+) -> Result<Option<String>, TextComponentError> {
+    object
+        .field(field)
+        .map(|value| {
+            value
+                .as_str()
+                .map(str::to_owned)
+                .ok_or_else(|| invalid(field, "a string", value))
+        })
+        .transpose()
+}
+
+fn required_string_field<S: ComponentSource>(
+    object: &S,
+    field:  &'static str,
+) -> Result<String, TextComponentError> {
+    string_field(object, field)?.ok_or(TextComponentError::MissingField(field))
+}
+
+fn bool_field<S: ComponentSource>(
+    object: &S,
+    field:  &'static str,
+) -> Result<Option<bool>, TextComponentError> {
+    object
+        .field(field)
+        .map(|value| value.as_bool().ok_or_else(|| invalid(field, "a boolean", value)))
+        .transpose()
+}
+
+fn component_field<S: ComponentSource>(
+    object: &S,
+    field:  &'static str,
+    depth:  u32,
+) -> Result<Option<TextComponent>, TextComponentError> {
+    object
+        .field(field)
+        .map(|value| read_component(value, depth + 1))
+        .transpose()
+}
+
+fn component_list_field<S: ComponentSource>(
+    object: &S,
+    field:  &'static str,
+    depth:  u32,
+) -> Result<Vec<TextComponent>, TextComponentError> {
+    let Some(list) = object.field(field) else {
+        return Ok(Vec::new());
+    };
+    let elements = list.elements().ok_or_else(|| invalid(field, "a list", list))?;
+
+    elements
+        .into_iter()
+        .map(|element| read_component(element, depth + 1))
+        .collect()
+}
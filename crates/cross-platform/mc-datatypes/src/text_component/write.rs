@@ -0,0 +1,298 @@
+use std::borrow::Cow;
+
+use serde_json::Value;
+
+use prismarine_anchor_nbt::{NbtCompound, NbtTag};
+use prismarine_anchor_nbt::json::compound_to_plain_json;
+
+use super::{
+    ClickEvent, HoverEvent, NbtSource, TextComponent, TextComponentFormat, TextContent, TextStyle,
+};
+
+
+/// A text component in the form it is written, which is converted into either JSON or NBT.
+#[derive(Debug)]
+pub(super) enum Output<'a> {
+    String(Cow<'a, str>),
+    Bool(bool),
+    Int(i32),
+    IntArray([i32; 4]),
+    List(Vec<Self>),
+    Object(Vec<(&'static str, Self)>),
+    Compound(&'a NbtCompound),
+}
+
+impl<'a> Output<'a> {
+    #[inline]
+    fn str(string: &'a str) -> Self {
+        Self::String(Cow::Borrowed(string))
+    }
+}
+
+pub(super) fn json_value(output: Output<'_>) -> Value {
+    match output {
+        Output::String(string)     => Value::String(string.into_owned()),
+        Output::Bool(value)        => Value::Bool(value),
+        Output::Int(value)         => Value::from(value),
+        Output::IntArray(values)   => Value::from(values.as_slice()),
+        Output::List(elements)     => Value::Array(elements.into_iter().map(json_value).collect()),
+        Output::Object(fields)     => Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_owned(), json_value(value)))
+                .collect(),
+        ),
+        Output::Compound(compound) => compound_to_plain_json(compound),
+    }
+}
+
+pub(super) fn nbt_tag(output: Output<'_>) -> NbtTag {
+    match output {
+        Output::String(string)     => NbtTag::String(string.into_owned()),
+        Output::Bool(value)        => NbtTag::Byte(i8::from(value)),
+        Output::Int(value)         => NbtTag::Int(value),
+        Output::IntArray(values)   => NbtTag::IntArray(values.to_vec()),
+        Output::List(elements)     => NbtTag::List(elements.into_iter().map(nbt_tag).collect()),
+        Output::Object(fields)     => NbtTag::Compound(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_owned(), nbt_tag(value)))
+                .collect(),
+        ),
+        Output::Compound(compound) => NbtTag::Compound(compound.clone()),
+    }
+}
+
+pub(super) fn component_output(
+    component: &TextComponent,
+    format:    TextComponentFormat,
+) -> Output<'_> {
+    if let TextContent::Text(text) = &component.content {
+        if component.is_plain_text() {
+            return Output::str(text);
+        }
+    }
+
+    let mut fields = Vec::new();
+    component.content.write_fields(&mut fields, format);
+    write_style_fields(&component.style, &mut fields, format);
+    if !component.extra.is_empty() {
+        fields.push(("extra", component_list(&component.extra, format)));
+    }
+    Output::Object(fields)
+}
+
+impl TextContent {
+    fn write_fields<'a>(
+        &'a self,
+        fields: &mut Vec<(&'static str, Output<'a>)>,
+        format: TextComponentFormat,
+    ) {
+        match self {
+            Self::Text(text) => fields.push(("text", Output::str(text))),
+            Self::Translatable { key, fallback, with } => {
+                fields.push(("translate", Output::str(key)));
+                if let Some(fallback) = fallback {
+                    fields.push(("fallback", Output::str(fallback)));
+                }
+                if !with.is_empty() {
+                    fields.push(("with", component_list(with, format)));
+                }
+            }
+            Self::Score { name, objective, value } => {
+                let mut score = vec![
+                    ("name",      Output::str(name)),
+                    ("objective", Output::str(objective)),
+                ];
+                if let Some(value) = value {
+                    score.push(("value", Output::str(value)));
+                }
+                fields.push(("score", Output::Object(score)));
+            }
+            Self::Selector { selector, separator } => {
+                fields.push(("selector", Output::str(selector)));
+                if let Some(separator) = separator {
+                    fields.push(("separator", component_output(separator, format)));
+                }
+            }
+            Self::Keybind(keybind) => fields.push(("keybind", Output::str(keybind))),
+            Self::Nbt { path, interpret, separator, source } => {
+                fields.push(("nbt", Output::str(path)));
+                if let Some(interpret) = interpret {
+                    fields.push(("interpret", Output::Bool(*interpret)));
+                }
+                if let Some(separator) = separator {
+                    fields.push(("separator", component_output(separator, format)));
+                }
+                fields.push(match source {
+                    NbtSource::Block(block)     => ("block",   Output::str(block)),
+                    NbtSource::Entity(entity)   => ("entity",  Output::str(entity)),
+                    NbtSource::Storage(storage) => ("storage", Output::str(storage)),
+                });
+            }
+        }
+    }
+}
+
+fn write_style_fields<'a>(
+    style:  &'a TextStyle,
+    fields: &mut Vec<(&'static str, Output<'a>)>,
+    format: TextComponentFormat,
+) {
+    if let Some(color) = style.color {
+        fields.push(("color", Output::String(Cow::Owned(color.to_string()))));
+    }
+    if let Some(font) = &style.font {
+        fields.push(("font", Output::str(font)));
+    }
+
+    let formats = [
+        ("bold",          style.bold),
+        ("italic",        style.italic),
+        ("underlined",    style.underlined),
+        ("strikethrough", style.strikethrough),
+        ("obfuscated",    style.obfuscated),
+    ];
+    for (field, enabled) in formats {
+        if let Some(enabled) = enabled {
+            fields.push((field, Output::Bool(enabled)));
+        }
+    }
+
+    if let Some(shadow_color) = style.shadow_color {
+        // Minecraft writes the color as a signed integer
+        let shadow_color = i32::from_be_bytes(shadow_color.to_be_bytes());
+        fields.push(("shadow_color", Output::Int(shadow_color)));
+    }
+    if let Some(insertion) = &style.insertion {
+        fields.push(("insertion", Output::str(insertion)));
+    }
+
+    if let Some(click_event) = &style.click_event {
+        let field = match format {
+            TextComponentFormat::Legacy => "clickEvent",
+            TextComponentFormat::Modern => "click_event",
+        };
+        fields.push((field, click_event.to_output(format)));
+    }
+    if let Some(hover_event) = &style.hover_event {
+        let field = match format {
+            TextComponentFormat::Legacy => "hoverEvent",
+            TextComponentFormat::Modern => "hover_event",
+        };
+        fields.push((field, hover_event.to_output(format)));
+    }
+}
+
+impl ClickEvent {
+    fn to_output(&self, format: TextComponentFormat) -> Output<'_> {
+        let (action, field, argument) = match self {
+            Self::OpenUrl(url)            => ("open_url",          "url",     Output::str(url)),
+            Self::OpenFile(path)          => ("open_file",         "path",    Output::str(path)),
+            Self::RunCommand(cmd)         => ("run_command",       "command", Output::str(cmd)),
+            Self::SuggestCommand(cmd)     => ("suggest_command",   "command", Output::str(cmd)),
+            Self::ChangePage(page)        => ("change_page",       "page",    Output::Int(*page)),
+            Self::CopyToClipboard(value)  => ("copy_to_clipboard", "value",   Output::str(value)),
+        };
+
+        let (field, argument) = match (format, self) {
+            (TextComponentFormat::Legacy, Self::ChangePage(page)) => {
+                ("value", Output::String(Cow::Owned(page.to_string())))
+            }
+            (TextComponentFormat::Legacy, _) => ("value", argument),
+            (TextComponentFormat::Modern, _) => (field, argument),
+        };
+
+        Output::Object(vec![
+            ("action", Output::str(action)),
+            (field,    argument),
+        ])
+    }
+}
+
+impl HoverEvent {
+    fn to_output(&self, format: TextComponentFormat) -> Output<'_> {
+        let mut fields = vec![("action", Output::str(self.action()))];
+
+        match (format, self) {
+            (TextComponentFormat::Legacy, Self::ShowText(text)) => {
+                fields.push(("contents", component_output(text, format)));
+            }
+            (TextComponentFormat::Modern, Self::ShowText(text)) => {
+                fields.push(("value", component_output(text, format)));
+            }
+            (_, Self::ShowItem { id, count, components, tag }) => {
+                let mut item = vec![
+                    ("id",    Output::str(id)),
+                    ("count", Output::Int(*count)),
+                ];
+                if let Some(components) = components {
+                    item.push(("components", Output::Compound(components)));
+                }
+
+                if format == TextComponentFormat::Legacy {
+                    if let Some(tag) = tag {
+                        item.push(("tag", Output::String(Cow::Owned(tag.to_snbt()))));
+                    }
+                    fields.push(("contents", Output::Object(item)));
+                } else {
+                    fields.extend(item);
+                }
+            }
+            (TextComponentFormat::Legacy, Self::ShowEntity { entity_type, uuid, name }) => {
+                let mut entity = vec![
+                    ("type", Output::str(entity_type)),
+                    ("id",   Output::String(Cow::Owned(uuid_string(*uuid)))),
+                ];
+                if let Some(name) = name {
+                    entity.push(("name", component_output(name, format)));
+                }
+                fields.push(("contents", Output::Object(entity)));
+            }
+            (TextComponentFormat::Modern, Self::ShowEntity { entity_type, uuid, name }) => {
+                fields.push(("id",   Output::str(entity_type)));
+                fields.push(("uuid", Output::IntArray(uuid_ints(*uuid))));
+                if let Some(name) = name {
+                    fields.push(("name", component_output(name, format)));
+                }
+            }
+        }
+
+        Output::Object(fields)
+    }
+
+    fn action(&self) -> &'static str {
+        match self {
+            Self::ShowText(_)       => "show_text",
+            Self::ShowItem { .. }   => "show_item",
+            Self::ShowEntity { .. } => "show_entity",
+        }
+    }
+}
+
+fn component_list(
+    components: &[TextComponent],
+    format:     TextComponentFormat,
+) -> Output<'_> {
+    Output::List(
+        components
+            .iter()
+            .map(|component| component_output(component, format))
+            .collect(),
+    )
+}
+
+/// Formats a UUID in its usual hyphenated form.
+fn uuid_string(uuid: u128) -> String {
+    let hex = format!("{uuid:032x}");
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+/// Splits a UUID into four ints, from most to least significant.
+fn uuid_ints(uuid: u128) -> [i32; 4] {
+    [96, 64, 32, 0].map(|shift| {
+        // Truncate to the 32 bits at the shift
+        let bits = (uuid >> shift) as u32;
+        i32::from_be_bytes(bits.to_be_bytes())
+    })
+}
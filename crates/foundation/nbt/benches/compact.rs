@@ -0,0 +1,197 @@
+//! Compares the speed of `NbtCompound`s against an `NbtArena`,
+//! for a large number of compounds similar to block entities and actors.
+//!
+//! Run with `cargo bench --package prismarine-anchor-nbt --bench compact`.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use prismarine_anchor_nbt::{io, IoOptions, NbtCompound, NbtList, NbtTag};
+use prismarine_anchor_nbt::compact::{CompactCompoundId, NbtArena};
+
+
+const COMPOUND_COUNT: usize = 10_000;
+
+fn opts() -> IoOptions {
+    IoOptions::bedrock_uncompressed()
+}
+
+/// The sample compounds, both as `NbtCompound`s and encoded as NBT.
+fn sample_data() -> (Vec<NbtCompound>, Vec<Vec<u8>>) {
+    let compounds = (0..COMPOUND_COUNT).map(sample_compound).collect::<Vec<_>>();
+
+    let encoded = compounds
+        .iter()
+        .map(|compound| {
+            let mut bytes = Vec::new();
+            io::write_compound(&mut bytes, opts(), None, compound)
+                .expect("writing to a Vec succeeds");
+            bytes
+        })
+        .collect();
+
+    (compounds, encoded)
+}
+
+fn sample_arena(compounds: &[NbtCompound]) -> (NbtArena, Vec<CompactCompoundId>) {
+    let mut arena = NbtArena::new();
+    let ids = compounds
+        .iter()
+        .map(|compound| arena.insert_compound(compound))
+        .collect();
+    (arena, ids)
+}
+
+fn read(c: &mut Criterion) {
+    let (_, encoded) = sample_data();
+
+    let mut group = c.benchmark_group("read");
+    group.bench_function("NbtCompound", |b| {
+        b.iter(|| {
+            black_box(&encoded)
+                .iter()
+                .map(|bytes| {
+                    io::read_compound(&mut bytes.as_slice(), opts()).expect("valid NBT").0
+                })
+                .collect::<Vec<_>>()
+        });
+    });
+    group.bench_function("NbtArena", |b| {
+        b.iter(|| {
+            let mut arena = NbtArena::new();
+            let ids = black_box(&encoded)
+                .iter()
+                .map(|bytes| {
+                    arena.read_compound(&mut bytes.as_slice(), opts()).expect("valid NBT").0
+                })
+                .collect::<Vec<_>>();
+            (arena, ids)
+        });
+    });
+    group.finish();
+}
+
+fn convert(c: &mut Criterion) {
+    let (compounds, _) = sample_data();
+    let (arena, ids) = sample_arena(&compounds);
+
+    let mut group = c.benchmark_group("convert");
+    group.bench_function("NbtCompound into NbtArena", |b| {
+        b.iter(|| sample_arena(black_box(&compounds)));
+    });
+    group.bench_function("NbtArena into NbtCompound", |b| {
+        b.iter(|| {
+            black_box(&ids)
+                .iter()
+                .map(|&id| arena.compound(id).to_compound())
+                .collect::<Vec<_>>()
+        });
+    });
+    group.finish();
+}
+
+fn write(c: &mut Criterion) {
+    let (compounds, _) = sample_data();
+    let (arena, ids) = sample_arena(&compounds);
+
+    let mut group = c.benchmark_group("write");
+    group.bench_function("NbtCompound", |b| {
+        b.iter(|| {
+            let mut bytes = Vec::new();
+            for compound in black_box(&compounds) {
+                io::write_compound(&mut bytes, opts(), None, compound)
+                    .expect("writing to a Vec succeeds");
+            }
+            bytes
+        });
+    });
+    group.bench_function("NbtArena", |b| {
+        b.iter(|| {
+            let mut bytes = Vec::new();
+            for &id in black_box(&ids) {
+                arena.write_compound(&mut bytes, opts(), None, id)
+                    .expect("writing to a Vec succeeds");
+            }
+            bytes
+        });
+    });
+    group.finish();
+}
+
+fn look_up(c: &mut Criterion) {
+    let (compounds, _) = sample_data();
+    let (arena, ids) = sample_arena(&compounds);
+
+    let mut group = c.benchmark_group("look_up_key");
+    group.bench_function("NbtCompound", |b| {
+        b.iter(|| {
+            black_box(&compounds)
+                .iter()
+                .filter(|compound| compound.get_tag("isMovable").is_some())
+                .count()
+        });
+    });
+    group.bench_function("NbtArena", |b| {
+        b.iter(|| {
+            black_box(&ids)
+                .iter()
+                .filter(|&&id| arena.compound(id).contains_key("isMovable"))
+                .count()
+        });
+    });
+    group.finish();
+}
+
+/// Alternates between chests holding items, signs, and zombies.
+#[expect(clippy::cast_possible_truncation, clippy::cast_possible_wrap, reason = "sample data")]
+fn sample_compound(index: usize) -> NbtCompound {
+    let mut compound = NbtCompound::new();
+    compound.insert("x", index as i32);
+    compound.insert("y", (index % 320) as i32 - 64);
+    compound.insert("z", -(index as i32));
+    compound.insert("isMovable", 1_i8);
+
+    match index % 3 {
+        0 => {
+            compound.insert("id", "Chest");
+            let items = (0..(index % 27))
+                .map(|slot| {
+                    let mut item = NbtCompound::new();
+                    item.insert("Name", "minecraft:cobblestone");
+                    item.insert("Count", (slot % 64) as i8);
+                    item.insert("Damage", 0_i16);
+                    item.insert("Slot", slot as i8);
+                    item.insert("WasPickedUp", 0_i8);
+                    NbtTag::Compound(item)
+                })
+                .collect::<NbtList>();
+            compound.insert("Items", items);
+        }
+        1 => {
+            compound.insert("id", "Sign");
+            let mut text = NbtCompound::new();
+            text.insert("Text", format!("Sign number {index}, placed by a player"));
+            text.insert("SignTextColor", -16_777_216_i32);
+            text.insert("IgnoreLighting", 0_i8);
+            compound.insert("FrontText", text.clone());
+            compound.insert("BackText", text);
+        }
+        _ => {
+            compound.insert("identifier", "minecraft:zombie");
+            compound.insert("UniqueID", index as i64 * 4099);
+            compound.insert("Pos", NbtList::from(vec![index as f32, 64.0, -(index as f32)]));
+            compound.insert("Rotation", NbtList::from(vec![90.0_f32, 0.0]));
+            compound.insert("definitions", NbtList::from(vec![
+                "+minecraft:zombie",
+                "+minecraft:zombie_adult",
+                "+minecraft:can_have_equipment",
+            ]));
+        }
+    }
+
+    compound
+}
+
+criterion_group!(benches, read, convert, write, look_up);
+criterion_main!(benches);
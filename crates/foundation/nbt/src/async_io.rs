@@ -0,0 +1,837 @@
+//! Asynchronous reading and writing of binary NBT data, on the [`AsyncRead`] and [`AsyncWrite`]
+//! traits of `futures-io`.
+//!
+//! The functions here mirror those of the [`io`] module, and support the same compression and
+//! [`IoOptions`]. Data is read from and written to the async reader or writer in chunks, so the
+//! executor is never blocked waiting on IO. The entries of a root compound are read and written
+//! one at a time, so that a large file need not be buffered in full; other root tags are
+//! buffered whole.
+//!
+//! When reading uncompressed data, nothing past the end of the NBT data is read, so the
+//! reader may be used to read any data which follows. Compressed data is read in chunks,
+//! and so may be read past its end.
+//!
+//! Runtimes with their own IO traits, such as Tokio, usually provide compatibility wrappers
+//! for the `futures-io` traits.
+//!
+//! # Examples:
+//! ```
+//! use futures_io::{AsyncRead, AsyncWrite};
+//! use prismarine_anchor_nbt::{async_io, io::NbtIoError, IoOptions};
+//!
+//! async fn copy_compound<R, W>(reader: &mut R, writer: &mut W) -> Result<(), NbtIoError>
+//! where
+//!     R: AsyncRead + Unpin,
+//!     W: AsyncWrite + Unpin,
+//! {
+//!     let opts = IoOptions::java();
+//!     let (compound, root_name) = async_io::read_compound(reader, opts).await?;
+//!     async_io::write_compound(writer, opts, Some(&root_name), &compound).await
+//! }
+//! ```
+//!
+//! [`io`]: crate::io
+
+use std::future;
+use std::pin::Pin;
+use std::task::Poll;
+use std::io::{self, BufRead, Read, Write};
+
+use flate2::Compression;
+use flate2::{
+    bufread::{GzDecoder, ZlibDecoder},
+    write::{GzEncoder, ZlibEncoder},
+};
+use futures_io::{AsyncRead, AsyncWrite};
+
+use crate::raw::{self, BudgetTracker};
+use crate::{
+    io::{read_string, write_tag_body, NbtIoError},
+    raw::{COMPOUND_ID, TAG_END_ID},
+    settings::{IoOptions, NbtCompression},
+    stream::{read_tag_body, scan::{Scan, Scanned, TagScanner}},
+    tag::NbtCompound,
+};
+#[cfg(feature = "allow_any_root")]
+use crate::{raw::LIST_ID, tag::{NbtList, NbtTag}};
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
+
+
+/// The number of bytes which are read from an async reader, decompressed, or written to an
+/// async writer at once.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+// ================================
+//  Reading functions
+// ================================
+
+/// Reads the given encoding of NBT compound data from the given async reader,
+/// returning the resulting NBT compound and associated root name.
+///
+/// See [`io::read_compound`].
+///
+/// [`io::read_compound`]: crate::io::read_compound
+pub async fn read_compound<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    opts:   IoOptions,
+) -> Result<(NbtCompound, String), NbtIoError> {
+    let mut source = AsyncSource::new(reader, opts);
+
+    source.buffer(Scanned::Header).await?;
+    let root_name = source
+        .parse(|reader, budget| read_root_header(reader, opts, budget, COMPOUND_ID))
+        .await?;
+
+    let mut compound = NbtCompound::new();
+    loop {
+        source.buffer(Scanned::Entry { depth: 1 }).await?;
+        let entry = source.parse(|reader, budget| {
+            let tag_id = raw::read_u8(reader, opts)?;
+            if tag_id == TAG_END_ID {
+                return Ok(None);
+            }
+
+            if 0 == opts.depth_limit.0 {
+                return Err(NbtIoError::ExceededDepthLimit {
+                    limit: opts.depth_limit,
+                });
+            }
+
+            budget.charge_tags(1)?;
+            let name = read_string(reader, opts, budget)?;
+            let tag = read_tag_body(reader, opts, budget, tag_id, 1)?;
+            Ok(Some((name, tag)))
+        });
+
+        match entry.await? {
+            Some((name, tag)) => compound.insert(name, tag),
+            None              => return Ok((compound, root_name)),
+        }
+    }
+}
+
+/// Reads the given encoding of NBT list data from the given async reader,
+/// returning the resulting NBT list and associated root name.
+///
+/// See [`io::read_list`].
+///
+/// [`io::read_list`]: crate::io::read_list
+#[cfg(feature = "allow_any_root")]
+pub async fn read_list<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    opts:   IoOptions,
+) -> Result<(NbtList, String), NbtIoError> {
+    let mut source = AsyncSource::new(reader, opts);
+
+    source.buffer(Scanned::Root { named: true }).await?;
+    source
+        .parse(|reader, budget| {
+            let root_name = read_root_header(reader, opts, budget, LIST_ID)?;
+            match read_tag_body(reader, opts, budget, LIST_ID, 0)? {
+                NbtTag::List(list) => Ok((list, root_name)),
+                _ => unreachable!(),
+            }
+        })
+        .await
+}
+
+/// Reads the given encoding of NBT data from the given async reader,
+/// returning the resulting NBT tag and associated root name.
+///
+/// See [`io::read_any_nbt`].
+///
+/// [`io::read_any_nbt`]: crate::io::read_any_nbt
+#[cfg(feature = "allow_any_root")]
+pub async fn read_any_nbt<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    opts:   IoOptions,
+) -> Result<(NbtTag, String), NbtIoError> {
+    let mut source = AsyncSource::new(reader, opts);
+
+    source.buffer(Scanned::Root { named: true }).await?;
+    source
+        .parse(|reader, budget| {
+            let root_id = raw::read_u8(reader, opts)?;
+            let root_name = read_string(reader, opts, budget)?;
+            read_tag_body(reader, opts, budget, root_id, 0).map(|tag| (tag, root_name))
+        })
+        .await
+}
+
+/// Reads the given encoding of NBT data with no root name from the given async reader,
+/// returning the resulting NBT tag.
+///
+/// See [`io::read_any_unnamed_nbt`].
+///
+/// [`io::read_any_unnamed_nbt`]: crate::io::read_any_unnamed_nbt
+#[cfg(feature = "allow_any_root")]
+pub async fn read_any_unnamed_nbt<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    opts:   IoOptions,
+) -> Result<NbtTag, NbtIoError> {
+    let mut source = AsyncSource::new(reader, opts);
+
+    source.buffer(Scanned::Root { named: false }).await?;
+    source
+        .parse(|reader, budget| {
+            let root_id = raw::read_u8(reader, opts)?;
+            read_tag_body(reader, opts, budget, root_id, 0)
+        })
+        .await
+}
+
+/// Deserializes the given type from binary NBT data read from the given async reader.
+///
+/// The NBT data must start with a compound tag and represent the type `T` correctly, else the
+/// deserializer will return with an error. The decompressed NBT data is buffered in full
+/// before being deserialized.
+///
+/// See [`serde::deserialize_from`].
+///
+/// [`serde::deserialize_from`]: crate::serde::deserialize_from
+#[cfg(feature = "serde")]
+pub async fn deserialize_from<R: AsyncRead + Unpin, T: DeserializeOwned>(
+    reader: &mut R,
+    opts:   IoOptions,
+) -> Result<(T, String), NbtIoError> {
+    let mut source = AsyncSource::new(reader, opts);
+    source.retain = true;
+
+    // Buffer the root compound one entry at a time. If the data is invalid or ends early,
+    // everything buffered is deserialized, so that the deserializer reports the error.
+    let mut scanned = Scanned::Header;
+    let len = loop {
+        let Some(end) = source.buffer(scanned).await? else {
+            break source.buffer.len();
+        };
+
+        let at_end = if matches!(scanned, Scanned::Header) {
+            source.buffer.first() != Some(&COMPOUND_ID)
+        } else {
+            source.unparsed().first() == Some(&TAG_END_ID)
+        };
+
+        source.pos = end;
+        if at_end {
+            break end;
+        }
+        scanned = Scanned::Entry { depth: 1 };
+    };
+
+    let opts = IoOptions {
+        compression: NbtCompression::Uncompressed,
+        ..opts
+    };
+    let buffer = source.buffer.get(..len).unwrap_or_default();
+    let (value, root_name) = crate::serde::deserialize_from_buffer(buffer, opts)?;
+    Ok((value, root_name.into_owned()))
+}
+
+/// Read the Bedrock Edition NBT header from the given async reader.
+///
+/// See [`io::read_bedrock_header`].
+///
+/// [`io::read_bedrock_header`]: crate::io::read_bedrock_header
+pub async fn read_bedrock_header<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    opts:   IoOptions,
+) -> Result<(i32, usize), NbtIoError> {
+    let mut header = [0; 8];
+    read_exact(reader, &mut header).await?;
+    crate::io::read_bedrock_header(&mut header.as_slice(), opts)
+}
+
+/// Reads the ID and name of the root tag, which must have the given ID.
+fn read_root_header(
+    reader:  &mut &[u8],
+    opts:    IoOptions,
+    budget:  &BudgetTracker,
+    root_id: u8,
+) -> Result<String, NbtIoError> {
+    let found = raw::read_u8(reader, opts)?;
+    if found != root_id {
+        return Err(NbtIoError::TagTypeMismatch {
+            expected: root_id,
+            found,
+        });
+    }
+
+    read_string(reader, opts, budget)
+}
+
+async fn read_exact<R: AsyncRead + Unpin>(reader: &mut R, mut buf: &mut [u8]) -> io::Result<()> {
+    while !buf.is_empty() {
+        match future::poll_fn(|cx| Pin::new(&mut *reader).poll_read(cx, buf)).await {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(len) => buf = &mut buf[len..],
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+// ================================
+//  Buffering
+// ================================
+
+/// Reads and decompresses data from an async reader into a buffer, from which it is parsed.
+///
+/// Before being parsed, data is scanned with a `TagScanner` until it is entirely buffered,
+/// so that the reader is only waited on for data which is part of the NBT data, and so that
+/// data is not parsed more than once.
+struct AsyncSource<'a, R> {
+    reader:  &'a mut R,
+    opts:    IoOptions,
+    decoder: Decoder,
+    budget:  BudgetTracker,
+    /// Decompressed data. The bytes before `pos` have been parsed.
+    buffer:  Vec<u8>,
+    pos:     usize,
+    /// Whether parsed data is kept in the buffer, instead of being discarded.
+    retain:  bool,
+    /// Whether all of the decompressed data is in the buffer.
+    eof:     bool,
+    /// The error which stopped decompression, if any, which is returned if more data is needed.
+    error:   Option<io::Error>,
+}
+
+impl<'a, R: AsyncRead + Unpin> AsyncSource<'a, R> {
+    fn new(reader: &'a mut R, opts: IoOptions) -> Self {
+        Self {
+            reader,
+            opts,
+            decoder: Decoder::new(opts.compression),
+            budget:  BudgetTracker::new(opts.memory_budget),
+            buffer:  Vec::new(),
+            pos:     0,
+            retain:  false,
+            eof:     false,
+            error:   None,
+        }
+    }
+
+    #[inline]
+    fn unparsed(&self) -> &[u8] {
+        &self.buffer[self.pos..]
+    }
+
+    /// Buffers the given data, which starts at the unparsed data, and returns the offset of its
+    /// end in the buffer. Returns `None` if the data is invalid or ends early; parsing the data
+    /// then returns the error.
+    async fn buffer(&mut self, scanned: Scanned) -> io::Result<Option<usize>> {
+        let mut scanner = TagScanner::new(self.opts, scanned);
+
+        loop {
+            match scanner.scan(self.unparsed()) {
+                Scan::Complete(len) => return Ok(Some(self.pos + len)),
+                Scan::Incomplete { needed } if !self.eof => self.fill(needed).await?,
+                Scan::Incomplete { .. } | Scan::Invalid => return Ok(None),
+            }
+        }
+    }
+
+    /// Runs the parser on the unparsed data. If it runs out of data, which should only happen
+    /// if the data was not buffered, it is rerun with more data and with the budget as it was
+    /// before the failed attempt.
+    async fn parse<T, F>(&mut self, mut parser: F) -> Result<T, NbtIoError>
+    where
+        F: FnMut(&mut &[u8], &BudgetTracker) -> Result<T, NbtIoError>,
+    {
+        loop {
+            let budget = self.budget.clone();
+            let mut unparsed = self.unparsed();
+
+            match parser(&mut unparsed, &budget) {
+                Ok(value) => {
+                    self.pos = self.buffer.len() - unparsed.len();
+                    self.budget = budget;
+                    return Ok(value);
+                }
+                Err(NbtIoError::StdIo(err)) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                    if self.eof {
+                        // Running out of data is better explained by a decompression error
+                        return Err(NbtIoError::StdIo(self.error.take().unwrap_or(err)));
+                    }
+                    self.fill(self.unparsed().len() + 1).await?;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Decompresses more data into the buffer, after discarding the parsed data unless it is
+    /// retained. The reader is waited on until at least `needed` bytes are unparsed, which must
+    /// be known to be part of the NBT data.
+    ///
+    /// If the data is compressed, up to `CHUNK_SIZE` more bytes are then decompressed if they
+    /// are available without waiting. Uncompressed data is never read past `needed` bytes,
+    /// so that the reader is left at the end of the NBT data.
+    async fn fill(&mut self, needed: usize) -> io::Result<()> {
+        if !self.retain {
+            self.buffer.drain(..self.pos);
+            self.pos = 0;
+        }
+
+        let needed = self.pos + needed;
+        let target = if matches!(self.decoder, Decoder::Uncompressed(_)) {
+            needed
+        } else {
+            needed.max(self.buffer.len() + CHUNK_SIZE)
+        };
+
+        while self.buffer.len() < target && !self.eof {
+            let len = self.buffer.len();
+            self.buffer.resize(len + (target - len).min(CHUNK_SIZE), 0);
+            let result = self.decoder.read(&mut self.buffer[len..]);
+            self.buffer.truncate(len + *result.as_ref().unwrap_or(&0));
+
+            match result {
+                Ok(0) => self.eof = true,
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    let wait = self.buffer.len() < needed;
+                    if !self.read_input(wait, target - self.buffer.len()).await? {
+                        break;
+                    }
+                }
+                Err(err) => {
+                    self.error = Some(err);
+                    self.eof = true;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads data from the reader into the input of the decoder. If `wait` is false and no data
+    /// is available without waiting, returns false. If the data is uncompressed, at most
+    /// `remaining` bytes are read, which is the amount of decompressed data still wanted.
+    async fn read_input(&mut self, wait: bool, remaining: usize) -> io::Result<bool> {
+        let read_len = if matches!(self.decoder, Decoder::Uncompressed(_)) {
+            remaining.min(CHUNK_SIZE)
+        } else {
+            CHUNK_SIZE
+        };
+
+        let input = self.decoder.input();
+        input.data.drain(..input.pos);
+        input.pos = 0;
+
+        let len = input.data.len();
+        input.data.resize(len + read_len, 0);
+
+        let reader = &mut *self.reader;
+        let result = future::poll_fn(|cx| {
+            match Pin::new(&mut *reader).poll_read(cx, &mut input.data[len..]) {
+                Poll::Pending if !wait => Poll::Ready(Ok(None)),
+                poll => poll.map(|result| result.map(Some)),
+            }
+        });
+
+        let result = result.await;
+        input.data.truncate(len + result.as_ref().map_or(0, |read| read.unwrap_or(0)));
+
+        match result {
+            Ok(Some(0)) => input.eof = true,
+            Ok(Some(_)) => {}
+            Ok(None) => return Ok(false),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+
+        Ok(true)
+    }
+}
+
+/// Decompresses the data read from an async reader.
+enum Decoder {
+    Uncompressed(Input),
+    Zlib(ZlibDecoder<Input>),
+    Gzip(GzDecoder<Input>),
+}
+
+impl Decoder {
+    fn new(compression: NbtCompression) -> Self {
+        match compression {
+            NbtCompression::Uncompressed => Self::Uncompressed(Input::default()),
+            NbtCompression::ZlibCompressed | NbtCompression::ZlibCompressedWith(_) => {
+                Self::Zlib(ZlibDecoder::new(Input::default()))
+            }
+            NbtCompression::GzipCompressed | NbtCompression::GzipCompressedWith(_) => {
+                Self::Gzip(GzDecoder::new(Input::default()))
+            }
+        }
+    }
+
+    fn input(&mut self) -> &mut Input {
+        match self {
+            Self::Uncompressed(input) => input,
+            Self::Zlib(decoder)       => decoder.get_mut(),
+            Self::Gzip(decoder)       => decoder.get_mut(),
+        }
+    }
+}
+
+impl Read for Decoder {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Uncompressed(input) => input.read(buf),
+            Self::Zlib(decoder)       => decoder.read(buf),
+            Self::Gzip(decoder)       => decoder.read(buf),
+        }
+    }
+}
+
+/// Data read from an async reader which has not yet been decompressed. Reading past the
+/// available data fails with [`io::ErrorKind::WouldBlock`], unless the reader is exhausted.
+#[derive(Debug, Default)]
+struct Input {
+    data: Vec<u8>,
+    pos:  usize,
+    eof:  bool,
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.fill_buf()?.read(buf)?;
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for Input {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos < self.data.len() || self.eof {
+            Ok(&self.data[self.pos..])
+        } else {
+            Err(io::ErrorKind::WouldBlock.into())
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt;
+    }
+}
+
+// ================================
+//  Writing functions
+// ================================
+
+/// Writes the provided NBT compound tag to the given async writer using the indicated encoding.
+/// If no root name is provided, the empty string is used. The writer is flushed, but not closed.
+///
+/// See [`io::write_compound`].
+///
+/// [`io::write_compound`]: crate::io::write_compound
+pub async fn write_compound<W: AsyncWrite + Unpin>(
+    writer:    &mut W,
+    opts:      IoOptions,
+    root_name: Option<&str>,
+    root:      &NbtCompound,
+) -> Result<(), NbtIoError> {
+    let mut encoder = Encoder::new(opts.compression);
+    raw::write_u8(&mut encoder, opts, COMPOUND_ID)?;
+    raw::write_string(&mut encoder, opts, root_name.unwrap_or(""))?;
+
+    if 0 == opts.depth_limit.0 && !root.is_empty() {
+        return Err(NbtIoError::ExceededDepthLimit {
+            limit: opts.depth_limit,
+        });
+    }
+
+    #[cfg_attr(
+        not(feature = "preserve_order"),
+        expect(
+            clippy::iter_over_hash_type,
+            reason = "entries are written in the order of the compound, as in `io`",
+        ),
+    )]
+    for (name, tag) in root.inner() {
+        raw::write_u8(&mut encoder, opts, raw::id_for_tag(Some(tag)))?;
+        raw::write_string(&mut encoder, opts, name)?;
+        write_tag_body(&mut encoder, opts, tag, 1)?;
+
+        let output = encoder.output();
+        if output.len() >= CHUNK_SIZE {
+            write_all(writer, output).await?;
+            output.clear();
+        }
+    }
+
+    // TAG_End
+    raw::write_u8(&mut encoder, opts, TAG_END_ID)?;
+
+    write_all(writer, &encoder.finish()?).await?;
+    flush(writer).await?;
+    Ok(())
+}
+
+/// Writes the provided NBT list tag to the given async writer using the indicated encoding.
+/// If no root name is provided, the empty string is used. The writer is flushed, but not closed.
+///
+/// See [`io::write_list`].
+///
+/// [`io::write_list`]: crate::io::write_list
+#[cfg(feature = "allow_any_root")]
+pub async fn write_list<W: AsyncWrite + Unpin>(
+    writer:    &mut W,
+    opts:      IoOptions,
+    root_name: Option<&str>,
+    root:      &NbtList,
+) -> Result<(), NbtIoError> {
+    let mut bytes = Vec::new();
+    crate::io::write_list(&mut bytes, opts, root_name, root)?;
+    write_buffered(writer, &bytes).await
+}
+
+/// Write any NBT tag to the provided async writer, using the provided encoding. If a root name
+/// for the tag is not provided, then the empty string is used. The writer is flushed, but not
+/// closed.
+///
+/// See [`io::write_any_nbt`].
+///
+/// [`io::write_any_nbt`]: crate::io::write_any_nbt
+#[cfg(feature = "allow_any_root")]
+pub async fn write_any_nbt<W: AsyncWrite + Unpin>(
+    writer:    &mut W,
+    opts:      IoOptions,
+    root_name: Option<&str>,
+    root:      &NbtTag,
+) -> Result<(), NbtIoError> {
+    let mut bytes = Vec::new();
+    crate::io::write_any_nbt(&mut bytes, opts, root_name, root)?;
+    write_buffered(writer, &bytes).await
+}
+
+/// Write any NBT tag to the provided async writer, using the provided encoding. Does not write
+/// any root name for the tag. The writer is flushed, but not closed.
+///
+/// See [`io::write_any_nbt_unnamed`].
+///
+/// [`io::write_any_nbt_unnamed`]: crate::io::write_any_nbt_unnamed
+#[cfg(feature = "allow_any_root")]
+pub async fn write_any_nbt_unnamed<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    opts:   IoOptions,
+    root:   &NbtTag,
+) -> Result<(), NbtIoError> {
+    let mut bytes = Vec::new();
+    crate::io::write_any_nbt_unnamed(&mut bytes, opts, root)?;
+    write_buffered(writer, &bytes).await
+}
+
+/// Write the Bedrock Edition NBT header to the given async writer.
+/// The writer is not flushed.
+///
+/// See [`io::write_bedrock_header`].
+///
+/// [`io::write_bedrock_header`]: crate::io::write_bedrock_header
+pub async fn write_bedrock_header<W: AsyncWrite + Unpin>(
+    writer:    &mut W,
+    opts:      IoOptions,
+    first_num: i32,
+    nbt_len:   usize,
+) -> Result<(), NbtIoError> {
+    let mut header = Vec::with_capacity(8);
+    crate::io::write_bedrock_header(&mut header, opts, first_num, nbt_len)?;
+    write_all(writer, &header).await?;
+    Ok(())
+}
+
+#[cfg(feature = "allow_any_root")]
+async fn write_buffered<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    bytes:  &[u8],
+) -> Result<(), NbtIoError> {
+    write_all(writer, bytes).await?;
+    flush(writer).await?;
+    Ok(())
+}
+
+async fn write_all<W: AsyncWrite + Unpin>(writer: &mut W, mut bytes: &[u8]) -> io::Result<()> {
+    while !bytes.is_empty() {
+        match future::poll_fn(|cx| Pin::new(&mut *writer).poll_write(cx, bytes)).await {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(written) => bytes = &bytes[written..],
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+async fn flush<W: AsyncWrite + Unpin>(writer: &mut W) -> io::Result<()> {
+    future::poll_fn(|cx| Pin::new(&mut *writer).poll_flush(cx)).await
+}
+
+/// Compresses data into a buffer, which is periodically written to an async writer.
+enum Encoder {
+    Uncompressed(Vec<u8>),
+    Zlib(ZlibEncoder<Vec<u8>>),
+    Gzip(GzEncoder<Vec<u8>>),
+}
+
+impl Encoder {
+    fn new(compression: NbtCompression) -> Self {
+        match compression {
+            NbtCompression::Uncompressed => Self::Uncompressed(Vec::new()),
+            NbtCompression::ZlibCompressed => {
+                Self::Zlib(ZlibEncoder::new(Vec::new(), Compression::default()))
+            }
+            NbtCompression::ZlibCompressedWith(compression) => {
+                Self::Zlib(ZlibEncoder::new(Vec::new(), compression.into()))
+            }
+            NbtCompression::GzipCompressed => {
+                Self::Gzip(GzEncoder::new(Vec::new(), Compression::default()))
+            }
+            NbtCompression::GzipCompressedWith(compression) => {
+                Self::Gzip(GzEncoder::new(Vec::new(), compression.into()))
+            }
+        }
+    }
+
+    /// The output which has not yet been written to the async writer. The encoders only append
+    /// to their output, so it may be cleared after being written.
+    fn output(&mut self) -> &mut Vec<u8> {
+        match self {
+            Self::Uncompressed(output) => output,
+            Self::Zlib(encoder)        => encoder.get_mut(),
+            Self::Gzip(encoder)        => encoder.get_mut(),
+        }
+    }
+
+    fn finish(self) -> io::Result<Vec<u8>> {
+        match self {
+            Self::Uncompressed(output) => Ok(output),
+            Self::Zlib(encoder)        => encoder.finish(),
+            Self::Gzip(encoder)        => encoder.finish(),
+        }
+    }
+}
+
+impl Write for Encoder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Uncompressed(output) => output.write(buf),
+            Self::Zlib(encoder)        => encoder.write(buf),
+            Self::Gzip(encoder)        => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Uncompressed(output) => output.flush(),
+            Self::Zlib(encoder)        => encoder.flush(),
+            Self::Gzip(encoder)        => encoder.flush(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::{future::Future, io::Read as _, pin::{pin, Pin}};
+    use std::task::{Context, Poll, Waker};
+
+    use futures_io::AsyncRead;
+
+    use crate::{io, settings::IoOptions};
+    use crate::tag::{NbtCompound, NbtList, NbtTag};
+    use super::read_compound;
+
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    /// Provides one byte at a time, and is pending before each byte.
+    struct Trickle<'a> {
+        data:    &'a [u8],
+        pending: bool,
+    }
+
+    impl AsyncRead for Trickle<'_> {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx:       &mut Context<'_>,
+            buf:      &mut [u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.pending = !self.pending;
+            if self.pending {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+
+            let len = buf.len().min(1);
+            Poll::Ready(self.data.read(&mut buf[..len]))
+        }
+    }
+
+    fn sample_compound() -> NbtCompound {
+        let mut inner = NbtCompound::new();
+        inner.insert("bytes", NbtTag::ByteArray(vec![3; 5000]));
+        inner.insert("longs", NbtTag::LongArray(vec![-1, 1 << 40, 7]));
+        let mut compound = NbtCompound::new();
+        compound.insert("inner", inner);
+        compound.insert("list", NbtList::from(vec![1_i32, -300, 70_000]));
+        compound.insert("name", "value");
+        compound
+    }
+
+    #[test]
+    fn uncompressed_data_is_not_read_past_its_end() {
+        let compound = sample_compound();
+
+        for opts in [
+            IoOptions::java_uncompressed(),
+            IoOptions::bedrock_uncompressed(),
+            IoOptions::bedrock_network_uncompressed(),
+        ] {
+            let mut bytes = Vec::new();
+            io::write_compound(&mut bytes, opts, Some("root"), &compound)
+                .expect("writing to a Vec succeeds");
+            bytes.extend_from_slice(b"trailing");
+
+            let mut reader = bytes.as_slice();
+            let (read, root_name) = block_on(read_compound(&mut reader, opts)).expect("valid NBT");
+            assert_eq!((&read, root_name.as_str()), (&compound, "root"), "the data should be read");
+            assert_eq!(reader, b"trailing", "the trailing data should not be read");
+
+            let mut reader = Trickle {
+                data:    bytes.as_slice(),
+                pending: false,
+            };
+            let (read, _) = block_on(read_compound(&mut reader, opts)).expect("valid NBT");
+            assert_eq!(read, compound, "the data should be read a byte at a time");
+            assert_eq!(reader.data, b"trailing", "the trailing data should not be read");
+        }
+    }
+
+    #[test]
+    fn compressed_data_matches_io() {
+        let compound = sample_compound();
+        let opts = IoOptions::java();
+
+        let mut bytes = Vec::new();
+        io::write_compound(&mut bytes, opts, None, &compound).expect("writing to a Vec succeeds");
+
+        let mut reader = Trickle {
+            data:    bytes.as_slice(),
+            pending: false,
+        };
+        let (read, _) = block_on(read_compound(&mut reader, opts)).expect("valid NBT");
+        assert_eq!(read, compound, "the data should be read a byte at a time");
+    }
+}
@@ -0,0 +1,649 @@
+//! Reading values from NBT data whose tag types vary, such as Bedrock data which stores the same
+//! field as a `Byte` in one version and as an `Int` in another.
+//!
+//! A [`Coercion`] determines which tag types are accepted for a value. Values are read from
+//! a compound through a [`CoercingCompound`], which tracks the path to the compound so that
+//! an [`NbtCoercionError`] reports where the offending tag is, along with the expected and
+//! actual types of the tag. The most common lenient accessors, such as
+//! [`NbtCompound::get_int_lenient`], are also available directly on [`NbtCompound`].
+//!
+//! # Examples:
+//! ```
+//! use prismarine_anchor_nbt::{snbt, SnbtParseOptions};
+//! use prismarine_anchor_nbt::coerce::Coercion;
+//!
+//! let opts = SnbtParseOptions::default_updated();
+//! let actor = snbt::parse_compound(
+//!     "{Variant: 2b, Invulnerable: 0, Attributes: {Health: 20}, Pos: [1.5, 64.0, -3.5]}",
+//!     opts,
+//! ).unwrap();
+//!
+//! assert_eq!(actor.get_int_lenient("Variant").unwrap(), 2);
+//! assert!(!actor.get_bool("Invulnerable").unwrap());
+//!
+//! let actor = actor.coerce(Coercion::Widening);
+//! let health = actor.get_compound("Attributes").unwrap().get_float("Health");
+//! assert_eq!(
+//!     health.unwrap_err().to_string(),
+//!     "expected Float tag, but found Int at Attributes.Health",
+//! );
+//! ```
+
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "derive_serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    path::NbtPath,
+    tag::{NbtCompound, NbtList, NbtTag, NbtType, unwrap_element},
+};
+
+
+// ================================
+//  Coercion
+// ================================
+
+/// Which tag types are accepted when reading a value of some type.
+#[cfg_attr(feature = "derive_serde",    derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "derive_standard", derive(PartialEq, Eq, PartialOrd, Ord, Hash))]
+#[derive(Debug, Default, Clone, Copy)]
+pub enum Coercion {
+    /// Only the tag type corresponding to the value's type is accepted; for instance, an `i32`
+    /// may only be read from an `Int` tag, and a `bool` from a `Byte` tag.
+    Strict,
+    /// Tag types whose values can always be converted without loss are also accepted:
+    /// - integers are read from any integer tag at most as wide as the value's type,
+    /// - `bool`s are read from any integer tag, with nonzero values being `true`,
+    /// - `f32`s are read from `Byte` and `Short` tags, and `f64`s from `Float`, `Byte`,
+    ///   `Short`, and `Int` tags,
+    /// - strings are read from `ByteString` tags which are valid UTF-8,
+    /// - arrays are read from narrower arrays, and from lists of numbers which can be read
+    ///   as the array's elements.
+    #[default]
+    Widening,
+    /// Any tag type which holds a suitable value is accepted:
+    /// - integers are read from any integer tag whose value fits in the value's type,
+    ///   and from `Float` and `Double` tags holding such an integer,
+    /// - `bool`s are additionally read from the strings `"true"` and `"false"`,
+    /// - floating-point numbers are read from any numeric tag, possibly losing precision,
+    /// - strings are read from any `ByteString` tag, replacing invalid UTF-8,
+    /// - arrays are read from any array or list whose elements can be read leniently.
+    Lenient,
+}
+
+impl Coercion {
+    #[inline]
+    fn widens(self) -> bool {
+        !matches!(self, Self::Strict)
+    }
+
+    #[inline]
+    fn is_lenient(self) -> bool {
+        matches!(self, Self::Lenient)
+    }
+}
+
+/// A type which can be read from NBT tags of varying types, as determined by a [`Coercion`].
+pub trait Coerce<'a>: Sized {
+    /// The tag type corresponding to this type, which is read even with [`Coercion::Strict`].
+    const EXPECTED: NbtType;
+
+    /// Reads a value from the tag, or returns an error whose path is `path`, or a path
+    /// into the tag relative to `path`.
+    fn coerce(
+        tag:      &'a NbtTag,
+        coercion: Coercion,
+        path:     &NbtPath,
+    ) -> Result<Self, NbtCoercionError>;
+}
+
+/// An integer tag's value, along with the width of its type in bits.
+fn integer(tag: &NbtTag) -> Option<(i64, u32)> {
+    match *tag {
+        NbtTag::Byte(value)  => Some((i64::from(value), 8)),
+        NbtTag::Short(value) => Some((i64::from(value), 16)),
+        NbtTag::Int(value)   => Some((i64::from(value), 32)),
+        NbtTag::Long(value)  => Some((value, 64)),
+        _ => None,
+    }
+}
+
+/// Returns the value of a `Float` or `Double` tag.
+fn float(tag: &NbtTag) -> Option<f64> {
+    match *tag {
+        NbtTag::Float(value)  => Some(f64::from(value)),
+        NbtTag::Double(value) => Some(value),
+        _ => None,
+    }
+}
+
+/// Returns the value if it is an integer which fits in an `i64`.
+#[expect(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    reason = "the value is checked to be an integer, and the bounds are powers of two",
+)]
+fn integral(value: f64) -> Option<i64> {
+    let in_range = (i64::MIN as f64..-(i64::MIN as f64)).contains(&value);
+    (value.fract() == 0. && in_range).then_some(value as i64)
+}
+
+fn coerce_integer<T: TryFrom<i64>>(
+    tag:      &NbtTag,
+    coercion: Coercion,
+    path:     &NbtPath,
+    expected: NbtType,
+    bits:     u32,
+) -> Result<T, NbtCoercionError> {
+    if let Some((value, width)) = integer(tag) {
+        let accepted = match coercion {
+            Coercion::Strict   => width == bits,
+            Coercion::Widening => width <= bits,
+            Coercion::Lenient  => true,
+        };
+
+        if accepted {
+            return T::try_from(value)
+                .map_err(|_err| NbtCoercionError::out_of_range(path, expected, tag));
+        }
+    } else if coercion.is_lenient() {
+        if let Some(value) = float(tag) {
+            return integral(value)
+                .and_then(|value| T::try_from(value).ok())
+                .ok_or_else(|| NbtCoercionError::out_of_range(path, expected, tag));
+        }
+    }
+
+    Err(NbtCoercionError::type_mismatch(path, expected, tag))
+}
+
+macro_rules! coerce_integer {
+    ($($type:ty, $variant:ident, $bits:literal);* $(;)?) => {
+        $(
+            impl Coerce<'_> for $type {
+                const EXPECTED: NbtType = NbtType::$variant;
+
+                #[inline]
+                fn coerce(
+                    tag:      &NbtTag,
+                    coercion: Coercion,
+                    path:     &NbtPath,
+                ) -> Result<Self, NbtCoercionError> {
+                    coerce_integer(tag, coercion, path, Self::EXPECTED, $bits)
+                }
+            }
+        )*
+    };
+}
+
+coerce_integer!(
+    i8,  Byte,  8;
+    i16, Short, 16;
+    i32, Int,   32;
+    i64, Long,  64;
+);
+
+impl Coerce<'_> for bool {
+    const EXPECTED: NbtType = NbtType::Byte;
+
+    fn coerce(tag: &NbtTag, coercion: Coercion, path: &NbtPath) -> Result<Self, NbtCoercionError> {
+        match tag {
+            NbtTag::Byte(value) => return Ok(*value != 0),
+            NbtTag::String(value) if coercion.is_lenient() => match value.as_str() {
+                "true"  => return Ok(true),
+                "false" => return Ok(false),
+                _ => {}
+            },
+            _ if coercion.widens() => {
+                if let Some((value, _)) = integer(tag) {
+                    return Ok(value != 0);
+                }
+            }
+            _ => {}
+        }
+
+        Err(NbtCoercionError::type_mismatch(path, Self::EXPECTED, tag))
+    }
+}
+
+impl Coerce<'_> for f32 {
+    const EXPECTED: NbtType = NbtType::Float;
+
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        reason = "lenient coercions may lose precision",
+    )]
+    fn coerce(tag: &NbtTag, coercion: Coercion, path: &NbtPath) -> Result<Self, NbtCoercionError> {
+        match *tag {
+            NbtTag::Float(value)                           => Ok(value),
+            NbtTag::Byte(value)   if coercion.widens()     => Ok(Self::from(value)),
+            NbtTag::Short(value)  if coercion.widens()     => Ok(Self::from(value)),
+            NbtTag::Int(value)    if coercion.is_lenient() => Ok(value as Self),
+            NbtTag::Long(value)   if coercion.is_lenient() => Ok(value as Self),
+            NbtTag::Double(value) if coercion.is_lenient() => {
+                if value.is_finite() && value.abs() > f64::from(Self::MAX) {
+                    Err(NbtCoercionError::out_of_range(path, Self::EXPECTED, tag))
+                } else {
+                    Ok(value as Self)
+                }
+            }
+            _ => Err(NbtCoercionError::type_mismatch(path, Self::EXPECTED, tag)),
+        }
+    }
+}
+
+impl Coerce<'_> for f64 {
+    const EXPECTED: NbtType = NbtType::Double;
+
+    #[expect(clippy::cast_precision_loss, reason = "lenient coercions may lose precision")]
+    fn coerce(tag: &NbtTag, coercion: Coercion, path: &NbtPath) -> Result<Self, NbtCoercionError> {
+        match *tag {
+            NbtTag::Double(value)                         => Ok(value),
+            NbtTag::Float(value) if coercion.widens()     => Ok(Self::from(value)),
+            NbtTag::Byte(value)  if coercion.widens()     => Ok(Self::from(value)),
+            NbtTag::Short(value) if coercion.widens()     => Ok(Self::from(value)),
+            NbtTag::Int(value)   if coercion.widens()     => Ok(Self::from(value)),
+            NbtTag::Long(value)  if coercion.is_lenient() => Ok(value as Self),
+            _ => Err(NbtCoercionError::type_mismatch(path, Self::EXPECTED, tag)),
+        }
+    }
+}
+
+impl<'a> Coerce<'a> for &'a str {
+    const EXPECTED: NbtType = NbtType::String;
+
+    /// Even with [`Coercion::Lenient`], a `ByteString` is only read if it is valid UTF-8.
+    /// Use `Cow<str>` to replace invalid UTF-8 instead.
+    fn coerce(
+        tag:      &'a NbtTag,
+        coercion: Coercion,
+        path:     &NbtPath,
+    ) -> Result<Self, NbtCoercionError> {
+        match tag {
+            NbtTag::String(string) => Ok(string),
+            NbtTag::ByteString(bytes) if coercion.widens() => std::str::from_utf8(bytes)
+                .map_err(|_err| NbtCoercionError::new(path, NbtCoercionErrorKind::InvalidUtf8)),
+            _ => Err(NbtCoercionError::type_mismatch(path, Self::EXPECTED, tag)),
+        }
+    }
+}
+
+impl<'a> Coerce<'a> for Cow<'a, str> {
+    const EXPECTED: NbtType = NbtType::String;
+
+    fn coerce(
+        tag:      &'a NbtTag,
+        coercion: Coercion,
+        path:     &NbtPath,
+    ) -> Result<Self, NbtCoercionError> {
+        match tag {
+            NbtTag::ByteString(bytes) if coercion.is_lenient() => {
+                Ok(String::from_utf8_lossy(bytes))
+            }
+            _ => <&str>::coerce(tag, coercion, path).map(Cow::Borrowed),
+        }
+    }
+}
+
+macro_rules! coerce_array {
+    ($($type:ty, $variant:ident);* $(;)?) => {
+        $(
+            impl<'a> Coerce<'a> for Cow<'a, [$type]> {
+                const EXPECTED: NbtType = NbtType::$variant;
+
+                /// Arrays of the expected type are borrowed, while other arrays and lists
+                /// are converted into a new `Vec`.
+                fn coerce(
+                    tag:      &'a NbtTag,
+                    coercion: Coercion,
+                    path:     &NbtPath,
+                ) -> Result<Self, NbtCoercionError> {
+                    if let NbtTag::$variant(values) = tag {
+                        return Ok(Cow::Borrowed(values));
+                    }
+
+                    if coercion.widens() {
+                        if let Some(values) = coerce_elements(tag, coercion, path) {
+                            return values.map(Cow::Owned);
+                        }
+                    }
+
+                    Err(NbtCoercionError::type_mismatch(path, Self::EXPECTED, tag))
+                }
+            }
+        )*
+    };
+}
+
+coerce_array!(
+    i8,  ByteArray;
+    i32, IntArray;
+    i64, LongArray;
+);
+
+/// Reads each element of an array or list, or returns `None` if the tag is neither.
+fn coerce_elements<T: for<'b> Coerce<'b>>(
+    tag:      &NbtTag,
+    coercion: Coercion,
+    path:     &NbtPath,
+) -> Option<Result<Vec<T>, NbtCoercionError>> {
+    let coerce_element = |index: usize, element: &NbtTag| {
+        T::coerce(element, coercion, path).map_err(|err| err.at_index(index))
+    };
+
+    Some(match tag {
+        NbtTag::ByteArray(values) => values
+            .iter()
+            .enumerate()
+            .map(|(index, &value)| coerce_element(index, &NbtTag::Byte(value)))
+            .collect(),
+        NbtTag::IntArray(values) => values
+            .iter()
+            .enumerate()
+            .map(|(index, &value)| coerce_element(index, &NbtTag::Int(value)))
+            .collect(),
+        NbtTag::LongArray(values) => values
+            .iter()
+            .enumerate()
+            .map(|(index, &value)| coerce_element(index, &NbtTag::Long(value)))
+            .collect(),
+        NbtTag::List(list) => list
+            .iter()
+            .enumerate()
+            .map(|(index, element)| coerce_element(index, unwrap_element(element)))
+            .collect(),
+        _ => return None,
+    })
+}
+
+impl<'a> Coerce<'a> for &'a NbtList {
+    const EXPECTED: NbtType = NbtType::List;
+
+    #[inline]
+    fn coerce(tag: &'a NbtTag, _: Coercion, path: &NbtPath) -> Result<Self, NbtCoercionError> {
+        match tag {
+            NbtTag::List(list) => Ok(list),
+            _ => Err(NbtCoercionError::type_mismatch(path, Self::EXPECTED, tag)),
+        }
+    }
+}
+
+impl<'a> Coerce<'a> for &'a NbtCompound {
+    const EXPECTED: NbtType = NbtType::Compound;
+
+    #[inline]
+    fn coerce(tag: &'a NbtTag, _: Coercion, path: &NbtPath) -> Result<Self, NbtCoercionError> {
+        match tag {
+            NbtTag::Compound(compound) => Ok(compound),
+            _ => Err(NbtCoercionError::type_mismatch(path, Self::EXPECTED, tag)),
+        }
+    }
+}
+
+
+// ================================
+//  Coercing compound
+// ================================
+
+/// A compound from which values are read with a [`Coercion`]. Errors include the path from the
+/// compound that [`NbtCompound::coerce`] was called on to the offending tag.
+#[derive(Debug, Clone)]
+pub struct CoercingCompound<'a> {
+    compound: &'a NbtCompound,
+    coercion: Coercion,
+    path:     NbtPath,
+}
+
+impl<'a> CoercingCompound<'a> {
+    /// Reads values from the compound with the given coercion.
+    #[inline]
+    pub fn new(compound: &'a NbtCompound, coercion: Coercion) -> Self {
+        Self {
+            compound,
+            coercion,
+            path: NbtPath::root(),
+        }
+    }
+
+    /// Reads values from the compound with the given coercion. Errors include the given path,
+    /// which should be the path to the compound.
+    #[inline]
+    pub fn at_path(compound: &'a NbtCompound, coercion: Coercion, path: NbtPath) -> Self {
+        Self {
+            compound,
+            coercion,
+            path,
+        }
+    }
+
+    #[inline]
+    pub fn compound(&self) -> &'a NbtCompound {
+        self.compound
+    }
+
+    #[inline]
+    pub fn coercion(&self) -> Coercion {
+        self.coercion
+    }
+
+    /// The path to this compound.
+    #[inline]
+    pub fn path(&self) -> &NbtPath {
+        &self.path
+    }
+
+    /// Reads the value of the tag with the given key.
+    pub fn get<T: Coerce<'a>>(&self, key: &str) -> Result<T, NbtCoercionError> {
+        let mut path = self.path.clone();
+        path.push_key(key);
+
+        match self.compound.get_tag(key) {
+            Some(tag) => T::coerce(tag, self.coercion, &path),
+            None => Err(NbtCoercionError::new(&path, NbtCoercionErrorKind::Missing {
+                expected: T::EXPECTED,
+            })),
+        }
+    }
+
+    /// Reads the value of the tag with the given key, or returns `None` if there is no such tag.
+    pub fn get_optional<T: Coerce<'a>>(&self, key: &str) -> Result<Option<T>, NbtCoercionError> {
+        if self.compound.contains_key(key) {
+            self.get(key).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    #[inline]
+    pub fn get_byte(&self, key: &str) -> Result<i8, NbtCoercionError> {
+        self.get(key)
+    }
+
+    #[inline]
+    pub fn get_short(&self, key: &str) -> Result<i16, NbtCoercionError> {
+        self.get(key)
+    }
+
+    #[inline]
+    pub fn get_int(&self, key: &str) -> Result<i32, NbtCoercionError> {
+        self.get(key)
+    }
+
+    #[inline]
+    pub fn get_long(&self, key: &str) -> Result<i64, NbtCoercionError> {
+        self.get(key)
+    }
+
+    #[inline]
+    pub fn get_float(&self, key: &str) -> Result<f32, NbtCoercionError> {
+        self.get(key)
+    }
+
+    #[inline]
+    pub fn get_double(&self, key: &str) -> Result<f64, NbtCoercionError> {
+        self.get(key)
+    }
+
+    #[inline]
+    pub fn get_bool(&self, key: &str) -> Result<bool, NbtCoercionError> {
+        self.get(key)
+    }
+
+    /// Reads a `String` tag, or a `ByteString` tag as permitted by the coercion.
+    #[inline]
+    pub fn get_str(&self, key: &str) -> Result<Cow<'a, str>, NbtCoercionError> {
+        self.get(key)
+    }
+
+    /// Reads a `ByteArray` tag, or another array or a list as permitted by the coercion.
+    #[inline]
+    pub fn get_byte_array(&self, key: &str) -> Result<Cow<'a, [i8]>, NbtCoercionError> {
+        self.get(key)
+    }
+
+    /// Reads an `IntArray` tag, or another array or a list as permitted by the coercion.
+    #[inline]
+    pub fn get_int_array(&self, key: &str) -> Result<Cow<'a, [i32]>, NbtCoercionError> {
+        self.get(key)
+    }
+
+    /// Reads a `LongArray` tag, or another array or a list as permitted by the coercion.
+    #[inline]
+    pub fn get_long_array(&self, key: &str) -> Result<Cow<'a, [i64]>, NbtCoercionError> {
+        self.get(key)
+    }
+
+    #[inline]
+    pub fn get_list(&self, key: &str) -> Result<&'a NbtList, NbtCoercionError> {
+        self.get(key)
+    }
+
+    /// Reads each element of a list. Errors include the index of the offending element.
+    pub fn get_list_of<T: Coerce<'a>>(&self, key: &str) -> Result<Vec<T>, NbtCoercionError> {
+        let list = self.get_list(key)?;
+        let mut path = self.path.clone();
+        path.push_key(key);
+
+        list.iter()
+            .enumerate()
+            .map(|(index, element)| {
+                T::coerce(unwrap_element(element), self.coercion, &path)
+                    .map_err(|err| err.at_index(index))
+            })
+            .collect()
+    }
+
+    /// Reads a nested compound, from which values are read with the same coercion.
+    pub fn get_compound(&self, key: &str) -> Result<Self, NbtCoercionError> {
+        let compound = self.get::<&NbtCompound>(key)?;
+        let mut path = self.path.clone();
+        path.push_key(key);
+
+        Ok(Self {
+            compound,
+            coercion: self.coercion,
+            path,
+        })
+    }
+}
+
+
+// ================================
+//  Errors
+// ================================
+
+/// An error from reading a value with a [`Coercion`], along with the path to the offending tag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NbtCoercionError {
+    pub path: NbtPath,
+    pub kind: NbtCoercionErrorKind,
+}
+
+impl NbtCoercionError {
+    #[inline]
+    fn new(path: &NbtPath, kind: NbtCoercionErrorKind) -> Self {
+        Self {
+            path: path.clone(),
+            kind,
+        }
+    }
+
+    fn type_mismatch(path: &NbtPath, expected: NbtType, found: &NbtTag) -> Self {
+        Self::new(path, NbtCoercionErrorKind::TypeMismatch {
+            expected,
+            found: found.tag_type(),
+        })
+    }
+
+    fn out_of_range(path: &NbtPath, expected: NbtType, found: &NbtTag) -> Self {
+        Self::new(path, NbtCoercionErrorKind::OutOfRange {
+            expected,
+            found: found.tag_type(),
+            value: found.to_string(),
+        })
+    }
+
+    fn at_index(mut self, index: usize) -> Self {
+        self.path.push_index(i32::try_from(index).unwrap_or(i32::MAX));
+        self
+    }
+
+    /// The tag type corresponding to the value which was read, if known.
+    pub fn expected(&self) -> Option<NbtType> {
+        match self.kind {
+            NbtCoercionErrorKind::Missing { expected }
+            | NbtCoercionErrorKind::TypeMismatch { expected, .. }
+            | NbtCoercionErrorKind::OutOfRange { expected, .. } => Some(expected),
+            NbtCoercionErrorKind::InvalidUtf8 => Some(NbtType::String),
+        }
+    }
+
+    /// The type of the offending tag, or `None` if the tag was missing.
+    pub fn found(&self) -> Option<NbtType> {
+        match self.kind {
+            NbtCoercionErrorKind::Missing { .. } => None,
+            NbtCoercionErrorKind::TypeMismatch { found, .. }
+            | NbtCoercionErrorKind::OutOfRange { found, .. } => Some(found),
+            NbtCoercionErrorKind::InvalidUtf8 => Some(NbtType::String),
+        }
+    }
+}
+
+impl Display for NbtCoercionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.path.is_root() {
+            write!(f, "{}", self.kind)
+        } else {
+            write!(f, "{} at {}", self.kind, self.path)
+        }
+    }
+}
+
+impl std::error::Error for NbtCoercionError {}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum NbtCoercionErrorKind {
+    /// A compound did not have the key which was read.
+    #[error("missing {expected:?} tag")]
+    Missing { expected: NbtType },
+    /// A tag's type could not be coerced into the expected type.
+    #[error("expected {expected:?} tag, but found {found:?}")]
+    TypeMismatch {
+        expected: NbtType,
+        found:    NbtType,
+    },
+    /// A tag's value did not fit in the value which was read. The value is formatted as SNBT.
+    #[error("{found:?} tag {value} does not fit in {expected:?}")]
+    OutOfRange {
+        expected: NbtType,
+        found:    NbtType,
+        value:    String,
+    },
+    /// A `ByteString` tag was read as a string, but was not valid UTF-8.
+    #[error("string tag is not valid UTF-8")]
+    InvalidUtf8,
+}
@@ -0,0 +1,1047 @@
+//! A compact representation of NBT data, for workloads which hold very many compounds in memory
+//! at once, such as every block entity and actor of a world.
+//!
+//! An [`NbtArena`] stores the tags of any number of compounds in flat, shared buffers:
+//! - the keys of compounds are interned, so each distinct key is stored once per arena,
+//! - each tag is a 16-byte node, and short strings are stored within their node,
+//! - the contents of longer strings, arrays, lists, and compounds are stored in pools
+//!   shared by every tag in the arena.
+//!
+//! Compounds in an arena are referred to with [`CompactCompoundId`]s, and are read through
+//! borrowed views such as [`CompactCompound`]. Compounds can be read from and written to NBT
+//! bytes directly, or converted to and from [`NbtCompound`]s.
+//!
+//! Data is never removed from an arena, except by clearing the entire arena.
+//!
+//! # Examples:
+//! ```
+//! use prismarine_anchor_nbt::{IoOptions, NbtCompound};
+//! use prismarine_anchor_nbt::compact::{CompactTag, NbtArena};
+//!
+//! let mut compound = NbtCompound::new();
+//! compound.insert("id", "minecraft:chest");
+//! compound.insert("x", 12_i32);
+//!
+//! let mut arena = NbtArena::new();
+//! let id = arena.insert_compound(&compound);
+//!
+//! let view = arena.compound(id);
+//! assert!(matches!(view.get("id"), Some(CompactTag::String("minecraft:chest"))));
+//! assert_eq!(view.to_compound(), compound);
+//!
+//! let mut bytes = Vec::new();
+//! arena.write_compound(&mut bytes, IoOptions::bedrock_uncompressed(), None, id).unwrap();
+//! let (read_id, _) = arena
+//!     .read_compound(&mut bytes.as_slice(), IoOptions::bedrock_uncompressed())
+//!     .unwrap();
+//! assert_eq!(arena.compound(read_id).to_compound(), compound);
+//! ```
+
+use std::{fmt, mem, str, slice};
+use std::{collections::HashMap, ops::Range};
+use std::fmt::{Debug, Formatter};
+use std::io::{Read, Write};
+
+use flate2::Compression;
+use flate2::write::{GzEncoder, ZlibEncoder};
+
+use crate::raw::{self, BudgetTracker};
+use crate::{
+    io::{non_homogenous_list, NbtIoError},
+    raw::{
+        BYTE_ARRAY_ID, BYTE_ID, COMPOUND_ID, DOUBLE_ID, FLOAT_ID, INT_ARRAY_ID,
+        INT_ID, LIST_ID, LONG_ID, LONG_ARRAY_ID, SHORT_ID, STRING_ID, TAG_END_ID,
+    },
+    settings::{IoOptions, NbtCompression},
+    stream::{NbtEvent, NbtReader},
+    tag::{NbtCompound, NbtList, NbtTag, NbtType},
+};
+
+
+/// The maximum length in bytes of strings which are stored within their node.
+const INLINE_STRING_LEN: usize = 14;
+
+const _: () = assert!(mem::size_of::<Node>() == 16, "nodes should be 16 bytes");
+
+
+// ================================
+//  Arena
+// ================================
+
+/// Flat storage for the tags of many NBT compounds. See the [module-level documentation](self).
+///
+/// The nodes and pools of an arena are indexed with `u32`s, so an arena holds less than
+/// 2<sup>32</sup> tags, compound entries, list elements, and bytes of string data.
+#[derive(Default, Clone)]
+pub struct NbtArena {
+    keys:        Vec<Box<str>>,
+    key_ids:     HashMap<Box<str>, u32>,
+    nodes:       Vec<Node>,
+    entries:     Vec<Entry>,
+    elements:    Vec<u32>,
+    text:        String,
+    bytes:       Vec<u8>,
+    byte_arrays: Vec<i8>,
+    ints:        Vec<i32>,
+    longs:       Vec<i64>,
+}
+
+/// Refers to a compound stored in an [`NbtArena`].
+///
+/// Ids are only meaningful for the arena which returned them, and are invalidated
+/// when that arena is cleared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CompactCompoundId(u32);
+
+impl NbtArena {
+    /// Creates an empty arena.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Removes every compound from the arena, invalidating every `CompactCompoundId`
+    /// returned by it, while keeping its allocated buffers.
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        self.key_ids.clear();
+        self.nodes.clear();
+        self.entries.clear();
+        self.elements.clear();
+        self.text.clear();
+        self.bytes.clear();
+        self.byte_arrays.clear();
+        self.ints.clear();
+        self.longs.clear();
+    }
+
+    /// The number of distinct keys of compounds in the arena.
+    #[inline]
+    pub fn key_count(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// The number of tags in the arena, including nested tags.
+    #[inline]
+    pub fn tag_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns a view of a compound in the arena.
+    ///
+    /// # Panics
+    /// May panic if the id was not returned by this arena, or if the arena
+    /// was cleared after the id was returned.
+    pub fn compound(&self, id: CompactCompoundId) -> CompactCompound<'_> {
+        match self.tag(id.0) {
+            CompactTag::Compound(compound) => compound,
+            _ => panic!("CompactCompoundId {} does not refer to a compound", id.0),
+        }
+    }
+
+    /// Copies a compound into the arena.
+    ///
+    /// # Panics
+    /// Panics if the arena would exceed its capacity; see [`NbtArena`].
+    pub fn insert_compound(&mut self, compound: &NbtCompound) -> CompactCompoundId {
+        let node = self.insert_compound_node(compound);
+        CompactCompoundId(self.push_node(node))
+    }
+
+    /// Reads the given encoding of NBT compound data into the arena, returning the id of the
+    /// compound and its root name. This honors the same `IoOptions` as [`io::read_compound`].
+    ///
+    /// If an error occurs, the tags which were partially read are removed from the arena.
+    ///
+    /// # Panics
+    /// Panics if the arena would exceed its capacity; see [`NbtArena`].
+    ///
+    /// [`io::read_compound`]: crate::io::read_compound
+    pub fn read_compound<R: Read>(
+        &mut self,
+        reader: &mut R,
+        opts:   IoOptions,
+    ) -> Result<(CompactCompoundId, String), NbtIoError> {
+        let lengths = self.lengths();
+        let result = self.read_events(NbtReader::new(reader, opts), opts);
+
+        if result.is_err() {
+            self.truncate(lengths);
+        }
+        result
+    }
+
+    /// Writes a compound in the arena to the given writer using the indicated encoding,
+    /// like [`io::write_compound`]. If no root name is provided, the empty string is used.
+    ///
+    /// [`io::write_compound`]: crate::io::write_compound
+    pub fn write_compound<W: Write>(
+        &self,
+        writer:    &mut W,
+        opts:      IoOptions,
+        root_name: Option<&str>,
+        id:        CompactCompoundId,
+    ) -> Result<(), NbtIoError> {
+        let compound = self.compound(id);
+        let root_name = root_name.unwrap_or("");
+
+        let (gzip, compression) = match opts.compression {
+            NbtCompression::Uncompressed => {
+                return write_named_compound(writer, opts, root_name, compound);
+            }
+            NbtCompression::ZlibCompressed                  => (false, Compression::default()),
+            NbtCompression::ZlibCompressedWith(compression) => (false, compression.into()),
+            NbtCompression::GzipCompressed                  => (true,  Compression::default()),
+            NbtCompression::GzipCompressedWith(compression) => (true,  compression.into()),
+        };
+
+        if gzip {
+            let mut writer = GzEncoder::new(writer, compression);
+            write_named_compound(&mut writer, opts, root_name, compound)
+        } else {
+            let mut writer = ZlibEncoder::new(writer, compression);
+            write_named_compound(&mut writer, opts, root_name, compound)
+        }
+    }
+
+    fn lengths(&self) -> ArenaLengths {
+        ArenaLengths {
+            nodes:       self.nodes.len(),
+            entries:     self.entries.len(),
+            elements:    self.elements.len(),
+            text:        self.text.len(),
+            bytes:       self.bytes.len(),
+            byte_arrays: self.byte_arrays.len(),
+            ints:        self.ints.len(),
+            longs:       self.longs.len(),
+        }
+    }
+
+    /// Interned keys are kept, since they may be used by other compounds.
+    fn truncate(&mut self, lengths: ArenaLengths) {
+        self.nodes.truncate(lengths.nodes);
+        self.entries.truncate(lengths.entries);
+        self.elements.truncate(lengths.elements);
+        self.text.truncate(lengths.text);
+        self.bytes.truncate(lengths.bytes);
+        self.byte_arrays.truncate(lengths.byte_arrays);
+        self.ints.truncate(lengths.ints);
+        self.longs.truncate(lengths.longs);
+    }
+
+    fn intern(&mut self, key: &str) -> u32 {
+        if let Some(&id) = self.key_ids.get(key) {
+            return id;
+        }
+
+        let id = to_index(self.keys.len());
+        self.keys.push(key.into());
+        self.key_ids.insert(key.into(), id);
+        id
+    }
+
+    fn push_node(&mut self, node: Node) -> u32 {
+        let index = to_index(self.nodes.len());
+        self.nodes.push(node);
+        index
+    }
+
+    fn string_node(&mut self, string: &str) -> Node {
+        if let Some(inline) = InlineString::new(string) {
+            return Node::InlineString(inline);
+        }
+
+        let start = to_index(self.text.len());
+        self.text.push_str(string);
+        to_index(self.text.len());
+
+        Node::String(Span {
+            start,
+            len: to_index(string.len()),
+        })
+    }
+
+    /// Converts a tag other than a list or compound into a node.
+    fn value_node(&mut self, tag: &NbtTag) -> Node {
+        match tag {
+            &NbtTag::Byte(value)     => Node::Byte(value),
+            &NbtTag::Short(value)    => Node::Short(value),
+            &NbtTag::Int(value)      => Node::Int(value),
+            &NbtTag::Long(value)     => Node::Long(value),
+            &NbtTag::Float(value)    => Node::Float(value),
+            &NbtTag::Double(value)   => Node::Double(value),
+            NbtTag::String(value)     => self.string_node(value),
+            NbtTag::ByteString(value) => Node::ByteString(push_span(&mut self.bytes, value)),
+            NbtTag::ByteArray(value)  => Node::ByteArray(push_span(&mut self.byte_arrays, value)),
+            NbtTag::IntArray(value)   => Node::IntArray(push_span(&mut self.ints, value)),
+            NbtTag::LongArray(value)  => Node::LongArray(push_span(&mut self.longs, value)),
+            NbtTag::List(value)       => self.insert_list_node(value),
+            NbtTag::Compound(value)   => self.insert_compound_node(value),
+        }
+    }
+
+    fn insert_list_node(&mut self, list: &NbtList) -> Node {
+        let elements = list
+            .iter()
+            .map(|element| {
+                let node = self.value_node(element);
+                self.push_node(node)
+            })
+            .collect::<Vec<_>>();
+
+        Node::List(push_span(&mut self.elements, &elements))
+    }
+
+    fn insert_compound_node(&mut self, compound: &NbtCompound) -> Node {
+        let entries = compound
+            .iter()
+            .map(|(key, value)| {
+                let node = self.value_node(value);
+                Entry {
+                    key:  self.intern(key),
+                    node: self.push_node(node),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Node::Compound(push_span(&mut self.entries, &entries))
+    }
+
+    /// Adds the entries of a compound which was read from NBT bytes. If a key is repeated,
+    /// the last value of the key is used, at the position of the first occurrence of the key.
+    fn push_read_entries(&mut self, read_entries: &[Entry]) -> Span {
+        // Compounds are usually small enough that a linear search is faster than hashing
+        const LINEAR_SEARCH_LEN: usize = 16;
+
+        let start = self.entries.len();
+
+        if read_entries.len() <= LINEAR_SEARCH_LEN {
+            for &entry in read_entries {
+                match self.entries[start..].iter_mut().find(|existing| existing.key == entry.key) {
+                    Some(existing) => existing.node = entry.node,
+                    None => self.entries.push(entry),
+                }
+            }
+        } else {
+            let mut positions = HashMap::with_capacity(read_entries.len());
+            for &entry in read_entries {
+                if let Some(&position) = positions.get(&entry.key) {
+                    self.entries[position] = entry;
+                } else {
+                    positions.insert(entry.key, self.entries.len());
+                    self.entries.push(entry);
+                }
+            }
+        }
+
+        let len = self.entries.len() - start;
+        to_index(self.entries.len());
+
+        Span {
+            start: to_index(start),
+            len:   to_index(len),
+        }
+    }
+
+    fn tag(&self, node: u32) -> CompactTag<'_> {
+        match &self.nodes[node as usize] {
+            &Node::Byte(value)     => CompactTag::Byte(value),
+            &Node::Short(value)    => CompactTag::Short(value),
+            &Node::Int(value)      => CompactTag::Int(value),
+            &Node::Long(value)     => CompactTag::Long(value),
+            &Node::Float(value)    => CompactTag::Float(value),
+            &Node::Double(value)   => CompactTag::Double(value),
+            Node::InlineString(value) => CompactTag::String(value.as_str()),
+            &Node::String(span)     => CompactTag::String(&self.text[span.range()]),
+            &Node::ByteString(span) => CompactTag::ByteString(&self.bytes[span.range()]),
+            &Node::ByteArray(span)  => CompactTag::ByteArray(&self.byte_arrays[span.range()]),
+            &Node::IntArray(span)   => CompactTag::IntArray(&self.ints[span.range()]),
+            &Node::LongArray(span)  => CompactTag::LongArray(&self.longs[span.range()]),
+            &Node::List(span) => CompactTag::List(CompactList {
+                arena:    self,
+                elements: &self.elements[span.range()],
+            }),
+            &Node::Compound(span) => CompactTag::Compound(CompactCompound {
+                arena:   self,
+                entries: &self.entries[span.range()],
+            }),
+        }
+    }
+
+    /// Builds a compound from the events of a reader. The reader handles decompression,
+    /// the depth limit, and validation of the data.
+    fn read_events<R: Read>(
+        &mut self,
+        mut reader: NbtReader<R>,
+        opts:       IoOptions,
+    ) -> Result<(CompactCompoundId, String), NbtIoError> {
+        let budget = BudgetTracker::new(opts.memory_budget);
+        let mut root_name = String::new();
+        let mut stack = Vec::new();
+        let mut read_entries = Vec::<Entry>::new();
+        let mut read_elements = Vec::new();
+        // The key of the next entry of the innermost compound
+        let mut next_key = None;
+
+        while let Some(event) = reader.next_event()? {
+            let (key, node) = match event {
+                NbtEvent::Name { name, .. } => {
+                    if stack.is_empty() {
+                        root_name = name;
+                    } else {
+                        next_key = Some(self.intern(&name));
+                    }
+                    continue;
+                }
+                NbtEvent::CompoundStart => {
+                    stack.push(Frame::Compound {
+                        key:   next_key.take(),
+                        start: read_entries.len(),
+                    });
+                    continue;
+                }
+                NbtEvent::ListStart { .. } => {
+                    stack.push(Frame::List {
+                        key:   next_key.take(),
+                        start: read_elements.len(),
+                    });
+                    continue;
+                }
+                NbtEvent::CompoundEnd => {
+                    let Some(Frame::Compound { key, start }) = stack.pop() else {
+                        unreachable!("the reader ends compounds which it started");
+                    };
+
+                    let compound = &read_entries[start..];
+                    let in_list = matches!(stack.last(), Some(Frame::List { .. }));
+
+                    let node = match compound {
+                        [wrapped] if in_list
+                            && opts.unwrap_heterogenous_lists
+                            && self.keys[wrapped.key as usize].is_empty() =>
+                        {
+                            // See `NbtList::unwrap_heterogenous`
+                            wrapped.node
+                        }
+                        _ => {
+                            budget.charge(mem::size_of::<Node>(), 1)?;
+                            let span = self.push_read_entries(compound);
+                            self.push_node(Node::Compound(span))
+                        }
+                    };
+
+                    read_entries.truncate(start);
+                    (key, node)
+                }
+                NbtEvent::ListEnd => {
+                    let Some(Frame::List { key, start }) = stack.pop() else {
+                        unreachable!("the reader ends lists which it started");
+                    };
+
+                    budget.charge(mem::size_of::<Node>(), 1)?;
+                    let span = push_span(&mut self.elements, &read_elements[start..]);
+                    read_elements.truncate(start);
+                    (key, self.push_node(Node::List(span)))
+                }
+                NbtEvent::Value(tag) => {
+                    budget.charge(mem::size_of::<Node>() + pooled_len(&tag), 1)?;
+                    let node = self.value_node(&tag);
+                    (next_key.take(), self.push_node(node))
+                }
+            };
+
+            match (stack.last(), key) {
+                (Some(Frame::Compound { .. }), Some(key)) => {
+                    budget.charge(mem::size_of::<Entry>(), 0)?;
+                    read_entries.push(Entry { key, node });
+                }
+                (Some(Frame::Compound { .. }), None) => {
+                    unreachable!("the reader yields the name of each entry of a compound");
+                }
+                (Some(Frame::List { .. }), _) => {
+                    budget.charge(mem::size_of::<u32>(), 0)?;
+                    read_elements.push(node);
+                }
+                (None, _) => return Ok((CompactCompoundId(node), root_name)),
+            }
+        }
+
+        Err(NbtIoError::MissingRootTag)
+    }
+}
+
+impl Debug for NbtArena {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NbtArena")
+            .field("key_count", &self.keys.len())
+            .field("tag_count", &self.nodes.len())
+            .finish_non_exhaustive()
+    }
+}
+
+// ================================
+//  Storage
+// ================================
+
+/// A single tag. The contents of strings which are too long to be inline, arrays, lists,
+/// and compounds are spans of the pools of the arena.
+#[derive(Debug, Clone, Copy)]
+enum Node {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    InlineString(InlineString),
+    /// A span of `text`.
+    String(Span),
+    /// A span of `bytes`.
+    ByteString(Span),
+    /// A span of `byte_arrays`.
+    ByteArray(Span),
+    /// A span of `ints`.
+    IntArray(Span),
+    /// A span of `longs`.
+    LongArray(Span),
+    /// A span of `elements`.
+    List(Span),
+    /// A span of `entries`.
+    Compound(Span),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct InlineString {
+    len:   u8,
+    bytes: [u8; INLINE_STRING_LEN],
+}
+
+impl InlineString {
+    fn new(string: &str) -> Option<Self> {
+        let len = u8::try_from(string.len()).ok()
+            .filter(|&len| usize::from(len) <= INLINE_STRING_LEN)?;
+
+        let mut bytes = [0; INLINE_STRING_LEN];
+        bytes[..string.len()].copy_from_slice(string.as_bytes());
+        Some(Self { len, bytes })
+    }
+
+    fn as_str(&self) -> &str {
+        match str::from_utf8(&self.bytes[..usize::from(self.len)]) {
+            Ok(string) => string,
+            Err(_) => unreachable!("inline strings are copied from valid strings"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    start: u32,
+    len:   u32,
+}
+
+impl Span {
+    #[inline]
+    fn range(self) -> Range<usize> {
+        let start = self.start as usize;
+        start .. start + self.len as usize
+    }
+}
+
+/// An entry of a compound, whose key is an index into `keys`
+/// and whose value is an index into `nodes`.
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    key:  u32,
+    node: u32,
+}
+
+/// The lengths of the buffers of an arena, used to undo partial reads.
+#[derive(Debug, Clone, Copy)]
+struct ArenaLengths {
+    nodes:       usize,
+    entries:     usize,
+    elements:    usize,
+    text:        usize,
+    bytes:       usize,
+    byte_arrays: usize,
+    ints:        usize,
+    longs:       usize,
+}
+
+/// Converts the length of a buffer of an arena into an index.
+fn to_index(len: usize) -> u32 {
+    u32::try_from(len).expect("an NbtArena may hold fewer than 2^32 tags and bytes of data")
+}
+
+fn push_span<T: Copy>(pool: &mut Vec<T>, values: &[T]) -> Span {
+    let start = to_index(pool.len());
+    pool.extend_from_slice(values);
+    // Check that the end of the span is also in range
+    to_index(pool.len());
+
+    Span {
+        start,
+        len: to_index(values.len()),
+    }
+}
+
+// ================================
+//  Reading
+// ================================
+
+/// A compound or list which has been started but not ended while reading NBT bytes.
+#[derive(Debug, Clone, Copy)]
+enum Frame {
+    Compound {
+        /// The key of this tag in its parent compound, if any.
+        key:   Option<u32>,
+        /// The index of the first entry of this compound in the read entries.
+        start: usize,
+    },
+    List {
+        key:   Option<u32>,
+        start: usize,
+    },
+}
+
+/// The number of bytes which a tag other than a list or compound occupies in the pools
+/// of an arena.
+fn pooled_len(tag: &NbtTag) -> usize {
+    match tag {
+        NbtTag::String(value) if value.len() > INLINE_STRING_LEN => value.len(),
+        NbtTag::ByteString(value) => value.len(),
+        NbtTag::ByteArray(value)  => value.len(),
+        NbtTag::IntArray(value)   => value.len() * mem::size_of::<i32>(),
+        NbtTag::LongArray(value)  => value.len() * mem::size_of::<i64>(),
+        _ => 0,
+    }
+}
+
+// ================================
+//  Writing
+// ================================
+
+fn write_named_compound<W: Write>(
+    writer:    &mut W,
+    opts:      IoOptions,
+    root_name: &str,
+    root:      CompactCompound<'_>,
+) -> Result<(), NbtIoError> {
+    raw::write_u8(writer, opts, COMPOUND_ID)?;
+    raw::write_string(writer, opts, root_name)?;
+    write_compound_body(writer, opts, root, 0)
+}
+
+fn write_compound_body<W: Write>(
+    writer:        &mut W,
+    opts:          IoOptions,
+    compound:      CompactCompound<'_>,
+    current_depth: u32,
+) -> Result<(), NbtIoError> {
+    if current_depth >= opts.depth_limit.0 && !compound.is_empty() {
+        return Err(NbtIoError::ExceededDepthLimit {
+            limit: opts.depth_limit,
+        });
+    }
+
+    for (key, tag) in compound {
+        raw::write_u8(writer, opts, tag.tag_id())?;
+        raw::write_string(writer, opts, key)?;
+        write_tag_body(writer, opts, tag, current_depth + 1)?;
+    }
+
+    raw::write_u8(writer, opts, TAG_END_ID)?;
+    Ok(())
+}
+
+fn write_tag_body<W: Write>(
+    writer:        &mut W,
+    opts:          IoOptions,
+    tag:           CompactTag<'_>,
+    current_depth: u32,
+) -> Result<(), NbtIoError> {
+    match tag {
+        CompactTag::Byte(value)   => raw::write_i8( writer, opts, value)?,
+        CompactTag::Short(value)  => raw::write_i16(writer, opts, value)?,
+        CompactTag::Int(value)    => raw::write_i32(writer, opts, value)?,
+        CompactTag::Long(value)   => raw::write_i64(writer, opts, value)?,
+        CompactTag::Float(value)  => raw::write_f32(writer, opts, value)?,
+        CompactTag::Double(value) => raw::write_f64(writer, opts, value)?,
+        CompactTag::ByteArray(value) => {
+            raw::write_usize_as_i32(writer, opts, value.len())?;
+            writer.write_all(raw::cast_bytes_to_unsigned(value))?;
+        }
+        CompactTag::String(value)     => raw::write_string(writer, opts, value)?,
+        CompactTag::ByteString(value) => raw::write_byte_string(writer, opts, value)?,
+        CompactTag::List(value)       => write_list_body(writer, opts, value, current_depth)?,
+        CompactTag::Compound(value)   => write_compound_body(writer, opts, value, current_depth)?,
+        CompactTag::IntArray(value) => {
+            raw::write_usize_as_i32(writer, opts, value.len())?;
+
+            for &int in value {
+                raw::write_i32(writer, opts, int)?;
+            }
+        }
+        CompactTag::LongArray(value) => {
+            raw::write_usize_as_i32(writer, opts, value.len())?;
+
+            for &long in value {
+                raw::write_i64(writer, opts, long)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the given list, wrapping its elements in compounds if it is heterogenous,
+/// as done by [`io::write_compound`].
+///
+/// [`io::write_compound`]: crate::io::write_compound
+fn write_list_body<W: Write>(
+    writer:        &mut W,
+    opts:          IoOptions,
+    list:          CompactList<'_>,
+    current_depth: u32,
+) -> Result<(), NbtIoError> {
+    let list_type = match list.element_id() {
+        Some(list_type) => list_type,
+        // Heterogenous lists are written as lists of compounds
+        None if opts.unwrap_heterogenous_lists => COMPOUND_ID,
+        None => return Err(non_homogenous_list(list.iter().map(|element| element.tag_id()))),
+    };
+    raw::write_u8(writer, opts, list_type)?;
+    raw::write_usize_as_i32(writer, opts, list.len())?;
+
+    if current_depth >= opts.depth_limit.0 && !list.is_empty() {
+        return Err(NbtIoError::ExceededDepthLimit {
+            limit: opts.depth_limit,
+        });
+    }
+
+    for element in list {
+        let needs_wrapper = list_type == COMPOUND_ID && match element {
+            CompactTag::Compound(compound) => {
+                opts.unwrap_heterogenous_lists && compound.is_wrapper()
+            }
+            _ => true,
+        };
+
+        if needs_wrapper {
+            // The wrapper compound is one level deeper than the list
+            if current_depth + 1 >= opts.depth_limit.0 {
+                return Err(NbtIoError::ExceededDepthLimit {
+                    limit: opts.depth_limit,
+                });
+            }
+
+            raw::write_u8(writer, opts, element.tag_id())?;
+            raw::write_string(writer, opts, "")?;
+            write_tag_body(writer, opts, element, current_depth + 2)?;
+            raw::write_u8(writer, opts, TAG_END_ID)?;
+        } else {
+            write_tag_body(writer, opts, element, current_depth + 1)?;
+        }
+    }
+
+    Ok(())
+}
+
+// ================================
+//  Views
+// ================================
+
+/// A borrowed view of a tag stored in an [`NbtArena`].
+#[derive(Debug, Clone, Copy)]
+pub enum CompactTag<'a> {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(&'a [i8]),
+    String(&'a str),
+    /// See [`NbtTag::ByteString`].
+    ByteString(&'a [u8]),
+    List(CompactList<'a>),
+    Compound(CompactCompound<'a>),
+    IntArray(&'a [i32]),
+    LongArray(&'a [i64]),
+}
+
+impl CompactTag<'_> {
+    /// Returns this tag's type.
+    #[inline]
+    pub fn tag_type(&self) -> NbtType {
+        match self {
+            Self::Byte(_)       => NbtType::Byte,
+            Self::Short(_)      => NbtType::Short,
+            Self::Int(_)        => NbtType::Int,
+            Self::Long(_)       => NbtType::Long,
+            Self::Float(_)      => NbtType::Float,
+            Self::Double(_)     => NbtType::Double,
+            Self::ByteArray(_)  => NbtType::ByteArray,
+            #[expect(clippy::match_same_arms)]
+            Self::String(_)     => NbtType::String,
+            Self::ByteString(_) => NbtType::String,
+            Self::List(_)       => NbtType::List,
+            Self::Compound(_)   => NbtType::Compound,
+            Self::IntArray(_)   => NbtType::IntArray,
+            Self::LongArray(_)  => NbtType::LongArray,
+        }
+    }
+
+    /// Copies this tag out of its arena.
+    pub fn to_tag(&self) -> NbtTag {
+        match *self {
+            Self::Byte(value)       => NbtTag::Byte(value),
+            Self::Short(value)      => NbtTag::Short(value),
+            Self::Int(value)        => NbtTag::Int(value),
+            Self::Long(value)       => NbtTag::Long(value),
+            Self::Float(value)      => NbtTag::Float(value),
+            Self::Double(value)     => NbtTag::Double(value),
+            Self::ByteArray(value)  => NbtTag::ByteArray(value.to_vec()),
+            Self::String(value)     => NbtTag::String(value.to_owned()),
+            Self::ByteString(value) => NbtTag::ByteString(value.to_vec()),
+            Self::List(value)       => NbtTag::List(value.to_list()),
+            Self::Compound(value)   => NbtTag::Compound(value.to_compound()),
+            Self::IntArray(value)   => NbtTag::IntArray(value.to_vec()),
+            Self::LongArray(value)  => NbtTag::LongArray(value.to_vec()),
+        }
+    }
+
+    fn tag_id(&self) -> u8 {
+        match self {
+            Self::Byte(_)       => BYTE_ID,
+            Self::Short(_)      => SHORT_ID,
+            Self::Int(_)        => INT_ID,
+            Self::Long(_)       => LONG_ID,
+            Self::Float(_)      => FLOAT_ID,
+            Self::Double(_)     => DOUBLE_ID,
+            Self::ByteArray(_)  => BYTE_ARRAY_ID,
+            #[expect(clippy::match_same_arms)]
+            Self::String(_)     => STRING_ID,
+            Self::ByteString(_) => STRING_ID,
+            Self::List(_)       => LIST_ID,
+            Self::Compound(_)   => COMPOUND_ID,
+            Self::IntArray(_)   => INT_ARRAY_ID,
+            Self::LongArray(_)  => LONG_ARRAY_ID,
+        }
+    }
+}
+
+/// A borrowed view of a compound stored in an [`NbtArena`].
+#[derive(Clone, Copy)]
+pub struct CompactCompound<'a> {
+    arena:   &'a NbtArena,
+    entries: &'a [Entry],
+}
+
+impl<'a> CompactCompound<'a> {
+    /// Returns the number of entries in this compound.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether this compound has no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the value of the given key, if present.
+    pub fn get(&self, key: &str) -> Option<CompactTag<'a>> {
+        let &key = self.arena.key_ids.get(key)?;
+        self.entries
+            .iter()
+            .find(|entry| entry.key == key)
+            .map(|entry| self.arena.tag(entry.node))
+    }
+
+    /// Returns whether the compound contains the given key.
+    #[inline]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Iterates over the entries of this compound.
+    #[inline]
+    pub fn iter(&self) -> CompactCompoundIter<'a> {
+        CompactCompoundIter {
+            arena:   self.arena,
+            entries: self.entries.iter(),
+        }
+    }
+
+    /// Copies this compound out of its arena.
+    pub fn to_compound(&self) -> NbtCompound {
+        let mut compound = NbtCompound::with_capacity(self.len());
+        for (key, tag) in self {
+            compound.insert(key, tag.to_tag());
+        }
+        compound
+    }
+
+    /// Whether this compound is the wrapper of an element of a heterogenous list.
+    /// See [`NbtList::wrap_heterogenous`].
+    fn is_wrapper(&self) -> bool {
+        matches!(self.entries, [entry] if self.arena.keys[entry.key as usize].is_empty())
+    }
+}
+
+impl Debug for CompactCompound<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<'a> IntoIterator for CompactCompound<'a> {
+    type Item = (&'a str, CompactTag<'a>);
+    type IntoIter = CompactCompoundIter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &CompactCompound<'a> {
+    type Item = (&'a str, CompactTag<'a>);
+    type IntoIter = CompactCompoundIter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`CompactCompound`].
+#[derive(Debug, Clone)]
+pub struct CompactCompoundIter<'a> {
+    arena:   &'a NbtArena,
+    entries: slice::Iter<'a, Entry>,
+}
+
+impl<'a> Iterator for CompactCompoundIter<'a> {
+    type Item = (&'a str, CompactTag<'a>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.entries.next()?;
+        Some((&self.arena.keys[entry.key as usize], self.arena.tag(entry.node)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl ExactSizeIterator for CompactCompoundIter<'_> {}
+
+/// A borrowed view of a list stored in an [`NbtArena`].
+#[derive(Clone, Copy)]
+pub struct CompactList<'a> {
+    arena:    &'a NbtArena,
+    elements: &'a [u32],
+}
+
+impl<'a> CompactList<'a> {
+    /// Returns the number of elements in this list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns whether this list is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns the element at the given index, if in range.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<CompactTag<'a>> {
+        self.elements.get(index).map(|&node| self.arena.tag(node))
+    }
+
+    /// Iterates over the elements of this list.
+    #[inline]
+    pub fn iter(&self) -> CompactListIter<'a> {
+        CompactListIter {
+            arena:    self.arena,
+            elements: self.elements.iter(),
+        }
+    }
+
+    /// Copies this list out of its arena.
+    pub fn to_list(&self) -> NbtList {
+        self.iter().map(|tag| tag.to_tag()).collect()
+    }
+
+    /// The ID of the tag type of every element in this list, or `None` if the list is not
+    /// homogenous. Empty lists have the ID of `TAG_End`.
+    fn element_id(&self) -> Option<u8> {
+        let mut elements = self.iter();
+        let Some(first) = elements.next() else {
+            return Some(TAG_END_ID);
+        };
+
+        let tag_id = first.tag_id();
+        elements
+            .all(|element| element.tag_id() == tag_id)
+            .then_some(tag_id)
+    }
+}
+
+impl Debug for CompactList<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<'a> IntoIterator for CompactList<'a> {
+    type Item = CompactTag<'a>;
+    type IntoIter = CompactListIter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &CompactList<'a> {
+    type Item = CompactTag<'a>;
+    type IntoIter = CompactListIter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of a [`CompactList`].
+#[derive(Debug, Clone)]
+pub struct CompactListIter<'a> {
+    arena:    &'a NbtArena,
+    elements: slice::Iter<'a, u32>,
+}
+
+impl<'a> Iterator for CompactListIter<'a> {
+    type Item = CompactTag<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.elements.next().map(|&node| self.arena.tag(node))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.elements.size_hint()
+    }
+}
+
+impl ExactSizeIterator for CompactListIter<'_> {}
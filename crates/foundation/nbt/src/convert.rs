@@ -0,0 +1,502 @@
+//! Direct conversions between Rust types and NBT data, without going through serde.
+//!
+//! The [`ToNbt`] and [`FromNbt`] traits convert types to and from compounds, while
+//! [`ToNbtTag`] and [`FromNbtTag`] convert types to and from any kind of tag. They are usually
+//! implemented with the `ToNbt` and `FromNbt` derive macros of `prismarine-anchor-macros`,
+//! whose generated code uses the helper functions in this module.
+
+use std::{any, fmt};
+use std::fmt::{Display, Formatter};
+
+use thiserror::Error;
+
+use crate::{
+    path::NbtPath,
+    tag::{NbtCompound, NbtList, NbtTag},
+};
+
+
+// ================================
+//  Traits
+// ================================
+
+/// A type which can be converted into an NBT compound.
+pub trait ToNbt {
+    fn to_nbt(&self) -> NbtCompound;
+}
+
+/// A type which can be converted from an NBT compound.
+pub trait FromNbt: Sized {
+    fn from_nbt(compound: NbtCompound) -> Result<Self, NbtConvertError>;
+}
+
+/// A type which can be converted into an NBT tag.
+pub trait ToNbtTag {
+    fn to_nbt_tag(&self) -> NbtTag;
+}
+
+/// A type which can be converted from an NBT tag.
+pub trait FromNbtTag: Sized {
+    fn from_nbt_tag(tag: NbtTag) -> Result<Self, NbtConvertError>;
+}
+
+/// An integer type (or `bool`) which can be stored in any of the integer tag variants,
+/// or in any of the array tag variants when in a `Vec`. See [`IntEncoding`].
+pub trait NbtInteger: Copy {
+    /// Whether the type is unsigned, in which case values that do not fit in an encoding's
+    /// signed range are stored with the same bits, as Minecraft does for unsigned bytes.
+    const UNSIGNED: bool;
+
+    fn to_i128(self) -> i128;
+    fn from_i128(value: i128) -> Option<Self>;
+}
+
+macro_rules! nbt_integer {
+    ($($type:ty, $unsigned:literal);* $(;)?) => {
+        $(
+            impl NbtInteger for $type {
+                const UNSIGNED: bool = $unsigned;
+
+                #[inline]
+                fn to_i128(self) -> i128 {
+                    i128::from(self)
+                }
+
+                #[inline]
+                fn from_i128(value: i128) -> Option<Self> {
+                    Self::try_from(value).ok()
+                }
+            }
+        )*
+    };
+}
+
+nbt_integer!(
+    i8,  false;
+    i16, false;
+    i32, false;
+    i64, false;
+    u8,  true;
+    u16, true;
+    u32, true;
+    u64, true;
+);
+
+impl NbtInteger for bool {
+    #[expect(clippy::use_self, reason = "`Self` would obscure that this is a flag")]
+    const UNSIGNED: bool = true;
+
+    #[inline]
+    fn to_i128(self) -> i128 {
+        i128::from(self)
+    }
+
+    /// Any nonzero value is `true`.
+    #[inline]
+    fn from_i128(value: i128) -> Option<Self> {
+        Some(value != 0)
+    }
+}
+
+
+// ================================
+//  Encodings
+// ================================
+
+/// The tag variant used to store an [`NbtInteger`].
+///
+/// Values which do not fit in the encoding are truncated when written. When read, the tag
+/// must be of the encoding's variant, and its value must fit in the Rust type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntEncoding {
+    Byte,
+    Short,
+    Int,
+    Long,
+}
+
+impl IntEncoding {
+    fn bits(self) -> u32 {
+        match self {
+            Self::Byte  => 8,
+            Self::Short => 16,
+            Self::Int   => 32,
+            Self::Long  => 64,
+        }
+    }
+
+    fn tag_name(self) -> &'static str {
+        match self {
+            Self::Byte  => "Byte",
+            Self::Short => "Short",
+            Self::Int   => "Int",
+            Self::Long  => "Long",
+        }
+    }
+}
+
+/// The array tag variant used to store a `Vec` of [`NbtInteger`]s.
+/// The elements are treated as described in [`IntEncoding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayEncoding {
+    ByteArray,
+    IntArray,
+    LongArray,
+}
+
+impl ArrayEncoding {
+    fn element_encoding(self) -> IntEncoding {
+        match self {
+            Self::ByteArray => IntEncoding::Byte,
+            Self::IntArray  => IntEncoding::Int,
+            Self::LongArray => IntEncoding::Long,
+        }
+    }
+
+    fn tag_name(self) -> &'static str {
+        match self {
+            Self::ByteArray => "ByteArray",
+            Self::IntArray  => "IntArray",
+            Self::LongArray => "LongArray",
+        }
+    }
+}
+
+#[expect(clippy::cast_possible_truncation, reason = "truncation is documented")]
+pub fn encode_int<T: NbtInteger>(value: T, encoding: IntEncoding) -> NbtTag {
+    let value = value.to_i128();
+    match encoding {
+        IntEncoding::Byte  => NbtTag::Byte(value as i8),
+        IntEncoding::Short => NbtTag::Short(value as i16),
+        IntEncoding::Int   => NbtTag::Int(value as i32),
+        IntEncoding::Long  => NbtTag::Long(value as i64),
+    }
+}
+
+pub fn decode_int<T: NbtInteger>(tag: &NbtTag, encoding: IntEncoding) -> Result<T, NbtConvertError> {
+    let value = match (tag, encoding) {
+        (NbtTag::Byte(value),  IntEncoding::Byte)  => i64::from(*value),
+        (NbtTag::Short(value), IntEncoding::Short) => i64::from(*value),
+        (NbtTag::Int(value),   IntEncoding::Int)   => i64::from(*value),
+        (NbtTag::Long(value),  IntEncoding::Long)  => *value,
+        _ => return Err(NbtConvertError::type_mismatch(encoding.tag_name(), tag)),
+    };
+
+    int_from_i64(value, encoding)
+}
+
+#[expect(clippy::cast_possible_truncation, reason = "truncation is documented")]
+pub fn encode_array<T: NbtInteger>(values: &[T], encoding: ArrayEncoding) -> NbtTag {
+    let values = values.iter().map(|value| value.to_i128());
+    match encoding {
+        ArrayEncoding::ByteArray => NbtTag::ByteArray(values.map(|value| value as i8).collect()),
+        ArrayEncoding::IntArray  => NbtTag::IntArray(values.map(|value| value as i32).collect()),
+        ArrayEncoding::LongArray => NbtTag::LongArray(values.map(|value| value as i64).collect()),
+    }
+}
+
+pub fn decode_array<T: NbtInteger>(
+    tag:      &NbtTag,
+    encoding: ArrayEncoding,
+) -> Result<Vec<T>, NbtConvertError> {
+    let element_encoding = encoding.element_encoding();
+    let decode_all = |values: &mut dyn Iterator<Item = i64>| {
+        values
+            .enumerate()
+            .map(|(index, value)| {
+                int_from_i64(value, element_encoding).map_err(|err| err.at_index(index))
+            })
+            .collect()
+    };
+
+    match (tag, encoding) {
+        (NbtTag::ByteArray(values), ArrayEncoding::ByteArray) => {
+            decode_all(&mut values.iter().map(|&value| i64::from(value)))
+        }
+        (NbtTag::IntArray(values), ArrayEncoding::IntArray) => {
+            decode_all(&mut values.iter().map(|&value| i64::from(value)))
+        }
+        (NbtTag::LongArray(values), ArrayEncoding::LongArray) => {
+            decode_all(&mut values.iter().copied())
+        }
+        _ => Err(NbtConvertError::type_mismatch(encoding.tag_name(), tag)),
+    }
+}
+
+/// Stores the bytes in a `String` tag if they are valid UTF-8, and a `ByteString` tag otherwise.
+pub fn encode_byte_string(bytes: &[u8]) -> NbtTag {
+    match String::from_utf8(bytes.to_vec()) {
+        Ok(string) => NbtTag::String(string),
+        Err(err)   => NbtTag::ByteString(err.into_bytes()),
+    }
+}
+
+/// Reads the bytes of a `String` or `ByteString` tag.
+pub fn decode_byte_string(tag: NbtTag) -> Result<Vec<u8>, NbtConvertError> {
+    match tag {
+        NbtTag::String(string)    => Ok(string.into_bytes()),
+        NbtTag::ByteString(bytes) => Ok(bytes),
+        _ => Err(NbtConvertError::type_mismatch("String", &tag)),
+    }
+}
+
+/// Removes the tag with the given key from the compound, preserving the order of the remaining
+/// tags if the `preserve_order` feature is enabled.
+pub fn take(compound: &mut NbtCompound, key: &str) -> Option<NbtTag> {
+    #[cfg(feature = "preserve_order")]
+    {
+        compound.0.shift_remove(key)
+    }
+    #[cfg(not(feature = "preserve_order"))]
+    {
+        compound.0.remove(key)
+    }
+}
+
+fn int_from_i64<T: NbtInteger>(value: i64, encoding: IntEncoding) -> Result<T, NbtConvertError> {
+    let value = i128::from(value);
+
+    T::from_i128(value)
+        .or_else(|| {
+            // Reinterpret negative values as unsigned, with the encoding's width.
+            if T::UNSIGNED && value < 0 {
+                T::from_i128(value + (1_i128 << encoding.bits()))
+            } else {
+                None
+            }
+        })
+        .ok_or_else(|| NbtConvertError::new(NbtConvertErrorKind::OutOfRange {
+            value,
+            target: any::type_name::<T>(),
+        }))
+}
+
+
+// ================================
+//  Implementations
+// ================================
+
+macro_rules! convert_integer {
+    ($($type:ty, $encoding:ident);* $(;)?) => {
+        $(
+            impl ToNbtTag for $type {
+                #[inline]
+                fn to_nbt_tag(&self) -> NbtTag {
+                    encode_int(*self, IntEncoding::$encoding)
+                }
+            }
+
+            impl FromNbtTag for $type {
+                #[inline]
+                fn from_nbt_tag(tag: NbtTag) -> Result<Self, NbtConvertError> {
+                    decode_int(&tag, IntEncoding::$encoding)
+                }
+            }
+        )*
+    };
+}
+
+convert_integer!(
+    i8,   Byte;
+    i16,  Short;
+    i32,  Int;
+    i64,  Long;
+    u8,   Byte;
+    u16,  Short;
+    u32,  Int;
+    u64,  Long;
+    bool, Byte;
+);
+
+macro_rules! convert_variant {
+    ($($type:ty, $variant:ident);* $(;)?) => {
+        $(
+            impl ToNbtTag for $type {
+                #[inline]
+                fn to_nbt_tag(&self) -> NbtTag {
+                    NbtTag::$variant(self.clone())
+                }
+            }
+
+            impl FromNbtTag for $type {
+                #[inline]
+                fn from_nbt_tag(tag: NbtTag) -> Result<Self, NbtConvertError> {
+                    match tag {
+                        NbtTag::$variant(value) => Ok(value),
+                        _ => Err(NbtConvertError::type_mismatch(stringify!($variant), &tag)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+convert_variant!(
+    f32,         Float;
+    f64,         Double;
+    String,      String;
+    NbtList,     List;
+    NbtCompound, Compound;
+);
+
+impl ToNbtTag for NbtTag {
+    #[inline]
+    fn to_nbt_tag(&self) -> NbtTag {
+        self.clone()
+    }
+}
+
+impl FromNbtTag for NbtTag {
+    #[inline]
+    fn from_nbt_tag(tag: NbtTag) -> Result<Self, NbtConvertError> {
+        Ok(tag)
+    }
+}
+
+/// Stored as a `List` tag.
+impl<T: ToNbtTag> ToNbtTag for Vec<T> {
+    fn to_nbt_tag(&self) -> NbtTag {
+        NbtTag::List(self.iter().map(ToNbtTag::to_nbt_tag).collect())
+    }
+}
+
+/// Read from a `List` tag.
+impl<T: FromNbtTag> FromNbtTag for Vec<T> {
+    fn from_nbt_tag(tag: NbtTag) -> Result<Self, NbtConvertError> {
+        match tag {
+            NbtTag::List(list) => list
+                .into_iter()
+                .enumerate()
+                .map(|(index, tag)| T::from_nbt_tag(tag).map_err(|err| err.at_index(index)))
+                .collect(),
+            _ => Err(NbtConvertError::type_mismatch("List", &tag)),
+        }
+    }
+}
+
+impl<T: ToNbtTag + ?Sized> ToNbtTag for Box<T> {
+    #[inline]
+    fn to_nbt_tag(&self) -> NbtTag {
+        T::to_nbt_tag(self)
+    }
+}
+
+impl<T: FromNbtTag> FromNbtTag for Box<T> {
+    #[inline]
+    fn from_nbt_tag(tag: NbtTag) -> Result<Self, NbtConvertError> {
+        T::from_nbt_tag(tag).map(Self::new)
+    }
+}
+
+
+// ================================
+//  Errors
+// ================================
+
+/// An error from converting NBT data into a Rust type, along with the path to the tag which
+/// could not be converted.
+#[derive(Debug, Clone)]
+pub struct NbtConvertError {
+    /// The path, innermost node first, since the path is built while returning the error.
+    reversed_path: Vec<PathNode>,
+    kind:          NbtConvertErrorKind,
+}
+
+#[derive(Debug, Clone)]
+enum PathNode {
+    Key(String),
+    Index(usize),
+}
+
+impl NbtConvertError {
+    pub fn new(kind: NbtConvertErrorKind) -> Self {
+        Self {
+            reversed_path: Vec::new(),
+            kind,
+        }
+    }
+
+    /// An error for a missing key of a compound. The key is included in the error's path.
+    pub fn missing_key(key: &str) -> Self {
+        Self::new(NbtConvertErrorKind::MissingKey).at_key(key)
+    }
+
+    pub fn type_mismatch(expected: &'static str, found: &NbtTag) -> Self {
+        Self::new(NbtConvertErrorKind::TypeMismatch {
+            expected,
+            found: found.tag_name(),
+        })
+    }
+
+    pub fn custom<T: Display>(message: T) -> Self {
+        Self::new(NbtConvertErrorKind::Custom(message.to_string()))
+    }
+
+    /// Indicates that the error occurred in the value of the given key of a compound,
+    /// relative to the error's current path.
+    #[must_use]
+    pub fn at_key(mut self, key: &str) -> Self {
+        self.reversed_path.push(PathNode::Key(key.to_owned()));
+        self
+    }
+
+    /// Indicates that the error occurred in the given element of a list or array,
+    /// relative to the error's current path.
+    #[must_use]
+    pub fn at_index(mut self, index: usize) -> Self {
+        self.reversed_path.push(PathNode::Index(index));
+        self
+    }
+
+    pub fn kind(&self) -> &NbtConvertErrorKind {
+        &self.kind
+    }
+
+    /// The path to the tag which could not be converted.
+    pub fn path(&self) -> NbtPath {
+        let mut path = NbtPath::root();
+        for node in self.reversed_path.iter().rev() {
+            match node {
+                PathNode::Key(key)     => path.push_key(key),
+                PathNode::Index(index) => path.push_index(i32::try_from(*index).unwrap_or(i32::MAX)),
+            }
+        }
+        path
+    }
+}
+
+impl Display for NbtConvertError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.reversed_path.is_empty() {
+            write!(f, "{}", self.kind)
+        } else {
+            write!(f, "{} at {}", self.kind, self.path())
+        }
+    }
+}
+
+impl std::error::Error for NbtConvertError {}
+
+#[derive(Error, Debug, Clone)]
+pub enum NbtConvertErrorKind {
+    /// A compound did not have a required key.
+    #[error("missing required key")]
+    MissingKey,
+    /// A tag was not of the expected variant.
+    #[error("expected {expected} tag, but found {found}")]
+    TypeMismatch {
+        expected: &'static str,
+        found:    &'static str,
+    },
+    /// An integer did not fit in the Rust type it was converted into.
+    #[error("{value} is out of range for {target}")]
+    OutOfRange {
+        value:  i128,
+        target: &'static str,
+    },
+    /// A string did not name any variant of an enum.
+    #[error("unknown variant \"{variant}\"")]
+    UnknownVariant { variant: String },
+    #[error("{0}")]
+    Custom(String),
+}
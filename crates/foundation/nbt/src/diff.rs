@@ -0,0 +1,527 @@
+//! Structural diffs of NBT data, which can be replayed onto other NBT data as patches.
+//!
+//! A diff is a sequence of operations addressed by [`NbtPath`]s. Compounds are compared
+//! key by key, and lists are aligned element by element, so that a single inserted or removed
+//! element of a list does not cause every later element to be reported as changed.
+//! Lists whose elements do not all have the same tag type are reported as changed as a whole.
+//!
+//! The operations of a diff are ordered such that list indices refer to the state of the list
+//! after all earlier operations were applied.
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    path::{NbtPath, NbtPathError, NbtPathRoot},
+    settings::SnbtWriteOptions,
+    tag::{FloatEquality, NbtCompound, NbtList, NbtTag, TagWithOptions},
+};
+
+
+/// Lists whose differing middle sections would require comparing more than this many pairs of
+/// elements are compared position by position, rather than by finding their longest common
+/// subsequence.
+const MAX_ALIGNMENT_PAIRS: usize = 1 << 20;
+
+
+// ================================
+//  Diffing and patching
+// ================================
+
+/// Computes the operations which turn `old` into `new`.
+///
+/// `Float` and `Double` tags are compared with the given comparator, such as [`CompareExact`]
+/// or `CompareApprox`.
+///
+/// [`CompareExact`]: crate::CompareExact
+pub fn diff<E: FloatEquality>(old: &NbtTag, new: &NbtTag, equal: E) -> NbtDiff {
+    let mut differ = Differ {
+        equal,
+        path: NbtPath::root(),
+        ops:  Vec::new(),
+    };
+    differ.diff_tags(old, new);
+
+    NbtDiff(differ.ops)
+}
+
+/// Computes the operations which turn the compound `old` into `new`.
+/// See [`diff`] for more information.
+pub fn diff_compounds<E: FloatEquality>(
+    old:   &NbtCompound,
+    new:   &NbtCompound,
+    equal: E,
+) -> NbtDiff {
+    let mut differ = Differ {
+        equal,
+        path: NbtPath::root(),
+        ops:  Vec::new(),
+    };
+    differ.diff_compounds(old, new);
+
+    NbtDiff(differ.ops)
+}
+
+/// Replays the operations of a diff onto the given root, in order.
+///
+/// Applying a diff of `old` and `new` onto `old` results in `new`. When applied to other data,
+/// an error is returned if the path of an operation selects nothing, or if a removed tag
+/// does not exist. Operations which were applied before an error occurred are not undone.
+///
+/// ```
+/// use prismarine_anchor_nbt::{snbt, CompareExact, SnbtParseOptions};
+/// use prismarine_anchor_nbt::diff::{apply_patch, diff_compounds};
+///
+/// let opts = SnbtParseOptions::default_updated();
+/// let old = snbt::parse_compound(r#"{l: [1, 2]}"#, opts).unwrap();
+/// let new = snbt::parse_compound(r#"{l: ["a"]}"#, opts).unwrap();
+///
+/// let mut patched = old.clone();
+/// apply_patch(&mut patched, &diff_compounds(&old, &new, CompareExact)).unwrap();
+/// assert_eq!(patched, new);
+/// ```
+pub fn apply_patch<R>(root: &mut R, patch: &NbtDiff) -> Result<(), NbtPathError>
+where
+    R: NbtPathRoot + ?Sized,
+{
+    for op in &patch.0 {
+        match op {
+            NbtDiffOp::Added { path, value } => {
+                path.set(root, value)?;
+            }
+            NbtDiffOp::Removed { path, .. } => {
+                if path.remove(root)? == 0 {
+                    return Err(path.no_match_at_end());
+                }
+            }
+            NbtDiffOp::Changed { path, new, .. } => {
+                path.set(root, new)?;
+            }
+            NbtDiffOp::Inserted { path, index, value } => {
+                path.insert(root, list_index(*index), value)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The operations which turn one NBT tag into another, produced by [`diff`] or
+/// [`diff_compounds`]. The `Display` implementation renders one operation per line,
+/// with values written as SNBT.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NbtDiff(Vec<NbtDiffOp>);
+
+impl NbtDiff {
+    /// Returns the operations of this diff, in the order they should be applied.
+    #[inline]
+    pub fn ops(&self) -> &[NbtDiffOp] {
+        &self.0
+    }
+
+    /// Returns the operations of this diff, in the order they should be applied.
+    #[inline]
+    pub fn into_ops(self) -> Vec<NbtDiffOp> {
+        self.0
+    }
+
+    /// Returns the number of operations in this diff.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if the diffed tags were equal, false otherwise.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterates over the operations of this diff, in the order they should be applied.
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, NbtDiffOp> {
+        self.0.iter()
+    }
+
+    /// Replays this diff onto the given root. See [`apply_patch`].
+    #[inline]
+    pub fn apply<R: NbtPathRoot + ?Sized>(&self, root: &mut R) -> Result<(), NbtPathError> {
+        apply_patch(root, self)
+    }
+
+    /// Renders this diff with one operation per line, writing values as SNBT
+    /// with the given options.
+    pub fn to_string_with_options(&self, opts: SnbtWriteOptions) -> String {
+        let mut string = String::new();
+        for op in &self.0 {
+            string.push_str(&op.to_string_with_options(opts));
+            string.push('\n');
+        }
+        string
+    }
+}
+
+impl<'a> IntoIterator for &'a NbtDiff {
+    type IntoIter = std::slice::Iter<'a, NbtDiffOp>;
+    type Item = &'a NbtDiffOp;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for NbtDiff {
+    type IntoIter = std::vec::IntoIter<NbtDiffOp>;
+    type Item = NbtDiffOp;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl Display for NbtDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for op in &self.0 {
+            writeln!(f, "{op}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A single operation of an [`NbtDiff`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtDiffOp {
+    /// A tag was added to a compound.
+    Added { path: NbtPath, value: NbtTag },
+    /// A tag was removed from a compound or list.
+    Removed { path: NbtPath, value: NbtTag },
+    /// A tag was replaced by a tag which is not equal to it.
+    Changed { path: NbtPath, old: NbtTag, new: NbtTag },
+    /// A tag was inserted into the list at `path`, at the given index.
+    Inserted { path: NbtPath, index: usize, value: NbtTag },
+}
+
+impl NbtDiffOp {
+    /// Returns the path of the tag affected by this operation, or the path of the list
+    /// for an insertion.
+    #[inline]
+    pub fn path(&self) -> &NbtPath {
+        match self {
+            Self::Added { path, .. }
+            | Self::Removed { path, .. }
+            | Self::Changed { path, .. }
+            | Self::Inserted { path, .. } => path,
+        }
+    }
+
+    /// Renders this operation as a single line, writing values as SNBT with the given options.
+    pub fn to_string_with_options(&self, opts: SnbtWriteOptions) -> String {
+        OpWithOptions { op: self, opts }.to_string()
+    }
+}
+
+impl Display for NbtDiffOp {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(
+            &OpWithOptions {
+                op:   self,
+                opts: SnbtWriteOptions::default_original(),
+            },
+            f,
+        )
+    }
+}
+
+struct OpWithOptions<'a> {
+    op:   &'a NbtDiffOp,
+    opts: SnbtWriteOptions,
+}
+
+impl Display for OpWithOptions<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let snbt = |tag| TagWithOptions::new(tag, self.opts);
+        let path = |path: &NbtPath| {
+            if path.is_root() {
+                "(root)".to_owned()
+            } else {
+                path.to_string()
+            }
+        };
+
+        match self.op {
+            NbtDiffOp::Added { path: p, value } => {
+                write!(f, "+ {}: {}", path(p), snbt(value))
+            }
+            NbtDiffOp::Removed { path: p, value } => {
+                write!(f, "- {}: {}", path(p), snbt(value))
+            }
+            NbtDiffOp::Changed { path: p, old, new } => {
+                write!(f, "~ {}: {} -> {}", path(p), snbt(old), snbt(new))
+            }
+            NbtDiffOp::Inserted { path: p, index, value } => {
+                write!(f, "+ {}[{index}]: {}", path(p), snbt(value))
+            }
+        }
+    }
+}
+
+// ================================
+//  Diff engine
+// ================================
+
+struct Differ<E> {
+    equal: E,
+    /// The path of the tags currently being compared
+    path:  NbtPath,
+    ops:   Vec<NbtDiffOp>,
+}
+
+/// An edit in the alignment of two lists.
+enum Edit<'a> {
+    Keep,
+    Replace(&'a NbtTag, &'a NbtTag),
+    Remove(&'a NbtTag),
+    Insert(&'a NbtTag),
+}
+
+impl<E: FloatEquality> Differ<E> {
+    fn diff_tags(&mut self, old: &NbtTag, new: &NbtTag) {
+        match (old, new) {
+            (NbtTag::Compound(old), NbtTag::Compound(new)) => self.diff_compounds(old, new),
+            (NbtTag::List(old),     NbtTag::List(new))
+                if share_element_type(old, new) => self.diff_lists(old, new),
+            _ => {
+                if !tags_equal(old, new, self.equal) {
+                    self.ops.push(NbtDiffOp::Changed {
+                        path: self.path.clone(),
+                        old:  old.clone(),
+                        new:  new.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn diff_compounds(&mut self, old: &NbtCompound, new: &NbtCompound) {
+        #[cfg_attr(feature = "preserve_order", expect(unused_mut))]
+        let mut old_keys: Vec<&String> = old.inner().keys().collect();
+        #[cfg_attr(feature = "preserve_order", expect(unused_mut))]
+        let mut added_keys: Vec<&String> = new
+            .inner()
+            .keys()
+            .filter(|key| !old.contains_key(key.as_str()))
+            .collect();
+
+        // Without a consistent iteration order, sort keys so that diffs are reproducible
+        #[cfg(not(feature = "preserve_order"))]
+        old_keys.sort_unstable();
+        #[cfg(not(feature = "preserve_order"))]
+        added_keys.sort_unstable();
+
+        for key in old_keys {
+            let old_value = &old.0[key];
+            self.path.push_key(key);
+
+            if let Some(new_value) = new.0.get(key) {
+                self.diff_tags(old_value, new_value);
+            } else {
+                self.ops.push(NbtDiffOp::Removed {
+                    path:  self.path.clone(),
+                    value: old_value.clone(),
+                });
+            }
+
+            self.path.pop();
+        }
+
+        for key in added_keys {
+            self.path.push_key(key);
+            self.ops.push(NbtDiffOp::Added {
+                path:  self.path.clone(),
+                value: new.0[key].clone(),
+            });
+            self.path.pop();
+        }
+    }
+
+    fn diff_lists(&mut self, old: &NbtList, new: &NbtList) {
+        let mut index = 0;
+
+        for edit in self.align(old, new) {
+            match edit {
+                Edit::Keep => index += 1,
+                Edit::Replace(old, new) => {
+                    self.path.push_index(list_index(index));
+                    self.diff_tags(old, new);
+                    self.path.pop();
+                    index += 1;
+                }
+                Edit::Remove(old) => {
+                    self.path.push_index(list_index(index));
+                    self.ops.push(NbtDiffOp::Removed {
+                        path:  self.path.clone(),
+                        value: old.clone(),
+                    });
+                    self.path.pop();
+                }
+                Edit::Insert(new) => {
+                    self.ops.push(NbtDiffOp::Inserted {
+                        path:  self.path.clone(),
+                        index,
+                        value: new.clone(),
+                    });
+                    index += 1;
+                }
+            }
+        }
+    }
+
+    /// Aligns the elements of two lists, by finding their longest common subsequence if they
+    /// are not too large. Removed and inserted elements between kept elements are paired up
+    /// as replacements where possible.
+    fn align<'a>(&self, old: &'a [NbtTag], new: &'a [NbtTag]) -> Vec<Edit<'a>> {
+        let equal = |old, new| tags_equal(old, new, self.equal);
+
+        let prefix = old
+            .iter()
+            .zip(new)
+            .take_while(|&(old, new)| equal(old, new))
+            .count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|&(old, new)| equal(old, new))
+            .count();
+
+        let old_middle = &old[prefix..old.len() - suffix];
+        let new_middle = &new[prefix..new.len() - suffix];
+
+        let mut edits = Vec::with_capacity(old.len().max(new.len()));
+        edits.extend((0..prefix).map(|_| Edit::Keep));
+
+        if old_middle.len().saturating_mul(new_middle.len()) <= MAX_ALIGNMENT_PAIRS {
+            self.align_subsequence(old_middle, new_middle, &mut edits);
+        } else {
+            pair_edits(old_middle, new_middle, &mut edits);
+        }
+
+        edits.extend((0..suffix).map(|_| Edit::Keep));
+        edits
+    }
+
+    fn align_subsequence<'a>(
+        &self,
+        old:   &'a [NbtTag],
+        new:   &'a [NbtTag],
+        edits: &mut Vec<Edit<'a>>,
+    ) {
+        let equal = |old, new| tags_equal(old, new, self.equal);
+        let width = new.len() + 1;
+
+        // `lengths[i * width + j]` is the length of the longest common subsequence
+        // of `old[i..]` and `new[j..]`.
+        let mut lengths = vec![0_u32; (old.len() + 1) * width];
+        for i in (0..old.len()).rev() {
+            for j in (0..new.len()).rev() {
+                lengths[i * width + j] = if equal(&old[i], &new[j]) {
+                    lengths[(i + 1) * width + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+                };
+            }
+        }
+
+        // Between kept elements, the removed and inserted elements are contiguous
+        let (mut i, mut j) = (0, 0);
+        let (mut gap_start_i, mut gap_start_j) = (0, 0);
+
+        while i < old.len() && j < new.len() {
+            if equal(&old[i], &new[j]) {
+                pair_edits(&old[gap_start_i..i], &new[gap_start_j..j], edits);
+                edits.push(Edit::Keep);
+
+                i += 1;
+                j += 1;
+                (gap_start_i, gap_start_j) = (i, j);
+            } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        pair_edits(&old[gap_start_i..], &new[gap_start_j..], edits);
+    }
+}
+
+/// Pairs up removed and inserted elements as replacements, followed by any remaining
+/// removals or insertions.
+fn pair_edits<'a>(removed: &'a [NbtTag], inserted: &'a [NbtTag], edits: &mut Vec<Edit<'a>>) {
+    let paired = removed.len().min(inserted.len());
+
+    edits.extend(removed.iter().zip(inserted).map(|(old, new)| Edit::Replace(old, new)));
+    edits.extend(removed[paired..].iter().map(Edit::Remove));
+    edits.extend(inserted[paired..].iter().map(Edit::Insert));
+}
+
+/// Returns true if every element of both lists has the same tag type.
+///
+/// Paths can only place a tag into a list whose other elements have the same type, so lists
+/// are only diffed element by element if this holds; otherwise, they are changed as a whole.
+fn share_element_type(old: &NbtList, new: &NbtList) -> bool {
+    let mut elements = old.iter().chain(new.iter());
+    elements.next().is_none_or(|first| {
+        let tag_type = first.tag_type();
+        elements.all(|element| element.tag_type() == tag_type)
+    })
+}
+
+/// Compares two tags, where the order of list elements matters.
+fn tags_equal<E: FloatEquality>(old: &NbtTag, new: &NbtTag, equal: E) -> bool {
+    match (old, new) {
+        (NbtTag::Float(old),  NbtTag::Float(new))  => equal.equal_f32(*old, *new),
+        (NbtTag::Double(old), NbtTag::Double(new)) => equal.equal_f64(*old, *new),
+        (NbtTag::List(old),   NbtTag::List(new))   => {
+            old.len() == new.len()
+                && old.iter().zip(new.iter()).all(|(old, new)| tags_equal(old, new, equal))
+        }
+        (NbtTag::Compound(old), NbtTag::Compound(new)) => {
+            old.len() == new.len()
+                && old.iter().all(|(key, old)| {
+                    new.0.get(key).is_some_and(|new| tags_equal(old, new, equal))
+                })
+        }
+        _ => old == new,
+    }
+}
+
+/// Converts the index of an element of a list into the index type used by paths.
+#[inline]
+fn list_index(index: usize) -> i32 {
+    i32::try_from(index).unwrap_or(i32::MAX)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::CompareExact;
+    use crate::tag::{NbtList, NbtTag};
+    use super::diff;
+
+
+    #[test]
+    fn root_paths_are_labelled() {
+        let old = NbtTag::List(NbtList::from(vec![1_i32, 3]));
+        let new = NbtTag::List(NbtList::from(vec![1_i32, 2, 3]));
+        let lines = diff(&old, &new, CompareExact).to_string();
+        assert_eq!(lines, "+ (root)[1]: 2\n", "an insertion into a root list");
+
+        let new = NbtTag::Int(1);
+        let lines = diff(&old, &new, CompareExact).to_string();
+        assert!(lines.starts_with("~ (root): "), "a change of the root tag: {lines}");
+    }
+}
@@ -0,0 +1,98 @@
+//! Entry points for fuzzing the readers of this crate, enabled by the `fuzzing` feature.
+//!
+//! Each entry point feeds arbitrary input to readers with a [`MemoryBudget`] enabled,
+//! discarding any errors; a fuzzer should only be able to find panics, hangs, or allocations
+//! which exceed the budget. The fuzz targets in the `fuzz` directory of this crate call
+//! these functions.
+
+use std::io::Cursor;
+
+use crate::{io, snbt, view};
+use crate::{
+    settings::{IoOptions, MemoryBudget, NbtCompression, SnbtParseOptions},
+    stream::NbtReader,
+};
+#[cfg(feature = "serde")]
+use crate::tag::NbtCompound;
+
+
+/// The budget used by every entry point, which is far smaller than `MemoryBudget::untrusted()`
+/// so that fuzzers are quick to notice allocations which escape it.
+fn fuzzing_budget() -> MemoryBudget {
+    MemoryBudget::new(1 << 20, 1 << 16)
+}
+
+/// Feeds the input to every binary NBT reader, using each encoding of NBT.
+pub fn read_binary(data: &[u8]) {
+    let budget = fuzzing_budget();
+
+    for opts in [
+        IoOptions::java_uncompressed(),
+        IoOptions::bedrock_uncompressed(),
+        IoOptions::bedrock_network_uncompressed(),
+        IoOptions::java(),
+        IoOptions {
+            compression: NbtCompression::ZlibCompressed,
+            ..IoOptions::bedrock()
+        },
+    ] {
+        let opts = IoOptions {
+            memory_budget: budget,
+            ..opts
+        };
+
+        let _ = io::read_compound(&mut Cursor::new(data), opts);
+
+        #[cfg(feature = "allow_any_root")]
+        {
+            let _ = io::read_list(&mut Cursor::new(data), opts);
+            let _ = io::read_any_nbt(&mut Cursor::new(data), opts);
+            let _ = io::read_any_unnamed_nbt(&mut Cursor::new(data), opts);
+        }
+
+        #[cfg(feature = "serde")]
+        {
+            let _ = crate::serde::deserialize_from::<_, NbtCompound>(&mut Cursor::new(data), opts);
+            let _ = crate::serde::deserialize_from_buffer::<NbtCompound>(data, opts);
+        }
+
+        let _ = NbtReader::new(Cursor::new(data), opts).read_value();
+
+        // Reading the whole stream of events also checks that the reader terminates
+        for event in NbtReader::new(Cursor::new(data), opts) {
+            if event.is_err() {
+                break;
+            }
+        }
+
+        let mut bytes = data;
+        if let Ok((compound, _)) = view::read_compound_ref(&mut bytes, opts) {
+            // Accessing a view indexes it separately from converting it
+            let _ = compound.raw_bytes();
+            if let Ok(entries) = compound.entries() {
+                for (name, _) in entries.flatten() {
+                    let _ = compound.get(&name);
+                }
+            }
+            let _ = compound.to_compound();
+        }
+    }
+}
+
+/// Feeds the input, if it is valid UTF-8, to the SNBT parsers of both versions of SNBT.
+pub fn parse_snbt(data: &[u8]) {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    for opts in [SnbtParseOptions::default_original(), SnbtParseOptions::default_updated()] {
+        let opts = SnbtParseOptions {
+            memory_budget: fuzzing_budget(),
+            ..opts
+        };
+
+        let _ = snbt::parse_any(input, opts);
+        let _ = snbt::parse_compound(input, opts);
+        let _ = snbt::parse_any_recovering(input, opts);
+    }
+}
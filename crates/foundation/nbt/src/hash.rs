@@ -0,0 +1,441 @@
+//! Canonical hashes of NBT data, and deduplication of identical compounds.
+//!
+//! The canonical hash of a tag is an xxHash64 hash of a canonical encoding of the tag, which does
+//! not depend on the endianness of the platform, on the [`IoOptions`] the tag was read with, or
+//! (by default) on the order of the entries of compounds. Other details of the encoding are that:
+//! - the elements of heterogenous lists are unwrapped, as with [`NbtList::unwrap_heterogenous`],
+//! - `String` and `ByteString` tags with the same bytes have the same hash,
+//! - every NaN has the same hash, and `0.0` and `-0.0` have the same hash.
+//!
+//! Tags which are [`identical`] have the same canonical hash.
+//!
+//! Note that canonical hashes differ from the hashes which Minecraft computes for some data,
+//! such as the metadata of chunks, which are hashes of NBT bytes.
+//!
+//! # Examples:
+//! ```
+//! use prismarine_anchor_nbt::{snbt, SnbtParseOptions};
+//! use prismarine_anchor_nbt::hash::CompoundInterner;
+//!
+//! let opts = SnbtParseOptions::default_updated();
+//! let first = snbt::parse_compound(r#"{Name: "minecraft:stone", Count: 1b}"#, opts).unwrap();
+//! let second = snbt::parse_compound(r#"{Count: 1b, Name: "minecraft:stone"}"#, opts).unwrap();
+//! assert_eq!(first.canonical_hash(), second.canonical_hash());
+//!
+//! let mut interner = CompoundInterner::new();
+//! let id = interner.intern(first);
+//! assert_eq!(interner.intern(second), id);
+//! assert_eq!(interner.count(id), 2);
+//! ```
+//!
+//! [`IoOptions`]: crate::IoOptions
+
+use std::collections::HashMap;
+
+use xxhash_rust::xxh64::Xxh64;
+
+#[cfg(feature = "derive_serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::raw;
+use crate::{
+    raw::{
+        BYTE_ARRAY_ID, BYTE_ID, COMPOUND_ID, DOUBLE_ID, FLOAT_ID, INT_ARRAY_ID,
+        INT_ID, LIST_ID, LONG_ID, LONG_ARRAY_ID, SHORT_ID, STRING_ID,
+    },
+    tag::{NbtCompound, NbtList, NbtTag, unwrap_element},
+};
+
+
+// ================================
+//  Hashing
+// ================================
+
+/// Whether the order of the entries of compounds affects canonical hashes and
+/// [`identical`] comparisons.
+///
+/// `Significant` falls back to `Ignored` if the `preserve_order` feature is disabled, since
+/// compounds then iterate over their entries in an arbitrary order which can differ between
+/// compounds with the same entries.
+#[cfg_attr(feature = "derive_serde",    derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "derive_standard", derive(PartialEq, Eq, PartialOrd, Ord, Hash))]
+#[derive(Debug, Default, Clone, Copy)]
+pub enum CompoundOrder {
+    /// Compounds with the same entries are identical, regardless of order.
+    #[default]
+    Ignored,
+    /// Compounds are only identical if their entries are in the same order.
+    /// The same as `Ignored` if the `preserve_order` feature is disabled.
+    Significant,
+}
+
+impl CompoundOrder {
+    /// The order which is actually used, which is `Ignored` if the order of entries
+    /// is not preserved.
+    #[inline]
+    #[must_use]
+    pub const fn effective(self) -> Self {
+        if cfg!(feature = "preserve_order") {
+            self
+        } else {
+            Self::Ignored
+        }
+    }
+}
+
+/// Computes the canonical hash of a tag. See the [module-level documentation](self).
+pub fn canonical_hash(tag: &NbtTag, order: CompoundOrder) -> u64 {
+    let mut hasher = Xxh64::new(0);
+    hash_tag(&mut hasher, tag, order);
+    hasher.digest()
+}
+
+/// Computes the canonical hash of a compound, which is the canonical hash of
+/// a `Compound` tag with the same entries.
+pub fn canonical_compound_hash(compound: &NbtCompound, order: CompoundOrder) -> u64 {
+    let mut hasher = Xxh64::new(0);
+    hasher.update(&[COMPOUND_ID]);
+    hash_compound_body(&mut hasher, compound, order);
+    hasher.digest()
+}
+
+fn hash_tag(hasher: &mut Xxh64, tag: &NbtTag, order: CompoundOrder) {
+    match tag {
+        NbtTag::Byte(value)   => hash_value(hasher, BYTE_ID,   &value.to_le_bytes()),
+        NbtTag::Short(value)  => hash_value(hasher, SHORT_ID,  &value.to_le_bytes()),
+        NbtTag::Int(value)    => hash_value(hasher, INT_ID,    &value.to_le_bytes()),
+        NbtTag::Long(value)   => hash_value(hasher, LONG_ID,   &value.to_le_bytes()),
+        NbtTag::Float(value)  => {
+            hash_value(hasher, FLOAT_ID, &canonical_f32(*value).to_le_bytes());
+        }
+        NbtTag::Double(value) => {
+            hash_value(hasher, DOUBLE_ID, &canonical_f64(*value).to_le_bytes());
+        }
+        NbtTag::ByteArray(value) => {
+            hash_len(hasher, BYTE_ARRAY_ID, value.len());
+            hasher.update(raw::cast_bytes_to_unsigned(value));
+        }
+        NbtTag::String(value) => {
+            hash_len(hasher, STRING_ID, value.len());
+            hasher.update(value.as_bytes());
+        }
+        NbtTag::ByteString(value) => {
+            hash_len(hasher, STRING_ID, value.len());
+            hasher.update(value);
+        }
+        NbtTag::List(value) => hash_list(hasher, value, order),
+        NbtTag::Compound(value) => {
+            hasher.update(&[COMPOUND_ID]);
+            hash_compound_body(hasher, value, order);
+        }
+        NbtTag::IntArray(value) => {
+            hash_len(hasher, INT_ARRAY_ID, value.len());
+            for int in value {
+                hasher.update(&int.to_le_bytes());
+            }
+        }
+        NbtTag::LongArray(value) => {
+            hash_len(hasher, LONG_ARRAY_ID, value.len());
+            for long in value {
+                hasher.update(&long.to_le_bytes());
+            }
+        }
+    }
+}
+
+#[inline]
+fn hash_value(hasher: &mut Xxh64, tag_id: u8, bytes: &[u8]) {
+    hasher.update(&[tag_id]);
+    hasher.update(bytes);
+}
+
+#[inline]
+fn hash_len(hasher: &mut Xxh64, tag_id: u8, len: usize) {
+    hasher.update(&[tag_id]);
+    hasher.update(&(len as u64).to_le_bytes());
+}
+
+fn hash_list(hasher: &mut Xxh64, list: &NbtList, order: CompoundOrder) {
+    hash_len(hasher, LIST_ID, list.len());
+    for element in list {
+        hash_tag(hasher, unwrap_element(element), order);
+    }
+}
+
+fn hash_compound_body(hasher: &mut Xxh64, compound: &NbtCompound, order: CompoundOrder) {
+    hasher.update(&(compound.len() as u64).to_le_bytes());
+
+    match order.effective() {
+        CompoundOrder::Significant => {
+            for (key, value) in compound {
+                hash_entry(hasher, key, value, order);
+            }
+        }
+        CompoundOrder::Ignored => {
+            // Each entry is hashed separately, and the sorted hashes are then hashed,
+            // which does not depend on the order of entries
+            let mut entry_hashes = compound
+                .iter()
+                .map(|(key, value)| {
+                    let mut entry_hasher = Xxh64::new(0);
+                    hash_entry(&mut entry_hasher, key, value, order);
+                    entry_hasher.digest()
+                })
+                .collect::<Vec<_>>();
+            entry_hashes.sort_unstable();
+
+            for entry_hash in entry_hashes {
+                hasher.update(&entry_hash.to_le_bytes());
+            }
+        }
+    }
+}
+
+fn hash_entry(hasher: &mut Xxh64, key: &str, value: &NbtTag, order: CompoundOrder) {
+    hasher.update(&(key.len() as u64).to_le_bytes());
+    hasher.update(key.as_bytes());
+    hash_tag(hasher, value, order);
+}
+
+fn canonical_f32(value: f32) -> u32 {
+    if value.is_nan() {
+        f32::NAN.to_bits()
+    } else if value == 0.0 {
+        0
+    } else {
+        value.to_bits()
+    }
+}
+
+fn canonical_f64(value: f64) -> u64 {
+    if value.is_nan() {
+        f64::NAN.to_bits()
+    } else if value == 0.0 {
+        0
+    } else {
+        value.to_bits()
+    }
+}
+
+/// Checks whether two tags are identical, in the sense used by canonical hashes.
+/// See the [module-level documentation](self).
+///
+/// This differs from the `PartialEq` implementation of `NbtTag` in that NaNs are equal to
+/// each other, heterogenous lists are compared after unwrapping their elements, and `String`
+/// and `ByteString` tags with the same bytes are identical.
+pub fn identical(tag: &NbtTag, other: &NbtTag, order: CompoundOrder) -> bool {
+    match (tag, other) {
+        (NbtTag::Float(value), NbtTag::Float(other)) => {
+            canonical_f32(*value) == canonical_f32(*other)
+        }
+        (NbtTag::Double(value), NbtTag::Double(other)) => {
+            canonical_f64(*value) == canonical_f64(*other)
+        }
+        (NbtTag::String(value), NbtTag::ByteString(other))
+        | (NbtTag::ByteString(other), NbtTag::String(value)) => value.as_bytes() == other,
+        (NbtTag::List(list), NbtTag::List(other)) => {
+            list.len() == other.len()
+                && list.iter().zip(other).all(|(element, other)| {
+                    identical(unwrap_element(element), unwrap_element(other), order)
+                })
+        }
+        (NbtTag::Compound(compound), NbtTag::Compound(other)) => {
+            identical_compounds(compound, other, order)
+        }
+        _ => tag == other,
+    }
+}
+
+/// Checks whether two compounds are identical. See [`identical`].
+pub fn identical_compounds(
+    compound: &NbtCompound,
+    other:    &NbtCompound,
+    order:    CompoundOrder,
+) -> bool {
+    if compound.len() != other.len() {
+        return false;
+    }
+
+    match order.effective() {
+        CompoundOrder::Ignored => compound.iter().all(|(key, value)| {
+            other
+                .get_tag(key)
+                .is_some_and(|other| identical(value, other, order))
+        }),
+        CompoundOrder::Significant => compound.iter().zip(other).all(
+            |((key, value), (other_key, other))| {
+                key == other_key && identical(value, other, order)
+            },
+        ),
+    }
+}
+
+// ================================
+//  Deduplication
+// ================================
+
+/// Stores one copy of each distinct compound given to it, and counts how often each was given.
+///
+/// This can be used to find duplicates such as identical block palettes, item stacks,
+/// or structure templates.
+///
+/// Compounds are distinguished by their canonical hashes, and [`identical_compounds`]
+/// is used to tell apart compounds whose hashes collide.
+#[derive(Debug, Clone)]
+pub struct CompoundInterner {
+    order:     CompoundOrder,
+    compounds: Vec<InternedCompound>,
+    by_hash:   HashMap<u64, Vec<InternedId>>,
+}
+
+#[derive(Debug, Clone)]
+struct InternedCompound {
+    compound: NbtCompound,
+    hash:     u64,
+    count:    usize,
+}
+
+/// Refers to a distinct compound stored in a [`CompoundInterner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InternedId(usize);
+
+impl CompoundInterner {
+    /// Creates an empty interner, which ignores the order of the entries of compounds.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_order(CompoundOrder::Ignored)
+    }
+
+    /// Creates an empty interner which compares compounds with the given `CompoundOrder`.
+    #[inline]
+    pub fn with_order(order: CompoundOrder) -> Self {
+        Self {
+            order,
+            compounds: Vec::new(),
+            by_hash:   HashMap::new(),
+        }
+    }
+
+    /// The number of distinct compounds in the interner.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.compounds.len()
+    }
+
+    /// Returns whether the interner has no compounds.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.compounds.is_empty()
+    }
+
+    /// Adds a compound to the interner, returning the id of the stored compound which is
+    /// identical to it. The compound is only stored if no identical compound was stored.
+    pub fn intern(&mut self, compound: NbtCompound) -> InternedId {
+        let hash = canonical_compound_hash(&compound, self.order);
+        match self.find_with_hash(hash, &compound) {
+            Some(id) => self.count_duplicate(id),
+            None => self.push(hash, compound),
+        }
+    }
+
+    /// Adds a compound to the interner, like [`intern`], but only clones the compound
+    /// if no identical compound was stored.
+    ///
+    /// [`intern`]: CompoundInterner::intern
+    pub fn intern_cloned(&mut self, compound: &NbtCompound) -> InternedId {
+        let hash = canonical_compound_hash(compound, self.order);
+        match self.find_with_hash(hash, compound) {
+            Some(id) => self.count_duplicate(id),
+            None => self.push(hash, compound.clone()),
+        }
+    }
+
+    fn count_duplicate(&mut self, id: InternedId) -> InternedId {
+        self.compounds[id.0].count += 1;
+        id
+    }
+
+    fn push(&mut self, hash: u64, compound: NbtCompound) -> InternedId {
+        let id = InternedId(self.compounds.len());
+        self.compounds.push(InternedCompound {
+            compound,
+            hash,
+            count: 1,
+        });
+        self.by_hash.entry(hash).or_default().push(id);
+        id
+    }
+
+    /// Returns the id of the stored compound which is identical to the given compound, if any.
+    pub fn find(&self, compound: &NbtCompound) -> Option<InternedId> {
+        self.find_with_hash(canonical_compound_hash(compound, self.order), compound)
+    }
+
+    fn find_with_hash(&self, hash: u64, compound: &NbtCompound) -> Option<InternedId> {
+        self.by_hash
+            .get(&hash)?
+            .iter()
+            .copied()
+            .find(|id| identical_compounds(&self.compounds[id.0].compound, compound, self.order))
+    }
+
+    /// Returns the stored compound with the given id.
+    ///
+    /// # Panics
+    /// Panics if the id was not returned by this interner.
+    #[inline]
+    pub fn get(&self, id: InternedId) -> &NbtCompound {
+        &self.compounds[id.0].compound
+    }
+
+    /// Returns the canonical hash of the stored compound with the given id.
+    ///
+    /// # Panics
+    /// Panics if the id was not returned by this interner.
+    #[inline]
+    pub fn hash(&self, id: InternedId) -> u64 {
+        self.compounds[id.0].hash
+    }
+
+    /// Returns the number of times that compounds identical to the stored compound with
+    /// the given id were interned.
+    ///
+    /// # Panics
+    /// Panics if the id was not returned by this interner.
+    #[inline]
+    pub fn count(&self, id: InternedId) -> usize {
+        self.compounds[id.0].count
+    }
+
+    /// Iterates over the distinct compounds in the interner, in the order they were first
+    /// interned, along with their ids and the number of times each was interned.
+    pub fn iter(&self) -> impl Iterator<Item = (InternedId, &NbtCompound, usize)> {
+        self.compounds
+            .iter()
+            .enumerate()
+            .map(|(index, interned)| (InternedId(index), &interned.compound, interned.count))
+    }
+
+    /// Iterates over the compounds which were interned more than once. See [`iter`].
+    ///
+    /// [`iter`]: CompoundInterner::iter
+    pub fn duplicates(&self) -> impl Iterator<Item = (InternedId, &NbtCompound, usize)> {
+        self.iter().filter(|&(_, _, count)| count > 1)
+    }
+
+    /// Removes every compound from the interner, and returns the distinct compounds
+    /// in the order they were first interned.
+    pub fn into_compounds(self) -> Vec<NbtCompound> {
+        self.compounds
+            .into_iter()
+            .map(|interned| interned.compound)
+            .collect()
+    }
+}
+
+impl Default for CompoundInterner {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
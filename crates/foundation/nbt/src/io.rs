@@ -4,67 +4,33 @@ use std::io::{Read, Write};
 use std::fmt::Display;
 
 use flate2::Compression;
-use flate2::{
-    read::{GzDecoder, ZlibDecoder},
-    write::{GzEncoder, ZlibEncoder},
-};
+use flate2::write::{GzEncoder, ZlibEncoder};
 use thiserror::Error;
 
-use crate::raw;
+use crate::raw::{self, BudgetTracker};
 use crate::{
-    raw::{
-        BYTE_ARRAY_ID, BYTE_ID, COMPOUND_ID, DOUBLE_ID, FLOAT_ID, INT_ARRAY_ID,
-        INT_ID, LIST_ID, LONG_ID, LONG_ARRAY_ID, SHORT_ID, STRING_ID, TAG_END_ID,
-    },
-    settings::{DepthLimit, IoOptions, NbtCompression},
-    tag::{NbtCompound, NbtList, NbtTag},
+    raw::{COMPOUND_ID, TAG_END_ID},
+    settings::{DepthLimit, IoOptions, MemoryBudget, NbtCompression},
+    stream::{NbtEvent, NbtReader},
+    tag::{is_wrapper, NbtCompound, NbtList, NbtTag},
 };
+#[cfg(feature = "allow_any_root")]
+use crate::raw::LIST_ID;
 
 
 // ================================
 //  Reading functions
 // ================================
 
-macro_rules! compression_wrapper {
-    ($uncompressed_fn:ident, $reader:expr, $opts:expr) => {
-        match $opts.compression {
-            NbtCompression::Uncompressed => $uncompressed_fn($reader, $opts),
-            NbtCompression::ZlibCompressed | NbtCompression::ZlibCompressedWith(_) => {
-                $uncompressed_fn(&mut ZlibDecoder::new($reader), $opts)
-            }
-            NbtCompression::GzipCompressed | NbtCompression::GzipCompressedWith(_) => {
-                $uncompressed_fn(&mut GzDecoder::new($reader), $opts)
-            }
-        }
-    };
-}
-
 /// Reads the given encoding of NBT compound data from the given reader,
 /// returning the resulting NBT compound and associated root name.
 pub fn read_compound<R: Read>(
     reader: &mut R,
     opts:   IoOptions,
 ) -> Result<(NbtCompound, String), NbtIoError> {
-    compression_wrapper!(read_compound_uncompressed, reader, opts)
-}
-
-fn read_compound_uncompressed<R: Read>(
-    reader: &mut R,
-    opts:   IoOptions,
-) -> Result<(NbtCompound, String), NbtIoError> {
-    let root_id = raw::read_u8(reader, opts)?;
-    if root_id != COMPOUND_ID {
-        return Err(NbtIoError::TagTypeMismatch {
-            expected: COMPOUND_ID,
-            found:    root_id,
-        });
-    }
-
-    let root_name = raw::read_string(reader, opts)?;
-    match read_tag_body_const::<COMPOUND_ID>(reader, opts, 0) {
-        Ok(NbtTag::Compound(compound)) => Ok((compound, root_name)),
-        Err(e) => Err(e),
-        _ => unreachable!(),
+    match read_named_root(NbtReader::new(reader, opts))? {
+        (NbtTag::Compound(compound), root_name) => Ok((compound, root_name)),
+        _ => unreachable!("NbtReader::new only reads compound root tags"),
     }
 }
 
@@ -75,27 +41,9 @@ pub fn read_list<R: Read>(
     reader: &mut R,
     opts:   IoOptions,
 ) -> Result<(NbtList, String), NbtIoError> {
-    compression_wrapper!(read_list_uncompressed, reader, opts)
-}
-
-#[cfg(feature = "allow_any_root")]
-fn read_list_uncompressed<R: Read>(
-    reader: &mut R,
-    opts:   IoOptions,
-) -> Result<(NbtList, String), NbtIoError> {
-    let root_id = raw::read_u8(reader, opts)?;
-    if root_id != LIST_ID {
-        return Err(NbtIoError::TagTypeMismatch {
-            expected: LIST_ID,
-            found:    root_id,
-        });
-    }
-
-    let root_name = raw::read_string(reader, opts)?;
-    match read_tag_body_const::<LIST_ID>(reader, opts, 0) {
-        Ok(NbtTag::List(list)) => Ok((list, root_name)),
-        Err(e) => Err(e),
-        _ => unreachable!(),
+    match read_named_root(NbtReader::new_list(reader, opts))? {
+        (NbtTag::List(list), root_name) => Ok((list, root_name)),
+        _ => unreachable!("NbtReader::new_list only reads list root tags"),
     }
 }
 
@@ -106,19 +54,7 @@ pub fn read_any_nbt<R: Read>(
     reader: &mut R,
     opts:   IoOptions,
 ) -> Result<(NbtTag, String), NbtIoError> {
-    compression_wrapper!(read_any_nbt_uncompressed, reader, opts)
-}
-
-#[cfg(feature = "allow_any_root")]
-fn read_any_nbt_uncompressed<R: Read>(
-    reader: &mut R,
-    opts:   IoOptions,
-) -> Result<(NbtTag, String), NbtIoError> {
-    let root_id = raw::read_u8(reader, opts)?;
-    let root_name = raw::read_string(reader, opts)?;
-
-    read_tag_body_dyn(reader, opts, root_id, 0)
-        .map(|tag| (tag, root_name))
+    read_named_root(NbtReader::new_any_root(reader, opts))
 }
 
 /// Reads the given encoding of NBT data with no root name from the given reader,
@@ -128,141 +64,29 @@ pub fn read_any_unnamed_nbt<R: Read>(
     reader: &mut R,
     opts:   IoOptions,
 ) -> Result<NbtTag, NbtIoError> {
-    compression_wrapper!(read_any_unnamed_nbt_uncompressed, reader, opts)
-}
-
-#[cfg(feature = "allow_any_root")]
-fn read_any_unnamed_nbt_uncompressed<R: Read>(
-    reader: &mut R,
-    opts:   IoOptions,
-) -> Result<NbtTag, NbtIoError> {
-    let root_id = raw::read_u8(reader, opts)?;
-    read_tag_body_dyn(reader, opts, root_id, 0)
+    let root = NbtReader::new_unnamed(reader, opts).read_value()?;
+    Ok(root.expect("NbtReader reads the root tag"))
 }
 
-fn read_tag_body_dyn<R: Read>(
-    reader:        &mut R,
-    opts:          IoOptions,
-    tag_id:        u8,
-    current_depth: u32,
-) -> Result<NbtTag, NbtIoError> {
-    macro_rules! drive_reader {
-        ($($id:literal)*) => {
-            match tag_id {
-                $( $id => read_tag_body_const::<$id>(reader, opts, current_depth), )*
-                _ => Err(NbtIoError::InvalidTagId(tag_id))
-            }
-        };
-    }
+/// Reads the name and body of the root tag of a new `NbtReader`.
+fn read_named_root<R: Read>(mut reader: NbtReader<R>) -> Result<(NbtTag, String), NbtIoError> {
+    let Some(NbtEvent::Name { name: root_name, .. }) = reader.next_event()? else {
+        unreachable!("the first event of a named root tag is its name");
+    };
+    let root = reader.read_value()?.expect("NbtReader reads the root tag");
 
-    drive_reader!(0x1 0x2 0x3 0x4 0x5 0x6 0x7 0x8 0x9 0xA 0xB 0xC)
+    Ok((root, root_name))
 }
 
-// Note that impl Read is used because this is an internal function
-// and we never need to specify the Read type with a turbofish here.
-// #[expect(clippy::impl_trait_in_params, reason = "internal function")]
-#[inline]
-fn read_tag_body_const<const TAG_ID: u8>(
-    reader:        &mut impl Read,
-    opts:          IoOptions,
-    current_depth: u32,
-) -> Result<NbtTag, NbtIoError> {
-    let tag = match TAG_ID {
-        BYTE_ID       => NbtTag::Byte(  raw::read_i8( reader, opts)?),
-        SHORT_ID      => NbtTag::Short( raw::read_i16(reader, opts)?),
-        INT_ID        => NbtTag::Int(   raw::read_i32(reader, opts)?),
-        LONG_ID       => NbtTag::Long(  raw::read_i64(reader, opts)?),
-        FLOAT_ID      => NbtTag::Float( raw::read_f32(reader, opts)?),
-        DOUBLE_ID     => NbtTag::Double(raw::read_f64(reader, opts)?),
-        BYTE_ARRAY_ID => {
-            let len = raw::read_i32_as_usize(reader, opts)?;
-            let mut array = vec![0_u8; len];
-
-            reader.read_exact(&mut array)?;
-
-            NbtTag::ByteArray(raw::cast_byte_buf_to_signed(array))
-        }
-        STRING_ID => {
-            if opts.enable_byte_strings {
-                raw::read_string_or_bytes(reader, opts)?
-            } else {
-                NbtTag::String(raw::read_string(reader, opts)?)
-            }
-        }
-        LIST_ID => {
-            let tag_id = raw::read_u8(reader, opts)?;
-            let len = raw::read_i32_as_usize(reader, opts)?;
-
-            // Make sure we don't have an invalid type or a nonempty list of TAG_End
-            if tag_id > 0xC || (tag_id == TAG_END_ID && len > 0) {
-                return Err(NbtIoError::InvalidTagId(tag_id));
-            }
-
-            if len == 0 {
-                return Ok(NbtTag::List(NbtList::new()));
-            }
-
-            if current_depth >= opts.depth_limit.0 {
-                return Err(NbtIoError::ExceededDepthLimit {
-                    limit: opts.depth_limit,
-                });
-            }
-
-            let mut list = NbtList::with_capacity(len);
-
-            macro_rules! drive_reader {
-                ($($id:literal)*) => {
-                    match tag_id {
-                        $(
-                            $id => {
-                                for _ in 0 .. len {
-                                    list.push(read_tag_body_const::<$id>(
-                                        reader, opts, current_depth + 1
-                                    )?);
-                                }
-                            },
-                        )*
-                        _ => return Err(NbtIoError::InvalidTagId(tag_id))
-                    }
-                };
-            }
-
-            drive_reader!(0x1 0x2 0x3 0x4 0x5 0x6 0x7 0x8 0x9 0xA 0xB 0xC);
-
-            NbtTag::List(list)
-        }
-        COMPOUND_ID => {
-            let mut compound = NbtCompound::new();
-            let mut tag_id = raw::read_u8(reader, opts)?;
-
-            if tag_id != TAG_END_ID && current_depth >= opts.depth_limit.0 {
-                return Err(NbtIoError::ExceededDepthLimit {
-                    limit: opts.depth_limit,
-                });
-            }
-
-            // Read until TAG_End
-            while tag_id != TAG_END_ID {
-                let name = raw::read_string(reader, opts)?;
-                let tag = read_tag_body_dyn(reader, opts, tag_id, current_depth + 1)?;
-                compound.insert(name, tag);
-                tag_id = raw::read_u8(reader, opts)?;
-            }
-
-            NbtTag::Compound(compound)
-        }
-        INT_ARRAY_ID => {
-            let len = raw::read_i32_as_usize(reader, opts)?;
-            NbtTag::IntArray(raw::read_i32_array(reader, opts, len)?)
-        }
-        LONG_ARRAY_ID => {
-            let len = raw::read_i32_as_usize(reader, opts)?;
-            NbtTag::LongArray(raw::read_i64_array(reader, opts, len)?)
-        }
-        _ => unreachable!("read_tag_body_const called with unchecked TAG_ID"),
-    };
-
-    Ok(tag)
+/// Reads a string, charging its length to the budget before allocating it.
+pub(crate) fn read_string<R: Read>(
+    reader: &mut R,
+    opts:   IoOptions,
+    budget: &BudgetTracker,
+) -> Result<String, NbtIoError> {
+    let len = raw::read_string_len(reader, opts)?;
+    budget.charge(len, 0)?;
+    raw::read_string_body(reader, opts, len)
 }
 
 // ================================
@@ -404,38 +228,10 @@ fn write_named_list_uncompressed<W: Write>(
     root_name: Option<&str>,
     root:      &NbtList,
 ) -> Result<(), NbtIoError> {
-    raw::write_u8(writer, opts, COMPOUND_ID)?;
+    raw::write_u8(writer, opts, LIST_ID)?;
     raw::write_string(writer, opts, root_name.unwrap_or(""))?;
 
-    if root.is_empty() {
-        raw::write_u8(writer, opts, TAG_END_ID)?;
-        raw::write_usize_as_i32(writer, opts, 0)?;
-
-    } else {
-        let list_type = raw::id_for_tag(Some(&root[0]));
-        raw::write_u8(writer, opts, list_type)?;
-        raw::write_usize_as_i32(writer, opts, root.len())?;
-
-        if 0 == opts.depth_limit.0 && !root.is_empty() {
-            return Err(NbtIoError::ExceededDepthLimit {
-                limit: opts.depth_limit,
-            });
-        }
-
-        for sub_tag in root.as_ref() {
-            let tag_id = raw::id_for_tag(Some(sub_tag));
-            if tag_id != list_type {
-                return Err(NbtIoError::NonHomogenousList {
-                    list_type,
-                    encountered_type: tag_id,
-                });
-            }
-
-            write_tag_body(writer, opts, sub_tag, 1)?;
-        }
-    }
-
-    Ok(())
+    write_list_body(writer, opts, root, 0)
 }
 
 /// Writes the given tag with an optional name to the provided writer, writing only the raw
@@ -455,7 +251,7 @@ fn write_any_nbt_uncompressed<W: Write>(
     write_tag_body(writer, opts, root, 0)
 }
 
-fn write_tag_body<W: Write>(
+pub(crate) fn write_tag_body<W: Write>(
     writer:        &mut W,
     opts:          IoOptions,
     tag:           &NbtTag,
@@ -474,35 +270,7 @@ fn write_tag_body<W: Write>(
         }
         NbtTag::String(value) => raw::write_string(writer, opts, value)?,
         NbtTag::ByteString(value) => raw::write_byte_string(writer, opts, value)?,
-        NbtTag::List(value) => {
-            if value.is_empty() {
-                raw::write_u8(writer, opts, TAG_END_ID)?;
-                raw::write_usize_as_i32(writer, opts, 0)?;
-
-            } else {
-                let list_type = raw::id_for_tag(Some(&value[0]));
-                raw::write_u8(writer, opts, list_type)?;
-                raw::write_usize_as_i32(writer, opts, value.len())?;
-
-                if current_depth >= opts.depth_limit.0 && !value.is_empty() {
-                    return Err(NbtIoError::ExceededDepthLimit {
-                        limit: opts.depth_limit,
-                    });
-                }
-
-                for sub_tag in value.as_ref() {
-                    let tag_id = raw::id_for_tag(Some(sub_tag));
-                    if tag_id != list_type {
-                        return Err(NbtIoError::NonHomogenousList {
-                            list_type,
-                            encountered_type: tag_id,
-                        });
-                    }
-
-                    write_tag_body(writer, opts, sub_tag, current_depth + 1)?;
-                }
-            }
-        }
+        NbtTag::List(value) => write_list_body(writer, opts, value, current_depth)?,
         NbtTag::Compound(value) => {
             if current_depth >= opts.depth_limit.0 && !value.is_empty() {
                 return Err(NbtIoError::ExceededDepthLimit {
@@ -537,6 +305,73 @@ fn write_tag_body<W: Write>(
     Ok(())
 }
 
+/// Writes the given list, wrapping its elements in compounds if it is heterogenous.
+/// See [`NbtList::wrap_heterogenous`].
+fn write_list_body<W: Write>(
+    writer:        &mut W,
+    opts:          IoOptions,
+    list:          &NbtList,
+    current_depth: u32,
+) -> Result<(), NbtIoError> {
+    let list_type = match list.element_id() {
+        Some(list_type) => list_type,
+        // Heterogenous lists are written as lists of compounds
+        None if opts.unwrap_heterogenous_lists => COMPOUND_ID,
+        None => {
+            return Err(non_homogenous_list(
+                list.iter().map(|element| raw::id_for_tag(Some(element))),
+            ));
+        }
+    };
+    raw::write_u8(writer, opts, list_type)?;
+    raw::write_usize_as_i32(writer, opts, list.len())?;
+
+    if current_depth >= opts.depth_limit.0 && !list.is_empty() {
+        return Err(NbtIoError::ExceededDepthLimit {
+            limit: opts.depth_limit,
+        });
+    }
+
+    for element in list.as_ref() {
+        let needs_wrapper = list_type == COMPOUND_ID && match element {
+            NbtTag::Compound(compound) => opts.unwrap_heterogenous_lists && is_wrapper(compound),
+            _                          => true,
+        };
+
+        if needs_wrapper {
+            // The wrapper compound is one level deeper than the list
+            if current_depth + 1 >= opts.depth_limit.0 {
+                return Err(NbtIoError::ExceededDepthLimit {
+                    limit: opts.depth_limit,
+                });
+            }
+
+            raw::write_u8(writer, opts, raw::id_for_tag(Some(element)))?;
+            raw::write_string(writer, opts, "")?;
+            write_tag_body(writer, opts, element, current_depth + 2)?;
+            raw::write_u8(writer, opts, TAG_END_ID)?;
+        } else {
+            write_tag_body(writer, opts, element, current_depth + 1)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The error for a heterogenous list which cannot be written, given the tag IDs
+/// of its elements.
+pub(crate) fn non_homogenous_list(mut element_ids: impl Iterator<Item = u8>) -> NbtIoError {
+    let list_type = element_ids.next().unwrap_or(TAG_END_ID);
+    let encountered_type = element_ids
+        .find(|&tag_id| tag_id != list_type)
+        .unwrap_or(list_type);
+
+    NbtIoError::NonHomogenousList {
+        list_type,
+        encountered_type,
+    }
+}
+
 // ================================
 //  Other data and functions
 // ================================
@@ -560,8 +395,19 @@ pub enum NbtIoError {
         /// The limit which was exceeded.
         limit: DepthLimit,
     },
+    /// Reading the data would have allocated more memory than permitted by the
+    /// [`MemoryBudget`] in the [`IoOptions`].
+    #[error(
+        "Exceeded memory budget of {} bytes and {} elements",
+        budget.max_bytes, budget.max_elements,
+    )]
+    ExceededMemoryBudget {
+        /// The budget which was exceeded.
+        budget: MemoryBudget,
+    },
     /// A sequential data structure was found to be non-homogenous. All sequential structures
-    /// in NBT data are homogenous.
+    /// in NBT data, other than lists, are homogenous. (Heterogenous lists are written
+    /// by wrapping their elements in compounds.)
     #[error(
         "Encountered non-homogenous list or sequential type: expected 0x{list_type:X} \
          but found 0x{encountered_type:X}",
@@ -634,6 +480,9 @@ pub enum NbtIoError {
     /// An unsupported type was passed to a serializer or queried from a deserializer.
     #[error("Type {0} is not supported by Minecraft's NBT format")]
     UnsupportedType(&'static str),
+    /// Borrowed views of NBT data were requested for compressed data, which cannot be borrowed.
+    #[error("Borrowed NBT views require uncompressed data")]
+    CompressedView,
     /// A custom error message.
     #[error("{0}")]
     Custom(Box<str>),
@@ -683,3 +532,41 @@ pub fn write_bedrock_header<W: Write>(
     raw::write_usize_as_i32(writer, opts, nbt_len)?;
     Ok(())
 }
+
+
+#[cfg(test)]
+mod tests {
+    use crate::settings::IoOptions;
+    use crate::tag::{NbtCompound, NbtList};
+    use super::{read_compound, write_compound};
+
+
+    #[test]
+    fn round_trip_in_every_encoding() {
+        let mut nested = NbtCompound::new();
+        nested.insert("ints", vec![1_i32, -2, i32::MAX]);
+        nested.insert("longs", vec![3_i64, i64::MIN]);
+        let mut compound = NbtCompound::new();
+        compound.insert("byte", 1_i8);
+        compound.insert("string", "text");
+        compound.insert("nested", nested.clone());
+        compound.insert("list", NbtList::from(vec![nested.clone(), nested]));
+
+        for opts in [
+            IoOptions::java(),
+            IoOptions::java_uncompressed(),
+            IoOptions::bedrock(),
+            IoOptions::bedrock_uncompressed(),
+            IoOptions::bedrock_network_uncompressed(),
+        ] {
+            let mut bytes = Vec::new();
+            write_compound(&mut bytes, opts, Some("root"), &compound)
+                .expect("writing to a Vec succeeds");
+
+            let mut reader = bytes.as_slice();
+            let (read, root_name) = read_compound(&mut reader, opts).expect("valid NBT");
+            assert_eq!(read, compound, "the compound should round-trip with {opts:?}");
+            assert_eq!(root_name, "root", "the root name should round-trip with {opts:?}");
+        }
+    }
+}
@@ -0,0 +1,672 @@
+//! Conversions between NBT data and JSON, in one of two formats.
+//!
+//! The typed format is lossless: each tag is an object with a `"type"` field naming the tag's
+//! variant and a `"value"` field holding its data, so converting NBT to typed JSON and back
+//! always yields the original NBT. For example, the SNBT `{a: 1b, b: [L; 2L]}` becomes
+//!
+//! ```json
+//! {"type": "compound", "value": {
+//!     "a": {"type": "byte", "value": 1},
+//!     "b": {"type": "long_array", "value": ["2"]}
+//! }}
+//! ```
+//!
+//! The type names are `byte`, `short`, `int`, `long`, `float`, `double`, `byte_array`,
+//! `string`, `byte_string`, `list`, `compound`, `int_array`, and `long_array`. `Long` values
+//! are written as strings, since JavaScript numbers cannot exactly hold every `i64`, though
+//! numbers are accepted as well. Non-finite `Float` and `Double` values are written as the
+//! strings `"NaN"`, `"Infinity"`, and `"-Infinity"`, and `ByteString` values are written as
+//! arrays of unsigned bytes.
+//!
+//! The plain format is what most JSON looks like: compounds are objects, lists and arrays are
+//! arrays, numbers are numbers, and strings are strings. Converting NBT to plain JSON loses the
+//! variant of each tag, as well as non-finite floats (which become `null`) and any invalid UTF-8
+//! in `ByteString`s. When converting plain JSON to NBT, the variant of each tag is inferred
+//! according to [`PlainJsonOptions`].
+//!
+//! Note that `serde_json` limits the nesting depth of the JSON it parses to 128 by default,
+//! and that each compound or list in the typed format takes up two levels of JSON nesting.
+
+use std::borrow::Cow;
+use std::num::{IntErrorKind, ParseIntError};
+
+use thiserror::Error;
+use serde_json::{Map as JsonMap, Number, Value};
+
+use crate::{
+    path::NbtPath,
+    settings::{DepthLimit, InferFloat, InferInteger, PlainJsonOptions},
+    tag::{NbtCompound, NbtList, NbtTag, NbtType},
+};
+
+
+// ================================
+//  Typed JSON
+// ================================
+
+/// Converts the tag into the lossless typed JSON format.
+/// See the [module-level documentation](self) for more information.
+pub fn to_typed_json(tag: &NbtTag) -> Value {
+    let (type_name, value) = match tag {
+        NbtTag::Byte(value)       => ("byte",        Value::from(*value)),
+        NbtTag::Short(value)      => ("short",       Value::from(*value)),
+        NbtTag::Int(value)        => ("int",         Value::from(*value)),
+        NbtTag::Long(value)       => ("long",        Value::String(value.to_string())),
+        NbtTag::Float(value)      => ("float",       typed_float(f32_number(*value), *value)),
+        NbtTag::Double(value)     => ("double",      typed_float(Number::from_f64(*value), *value)),
+        NbtTag::ByteArray(value)  => ("byte_array",  Value::from(value.as_slice())),
+        NbtTag::String(value)     => ("string",      Value::String(value.clone())),
+        NbtTag::ByteString(value) => ("byte_string", Value::from(value.as_slice())),
+        NbtTag::List(list)        => (
+            "list",
+            Value::Array(list.iter().map(to_typed_json).collect()),
+        ),
+        NbtTag::Compound(compound) => return compound_to_typed_json(compound),
+        NbtTag::IntArray(value)   => ("int_array",   Value::from(value.as_slice())),
+        NbtTag::LongArray(value)  => (
+            "long_array",
+            Value::Array(value.iter().map(|long| Value::String(long.to_string())).collect()),
+        ),
+    };
+
+    typed_object(type_name, value)
+}
+
+/// Converts the compound into the lossless typed JSON format, as a `compound` tag.
+/// See the [module-level documentation](self) for more information.
+pub fn compound_to_typed_json(compound: &NbtCompound) -> Value {
+    let entries = compound
+        .iter()
+        .map(|(key, tag)| (key.clone(), to_typed_json(tag)))
+        .collect();
+
+    typed_object("compound", Value::Object(entries))
+}
+
+/// Converts JSON in the lossless typed format into NBT data.
+/// See the [module-level documentation](self) for more information.
+pub fn from_typed_json(json: &Value, depth_limit: DepthLimit) -> Result<NbtTag, NbtJsonError> {
+    let mut reader = TypedReader {
+        depth_limit,
+        path: NbtPath::root(),
+    };
+    reader.read_tag(json, 0)
+}
+
+/// Converts JSON in the lossless typed format into NBT data, which must be a `compound` tag.
+/// See the [module-level documentation](self) for more information.
+pub fn compound_from_typed_json(
+    json:        &Value,
+    depth_limit: DepthLimit,
+) -> Result<NbtCompound, NbtJsonError> {
+    match from_typed_json(json, depth_limit)? {
+        NbtTag::Compound(compound) => Ok(compound),
+        tag => Err(NbtJsonError::InvalidValue {
+            path:     location(&NbtPath::root()),
+            expected: "Compound",
+            found:    tag.tag_name(),
+        }),
+    }
+}
+
+fn typed_object(type_name: &str, value: Value) -> Value {
+    let mut object = JsonMap::new();
+    object.insert("type".to_owned(), Value::String(type_name.to_owned()));
+    object.insert("value".to_owned(), value);
+    Value::Object(object)
+}
+
+/// The number is `None` if the float was not finite.
+fn typed_float<F: Into<f64>>(number: Option<Number>, float: F) -> Value {
+    let float = float.into();
+    match number {
+        Some(number) => Value::Number(number),
+        None if float.is_nan()           => Value::String("NaN".to_owned()),
+        None if float.is_sign_positive() => Value::String("Infinity".to_owned()),
+        None                             => Value::String("-Infinity".to_owned()),
+    }
+}
+
+#[derive(Debug)]
+struct TypedReader {
+    depth_limit: DepthLimit,
+    path:        NbtPath,
+}
+
+impl TypedReader {
+    fn read_tag(&mut self, json: &Value, depth: u32) -> Result<NbtTag, NbtJsonError> {
+        let fields = json.as_object().and_then(|object| {
+            Some((object.get("type")?.as_str()?, object.get("value")?))
+        });
+        let Some((type_name, value)) = fields else {
+            return Err(NbtJsonError::NotTyped {
+                path: location(&self.path),
+            });
+        };
+
+        Ok(match type_name {
+            "byte"        => NbtTag::Byte(integer(&self.path, value, "Byte")?),
+            "short"       => NbtTag::Short(integer(&self.path, value, "Short")?),
+            "int"         => NbtTag::Int(integer(&self.path, value, "Int")?),
+            "long"        => NbtTag::Long(long(&self.path, value)?),
+            "float"       => NbtTag::Float(self.float(value)? as f32),
+            "double"      => NbtTag::Double(self.float(value)?),
+            "byte_array"  => NbtTag::ByteArray(self.elements(value, |path, element| {
+                integer(path, element, "Byte")
+            })?),
+            "string"      => NbtTag::String(
+                value
+                    .as_str()
+                    .ok_or_else(|| invalid_value(&self.path, "a string", value))?
+                    .to_owned(),
+            ),
+            "byte_string" => NbtTag::ByteString(self.elements(value, |path, element| {
+                integer(path, element, "unsigned byte")
+            })?),
+            "list"        => NbtTag::List(self.read_list(value, depth)?),
+            "compound"    => NbtTag::Compound(self.read_compound(value, depth)?),
+            "int_array"   => NbtTag::IntArray(self.elements(value, |path, element| {
+                integer(path, element, "Int")
+            })?),
+            "long_array"  => NbtTag::LongArray(self.elements(value, |path, element| {
+                long(path, element)
+            })?),
+            _ => return Err(NbtJsonError::UnknownType {
+                path:      location(&self.path),
+                type_name: type_name.to_owned(),
+            }),
+        })
+    }
+
+    fn read_list(&mut self, json: &Value, depth: u32) -> Result<NbtList, NbtJsonError> {
+        let elements = json
+            .as_array()
+            .ok_or_else(|| invalid_value(&self.path, "an array", json))?;
+        check_depth(self.depth_limit, depth, !elements.is_empty())?;
+
+        let mut list = Vec::with_capacity(elements.len());
+        for (index, element) in elements.iter().enumerate() {
+            push_index(&mut self.path, index);
+            let tag = self.read_tag(element, depth + 1)?;
+            self.path.pop();
+            list.push(tag);
+        }
+
+        Ok(NbtList(list))
+    }
+
+    fn read_compound(&mut self, json: &Value, depth: u32) -> Result<NbtCompound, NbtJsonError> {
+        let object = json
+            .as_object()
+            .ok_or_else(|| invalid_value(&self.path, "an object", json))?;
+        check_depth(self.depth_limit, depth, !object.is_empty())?;
+
+        let mut compound = NbtCompound::with_capacity(object.len());
+        for (key, value) in object {
+            self.path.push_key(key);
+            let tag = self.read_tag(value, depth + 1)?;
+            self.path.pop();
+            compound.insert(key.clone(), tag);
+        }
+
+        Ok(compound)
+    }
+
+    fn elements<T, F>(&mut self, json: &Value, mut element: F) -> Result<Vec<T>, NbtJsonError>
+    where
+        F: FnMut(&NbtPath, &Value) -> Result<T, NbtJsonError>,
+    {
+        let elements = json
+            .as_array()
+            .ok_or_else(|| invalid_value(&self.path, "an array", json))?;
+
+        let mut values = Vec::with_capacity(elements.len());
+        for (index, value) in elements.iter().enumerate() {
+            push_index(&mut self.path, index);
+            values.push(element(&self.path, value)?);
+            self.path.pop();
+        }
+
+        Ok(values)
+    }
+
+    fn float(&self, json: &Value) -> Result<f64, NbtJsonError> {
+        match json {
+            Value::String(string) => match string.as_str() {
+                "NaN"       => Ok(f64::NAN),
+                "Infinity"  => Ok(f64::INFINITY),
+                "-Infinity" => Ok(f64::NEG_INFINITY),
+                _ => Err(invalid_value(&self.path, "a number", json)),
+            },
+            _ => json.as_f64().ok_or_else(|| invalid_value(&self.path, "a number", json)),
+        }
+    }
+}
+
+
+// ================================
+//  Plain JSON
+// ================================
+
+/// Converts the tag into plain JSON, losing type information.
+/// See the [module-level documentation](self) for more information.
+pub fn to_plain_json(tag: &NbtTag) -> Value {
+    match tag {
+        NbtTag::Byte(value)        => Value::from(*value),
+        NbtTag::Short(value)       => Value::from(*value),
+        NbtTag::Int(value)         => Value::from(*value),
+        NbtTag::Long(value)        => Value::from(*value),
+        NbtTag::Float(value)       => f32_number(*value).map_or(Value::Null, Value::Number),
+        NbtTag::Double(value)      => Number::from_f64(*value).map_or(Value::Null, Value::Number),
+        NbtTag::ByteArray(value)   => Value::from(value.as_slice()),
+        NbtTag::String(value)      => Value::String(value.clone()),
+        NbtTag::ByteString(value)  => Value::String(String::from_utf8_lossy(value).into_owned()),
+        NbtTag::List(list)         => Value::Array(list.iter().map(to_plain_json).collect()),
+        NbtTag::Compound(compound) => compound_to_plain_json(compound),
+        NbtTag::IntArray(value)    => Value::from(value.as_slice()),
+        NbtTag::LongArray(value)   => Value::from(value.as_slice()),
+    }
+}
+
+/// Converts the compound into a plain JSON object, losing type information.
+/// See the [module-level documentation](self) for more information.
+pub fn compound_to_plain_json(compound: &NbtCompound) -> Value {
+    Value::Object(
+        compound
+            .iter()
+            .map(|(key, tag)| (key.clone(), to_plain_json(tag)))
+            .collect(),
+    )
+}
+
+/// Converts plain JSON into NBT data, inferring the variant of each tag.
+/// See the [module-level documentation](self) for more information.
+pub fn from_plain_json(json: &Value, opts: PlainJsonOptions) -> Result<NbtTag, NbtJsonError> {
+    let mut reader = PlainReader {
+        opts,
+        path: NbtPath::root(),
+    };
+    reader.read_value(json, 0)
+}
+
+/// Converts a plain JSON object into an NBT compound, inferring the variant of each tag.
+/// See the [module-level documentation](self) for more information.
+pub fn compound_from_plain_json(
+    json: &Value,
+    opts: PlainJsonOptions,
+) -> Result<NbtCompound, NbtJsonError> {
+    let mut reader = PlainReader {
+        opts,
+        path: NbtPath::root(),
+    };
+    reader.read_object(json, 0)
+}
+
+#[derive(Debug)]
+struct PlainReader {
+    opts: PlainJsonOptions,
+    path: NbtPath,
+}
+
+impl PlainReader {
+    fn read_value(&mut self, json: &Value, depth: u32) -> Result<NbtTag, NbtJsonError> {
+        match json {
+            Value::Bool(value) if self.opts.booleans_as_bytes => Ok(NbtTag::Byte(i8::from(*value))),
+            Value::Null | Value::Bool(_) => Err(NbtJsonError::Unsupported {
+                path:  location(&self.path),
+                found: json_kind(json),
+            }),
+            Value::Number(number) => {
+                let nbt_type = self.number_type(number)?;
+                self.number_as(number, nbt_type)
+            }
+            Value::String(value)  => Ok(NbtTag::String(value.clone())),
+            Value::Array(array)   => self.read_array(array, depth),
+            Value::Object(_)      => self.read_object(json, depth).map(NbtTag::Compound),
+        }
+    }
+
+    fn read_object(&mut self, json: &Value, depth: u32) -> Result<NbtCompound, NbtJsonError> {
+        let object = json
+            .as_object()
+            .ok_or_else(|| invalid_value(&self.path, "an object", json))?;
+        check_depth(self.opts.depth_limit, depth, !object.is_empty())?;
+
+        let mut compound = NbtCompound::with_capacity(object.len());
+        for (key, value) in object {
+            if value.is_null() && self.opts.skip_nulls {
+                continue;
+            }
+
+            self.path.push_key(key);
+            let tag = self.read_value(value, depth + 1)?;
+            self.path.pop();
+            compound.insert(key.clone(), tag);
+        }
+
+        Ok(compound)
+    }
+
+    fn read_array(&mut self, array: &[Value], depth: u32) -> Result<NbtTag, NbtJsonError> {
+        if !array.is_empty() && array.iter().all(Value::is_number) {
+            return self.read_numbers(array, depth);
+        }
+        check_depth(self.opts.depth_limit, depth, !array.is_empty())?;
+
+        let mut list = Vec::with_capacity(array.len());
+        for (index, element) in array.iter().enumerate() {
+            push_index(&mut self.path, index);
+            let tag = self.read_value(element, depth + 1)?;
+            check_homogenous(&self.path, &list, &tag)?;
+            self.path.pop();
+            list.push(tag);
+        }
+
+        Ok(NbtTag::List(NbtList(list)))
+    }
+
+    /// Converts a nonempty array of numbers. Since lists are homogenous, every number is given
+    /// the variant needed by the widest one.
+    fn read_numbers(&mut self, array: &[Value], depth: u32) -> Result<NbtTag, NbtJsonError> {
+        let mut widest_integer = NbtType::Byte;
+        let mut has_float = false;
+
+        for (index, element) in array.iter().enumerate() {
+            let Value::Number(number) = element else { continue };
+
+            push_index(&mut self.path, index);
+            match self.number_type(number)? {
+                NbtType::Float | NbtType::Double => has_float = true,
+                nbt_type => widest_integer = widest_integer.max(nbt_type),
+            }
+            self.path.pop();
+        }
+
+        if has_float {
+            let float_type = match self.opts.floats {
+                InferFloat::Float  => NbtType::Float,
+                InferFloat::Double => NbtType::Double,
+            };
+            return self.read_list_of(array, float_type, depth);
+        }
+
+        if self.opts.typed_arrays {
+            return match widest_integer {
+                NbtType::Byte => self.integers(array, "Byte").map(NbtTag::ByteArray),
+                NbtType::Short | NbtType::Int => self.integers(array, "Int").map(NbtTag::IntArray),
+                _ => self.integers(array, "Long").map(NbtTag::LongArray),
+            };
+        }
+
+        self.read_list_of(array, widest_integer, depth)
+    }
+
+    fn read_list_of(
+        &mut self,
+        array:    &[Value],
+        nbt_type: NbtType,
+        depth:    u32,
+    ) -> Result<NbtTag, NbtJsonError> {
+        check_depth(self.opts.depth_limit, depth, true)?;
+
+        let mut list = Vec::with_capacity(array.len());
+        for (index, element) in array.iter().enumerate() {
+            let Value::Number(number) = element else { continue };
+
+            push_index(&mut self.path, index);
+            list.push(self.number_as(number, nbt_type)?);
+            self.path.pop();
+        }
+
+        Ok(NbtTag::List(NbtList(list)))
+    }
+
+    fn integers<T: TryFrom<i64>>(
+        &mut self,
+        array:    &[Value],
+        nbt_type: &'static str,
+    ) -> Result<Vec<T>, NbtJsonError> {
+        let mut values = Vec::with_capacity(array.len());
+        for (index, element) in array.iter().enumerate() {
+            push_index(&mut self.path, index);
+            values.push(integer(&self.path, element, nbt_type)?);
+            self.path.pop();
+        }
+
+        Ok(values)
+    }
+
+    fn number_type(&self, number: &Number) -> Result<NbtType, NbtJsonError> {
+        if let Some(value) = number.as_i64() {
+            let fits_byte = i8::try_from(value).is_ok();
+            let fits_short = i16::try_from(value).is_ok();
+            let fits_int = i32::try_from(value).is_ok();
+
+            Ok(match self.opts.integers {
+                InferInteger::Smallest if fits_byte  => NbtType::Byte,
+                InferInteger::Smallest if fits_short => NbtType::Short,
+                InferInteger::Smallest | InferInteger::IntOrLong if fits_int => NbtType::Int,
+                _ => NbtType::Long,
+            })
+
+        } else if number.is_u64() {
+            Err(NbtJsonError::OutOfRange {
+                path:     location(&self.path),
+                number:   number.to_string(),
+                nbt_type: "Long",
+            })
+
+        } else {
+            Ok(match self.opts.floats {
+                InferFloat::Float  => NbtType::Float,
+                InferFloat::Double => NbtType::Double,
+            })
+        }
+    }
+
+    /// The type should be a numeric type returned by `number_type` for this number or a number
+    /// in the same list.
+    fn number_as(&self, number: &Number, nbt_type: NbtType) -> Result<NbtTag, NbtJsonError> {
+        let integer = number.as_i64();
+
+        let tag = match nbt_type {
+            NbtType::Byte   => integer.and_then(|value| i8::try_from(value).ok()).map(NbtTag::Byte),
+            NbtType::Short  => integer.and_then(|value| i16::try_from(value).ok()).map(NbtTag::Short),
+            NbtType::Int    => integer.and_then(|value| i32::try_from(value).ok()).map(NbtTag::Int),
+            NbtType::Long   => integer.map(NbtTag::Long),
+            NbtType::Float  => number.as_f64().map(|value| NbtTag::Float(value as f32)),
+            NbtType::Double => number.as_f64().map(NbtTag::Double),
+            _ => None,
+        };
+
+        tag.ok_or_else(|| NbtJsonError::OutOfRange {
+            path:     location(&self.path),
+            number:   number.to_string(),
+            nbt_type: nbt_type_name(nbt_type),
+        })
+    }
+}
+
+
+// ================================
+//  Helpers
+// ================================
+
+/// Converts the float to a JSON number which prints as the float's shortest representation,
+/// instead of the (longer) shortest representation of the float as an `f64`.
+/// Returns `None` if the float is not finite.
+fn f32_number(float: f32) -> Option<Number> {
+    let shortest = float
+        .to_string()
+        .parse::<f64>()
+        .ok()
+        .filter(|&wide| (wide as f32).to_bits() == float.to_bits());
+
+    Number::from_f64(shortest.unwrap_or(f64::from(float)))
+}
+
+fn integer<T: TryFrom<i64>>(
+    path:     &NbtPath,
+    json:     &Value,
+    nbt_type: &'static str,
+) -> Result<T, NbtJsonError> {
+    let Value::Number(number) = json else {
+        return Err(invalid_value(path, "an integer", json));
+    };
+
+    number
+        .as_i64()
+        .and_then(|value| T::try_from(value).ok())
+        .ok_or_else(|| {
+            if number.is_f64() {
+                invalid_value(path, "an integer", json)
+            } else {
+                NbtJsonError::OutOfRange {
+                    path:     location(path),
+                    number:   number.to_string(),
+                    nbt_type,
+                }
+            }
+        })
+}
+
+/// Longs are usually strings in the typed format, but numbers are accepted as well.
+fn long(path: &NbtPath, json: &Value) -> Result<i64, NbtJsonError> {
+    match json {
+        Value::String(string) => string.parse().map_err(|error: ParseIntError| {
+            match error.kind() {
+                IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => NbtJsonError::OutOfRange {
+                    path:     location(path),
+                    number:   string.clone(),
+                    nbt_type: "Long",
+                },
+                _ => invalid_value(path, "an integer", json),
+            }
+        }),
+        _ => integer(path, json, "Long"),
+    }
+}
+
+fn check_depth(limit: DepthLimit, depth: u32, has_children: bool) -> Result<(), NbtJsonError> {
+    if has_children && depth >= limit.0 {
+        Err(NbtJsonError::ExceededDepthLimit { limit })
+    } else {
+        Ok(())
+    }
+}
+
+fn check_homogenous(path: &NbtPath, list: &[NbtTag], tag: &NbtTag) -> Result<(), NbtJsonError> {
+    match list.first() {
+        Some(first) if first.tag_type() != tag.tag_type() => {
+            Err(NbtJsonError::NonHomogenousList {
+                path:     location(path),
+                expected: first.tag_name(),
+                found:    tag.tag_name(),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+fn push_index(path: &mut NbtPath, index: usize) {
+    // JSON arrays cannot realistically be long enough for this to saturate.
+    path.push_index(i32::try_from(index).unwrap_or(i32::MAX));
+}
+
+fn location(path: &NbtPath) -> Cow<'static, str> {
+    if path.is_root() {
+        Cow::Borrowed("(root)")
+    } else {
+        Cow::Owned(path.to_string())
+    }
+}
+
+fn invalid_value(path: &NbtPath, expected: &'static str, found: &Value) -> NbtJsonError {
+    NbtJsonError::InvalidValue {
+        path: location(path),
+        expected,
+        found: json_kind(found),
+    }
+}
+
+fn json_kind(json: &Value) -> &'static str {
+    match json {
+        Value::Null      => "null",
+        Value::Bool(_)   => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_)  => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+fn nbt_type_name(nbt_type: NbtType) -> &'static str {
+    match nbt_type {
+        NbtType::Byte      => "Byte",
+        NbtType::Short     => "Short",
+        NbtType::Int       => "Int",
+        NbtType::Long      => "Long",
+        NbtType::Float     => "Float",
+        NbtType::Double    => "Double",
+        NbtType::ByteArray => "ByteArray",
+        NbtType::String    => "String",
+        NbtType::List      => "List",
+        NbtType::Compound  => "Compound",
+        NbtType::IntArray  => "IntArray",
+        NbtType::LongArray => "LongArray",
+    }
+}
+
+
+// ================================
+//  Errors
+// ================================
+
+/// An error from converting JSON into NBT data. Each error caused by a particular value in the
+/// JSON contains the NBT path to that value, or `(root)`.
+#[derive(Error, Debug, Clone)]
+pub enum NbtJsonError {
+    /// The limit on recursive nesting depth of NBT lists and compounds was exceeded.
+    #[error("Exceeded depth limit {} for nested tag lists and compound tags", limit.0)]
+    ExceededDepthLimit {
+        /// The limit which was exceeded.
+        limit: DepthLimit,
+    },
+    /// A value in the typed format was not an object with a string `"type"` field
+    /// and a `"value"` field.
+    #[error("expected an object with \"type\" and \"value\" fields at {path}")]
+    NotTyped { path: Cow<'static, str> },
+    /// The `"type"` field of a value in the typed format did not name a tag variant.
+    #[error("unknown tag type \"{type_name}\" at {path}")]
+    UnknownType {
+        path:      Cow<'static, str>,
+        type_name: String,
+    },
+    /// A value was not of the expected kind.
+    #[error("expected {expected} at {path}, but found {found}")]
+    InvalidValue {
+        path:     Cow<'static, str>,
+        expected: &'static str,
+        found:    &'static str,
+    },
+    /// A number did not fit in the tag variant it was converted into.
+    #[error("{number} at {path} is out of range for a {nbt_type} tag")]
+    OutOfRange {
+        path:     Cow<'static, str>,
+        number:   String,
+        nbt_type: &'static str,
+    },
+    /// An array contained values which converted into different tag variants.
+    #[error("cannot place {found} tag into list of {expected} tags at {path}")]
+    NonHomogenousList {
+        path:     Cow<'static, str>,
+        expected: &'static str,
+        found:    &'static str,
+    },
+    /// A `null` or boolean value could not be converted, per the [`PlainJsonOptions`] used.
+    #[error("{found} at {path} cannot be converted to NBT")]
+    Unsupported {
+        path:  Cow<'static, str>,
+        found: &'static str,
+    },
+}
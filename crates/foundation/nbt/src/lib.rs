@@ -4,6 +4,20 @@ mod repr; // Used by tag module
 pub mod snbt;
 
 pub mod io;
+pub mod stream;
+pub mod view;
+pub mod compact;
+pub mod salvage;
+#[cfg(feature = "async_io")]
+pub mod async_io;
+
+pub mod path;
+pub mod diff;
+pub mod convert;
+pub mod schema;
+pub mod matching;
+pub mod hash;
+pub mod coerce;
 
 #[expect(
     unreachable_pub,
@@ -16,6 +30,12 @@ mod settings;
 #[cfg(feature = "serde")]
 pub mod serde;
 
+#[cfg(feature = "json")]
+pub mod json;
+
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
+
 
 pub use self::repr::*;
 pub use self::tag::*;
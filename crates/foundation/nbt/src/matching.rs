@@ -0,0 +1,313 @@
+//! Matching of NBT data against partial patterns, as done by Minecraft's `nbt={...}` arguments
+//! of target selectors and its block and item predicates.
+//!
+//! A tag matches a pattern if:
+//! - the pattern is a compound, the tag is a compound, and every key of the pattern is present
+//!   in the tag with a value matching the pattern's value (other keys of the tag are ignored),
+//! - the pattern is a list, the tag is a list, and each element of the pattern matches at least
+//!   one element of the tag; an empty pattern list only matches an empty list,
+//! - otherwise, the pattern and the tag are equal.
+//!
+//! Note that a single element of the tag may match several elements of the pattern, and that
+//! the order of elements does not matter. Arrays, unlike lists, must be equal.
+//!
+//! With [`NumericMatching::Lenient`], numbers match numbers of other tag types with the same
+//! value; for instance, the pattern `{Count: 1}` matches `{Count: 1b}`.
+//!
+//! To match a single pattern against many tags, compile it into an [`NbtMatcher`] once.
+//!
+//! # Examples:
+//! ```
+//! use prismarine_anchor_nbt::{snbt, SnbtParseOptions};
+//! use prismarine_anchor_nbt::matching::{NbtMatcher, NumericMatching};
+//!
+//! let opts = SnbtParseOptions::default_updated();
+//! let actor = snbt::parse_compound(
+//!     r#"{identifier: "minecraft:zombie", Tags: ["boss", "spawned"], Health: 20s}"#,
+//!     opts,
+//! ).unwrap();
+//!
+//! let pattern = snbt::parse_compound(r#"{Tags: ["boss"]}"#, opts).unwrap();
+//! assert!(actor.matches(&pattern));
+//!
+//! let matcher = NbtMatcher::from_snbt(r#"{Health: 20}"#, NumericMatching::Lenient).unwrap();
+//! assert!(matcher.matches_compound(&actor));
+//! ```
+
+#[cfg(feature = "derive_serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::snbt;
+use crate::{
+    settings::SnbtParseOptions,
+    snbt::SnbtError,
+    tag::{NbtCompound, NbtList, NbtTag, unwrap_element},
+};
+
+
+// ================================
+//  Direct matching
+// ================================
+
+/// How numbers in a pattern are compared to numbers in a tag.
+#[cfg_attr(feature = "derive_serde",    derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "derive_standard", derive(PartialEq, Eq, PartialOrd, Ord, Hash))]
+#[derive(Debug, Default, Clone, Copy)]
+pub enum NumericMatching {
+    /// Numbers only match numbers of the same tag type, as in Minecraft.
+    #[default]
+    Exact,
+    /// Numbers match numbers of any tag type with the same value. Integers are compared
+    /// exactly, and otherwise, both numbers are compared as `f32` if either is a `Float`,
+    /// or as `f64` otherwise.
+    Lenient,
+}
+
+/// Checks whether `tag` matches `pattern`. See the [module-level documentation](self).
+pub fn tag_matches(tag: &NbtTag, pattern: &NbtTag, numeric: NumericMatching) -> bool {
+    match (pattern, tag) {
+        (NbtTag::Compound(pattern), NbtTag::Compound(tag)) => {
+            compound_matches(tag, pattern, numeric)
+        }
+        (NbtTag::List(pattern), NbtTag::List(tag)) => list_matches(tag, pattern, numeric),
+        (NbtTag::Compound(_) | NbtTag::List(_), _) => false,
+        _ => match numeric {
+            NumericMatching::Lenient => {
+                match (Number::from_tag(pattern), Number::from_tag(tag)) {
+                    (Some(pattern), Some(tag)) => pattern.equals(tag),
+                    _ => pattern == tag,
+                }
+            }
+            NumericMatching::Exact => pattern == tag,
+        },
+    }
+}
+
+/// Checks whether `compound` matches `pattern`. See the [module-level documentation](self).
+pub fn compound_matches(
+    compound: &NbtCompound,
+    pattern:  &NbtCompound,
+    numeric:  NumericMatching,
+) -> bool {
+    pattern.iter().all(|(key, pattern)| {
+        compound
+            .get_tag(key)
+            .is_some_and(|tag| tag_matches(tag, pattern, numeric))
+    })
+}
+
+fn list_matches(list: &NbtList, pattern: &NbtList, numeric: NumericMatching) -> bool {
+    if pattern.is_empty() {
+        return list.is_empty();
+    }
+
+    pattern.iter().all(|pattern| {
+        let pattern = unwrap_element(pattern);
+        list.iter()
+            .any(|tag| tag_matches(unwrap_element(tag), pattern, numeric))
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Number {
+    Integer(i64),
+    Float(f32),
+    Double(f64),
+}
+
+impl Number {
+    fn from_tag(tag: &NbtTag) -> Option<Self> {
+        Some(match *tag {
+            NbtTag::Byte(value)   => Self::Integer(i64::from(value)),
+            NbtTag::Short(value)  => Self::Integer(i64::from(value)),
+            NbtTag::Int(value)    => Self::Integer(i64::from(value)),
+            NbtTag::Long(value)   => Self::Integer(value),
+            NbtTag::Float(value)  => Self::Float(value),
+            NbtTag::Double(value) => Self::Double(value),
+            _ => return None,
+        })
+    }
+
+    #[expect(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        reason = "lenient comparisons are done in the precision of the less precise number",
+    )]
+    fn as_f32(self) -> f32 {
+        match self {
+            Self::Integer(value) => value as f32,
+            Self::Float(value)   => value,
+            Self::Double(value)  => value as f32,
+        }
+    }
+
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "lenient comparisons are done in the precision of the less precise number",
+    )]
+    fn as_f64(self) -> f64 {
+        match self {
+            Self::Integer(value) => value as f64,
+            Self::Float(value)   => f64::from(value),
+            Self::Double(value)  => value,
+        }
+    }
+
+    #[expect(clippy::float_cmp, reason = "numbers must match exactly, as in Minecraft")]
+    fn equals(self, other: Self) -> bool {
+        match (self, other) {
+            (Self::Integer(lhs), Self::Integer(rhs)) => lhs == rhs,
+            (Self::Float(_), _) | (_, Self::Float(_)) => self.as_f32() == other.as_f32(),
+            _ => self.as_f64() == other.as_f64(),
+        }
+    }
+}
+
+// ================================
+//  Compiled matchers
+// ================================
+
+/// A pattern compiled for matching against many tags.
+///
+/// Compiling a pattern resolves how each of its numbers is compared, unwraps the elements of
+/// its heterogenous lists, and orders the entries of its compounds such that cheap checks of
+/// numbers and strings are done before checks of nested compounds and lists.
+///
+/// The entries of the pattern which must be equal to the entries of a matching compound are
+/// available from [`exact_entries`], which can be used to look up candidates in an index,
+/// such as the `identifier` of actors.
+///
+/// [`exact_entries`]: NbtMatcher::exact_entries
+#[derive(Debug, Clone)]
+pub struct NbtMatcher {
+    root: MatchNode,
+}
+
+#[derive(Debug, Clone)]
+enum MatchNode {
+    /// Tags other than compounds and lists, which must be equal to the pattern.
+    Equal(NbtTag),
+    /// Numbers compared leniently.
+    Number(Number),
+    Compound(Vec<(String, Self)>),
+    /// Each node must match at least one element. An empty list only matches an empty list.
+    List(Vec<Self>),
+}
+
+impl NbtMatcher {
+    /// Compiles a pattern, which may be any tag.
+    pub fn new(pattern: &NbtTag, numeric: NumericMatching) -> Self {
+        Self {
+            root: MatchNode::compile(pattern, numeric),
+        }
+    }
+
+    /// Compiles a compound pattern.
+    pub fn for_compound(pattern: &NbtCompound, numeric: NumericMatching) -> Self {
+        Self {
+            root: MatchNode::compile_compound(pattern, numeric),
+        }
+    }
+
+    /// Parses a pattern, written in the updated version of SNBT, and compiles it.
+    pub fn from_snbt(pattern: &str, numeric: NumericMatching) -> Result<Self, SnbtError> {
+        let pattern = snbt::parse_any(pattern, SnbtParseOptions::default_updated())?;
+        Ok(Self::new(&pattern, numeric))
+    }
+
+    /// Checks whether `tag` matches the pattern.
+    #[inline]
+    pub fn matches(&self, tag: &NbtTag) -> bool {
+        self.root.matches(tag)
+    }
+
+    /// Checks whether `compound` matches the pattern.
+    pub fn matches_compound(&self, compound: &NbtCompound) -> bool {
+        match &self.root {
+            MatchNode::Compound(entries) => MatchNode::compound_matches(entries, compound),
+            _ => false,
+        }
+    }
+
+    /// Returns the entries of a compound pattern whose values must be equal to the values
+    /// in a matching compound, which are the entries whose values are neither compounds,
+    /// lists, nor leniently compared numbers.
+    pub fn exact_entries(&self) -> impl Iterator<Item = (&str, &NbtTag)> {
+        let entries = match &self.root {
+            MatchNode::Compound(entries) => entries.as_slice(),
+            _ => &[],
+        };
+
+        entries.iter().filter_map(|(key, node)| match node {
+            MatchNode::Equal(tag) => Some((key.as_str(), tag)),
+            _ => None,
+        })
+    }
+}
+
+impl MatchNode {
+    fn compile(pattern: &NbtTag, numeric: NumericMatching) -> Self {
+        match pattern {
+            NbtTag::Compound(pattern) => Self::compile_compound(pattern, numeric),
+            NbtTag::List(pattern) => Self::List(
+                pattern
+                    .iter()
+                    .map(|element| Self::compile(unwrap_element(element), numeric))
+                    .collect(),
+            ),
+            _ => match (numeric, Number::from_tag(pattern)) {
+                (NumericMatching::Lenient, Some(number)) => Self::Number(number),
+                _ => Self::Equal(pattern.clone()),
+            },
+        }
+    }
+
+    fn compile_compound(pattern: &NbtCompound, numeric: NumericMatching) -> Self {
+        let mut entries = pattern
+            .iter()
+            .map(|(key, value)| (key.clone(), Self::compile(value, numeric)))
+            .collect::<Vec<_>>();
+
+        entries.sort_by_key(|(_, node)| node.cost());
+        Self::Compound(entries)
+    }
+
+    /// A rough ordering of how expensive nodes are to match.
+    fn cost(&self) -> u8 {
+        match self {
+            Self::Equal(_) | Self::Number(_) => 0,
+            Self::Compound(_) => 1,
+            Self::List(_) => 2,
+        }
+    }
+
+    fn matches(&self, tag: &NbtTag) -> bool {
+        match (self, tag) {
+            (Self::Equal(pattern), tag) => pattern == tag,
+            (Self::Number(pattern), tag) => {
+                Number::from_tag(tag).is_some_and(|number| pattern.equals(number))
+            }
+            (Self::Compound(entries), NbtTag::Compound(compound)) => {
+                Self::compound_matches(entries, compound)
+            }
+            (Self::List(elements), NbtTag::List(list)) => {
+                if elements.is_empty() {
+                    return list.is_empty();
+                }
+
+                elements.iter().all(|pattern| {
+                    list.iter().any(|tag| pattern.matches(unwrap_element(tag)))
+                })
+            }
+            (Self::Compound(_) | Self::List(_), _) => false,
+        }
+    }
+
+    fn compound_matches(entries: &[(String, Self)], compound: &NbtCompound) -> bool {
+        entries.iter().all(|(key, pattern)| {
+            compound
+                .get_tag(key)
+                .is_some_and(|tag| pattern.matches(tag))
+        })
+    }
+}
@@ -0,0 +1,1167 @@
+//! Paths into NBT data, like those used by the `data` command in Java Edition.
+//!
+//! A path such as `Inventory[{Slot:2b}].tag.display.Name` is a sequence of nodes, separated by
+//! `.` (which may be omitted before `[`). The supported nodes are:
+//! - `{...}`, only at the start of a path, which selects the root compound if it matches
+//!   the filter,
+//! - `name` or `"quoted name"`, which selects the tag with the given name in a compound,
+//! - `name{...}`, which selects the compound with the given name if it matches the filter,
+//! - `[]`, which selects every element of a list,
+//! - `[index]`, which selects the element at the given index of a list, where negative indices
+//!   count backwards from the end of the list,
+//! - `[{...}]`, which selects every compound in a list that matches the filter.
+//!
+//! Filters are written in SNBT. A tag matches a filter if every tag in the filter is also present
+//! in the tag, where lists match if each element of the filter's list matches some element
+//! of the tag's list (and empty lists only match empty lists). This is the same as
+//! [`matching::compound_matches`] with [`NumericMatching::Exact`].
+//!
+//! Note that the elements of byte, int, and long arrays are not NBT tags,
+//! and cannot be selected by a path.
+
+use std::{fmt, mem};
+use std::{fmt::{Display, Formatter}, ops::Range, str::FromStr};
+
+use thiserror::Error;
+
+use crate::snbt;
+use crate::{
+    matching::{self, NumericMatching},
+    settings::SnbtParseOptions,
+    snbt::SnbtError,
+    tag::{NbtCompound, NbtList, NbtTag},
+};
+
+
+/// The characters which may not appear in unquoted keys.
+const UNQUOTED_DISALLOWED: [char; 8] = [' ', '"', '\'', '[', ']', '.', '{', '}'];
+
+
+// ================================
+//  Paths
+// ================================
+
+/// A parsed path into NBT data, like those used by the `data` command in Java Edition.
+/// See the [module-level documentation](self) for the syntax of paths.
+///
+/// Paths can be applied to anything implementing [`NbtPathRoot`], which includes [`NbtTag`],
+/// [`NbtCompound`], and [`NbtList`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct NbtPath {
+    path:     String,
+    segments: Vec<PathSegment>,
+}
+
+/// A node of a path, along with its location in the path.
+#[derive(Debug, Clone, PartialEq)]
+struct PathSegment {
+    node: NbtPathNode,
+    span: Range<usize>,
+}
+
+/// A single node of an [`NbtPath`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtPathNode {
+    /// `{...}`; selects the root compound if it matches the filter.
+    /// Only permitted at the start of a path.
+    RootCompound(NbtCompound),
+    /// `name`; selects the tag with the given name in a compound.
+    Key(String),
+    /// `name{...}`; selects the compound with the given name if it matches the filter.
+    FilteredKey(String, NbtCompound),
+    /// `[]`; selects every element of a list.
+    AllElements,
+    /// `[index]`; selects the element at the given index of a list. Negative indices count
+    /// backwards from the end of the list.
+    Index(i32),
+    /// `[{...}]`; selects every compound in a list that matches the filter.
+    FilteredElements(NbtCompound),
+}
+
+impl NbtPath {
+    /// Parses a path, using the given options to parse any filters in the path.
+    pub fn parse(path: &str, opts: SnbtParseOptions) -> Result<Self, NbtPathError> {
+        let segments = PathParser {
+            path,
+            index: 0,
+            opts,
+        }
+        .parse()?;
+
+        Ok(Self {
+            path: path.to_owned(),
+            segments,
+        })
+    }
+
+    /// Returns an empty path, which selects the root. Note that empty paths cannot be parsed.
+    #[inline]
+    pub fn root() -> Self {
+        Self {
+            path:     String::new(),
+            segments: Vec::new(),
+        }
+    }
+
+    /// Returns the path as it was written.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns whether the path is empty, and thus selects the root.
+    #[inline]
+    pub fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Appends a node which selects the tag with the given name in a compound,
+    /// quoting the name if necessary.
+    pub fn push_key(&mut self, key: &str) {
+        if !self.segments.is_empty() {
+            self.path.push('.');
+        }
+        let start = self.path.len();
+
+        if !key.is_empty() && !key.contains(UNQUOTED_DISALLOWED) {
+            self.path.push_str(key);
+        } else {
+            self.path.push('"');
+            for ch in key.chars() {
+                if matches!(ch, '"' | '\\') {
+                    self.path.push('\\');
+                }
+                self.path.push(ch);
+            }
+            self.path.push('"');
+        }
+
+        self.segments.push(PathSegment {
+            node: NbtPathNode::Key(key.to_owned()),
+            span: start..self.path.len(),
+        });
+    }
+
+    /// Removes the last node of the path, and returns it.
+    pub fn pop(&mut self) -> Option<NbtPathNode> {
+        let segment = self.segments.pop()?;
+        self.path.truncate(segment.span.start);
+
+        if !self.segments.is_empty() && self.path.ends_with('.') {
+            self.path.pop();
+        }
+
+        Some(segment.node)
+    }
+
+    /// Appends a node which selects the element at the given index of a list.
+    pub fn push_index(&mut self, index: i32) {
+        let start = self.path.len();
+        self.path.push('[');
+        self.path.push_str(&index.to_string());
+        self.path.push(']');
+
+        self.segments.push(PathSegment {
+            node: NbtPathNode::Index(index),
+            span: start..self.path.len(),
+        });
+    }
+
+    /// Iterates over the nodes of the path.
+    #[inline]
+    pub fn nodes(&self) -> impl ExactSizeIterator<Item = &NbtPathNode> {
+        self.segments.iter().map(|segment| &segment.node)
+    }
+
+    /// Returns the first tag selected by this path, or an error indicating the first
+    /// path segment which selected nothing.
+    pub fn get<'a, R>(&self, root: &'a R) -> Result<&'a NbtTag, NbtPathError>
+    where
+        R: NbtPathRoot + ?Sized,
+    {
+        Ok(self
+            .get_all(root)?
+            .swap_remove(0))
+    }
+
+    /// Returns every tag selected by this path, or an error indicating the first
+    /// path segment which selected nothing.
+    pub fn get_all<'a, R>(&self, root: &'a R) -> Result<Vec<&'a NbtTag>, NbtPathError>
+    where
+        R: NbtPathRoot + ?Sized,
+    {
+        let mut targets: Vec<_> = Target::from_root(root).into_iter().collect();
+
+        for segment in &self.segments {
+            let mut selected = Vec::new();
+            for target in targets {
+                segment.node.select(target, &mut selected);
+            }
+
+            if selected.is_empty() {
+                return Err(self.no_match(segment));
+            }
+            targets = selected;
+        }
+
+        targets
+            .into_iter()
+            .map(|target| target.into_tag().ok_or(NbtPathError::RootTarget { operation: "get" }))
+            .collect()
+    }
+
+    /// Returns a mutable reference to the first tag selected by this path, or an error
+    /// indicating the first path segment which selected nothing.
+    pub fn get_mut<'a, R>(&self, root: &'a mut R) -> Result<&'a mut NbtTag, NbtPathError>
+    where
+        R: NbtPathRoot + ?Sized,
+    {
+        Ok(self
+            .get_all_mut(root)?
+            .swap_remove(0))
+    }
+
+    /// Returns mutable references to every tag selected by this path, or an error indicating
+    /// the first path segment which selected nothing.
+    pub fn get_all_mut<'a, R>(&self, root: &'a mut R) -> Result<Vec<&'a mut NbtTag>, NbtPathError>
+    where
+        R: NbtPathRoot + ?Sized,
+    {
+        let targets = self.select_mut(root, &self.segments)?;
+
+        targets
+            .into_iter()
+            .map(|target| target.into_tag().ok_or(NbtPathError::RootTarget { operation: "get" }))
+            .collect()
+    }
+
+    /// Replaces every tag selected by this path with a copy of the given value, and returns
+    /// the number of tags which were changed.
+    ///
+    /// As with the `data modify ... set` command, missing compounds and lists along the path
+    /// are created as needed, the last node of the path may refer to a tag which does
+    /// not yet exist, and compound filters on list elements insert a copy of the filter if no
+    /// element matches. If the path is empty, the root is replaced, if it is a tag.
+    pub fn set<R>(&self, root: &mut R, value: &NbtTag) -> Result<usize, NbtPathError>
+    where
+        R: NbtPathRoot + ?Sized,
+    {
+        let Some((last, parents)) = self.segments.split_last() else {
+            let root = root
+                .root_tag_mut()
+                .ok_or(NbtPathError::RootTarget { operation: "set" })?;
+
+            return Ok(usize::from(mem::replace(root, value.clone()) != *value));
+        };
+
+        if matches!(last.node, NbtPathNode::RootCompound(_)) {
+            return Err(NbtPathError::RootTarget { operation: "set" });
+        }
+
+        let mut changed = 0;
+        for target in self.select_or_create(root, parents, &last.node.create_parent())? {
+            changed += self.set_in(last, target, value)?;
+        }
+
+        Ok(changed)
+    }
+
+    /// Removes every tag selected by this path, and returns the number of tags removed.
+    ///
+    /// An error is returned if a segment before the last one selected nothing.
+    pub fn remove<R>(&self, root: &mut R) -> Result<usize, NbtPathError>
+    where
+        R: NbtPathRoot + ?Sized,
+    {
+        let Some((last, parents)) = self.segments
+            .split_last()
+            .filter(|(last, _)| !matches!(last.node, NbtPathNode::RootCompound(_)))
+        else {
+            return Err(NbtPathError::RootTarget { operation: "remove" });
+        };
+
+        Ok(self
+            .select_mut(root, parents)?
+            .into_iter()
+            .map(|target| last.node.remove_in(target))
+            .sum())
+    }
+
+    /// Inserts a copy of the given value into every list selected by this path, and returns
+    /// the number of lists the value was inserted into.
+    ///
+    /// As with the `data modify ... insert` command, a negative index counts backwards from
+    /// the end of each list, such that an index of `-1` appends the value to the list.
+    /// Missing compounds and lists along the path are created as needed.
+    pub fn insert<R>(&self, root: &mut R, index: i32, value: &NbtTag) -> Result<usize, NbtPathError>
+    where
+        R: NbtPathRoot + ?Sized,
+    {
+        let last = self.segments.last();
+
+        let targets = self.select_or_create(
+            root,
+            &self.segments,
+            &|| NbtTag::List(NbtList::new()),
+        )?;
+        let count = targets.len();
+
+        for target in targets {
+            let list = match target {
+                TargetMut::Tag(NbtTag::List(list)) | TargetMut::List(list) => list,
+                TargetMut::Tag(tag) => return Err(self.type_mismatch(last, "List", tag.tag_name())),
+                TargetMut::Compound(_) => {
+                    return Err(self.type_mismatch(last, "List", "Compound"));
+                }
+            };
+
+            let len = list.len();
+            let position = if index < 0 {
+                i64::try_from(len).ok()
+                    .map(|len| len + i64::from(index) + 1)
+                    .and_then(|position| usize::try_from(position).ok())
+            } else {
+                usize::try_from(index).ok()
+            };
+
+            let Some(position) = position.filter(|&position| position <= len) else {
+                return Err(NbtPathError::InvalidInsertIndex { index, length: len });
+            };
+
+            self.check_list_type(last, list, value, |_| false)?;
+            list.0.insert(position, value.clone());
+        }
+
+        Ok(count)
+    }
+
+    /// Follows the given segments, returning an error if any segment selected nothing.
+    fn select_mut<'a, R>(
+        &self,
+        root:     &'a mut R,
+        segments: &[PathSegment],
+    ) -> Result<Vec<TargetMut<'a>>, NbtPathError>
+    where
+        R: NbtPathRoot + ?Sized,
+    {
+        let mut targets: Vec<_> = TargetMut::from_root(root).into_iter().collect();
+
+        for segment in segments {
+            let mut selected = Vec::new();
+            for target in targets {
+                segment.node.select_mut(target, &mut selected);
+            }
+
+            if selected.is_empty() {
+                return Err(self.no_match(segment));
+            }
+            targets = selected;
+        }
+
+        Ok(targets)
+    }
+
+    /// Follows the given segments, creating missing tags where possible. The tags at the end
+    /// of the segments are created with `create_last`.
+    fn select_or_create<'a, R>(
+        &self,
+        root:        &'a mut R,
+        segments:    &[PathSegment],
+        create_last: &dyn Fn() -> NbtTag,
+    ) -> Result<Vec<TargetMut<'a>>, NbtPathError>
+    where
+        R: NbtPathRoot + ?Sized,
+    {
+        let mut targets: Vec<_> = TargetMut::from_root(root).into_iter().collect();
+
+        for (index, segment) in segments.iter().enumerate() {
+            let next = segments.get(index + 1).map(|next| next.node.create_parent());
+            let create: &dyn Fn() -> NbtTag = match &next {
+                Some(create_next) => create_next,
+                None              => create_last,
+            };
+
+            let mut selected = Vec::new();
+            for target in targets {
+                segment.node.select_or_create(target, create, &mut selected);
+            }
+
+            if selected.is_empty() {
+                return Err(self.no_match(segment));
+            }
+            targets = selected;
+        }
+
+        Ok(targets)
+    }
+
+    /// Sets the tags selected by `segment` in the target to the value, and returns the number
+    /// of tags which were changed.
+    fn set_in(
+        &self,
+        segment: &PathSegment,
+        target:  TargetMut<'_>,
+        value:   &NbtTag,
+    ) -> Result<usize, NbtPathError> {
+        let replace = |tag: &mut NbtTag| usize::from(mem::replace(tag, value.clone()) != *value);
+
+        match &segment.node {
+            NbtPathNode::RootCompound(_) => Ok(0),
+            NbtPathNode::Key(name) => {
+                let Some(compound) = target.into_compound() else {
+                    return Ok(0);
+                };
+
+                match compound.0.insert(name.clone(), value.clone()) {
+                    Some(old) if old == *value => Ok(0),
+                    _ => Ok(1),
+                }
+            }
+            NbtPathNode::FilteredKey(name, filter) => {
+                let tag = target
+                    .into_compound()
+                    .and_then(|compound| compound.0.get_mut(name))
+                    .filter(|tag| matches_filter(filter, tag));
+
+                Ok(tag.map_or(0, replace))
+            }
+            NbtPathNode::AllElements => {
+                let Some(list) = target.into_list() else {
+                    return Ok(0);
+                };
+
+                Ok(list.0.iter_mut().map(replace).sum())
+            }
+            &NbtPathNode::Index(index) => {
+                let Some(list) = target.into_list() else {
+                    return Ok(0);
+                };
+                let Some(index) = resolve_index(index, list.len()) else {
+                    return Ok(0);
+                };
+
+                self.check_list_type(Some(segment), list, value, |other| other == index)?;
+                Ok(replace(&mut list.0[index]))
+            }
+            NbtPathNode::FilteredElements(filter) => {
+                let Some(list) = target.into_list() else {
+                    return Ok(0);
+                };
+
+                let selected: Vec<bool> = list
+                    .iter()
+                    .map(|tag| matches_filter(filter, tag))
+                    .collect();
+
+                self.check_list_type(Some(segment), list, value, |index| selected[index])?;
+
+                Ok(list.0
+                    .iter_mut()
+                    .zip(selected)
+                    .filter(|(_, selected)| *selected)
+                    .map(|(tag, _)| replace(tag))
+                    .sum())
+            }
+        }
+    }
+
+    /// Checks that the value may be placed into the list, assuming that the elements for which
+    /// `replaced` returns true are replaced.
+    fn check_list_type(
+        &self,
+        segment:  Option<&PathSegment>,
+        list:     &NbtList,
+        value:    &NbtTag,
+        replaced: impl Fn(usize) -> bool,
+    ) -> Result<(), NbtPathError> {
+        let mismatch = list
+            .iter()
+            .enumerate()
+            .find(|&(index, tag)| !replaced(index) && tag.tag_type() != value.tag_type());
+
+        if let Some((_, tag)) = mismatch {
+            let (segment, index) = self.location(segment);
+
+            Err(NbtPathError::ListTypeMismatch {
+                segment,
+                index,
+                expected: tag.tag_name(),
+                found:    value.tag_name(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn no_match(&self, segment: &PathSegment) -> NbtPathError {
+        NbtPathError::NoMatch {
+            segment: self.path[segment.span.clone()].to_owned(),
+            index:   segment.span.start,
+        }
+    }
+
+    fn type_mismatch(
+        &self,
+        segment:  Option<&PathSegment>,
+        expected: &'static str,
+        found:    &'static str,
+    ) -> NbtPathError {
+        let (segment, index) = self.location(segment);
+
+        NbtPathError::TypeMismatch {
+            segment,
+            index,
+            expected,
+            found,
+        }
+    }
+
+    /// Returns an error indicating that the last segment of the path selected nothing.
+    pub(crate) fn no_match_at_end(&self) -> NbtPathError {
+        let (segment, index) = self.location(self.segments.last());
+        NbtPathError::NoMatch { segment, index }
+    }
+
+    /// Returns a copy of the segment and its column in the path. A missing segment refers to
+    /// the root, which is at the start of the path.
+    fn location(&self, segment: Option<&PathSegment>) -> (String, usize) {
+        segment.map_or((String::new(), 0), |segment| {
+            (self.path[segment.span.clone()].to_owned(), segment.span.start)
+        })
+    }
+}
+
+impl FromStr for NbtPath {
+    type Err = NbtPathError;
+
+    /// Parses a path, using [`SnbtParseOptions::default_updated`] to parse any filters.
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        Self::parse(path, SnbtParseOptions::default_updated())
+    }
+}
+
+impl Display for NbtPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path)
+    }
+}
+
+// ================================
+//  Nodes
+// ================================
+
+impl NbtPathNode {
+    /// Creates the tag which this node can select from, if it is missing.
+    fn create_parent(&self) -> impl Fn() -> NbtTag + 'static {
+        let is_list_node = matches!(
+            self,
+            Self::AllElements | Self::Index(_) | Self::FilteredElements(_),
+        );
+
+        move || {
+            if is_list_node {
+                NbtTag::List(NbtList::new())
+            } else {
+                NbtTag::Compound(NbtCompound::new())
+            }
+        }
+    }
+
+    fn select<'a>(&self, target: Target<'a>, selected: &mut Vec<Target<'a>>) {
+        match self {
+            Self::RootCompound(filter) => {
+                let matches = target.compound().is_some_and(|compound| {
+                    matching::compound_matches(compound, filter, NumericMatching::Exact)
+                });
+                if matches {
+                    selected.push(target);
+                }
+            }
+            Self::Key(name) => {
+                selected.extend(
+                    target.compound()
+                        .and_then(|compound| compound.0.get(name))
+                        .map(Target::Tag),
+                );
+            }
+            Self::FilteredKey(name, filter) => {
+                selected.extend(
+                    target.compound()
+                        .and_then(|compound| compound.0.get(name))
+                        .filter(|tag| matches_filter(filter, tag))
+                        .map(Target::Tag),
+                );
+            }
+            Self::AllElements => {
+                if let Some(list) = target.list() {
+                    selected.extend(list.iter().map(Target::Tag));
+                }
+            }
+            &Self::Index(index) => {
+                selected.extend(
+                    target.list()
+                        .and_then(|list| list.0.get(resolve_index(index, list.len())?))
+                        .map(Target::Tag),
+                );
+            }
+            Self::FilteredElements(filter) => {
+                if let Some(list) = target.list() {
+                    selected.extend(
+                        list.iter()
+                            .filter(|tag| matches_filter(filter, tag))
+                            .map(Target::Tag),
+                    );
+                }
+            }
+        }
+    }
+
+    fn select_mut<'a>(&self, target: TargetMut<'a>, selected: &mut Vec<TargetMut<'a>>) {
+        match self {
+            Self::RootCompound(filter) => {
+                let matches = target.compound().is_some_and(|compound| {
+                    matching::compound_matches(compound, filter, NumericMatching::Exact)
+                });
+                if matches {
+                    selected.push(target);
+                }
+            }
+            Self::Key(name) => {
+                selected.extend(
+                    target.into_compound()
+                        .and_then(|compound| compound.0.get_mut(name))
+                        .map(TargetMut::Tag),
+                );
+            }
+            Self::FilteredKey(name, filter) => {
+                selected.extend(
+                    target.into_compound()
+                        .and_then(|compound| compound.0.get_mut(name))
+                        .filter(|tag| matches_filter(filter, tag))
+                        .map(TargetMut::Tag),
+                );
+            }
+            Self::AllElements => {
+                if let Some(list) = target.into_list() {
+                    selected.extend(list.0.iter_mut().map(TargetMut::Tag));
+                }
+            }
+            &Self::Index(index) => {
+                selected.extend(
+                    target.into_list()
+                        .and_then(|list| {
+                            let index = resolve_index(index, list.len())?;
+                            list.0.get_mut(index)
+                        })
+                        .map(TargetMut::Tag),
+                );
+            }
+            Self::FilteredElements(filter) => {
+                if let Some(list) = target.into_list() {
+                    selected.extend(
+                        list.0
+                            .iter_mut()
+                            .filter(|tag| matches_filter(filter, tag))
+                            .map(TargetMut::Tag),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Like `select_mut`, but missing tags are created with `create` where possible.
+    fn select_or_create<'a>(
+        &self,
+        target:   TargetMut<'a>,
+        create:   &dyn Fn() -> NbtTag,
+        selected: &mut Vec<TargetMut<'a>>,
+    ) {
+        match self {
+            Self::Key(name) => {
+                if let Some(compound) = target.into_compound() {
+                    let tag = compound.0.entry(name.clone()).or_insert_with(create);
+                    selected.push(TargetMut::Tag(tag));
+                }
+            }
+            Self::FilteredKey(name, filter) => {
+                if let Some(compound) = target.into_compound() {
+                    let tag = compound.0
+                        .entry(name.clone())
+                        .or_insert_with(|| NbtTag::Compound(filter.clone()));
+
+                    if matches_filter(filter, tag) {
+                        selected.push(TargetMut::Tag(tag));
+                    }
+                }
+            }
+            Self::AllElements => {
+                if let Some(list) = target.into_list() {
+                    if list.is_empty() {
+                        list.0.push(create());
+                    }
+                    selected.extend(list.0.iter_mut().map(TargetMut::Tag));
+                }
+            }
+            Self::FilteredElements(filter) => {
+                if let Some(list) = target.into_list() {
+                    let any_match = list.iter().any(|tag| matches_filter(filter, tag));
+                    let holds_compounds = list.iter().all(|tag| matches!(tag, NbtTag::Compound(_)));
+
+                    if !any_match && holds_compounds {
+                        list.0.push(NbtTag::Compound(filter.clone()));
+                    }
+
+                    selected.extend(
+                        list.0
+                            .iter_mut()
+                            .filter(|tag| matches_filter(filter, tag))
+                            .map(TargetMut::Tag),
+                    );
+                }
+            }
+            Self::RootCompound(_) | Self::Index(_) => self.select_mut(target, selected),
+        }
+    }
+
+    /// Removes the tags selected by this node in the target, and returns the number of tags
+    /// which were removed.
+    fn remove_in(&self, target: TargetMut<'_>) -> usize {
+        match self {
+            Self::RootCompound(_) => 0,
+            Self::Key(name) => target
+                .into_compound()
+                .and_then(|compound| remove_key(compound, name))
+                .map_or(0, |_| 1),
+            Self::FilteredKey(name, filter) => {
+                let Some(compound) = target.into_compound() else {
+                    return 0;
+                };
+
+                if compound.0.get(name).is_some_and(|tag| matches_filter(filter, tag)) {
+                    remove_key(compound, name);
+                    1
+                } else {
+                    0
+                }
+            }
+            Self::AllElements => target
+                .into_list()
+                .map_or(0, |list| list.0.drain(..).count()),
+            &Self::Index(index) => target
+                .into_list()
+                .and_then(|list| {
+                    let index = resolve_index(index, list.len())?;
+                    Some(list.0.remove(index))
+                })
+                .map_or(0, |_| 1),
+            Self::FilteredElements(filter) => {
+                let Some(list) = target.into_list() else {
+                    return 0;
+                };
+
+                let len = list.len();
+                list.0.retain(|tag| !matches_filter(filter, tag));
+                len - list.len()
+            }
+        }
+    }
+}
+
+/// Converts a possibly-negative index into an index into a list of the given length.
+fn resolve_index(index: i32, len: usize) -> Option<usize> {
+    let index = if index < 0 {
+        len.checked_sub(usize::try_from(index.unsigned_abs()).ok()?)?
+    } else {
+        usize::try_from(index).ok()?
+    };
+
+    (index < len).then_some(index)
+}
+
+fn remove_key(compound: &mut NbtCompound, name: &str) -> Option<NbtTag> {
+    #[cfg(feature = "preserve_order")]
+    {
+        compound.0.shift_remove(name)
+    }
+    #[cfg(not(feature = "preserve_order"))]
+    {
+        compound.0.remove(name)
+    }
+}
+
+/// Whether the tag is a compound which matches the filter.
+fn matches_filter(filter: &NbtCompound, tag: &NbtTag) -> bool {
+    matches!(
+        tag,
+        NbtTag::Compound(compound)
+            if matching::compound_matches(compound, filter, NumericMatching::Exact)
+    )
+}
+
+// ================================
+//  Roots and targets
+// ================================
+
+/// NBT data which an [`NbtPath`] can be applied to.
+///
+/// Implemented for [`NbtTag`], as well as [`NbtCompound`] and [`NbtList`] so that they do
+/// not need to be wrapped in a tag. At most one of the `root_*` methods should return `Some`.
+pub trait NbtPathRoot {
+    /// Returns the root as a tag, if it is a tag.
+    fn root_tag(&self) -> Option<&NbtTag>;
+    /// Returns the root as a mutable tag, if it is a tag.
+    fn root_tag_mut(&mut self) -> Option<&mut NbtTag>;
+    /// Returns the root as a compound, if it is a compound which is not wrapped in a tag.
+    fn root_compound(&self) -> Option<&NbtCompound>;
+    /// Returns the root as a mutable compound, if it is a compound which is not wrapped in a tag.
+    fn root_compound_mut(&mut self) -> Option<&mut NbtCompound>;
+    /// Returns the root as a list, if it is a list which is not wrapped in a tag.
+    fn root_list(&self) -> Option<&NbtList>;
+    /// Returns the root as a mutable list, if it is a list which is not wrapped in a tag.
+    fn root_list_mut(&mut self) -> Option<&mut NbtList>;
+}
+
+macro_rules! path_root {
+    ($type:ty, $tag:ident, $compound:ident, $list:ident) => {
+        impl NbtPathRoot for $type {
+            #[inline]
+            fn root_tag(&self) -> Option<&NbtTag> {
+                path_root!(@$tag self)
+            }
+
+            #[inline]
+            fn root_tag_mut(&mut self) -> Option<&mut NbtTag> {
+                path_root!(@$tag self)
+            }
+
+            #[inline]
+            fn root_compound(&self) -> Option<&NbtCompound> {
+                path_root!(@$compound self)
+            }
+
+            #[inline]
+            fn root_compound_mut(&mut self) -> Option<&mut NbtCompound> {
+                path_root!(@$compound self)
+            }
+
+            #[inline]
+            fn root_list(&self) -> Option<&NbtList> {
+                path_root!(@$list self)
+            }
+
+            #[inline]
+            fn root_list_mut(&mut self) -> Option<&mut NbtList> {
+                path_root!(@$list self)
+            }
+        }
+    };
+    (@yes $self:ident) => { Some($self) };
+    (@no $self:ident) => { None };
+}
+
+path_root!(NbtTag,      yes, no,  no);
+path_root!(NbtCompound, no,  yes, no);
+path_root!(NbtList,     no,  no,  yes);
+
+#[derive(Debug, Clone, Copy)]
+enum Target<'a> {
+    Tag(&'a NbtTag),
+    Compound(&'a NbtCompound),
+    List(&'a NbtList),
+}
+
+impl<'a> Target<'a> {
+    fn from_root<R: NbtPathRoot + ?Sized>(root: &'a R) -> Option<Self> {
+        root.root_tag()
+            .map(Self::Tag)
+            .or_else(|| root.root_compound().map(Self::Compound))
+            .or_else(|| root.root_list().map(Self::List))
+    }
+
+    fn compound(self) -> Option<&'a NbtCompound> {
+        match self {
+            Self::Tag(NbtTag::Compound(compound)) | Self::Compound(compound) => Some(compound),
+            _ => None,
+        }
+    }
+
+    fn list(self) -> Option<&'a NbtList> {
+        match self {
+            Self::Tag(NbtTag::List(list)) | Self::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    fn into_tag(self) -> Option<&'a NbtTag> {
+        match self {
+            Self::Tag(tag) => Some(tag),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum TargetMut<'a> {
+    Tag(&'a mut NbtTag),
+    Compound(&'a mut NbtCompound),
+    List(&'a mut NbtList),
+}
+
+impl<'a> TargetMut<'a> {
+    fn from_root<R: NbtPathRoot + ?Sized>(root: &'a mut R) -> Option<Self> {
+        if root.root_tag().is_some() {
+            root.root_tag_mut().map(Self::Tag)
+        } else if root.root_compound().is_some() {
+            root.root_compound_mut().map(Self::Compound)
+        } else {
+            root.root_list_mut().map(Self::List)
+        }
+    }
+
+    fn compound(&self) -> Option<&NbtCompound> {
+        match self {
+            Self::Tag(NbtTag::Compound(compound)) => Some(compound),
+            Self::Compound(compound)              => Some(compound),
+            _ => None,
+        }
+    }
+
+    fn into_compound(self) -> Option<&'a mut NbtCompound> {
+        match self {
+            Self::Tag(NbtTag::Compound(compound)) | Self::Compound(compound) => Some(compound),
+            _ => None,
+        }
+    }
+
+    fn into_list(self) -> Option<&'a mut NbtList> {
+        match self {
+            Self::Tag(NbtTag::List(list)) | Self::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    fn into_tag(self) -> Option<&'a mut NbtTag> {
+        match self {
+            Self::Tag(tag) => Some(tag),
+            _ => None,
+        }
+    }
+}
+
+// ================================
+//  Parsing
+// ================================
+
+struct PathParser<'a> {
+    path:  &'a str,
+    index: usize,
+    opts:  SnbtParseOptions,
+}
+
+impl PathParser<'_> {
+    fn parse(mut self) -> Result<Vec<PathSegment>, NbtPathError> {
+        let mut segments = Vec::new();
+
+        if self.peek() == Some('{') {
+            let filter = self.parse_filter()?;
+            segments.push(PathSegment {
+                node: NbtPathNode::RootCompound(filter),
+                span: 0..self.index,
+            });
+
+            if !self.parse_separator()? {
+                return Ok(segments);
+            }
+        }
+
+        loop {
+            let start = self.index;
+            let node = self.parse_node()?;
+
+            segments.push(PathSegment {
+                node,
+                span: start..self.index,
+            });
+
+            if !self.parse_separator()? {
+                return Ok(segments);
+            }
+        }
+    }
+
+    fn parse_node(&mut self) -> Result<NbtPathNode, NbtPathError> {
+        let key = match self.peek() {
+            Some('[') => return self.parse_brackets(),
+            Some(quote @ ('"' | '\'')) => self.parse_quoted(quote)?,
+            _ => {
+                let len = self.path[self.index..]
+                    .find(UNQUOTED_DISALLOWED)
+                    .unwrap_or(self.path.len() - self.index);
+
+                if len == 0 {
+                    return Err(self.syntax_error(self.index, "a key or '['"));
+                }
+
+                let key = self.path[self.index..self.index + len].to_owned();
+                self.index += len;
+                key
+            }
+        };
+
+        if self.peek() == Some('{') {
+            Ok(NbtPathNode::FilteredKey(key, self.parse_filter()?))
+        } else {
+            Ok(NbtPathNode::Key(key))
+        }
+    }
+
+    fn parse_brackets(&mut self) -> Result<NbtPathNode, NbtPathError> {
+        // Skip the '['
+        self.index += 1;
+
+        let node = match self.peek() {
+            Some(']') => NbtPathNode::AllElements,
+            Some('{') => NbtPathNode::FilteredElements(self.parse_filter()?),
+            _ => {
+                let len = self.path[self.index..]
+                    .find(|ch: char| ch != '-' && !ch.is_ascii_digit())
+                    .unwrap_or(self.path.len() - self.index);
+
+                #[expect(
+                    clippy::map_err_ignore,
+                    reason = "the error is reported at the index's position in the path",
+                )]
+                let index = self.path[self.index..self.index + len]
+                    .parse()
+                    .map_err(|_| self.syntax_error(self.index, "an integer index, '{', or ']'"))?;
+
+                self.index += len;
+                NbtPathNode::Index(index)
+            }
+        };
+
+        if self.peek() == Some(']') {
+            self.index += 1;
+            Ok(node)
+        } else {
+            Err(self.syntax_error(self.index, "']'"))
+        }
+    }
+
+    fn parse_quoted(&mut self, quote: char) -> Result<String, NbtPathError> {
+        let start = self.index;
+        let mut key = String::new();
+        let mut chars = self.path[start + 1..].char_indices();
+
+        loop {
+            match chars.next() {
+                Some((_, '\\')) => match chars.next() {
+                    Some((_, ch)) if ch == quote || ch == '\\' => key.push(ch),
+                    _ => return Err(self.syntax_error(start, "a valid escape sequence")),
+                },
+                Some((index, ch)) if ch == quote => {
+                    self.index = start + 1 + index + ch.len_utf8();
+                    return Ok(key);
+                }
+                Some((_, ch)) => key.push(ch),
+                None => return Err(self.syntax_error(start, "a closing quote")),
+            }
+        }
+    }
+
+    fn parse_filter(&mut self) -> Result<NbtCompound, NbtPathError> {
+        let (filter, len) = snbt::parse_compound_and_size(&self.path[self.index..], self.opts)
+            .map_err(|error| NbtPathError::InvalidFilter {
+                index: self.index,
+                error,
+            })?;
+
+        self.index += len;
+        Ok(filter)
+    }
+
+    /// Returns whether another node follows.
+    fn parse_separator(&mut self) -> Result<bool, NbtPathError> {
+        match self.peek() {
+            None => Ok(false),
+            Some('[') => Ok(true),
+            Some('.') => {
+                self.index += 1;
+                Ok(true)
+            }
+            Some(_) => Err(self.syntax_error(self.index, "'.' or '['")),
+        }
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<char> {
+        self.path[self.index..].chars().next()
+    }
+
+    fn syntax_error(&self, index: usize, expected: &'static str) -> NbtPathError {
+        NbtPathError::InvalidSyntax {
+            segment: self.path[index..].chars().take(15).collect(),
+            index,
+            expected,
+        }
+    }
+}
+
+// ================================
+//  Errors
+// ================================
+
+/// An error from parsing or applying an [`NbtPath`]. Errors which are caused by a particular
+/// segment of a path contain a copy of that segment and its column in the path.
+#[derive(Error, Debug, Clone)]
+pub enum NbtPathError {
+    /// The path could not be parsed.
+    #[error("invalid NBT path at column {index} near '{segment}', expected {expected}")]
+    InvalidSyntax {
+        segment:  String,
+        index:    usize,
+        /// The expected token or sequence of tokens.
+        expected: &'static str,
+    },
+    /// A compound filter in the path was not valid SNBT.
+    #[error("invalid compound filter at column {index} of NBT path: {error}")]
+    InvalidFilter {
+        index: usize,
+        /// The error from parsing the filter, whose columns are relative to the filter.
+        error: SnbtError,
+    },
+    /// A segment of the path did not select any tags.
+    #[error("nothing matched NBT path segment '{segment}' at column {index}")]
+    NoMatch { segment: String, index: usize },
+    /// A tag selected by a segment of the path had the wrong type for the operation.
+    #[error(
+        "expected {expected} tag at NBT path segment '{segment}' (column {index}), \
+         but found {found}",
+    )]
+    TypeMismatch {
+        segment:  String,
+        index:    usize,
+        expected: &'static str,
+        found:    &'static str,
+    },
+    /// A tag could not be placed into a list, because the list held tags of a different type.
+    #[error(
+        "cannot place {found} tag into list of {expected} tags at NBT path segment '{segment}' \
+         (column {index})",
+    )]
+    ListTypeMismatch {
+        segment:  String,
+        index:    usize,
+        expected: &'static str,
+        found:    &'static str,
+    },
+    /// An insertion index was out of bounds for a list selected by the path.
+    #[error("insertion index {index} is out of bounds for a list of length {length}")]
+    InvalidInsertIndex { index: i32, length: usize },
+    /// The path only selected the root, which does not support the operation.
+    #[error("cannot {operation} the root of an NBT path")]
+    RootTarget {
+        /// The attempted operation.
+        operation: &'static str,
+    },
+}
@@ -1,14 +1,14 @@
 #![expect(unsafe_code)]
 
-use std::{ptr, slice, str};
-use std::{borrow::Cow, mem::ManuallyDrop};
+use std::{io, mem, ptr, slice, str};
+use std::{borrow::Cow, cell::Cell, mem::ManuallyDrop};
 use std::io::{Read, Result as IoResult, Write};
 
 use byteorder::{BigEndian, LittleEndian, ReadBytesExt as _, WriteBytesExt as _};
 use varint_rs::{VarintReader as _, VarintWriter as _};
 
-use crate::{io::NbtIoError, tag::NbtTag};
-use crate::settings::{Endianness, IoOptions, StringEncoding};
+use crate::{io::NbtIoError, tag::{NbtTag, NbtType}};
+use crate::settings::{Endianness, IoOptions, MemoryBudget, StringEncoding};
 
 
 type NbtResult<T> = Result<T, NbtIoError>;
@@ -49,6 +49,25 @@ pub const fn id_for_tag(tag: Option<&NbtTag>) -> u8 {
     }
 }
 
+#[inline]
+pub const fn type_for_id(tag_id: u8) -> Option<NbtType> {
+    match tag_id {
+        BYTE_ID       => Some(NbtType::Byte),
+        SHORT_ID      => Some(NbtType::Short),
+        INT_ID        => Some(NbtType::Int),
+        LONG_ID       => Some(NbtType::Long),
+        FLOAT_ID      => Some(NbtType::Float),
+        DOUBLE_ID     => Some(NbtType::Double),
+        BYTE_ARRAY_ID => Some(NbtType::ByteArray),
+        STRING_ID     => Some(NbtType::String),
+        LIST_ID       => Some(NbtType::List),
+        COMPOUND_ID   => Some(NbtType::Compound),
+        INT_ARRAY_ID  => Some(NbtType::IntArray),
+        LONG_ARRAY_ID => Some(NbtType::LongArray),
+        _ => None,
+    }
+}
+
 #[cfg(feature = "serde")]
 #[inline]
 pub fn read_bool<R: Read>(reader: &mut R, opts: IoOptions) -> IoResult<bool> {
@@ -145,6 +164,89 @@ pub fn bytes_from_string(string: &str, opts: IoOptions) -> Cow<'_, [u8]> {
     }
 }
 
+/// Tracks the memory allocated over a single read or parse, to enforce a [`MemoryBudget`].
+///
+/// The counts are stored in cells so that the tracker can be shared by reference
+/// between the nested readers of recursive tags.
+#[derive(Debug, Clone)]
+pub struct BudgetTracker {
+    budget:   MemoryBudget,
+    bytes:    Cell<usize>,
+    elements: Cell<usize>,
+}
+
+impl BudgetTracker {
+    #[inline]
+    pub fn new(budget: MemoryBudget) -> Self {
+        Self {
+            budget,
+            bytes:    Cell::new(0),
+            elements: Cell::new(0),
+        }
+    }
+
+    #[inline]
+    pub fn budget(&self) -> MemoryBudget {
+        self.budget
+    }
+
+    /// Replaces the usage recorded by this tracker with the usage recorded by `other`,
+    /// which should be a clone of this tracker that was charged afterwards.
+    #[inline]
+    pub fn record_usage(&self, other: &Self) {
+        self.bytes.set(other.bytes.get());
+        self.elements.set(other.elements.get());
+    }
+
+    /// Records that the given number of bytes and elements are about to be allocated,
+    /// returning false if doing so would exceed the budget. Nothing is recorded in that case.
+    pub fn try_charge(&self, bytes: usize, elements: usize) -> bool {
+        let total_bytes = self.bytes.get().checked_add(bytes);
+        let total_elements = self.elements.get().checked_add(elements);
+
+        match (total_bytes, total_elements) {
+            (Some(total_bytes), Some(total_elements))
+                if total_bytes <= self.budget.max_bytes
+                    && total_elements <= self.budget.max_elements =>
+            {
+                self.bytes.set(total_bytes);
+                self.elements.set(total_elements);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Records that the given number of bytes and elements are about to be allocated,
+    /// returning an error if doing so would exceed the budget.
+    #[inline]
+    pub fn charge(&self, bytes: usize, elements: usize) -> NbtResult<()> {
+        if self.try_charge(bytes, elements) {
+            Ok(())
+        } else {
+            Err(NbtIoError::ExceededMemoryBudget { budget: self.budget })
+        }
+    }
+
+    /// Records that an array of `len` numbers with the given tag ID is about to be allocated.
+    pub fn charge_array(&self, element_id: u8, len: usize) -> NbtResult<()> {
+        let element_size = match element_id {
+            BYTE_ID  => mem::size_of::<i8>(),
+            INT_ID   => mem::size_of::<i32>(),
+            LONG_ID  => mem::size_of::<i64>(),
+            _ => unreachable!("charge_array called with non-array element ID"),
+        };
+
+        self.charge(len.saturating_mul(element_size), len)
+    }
+
+    /// Records that `len` tags in a list or compound are about to be allocated.
+    #[inline]
+    pub fn charge_tags(&self, len: usize) -> NbtResult<()> {
+        self.charge(len.saturating_mul(mem::size_of::<NbtTag>()), len)
+    }
+}
+
 #[inline]
 pub fn read_i32_as_usize<R: Read>(reader: &mut R, opts: IoOptions) -> NbtResult<usize> {
     #[expect(
@@ -170,16 +272,20 @@ pub fn read_string_len<R: Read>(reader: &mut R, opts: IoOptions) -> NbtResult<us
     }
 }
 
-pub fn read_string<R: Read>(reader: &mut R, opts: IoOptions) -> NbtResult<String> {
-    let len = read_string_len(reader, opts)?;
+/// Reads a string whose length has already been read.
+pub fn read_string_body<R: Read>(reader: &mut R, opts: IoOptions, len: usize) -> NbtResult<String> {
     let mut bytes = vec![0; len];
     reader.read_exact(&mut bytes)?;
 
     Ok(string_from_bytes(bytes.as_slice(), opts)?.into_owned())
 }
 
-pub fn read_string_or_bytes<R: Read>(reader: &mut R, opts: IoOptions) -> NbtResult<NbtTag> {
-    let len = read_string_len(reader, opts)?;
+/// Reads a string or `ByteString` whose length has already been read.
+pub fn read_string_or_bytes_body<R: Read>(
+    reader: &mut R,
+    opts:   IoOptions,
+    len:    usize,
+) -> NbtResult<NbtTag> {
     let mut bytes = vec![0; len];
     reader.read_exact(&mut bytes)?;
 
@@ -193,14 +299,31 @@ pub fn read_string_or_bytes<R: Read>(reader: &mut R, opts: IoOptions) -> NbtResu
     )
 }
 
+/// Reads and discards `len` bytes, without allocating a buffer for them.
+pub fn skip_bytes<R: Read>(reader: &mut R, len: usize) -> NbtResult<()> {
+    #[expect(
+        clippy::map_err_ignore,
+        reason = "out-of-range usize is the only possible error ignored",
+    )]
+    let len = u64::try_from(len).map_err(|_| NbtIoError::ExcessiveLength)?;
+
+    let skipped = io::copy(&mut reader.take(len), &mut io::sink())?;
+    if skipped < len {
+        return Err(NbtIoError::StdIo(io::ErrorKind::UnexpectedEof.into()));
+    }
+    Ok(())
+}
+
 // TODO: what is the point of this function? It seems unnecessary.
 #[cfg(feature = "serde")]
 pub fn read_string_into<'a, R: Read>(
     reader: &mut R,
     opts:   IoOptions,
+    budget: &BudgetTracker,
     dest:   &'a mut Vec<u8>,
 ) -> NbtResult<Cow<'a, str>> {
     let len = read_string_len(reader, opts)?;
+    budget.charge(len, 0)?;
     dest.resize(len, 0);
     reader.read_exact(dest)?;
     string_from_bytes(dest, opts)
@@ -371,9 +494,7 @@ pub fn cast_byte_buf_to_unsigned(buf: Vec<i8>) -> Vec<u8> {
     unsafe { Vec::from_raw_parts(ptr, length, capacity) }
 }
 
-// Currently unused, but might be used later
 #[inline]
-#[expect(dead_code)]
 pub fn cast_bytes_to_signed(bytes: &[u8]) -> &[i8] {
     let data = bytes.as_ptr().cast::<i8>();
     let len = bytes.len();
@@ -441,16 +562,15 @@ unsafe fn non_zst_slice_to_bytes<T>(data: &mut [T]) -> &mut [u8] {
     }
 
     let ptr = data.as_mut_ptr().cast::<u8>();
-    let t_len = data.len();
 
     // NOTE:
     // the byte length of slices and vectors in Rust is in the range
     // `0..=isize::MAX` for non-ZST types.
     // Therefore, the below code neither overflows nor exceeds isize::MAX.
-    let byte_len = t_len * size_of::<i32>();
+    let byte_len = size_of_val(data);
 
     // SAFETY:
-    // * `ptr` is valid for `t_len * size_of::<T>` bytes, a.k.a. `byte_len * 1` bytes
+    // * `ptr` is valid for `data.len() * size_of::<T>` bytes, a.k.a. `byte_len * 1` bytes
     //   which is `byte_len * size_of::<u8>` bytes.
     //     * The entire memory range of `ptr` is contained in a single allocated object since it
     //       came from a valid slice (`data`)
@@ -517,6 +637,30 @@ pub fn read_i32_array<R: Read>(
     }
 }
 
+/// Reads and discards `len` `i32` values, without allocating a buffer for them.
+pub fn skip_i32_array<R: Read>(reader: &mut R, opts: IoOptions, len: usize) -> NbtResult<()> {
+    if matches!(opts.endianness, Endianness::NetworkLittleEndian) {
+        for _ in 0..len {
+            reader.read_i32_varint()?;
+        }
+        Ok(())
+    } else {
+        skip_bytes(reader, len.checked_mul(4).ok_or(NbtIoError::ExcessiveLength)?)
+    }
+}
+
+/// Reads and discards `len` `i64` values, without allocating a buffer for them.
+pub fn skip_i64_array<R: Read>(reader: &mut R, opts: IoOptions, len: usize) -> NbtResult<()> {
+    if matches!(opts.endianness, Endianness::NetworkLittleEndian) {
+        for _ in 0..len {
+            reader.read_i64_varint()?;
+        }
+        Ok(())
+    } else {
+        skip_bytes(reader, len.checked_mul(8).ok_or(NbtIoError::ExcessiveLength)?)
+    }
+}
+
 #[inline]
 pub fn read_i64_array<R: Read>(
     reader: &mut R,
@@ -0,0 +1,351 @@
+//! Recovery of as much data as possible from binary NBT data which is truncated or corrupted,
+//! such as values from an old or damaged world.
+//!
+//! Instead of failing, [`salvage_compound`] returns every tag which was read before the first
+//! error, along with where and why reading stopped, and the bytes which were not read.
+//! Compounds and lists which enclose the point of failure are kept, holding the tags that were
+//! completely read before the failure.
+//!
+//! # Examples:
+//! ```
+//! use prismarine_anchor_nbt::IoOptions;
+//! use prismarine_anchor_nbt::salvage::salvage_compound;
+//!
+//! // A root compound with an empty name, holding `Count: 3b`, followed by the string tag
+//! // `Name: "minecraft:stone"` which is cut off partway through its value.
+//! let mut bytes = vec![0x0A, 0, 0];
+//! bytes.extend([0x01, 5, 0]);
+//! bytes.extend(b"Count");
+//! bytes.push(3);
+//! bytes.extend([0x08, 4, 0]);
+//! bytes.extend(b"Name");
+//! bytes.extend([15, 0]);
+//! bytes.extend(b"minecraft");
+//!
+//! let salvaged = salvage_compound(&bytes, IoOptions::bedrock_uncompressed());
+//! assert_eq!(salvaged.compound.len(), 1);
+//! assert_eq!(salvaged.compound.get::<_, i8>("Count").unwrap(), 3);
+//! assert!(!salvaged.compound.contains_key("Name"));
+//!
+//! let failure = salvaged.failure.as_ref().unwrap();
+//! assert_eq!(failure.path.to_string(), "Name");
+//! assert_eq!(failure.offset, 12);
+//! assert_eq!(salvaged.consumed, 12);
+//! assert_eq!(salvaged.trailing, bytes[12..]);
+//! ```
+
+use std::borrow::Cow;
+use std::io::{self, Read};
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+
+use crate::raw::{self, BudgetTracker};
+use crate::{
+    io::NbtIoError,
+    path::NbtPath,
+    raw::{COMPOUND_ID, LIST_ID, LONG_ARRAY_ID, TAG_END_ID},
+    settings::{IoOptions, NbtCompression},
+    stream::read_tag_body,
+    tag::{NbtCompound, NbtList, NbtTag},
+};
+
+
+/// The result of [`salvage_compound`].
+#[derive(Debug)]
+pub struct SalvagedCompound {
+    /// The root compound, containing every tag read before the failure, if any.
+    pub compound:  NbtCompound,
+    /// The name of the root compound, which is empty if it could not be read.
+    pub root_name: String,
+    /// The number of bytes of (decompressed) data which were salvaged. If the data was read
+    /// successfully, this is the length of the NBT data; otherwise, it is the offset of the
+    /// tag which could not be read.
+    pub consumed:  usize,
+    /// The bytes of (decompressed) data after the first `consumed` bytes. If the data was read
+    /// successfully, these are any bytes following the root compound.
+    pub trailing:  Vec<u8>,
+    /// Where and why reading stopped, or `None` if the data was read successfully.
+    pub failure:   Option<SalvageFailure>,
+}
+
+impl SalvagedCompound {
+    /// Returns whether the data was read successfully, in which case the compound
+    /// is the same as would be returned by [`io::read_compound`].
+    ///
+    /// [`io::read_compound`]: crate::io::read_compound
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    /// Returns the compound and root name if the data was read successfully,
+    /// or the reason it could not be read otherwise.
+    pub fn into_result(self) -> Result<(NbtCompound, String), NbtIoError> {
+        match self.failure {
+            None          => Ok((self.compound, self.root_name)),
+            Some(failure) => Err(failure.reason),
+        }
+    }
+}
+
+/// Where and why salvaging NBT data stopped.
+#[derive(Debug)]
+pub struct SalvageFailure {
+    /// The offset into the (decompressed) data of the start of the tag which could not be read.
+    /// For an entry of a compound, this is the offset of the entry's tag ID.
+    pub offset: usize,
+    /// The path to the tag which could not be read, or to the compound containing it if
+    /// the name of the entry could not be read.
+    pub path:   NbtPath,
+    /// The error which stopped reading.
+    pub reason: NbtIoError,
+}
+
+/// Reads the given encoding of NBT compound data, as [`io::read_compound`] does,
+/// but recovers every tag read before the first error instead of failing.
+///
+/// Compressed data is decompressed as far as possible before being read, and offsets
+/// are into the decompressed data. At most `opts.memory_budget.max_bytes` bytes are
+/// decompressed. If the NBT data is complete, errors from decompressing the data after it,
+/// such as a truncated checksum, are ignored.
+///
+/// [`io::read_compound`]: crate::io::read_compound
+pub fn salvage_compound(bytes: &[u8], opts: IoOptions) -> SalvagedCompound {
+    let limit = u64::try_from(opts.memory_budget.max_bytes).unwrap_or(u64::MAX);
+    let (data, decompression_error) = match opts.compression {
+        NbtCompression::Uncompressed => (Cow::Borrowed(bytes), None),
+        NbtCompression::ZlibCompressed | NbtCompression::ZlibCompressedWith(_) => {
+            decompress(ZlibDecoder::new(bytes).take(limit))
+        }
+        NbtCompression::GzipCompressed | NbtCompression::GzipCompressedWith(_) => {
+            decompress(GzDecoder::new(bytes).take(limit))
+        }
+    };
+
+    let mut salvager = Salvager {
+        data:   &data,
+        reader: &data,
+        opts:   IoOptions {
+            compression: NbtCompression::Uncompressed,
+            ..opts
+        },
+        budget: BudgetTracker::new(opts.memory_budget),
+        path:   NbtPath::root(),
+    };
+
+    let mut compound = NbtCompound::new();
+    let mut root_name = String::new();
+    let result = salvager.read_root(&mut compound, &mut root_name);
+
+    let consumed = match &result {
+        Ok(())       => salvager.offset(),
+        Err(failure) => failure.offset,
+    };
+
+    let failure = result.err().map(|mut failure| {
+        // Running out of decompressed data is better explained by the decompression error
+        if let (NbtIoError::StdIo(err), Some(decompression_error)) =
+            (&failure.reason, decompression_error)
+        {
+            if err.kind() == io::ErrorKind::UnexpectedEof {
+                failure.reason = NbtIoError::StdIo(decompression_error);
+            }
+        }
+        failure
+    });
+
+    SalvagedCompound {
+        compound,
+        root_name,
+        consumed,
+        trailing: data[consumed..].to_vec(),
+        failure,
+    }
+}
+
+/// Decompresses as much data as possible, returning the error which stopped decompression.
+fn decompress<R: Read>(mut decoder: R) -> (Cow<'static, [u8]>, Option<io::Error>) {
+    let mut data = Vec::new();
+    let error = decoder.read_to_end(&mut data).err();
+    (Cow::Owned(data), error)
+}
+
+struct Salvager<'a> {
+    data:   &'a [u8],
+    /// The unread suffix of `data`.
+    reader: &'a [u8],
+    opts:   IoOptions,
+    budget: BudgetTracker,
+    /// The path to the tag currently being read.
+    path:   NbtPath,
+}
+
+/// A tag which could not be completely read, along with the part of it which was read,
+/// if it is a compound or list. Boxed, since it is only returned once.
+type Partial = Box<(Option<NbtTag>, SalvageFailure)>;
+
+impl Salvager<'_> {
+    #[inline]
+    fn offset(&self) -> usize {
+        self.data.len() - self.reader.len()
+    }
+
+    fn fail(&self, offset: usize, reason: NbtIoError) -> SalvageFailure {
+        SalvageFailure {
+            offset,
+            path: self.path.clone(),
+            reason,
+        }
+    }
+
+    fn read_root(
+        &mut self,
+        compound:  &mut NbtCompound,
+        root_name: &mut String,
+    ) -> Result<(), SalvageFailure> {
+        *root_name = self.read_root_header().map_err(|err| self.fail(0, err))?;
+        self.read_compound_body(compound, 0)
+    }
+
+    fn read_root_header(&mut self) -> Result<String, NbtIoError> {
+        let root_id = raw::read_u8(&mut self.reader, self.opts)?;
+        if root_id != COMPOUND_ID {
+            return Err(NbtIoError::TagTypeMismatch {
+                expected: COMPOUND_ID,
+                found:    root_id,
+            });
+        }
+
+        self.read_name()
+    }
+
+    /// Reads a string, charging its length to the budget before allocating it.
+    fn read_name(&mut self) -> Result<String, NbtIoError> {
+        let len = raw::read_string_len(&mut self.reader, self.opts)?;
+        self.budget.charge(len, 0)?;
+        raw::read_string_body(&mut self.reader, self.opts, len)
+    }
+
+    /// Reads the body of a tag which starts at the given offset.
+    fn read_tag_body(
+        &mut self,
+        tag_id:        u8,
+        start:         usize,
+        current_depth: u32,
+    ) -> Result<NbtTag, Partial> {
+        match tag_id {
+            COMPOUND_ID => {
+                let mut compound = NbtCompound::new();
+                match self.read_compound_body(&mut compound, current_depth) {
+                    Ok(())       => Ok(NbtTag::Compound(compound)),
+                    Err(failure) => Err(Box::new((Some(NbtTag::Compound(compound)), failure))),
+                }
+            }
+            LIST_ID => self.read_list_body(start, current_depth),
+            _ => {
+                read_tag_body(&mut self.reader, self.opts, &self.budget, tag_id, current_depth)
+                    .map_err(|err| Box::new((None, self.fail(start, err))))
+            }
+        }
+    }
+
+    /// Reads entries into the compound until its `TAG_End`, or until an entry cannot be read.
+    fn read_compound_body(
+        &mut self,
+        compound:      &mut NbtCompound,
+        current_depth: u32,
+    ) -> Result<(), SalvageFailure> {
+        loop {
+            let start = self.offset();
+            let tag_id = raw::read_u8(&mut self.reader, self.opts)
+                .map_err(|err| self.fail(start, err.into()))?;
+
+            if tag_id == TAG_END_ID {
+                return Ok(());
+            }
+
+            let name = self
+                .read_entry_name(current_depth)
+                .map_err(|err| self.fail(start, err))?;
+
+            self.path.push_key(&name);
+            match self.read_tag_body(tag_id, start, current_depth + 1) {
+                Ok(tag) => {
+                    compound.insert(name, tag);
+                    self.path.pop();
+                }
+                Err(partial) => {
+                    let (partial, failure) = *partial;
+                    if let Some(partial) = partial {
+                        compound.insert(name, partial);
+                    }
+                    return Err(failure);
+                }
+            }
+        }
+    }
+
+    fn read_entry_name(&mut self, current_depth: u32) -> Result<String, NbtIoError> {
+        if current_depth >= self.opts.depth_limit.0 {
+            return Err(NbtIoError::ExceededDepthLimit {
+                limit: self.opts.depth_limit,
+            });
+        }
+
+        self.budget.charge_tags(1)?;
+        self.read_name()
+    }
+
+    fn read_list_body(&mut self, start: usize, current_depth: u32) -> Result<NbtTag, Partial> {
+        let (element_id, len) = self
+            .read_list_header(current_depth)
+            .map_err(|err| Box::new((None, self.fail(start, err))))?;
+
+        let mut list = NbtList::with_capacity(len);
+        for index in 0..len {
+            let element_start = self.offset();
+            self.path.push_index(i32::try_from(index).unwrap_or(i32::MAX));
+
+            match self.read_tag_body(element_id, element_start, current_depth + 1) {
+                Ok(tag) => list.push(tag),
+                Err(partial) => {
+                    let (partial, failure) = *partial;
+                    if let Some(partial) = partial {
+                        list.push(partial);
+                    }
+                    return Err(Box::new((Some(NbtTag::List(list)), failure)));
+                }
+            }
+
+            self.path.pop();
+        }
+
+        if element_id == COMPOUND_ID && self.opts.unwrap_heterogenous_lists {
+            list.unwrap_heterogenous();
+        }
+
+        Ok(NbtTag::List(list))
+    }
+
+    /// Reads the element type and length of a list, and charges its elements to the budget.
+    fn read_list_header(&mut self, current_depth: u32) -> Result<(u8, usize), NbtIoError> {
+        let element_id = raw::read_u8(&mut self.reader, self.opts)?;
+        let len = raw::read_i32_as_usize(&mut self.reader, self.opts)?;
+
+        // Make sure we don't have an invalid type or a nonempty list of TAG_End
+        if element_id > LONG_ARRAY_ID || (element_id == TAG_END_ID && len > 0) {
+            return Err(NbtIoError::InvalidTagId(element_id));
+        }
+
+        if len > 0 {
+            if current_depth >= self.opts.depth_limit.0 {
+                return Err(NbtIoError::ExceededDepthLimit {
+                    limit: self.opts.depth_limit,
+                });
+            }
+            self.budget.charge_tags(len)?;
+        }
+
+        Ok((element_id, len))
+    }
+}
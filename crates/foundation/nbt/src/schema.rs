@@ -0,0 +1,744 @@
+//! Declarative schemas for NBT data, which check that data has the expected structure before
+//! it is written back.
+//!
+//! An [`NbtSchema`] specifies the tag type of a tag, along with a range for numbers, the schema
+//! of each element of a list, or the keys of a compound, described by a [`CompoundSchema`].
+//! Validating data against a schema reports every violation at once, each with the path to the
+//! offending tag.
+//!
+//! Schemas can be built in Rust, or read from a description written in SNBT or (with the `json`
+//! feature) JSON. A description is either the name of a tag type, or a compound whose `type` key
+//! names a tag type, along with other keys depending on the type:
+//! - `byte`, `short`, `int`, `long`, `float`, and `double` accept `min` and `max` keys, which
+//!   may be any numbers,
+//! - `bool` is a `Byte` tag which is `0` or `1`,
+//! - `string`, `byte_array`, `int_array`, and `long_array` take no other keys,
+//! - `list` accepts an `element` key, the schema of each element of the list,
+//! - `compound` accepts a `fields` key, a compound whose values are the schemas of its keys,
+//!   and an `allow_unknown` key, which defaults to `true`,
+//! - `one_of` requires an `options` key, a list of schemas of which at least one must match,
+//! - `any` accepts any tag.
+//!
+//! Each field of a compound is required, unless its schema is a type name ending in `?` or a
+//! compound with `optional: true`. Note that since NBT lists are homogenous, the schemas in
+//! `options` must all be written as compounds.
+//!
+//! # Examples:
+//! ```
+//! use prismarine_anchor_nbt::{snbt, SnbtParseOptions};
+//! use prismarine_anchor_nbt::schema::CompoundSchema;
+//!
+//! let schema = CompoundSchema::from_snbt(r#"{type: "compound", fields: {
+//!     Name:  "string",
+//!     Count: {type: "byte", min: 1, max: 64},
+//!     tag:   "compound?"
+//! }}"#).unwrap();
+//!
+//! let opts = SnbtParseOptions::default_updated();
+//! let item = snbt::parse_compound(r#"{Name: "minecraft:apple", Count: 70b}"#, opts).unwrap();
+//!
+//! let violations = item.validate(&schema).unwrap_err();
+//! assert_eq!(violations[0].to_string(), "70 is outside the range 1..=64 at Count");
+//! ```
+
+use std::fmt;
+use std::{fmt::{Display, Formatter}, ops::RangeInclusive};
+
+use thiserror::Error;
+
+use crate::snbt;
+use crate::{
+    path::NbtPath,
+    settings::SnbtParseOptions,
+    snbt::SnbtError,
+    tag::{NbtCompound, NbtTag, NbtType},
+};
+#[cfg(feature = "json")]
+use crate::json::{self, NbtJsonError};
+
+
+// ================================
+//  Schemas
+// ================================
+
+/// The expected structure of an NBT tag. See the [module-level documentation](self).
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtSchema {
+    /// Any tag.
+    Any,
+    /// A `Byte` tag whose value is in the given range.
+    Byte(RangeInclusive<i64>),
+    /// A `Short` tag whose value is in the given range.
+    Short(RangeInclusive<i64>),
+    /// An `Int` tag whose value is in the given range.
+    Int(RangeInclusive<i64>),
+    /// A `Long` tag whose value is in the given range.
+    Long(RangeInclusive<i64>),
+    /// A `Float` tag whose value is in the given range. NaN is only rejected by bounded ranges.
+    Float(RangeInclusive<f64>),
+    /// A `Double` tag whose value is in the given range. NaN is only rejected by bounded ranges.
+    Double(RangeInclusive<f64>),
+    /// A `String` tag, or a `ByteString` tag.
+    String,
+    ByteArray,
+    IntArray,
+    LongArray,
+    /// A `List` tag whose elements each match the given schema.
+    List(Box<Self>),
+    Compound(CompoundSchema),
+    /// A tag which matches at least one of the given schemas.
+    OneOf(Vec<Self>),
+}
+
+impl NbtSchema {
+    /// A `Byte` tag with any value.
+    pub fn byte() -> Self {
+        Self::Byte(i64::from(i8::MIN)..=i64::from(i8::MAX))
+    }
+
+    /// A `Short` tag with any value.
+    pub fn short() -> Self {
+        Self::Short(i64::from(i16::MIN)..=i64::from(i16::MAX))
+    }
+
+    /// An `Int` tag with any value.
+    pub fn int() -> Self {
+        Self::Int(i64::from(i32::MIN)..=i64::from(i32::MAX))
+    }
+
+    /// A `Long` tag with any value.
+    pub fn long() -> Self {
+        Self::Long(i64::MIN..=i64::MAX)
+    }
+
+    /// A `Float` tag with any value.
+    pub fn float() -> Self {
+        Self::Float(f64::NEG_INFINITY..=f64::INFINITY)
+    }
+
+    /// A `Double` tag with any value.
+    pub fn double() -> Self {
+        Self::Double(f64::NEG_INFINITY..=f64::INFINITY)
+    }
+
+    /// A `Byte` tag which is `0` or `1`.
+    pub fn bool() -> Self {
+        Self::Byte(0..=1)
+    }
+
+    /// A `List` tag whose elements each match the given schema.
+    pub fn list(element: Self) -> Self {
+        Self::List(Box::new(element))
+    }
+
+    /// A `List` tag with elements of any type.
+    pub fn any_list() -> Self {
+        Self::list(Self::Any)
+    }
+
+    /// A `Compound` tag with any keys.
+    pub fn any_compound() -> Self {
+        Self::Compound(CompoundSchema::new())
+    }
+
+    /// Reads a schema from its description. See the [module-level documentation](self).
+    pub fn from_description(description: &NbtTag) -> Result<Self, NbtSchemaError> {
+        DescriptionReader { path: NbtPath::root() }
+            .read_schema(description)
+            .map(|(schema, _)| schema)
+    }
+
+    /// Reads a schema from its description, written in SNBT.
+    /// See the [module-level documentation](self).
+    pub fn from_snbt(description: &str) -> Result<Self, NbtSchemaError> {
+        let description = snbt::parse_any(description, SnbtParseOptions::default_updated())?;
+        Self::from_description(&description)
+    }
+
+    /// Reads a schema from its description, written in JSON.
+    /// See the [module-level documentation](self).
+    #[cfg(feature = "json")]
+    pub fn from_json(description: &serde_json::Value) -> Result<Self, NbtSchemaError> {
+        let description = json::from_plain_json(description, Default::default())?;
+        Self::from_description(&description)
+    }
+
+    /// Checks whether the tag matches this schema, returning every violation if not.
+    pub fn validate(&self, tag: &NbtTag) -> Result<(), Vec<SchemaViolation>> {
+        let mut validator = Validator {
+            path:       NbtPath::root(),
+            violations: Vec::new(),
+        };
+        validator.validate_tag(self, tag);
+        validator.finish()
+    }
+
+    /// The type of tag this schema requires, if it requires one particular type.
+    fn nbt_type(&self) -> Option<NbtType> {
+        Some(match self {
+            Self::Any | Self::OneOf(_) => return None,
+            Self::Byte(_)              => NbtType::Byte,
+            Self::Short(_)             => NbtType::Short,
+            Self::Int(_)               => NbtType::Int,
+            Self::Long(_)              => NbtType::Long,
+            Self::Float(_)             => NbtType::Float,
+            Self::Double(_)            => NbtType::Double,
+            Self::String               => NbtType::String,
+            Self::ByteArray            => NbtType::ByteArray,
+            Self::IntArray             => NbtType::IntArray,
+            Self::LongArray            => NbtType::LongArray,
+            Self::List(_)              => NbtType::List,
+            Self::Compound(_)          => NbtType::Compound,
+        })
+    }
+}
+
+/// The expected keys of an NBT compound. By default, keys which are not given a schema
+/// are permitted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompoundSchema {
+    fields:        Vec<FieldSchema>,
+    allow_unknown: bool,
+}
+
+/// The schema of a key of a compound.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSchema {
+    pub key:      String,
+    pub schema:   NbtSchema,
+    pub required: bool,
+}
+
+impl Default for CompoundSchema {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CompoundSchema {
+    /// A schema for a compound with any keys.
+    pub fn new() -> Self {
+        Self {
+            fields:        Vec::new(),
+            allow_unknown: true,
+        }
+    }
+
+    /// Adds a key which must be present and match the given schema. If the key was already
+    /// given a schema, it is replaced.
+    #[must_use]
+    pub fn required(self, key: &str, schema: NbtSchema) -> Self {
+        self.with_field(FieldSchema {
+            key: key.to_owned(),
+            schema,
+            required: true,
+        })
+    }
+
+    /// Adds a key which must match the given schema if it is present. If the key was already
+    /// given a schema, it is replaced.
+    #[must_use]
+    pub fn optional(self, key: &str, schema: NbtSchema) -> Self {
+        self.with_field(FieldSchema {
+            key: key.to_owned(),
+            schema,
+            required: false,
+        })
+    }
+
+    /// Sets whether keys which are not given a schema are permitted.
+    #[must_use]
+    pub fn allow_unknown(mut self, allow_unknown: bool) -> Self {
+        self.allow_unknown = allow_unknown;
+        self
+    }
+
+    #[must_use]
+    pub fn with_field(mut self, field: FieldSchema) -> Self {
+        if let Some(existing) = self.fields.iter_mut().find(|existing| existing.key == field.key) {
+            *existing = field;
+        } else {
+            self.fields.push(field);
+        }
+        self
+    }
+
+    #[inline]
+    pub fn fields(&self) -> &[FieldSchema] {
+        &self.fields
+    }
+
+    #[inline]
+    pub fn field(&self, key: &str) -> Option<&FieldSchema> {
+        self.fields.iter().find(|field| field.key == key)
+    }
+
+    /// Whether keys which are not given a schema are permitted.
+    #[inline]
+    pub fn allows_unknown(&self) -> bool {
+        self.allow_unknown
+    }
+
+    /// Reads a compound schema from its description, whose type must be `compound`.
+    /// See the [module-level documentation](self).
+    pub fn from_description(description: &NbtTag) -> Result<Self, NbtSchemaError> {
+        match NbtSchema::from_description(description)? {
+            NbtSchema::Compound(schema) => Ok(schema),
+            _ => Err(NbtSchemaError::invalid(&NbtPath::root(), "expected a compound schema")),
+        }
+    }
+
+    /// Reads a compound schema from its description, written in SNBT.
+    /// See the [module-level documentation](self).
+    pub fn from_snbt(description: &str) -> Result<Self, NbtSchemaError> {
+        let description = snbt::parse_any(description, SnbtParseOptions::default_updated())?;
+        Self::from_description(&description)
+    }
+
+    /// Reads a compound schema from its description, written in JSON.
+    /// See the [module-level documentation](self).
+    #[cfg(feature = "json")]
+    pub fn from_json(description: &serde_json::Value) -> Result<Self, NbtSchemaError> {
+        let description = json::from_plain_json(description, Default::default())?;
+        Self::from_description(&description)
+    }
+
+    /// Checks whether the compound matches this schema, returning every violation if not.
+    pub fn validate(&self, compound: &NbtCompound) -> Result<(), Vec<SchemaViolation>> {
+        let mut validator = Validator {
+            path:       NbtPath::root(),
+            violations: Vec::new(),
+        };
+        validator.validate_compound(self, compound);
+        validator.finish()
+    }
+}
+
+
+// ================================
+//  Validation
+// ================================
+
+#[derive(Debug)]
+struct Validator {
+    path:       NbtPath,
+    violations: Vec<SchemaViolation>,
+}
+
+impl Validator {
+    fn finish(self) -> Result<(), Vec<SchemaViolation>> {
+        if self.violations.is_empty() {
+            Ok(())
+        } else {
+            Err(self.violations)
+        }
+    }
+
+    fn violation(&mut self, kind: SchemaViolationKind) {
+        self.violations.push(SchemaViolation {
+            path: self.path.clone(),
+            kind,
+        });
+    }
+
+    fn validate_tag(&mut self, schema: &NbtSchema, tag: &NbtTag) {
+        match (schema, tag) {
+            (NbtSchema::Any, _) => {}
+            (NbtSchema::Byte(range),  NbtTag::Byte(value))  => self.check_int(range, i64::from(*value)),
+            (NbtSchema::Short(range), NbtTag::Short(value)) => self.check_int(range, i64::from(*value)),
+            (NbtSchema::Int(range),   NbtTag::Int(value))   => self.check_int(range, i64::from(*value)),
+            (NbtSchema::Long(range),  NbtTag::Long(value))  => self.check_int(range, *value),
+            (NbtSchema::Float(range), NbtTag::Float(value)) => {
+                self.check_float(range, f64::from(*value));
+            }
+            (NbtSchema::Double(range), NbtTag::Double(value)) => self.check_float(range, *value),
+            (NbtSchema::List(element), NbtTag::List(list)) => {
+                for (index, tag) in list.iter().enumerate() {
+                    self.path.push_index(i32::try_from(index).unwrap_or(i32::MAX));
+                    self.validate_tag(element, tag);
+                    self.path.pop();
+                }
+            }
+            (NbtSchema::Compound(schema), NbtTag::Compound(compound)) => {
+                self.validate_compound(schema, compound);
+            }
+            (NbtSchema::OneOf(options), _) => self.validate_one_of(options, tag),
+            _ => {
+                if let Some(expected) = schema.nbt_type() {
+                    if expected != tag.tag_type() {
+                        self.violation(SchemaViolationKind::TypeMismatch {
+                            expected,
+                            found: tag.tag_type(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    fn validate_compound(&mut self, schema: &CompoundSchema, compound: &NbtCompound) {
+        for field in &schema.fields {
+            self.path.push_key(&field.key);
+            match compound.0.get(&field.key) {
+                Some(tag) => self.validate_tag(&field.schema, tag),
+                None if field.required => self.violation(SchemaViolationKind::MissingKey),
+                None => {}
+            }
+            self.path.pop();
+        }
+
+        if schema.allow_unknown {
+            return;
+        }
+
+        #[cfg_attr(feature = "preserve_order", expect(unused_mut))]
+        let mut unknown_keys: Vec<&String> = compound
+            .inner()
+            .keys()
+            .filter(|key| schema.field(key).is_none())
+            .collect();
+
+        // Without a consistent iteration order, sort keys so that violations are reproducible
+        #[cfg(not(feature = "preserve_order"))]
+        unknown_keys.sort_unstable();
+
+        for key in unknown_keys {
+            self.path.push_key(key);
+            self.violation(SchemaViolationKind::UnknownKey);
+            self.path.pop();
+        }
+    }
+
+    /// If only one of the options requires the tag's type, its violations are reported, since
+    /// that option was most likely intended. Otherwise, a single violation is reported.
+    fn validate_one_of(&mut self, options: &[NbtSchema], tag: &NbtTag) {
+        let mut closest = None;
+        let mut candidates = 0_usize;
+
+        for option in options {
+            let mut validator = Self {
+                path:       self.path.clone(),
+                violations: Vec::new(),
+            };
+            validator.validate_tag(option, tag);
+
+            if validator.violations.is_empty() {
+                return;
+            }
+            if option.nbt_type().is_none_or(|nbt_type| nbt_type == tag.tag_type()) {
+                candidates += 1;
+                closest = Some(validator.violations);
+            }
+        }
+
+        match closest {
+            Some(violations) if candidates == 1 => self.violations.extend(violations),
+            _ => self.violation(SchemaViolationKind::NoMatchingOption {
+                options: options.len(),
+            }),
+        }
+    }
+
+    fn check_int(&mut self, range: &RangeInclusive<i64>, value: i64) {
+        if !range.contains(&value) {
+            self.violation(SchemaViolationKind::IntOutOfRange {
+                value,
+                min: *range.start(),
+                max: *range.end(),
+            });
+        }
+    }
+
+    fn check_float(&mut self, range: &RangeInclusive<f64>, value: f64) {
+        // Written this way so that NaN is permitted by unbounded ranges
+        if value < *range.start() || value > *range.end()
+            || (value.is_nan() && (range.start().is_finite() || range.end().is_finite()))
+        {
+            self.violation(SchemaViolationKind::FloatOutOfRange {
+                value,
+                min: *range.start(),
+                max: *range.end(),
+            });
+        }
+    }
+}
+
+
+// ================================
+//  Descriptions
+// ================================
+
+#[derive(Debug)]
+struct DescriptionReader {
+    path: NbtPath,
+}
+
+impl DescriptionReader {
+    /// Reads a schema, along with whether it is marked as optional.
+    fn read_schema(&mut self, description: &NbtTag) -> Result<(NbtSchema, bool), NbtSchemaError> {
+        match description {
+            NbtTag::String(type_name) => {
+                let (type_name, optional) = match type_name.strip_suffix('?') {
+                    Some(type_name) => (type_name, true),
+                    None            => (type_name.as_str(), false),
+                };
+                let schema = self.read_typed(type_name, &NbtCompound::new())?;
+                Ok((schema, optional))
+            }
+            NbtTag::Compound(description) => {
+                let type_name = match description.0.get("type") {
+                    Some(NbtTag::String(type_name)) => type_name,
+                    Some(_) => return Err(self.invalid_key("type", "expected a type name")),
+                    None    => return Err(self.invalid("missing the `type` key")),
+                };
+                let optional = self.read_bool(description, "optional", false)?;
+                let schema = self.read_typed(type_name, description)?;
+                Ok((schema, optional))
+            }
+            _ => Err(self.invalid("expected a type name or a compound")),
+        }
+    }
+
+    fn read_typed(
+        &mut self,
+        type_name:   &str,
+        description: &NbtCompound,
+    ) -> Result<NbtSchema, NbtSchemaError> {
+        let allowed_keys: &[&str] = match type_name {
+            "byte" | "short" | "int" | "long" | "float" | "double" => &["min", "max"],
+            "list"     => &["element"],
+            "compound" => &["fields", "allow_unknown"],
+            "one_of"   => &["options"],
+            _          => &[],
+        };
+        if let Some(key) = description
+            .inner()
+            .keys()
+            .find(|key| !matches!(key.as_str(), "type" | "optional") && !allowed_keys.contains(&key.as_str()))
+        {
+            return Err(self.invalid_key(key, &format!("unexpected key for `{type_name}` schema")));
+        }
+
+        Ok(match type_name {
+            "any"        => NbtSchema::Any,
+            "byte"       => NbtSchema::Byte(self.read_int_range(description, i8::MIN.into(), i8::MAX.into())?),
+            "short"      => NbtSchema::Short(self.read_int_range(description, i16::MIN.into(), i16::MAX.into())?),
+            "int"        => NbtSchema::Int(self.read_int_range(description, i32::MIN.into(), i32::MAX.into())?),
+            "long"       => NbtSchema::Long(self.read_int_range(description, i64::MIN, i64::MAX)?),
+            "float"      => NbtSchema::Float(self.read_float_range(description)?),
+            "double"     => NbtSchema::Double(self.read_float_range(description)?),
+            "bool"       => NbtSchema::bool(),
+            "string"     => NbtSchema::String,
+            "byte_array" => NbtSchema::ByteArray,
+            "int_array"  => NbtSchema::IntArray,
+            "long_array" => NbtSchema::LongArray,
+            "list" => match description.0.get("element") {
+                Some(element) => {
+                    self.path.push_key("element");
+                    let (element, _) = self.read_schema(element)?;
+                    self.path.pop();
+                    NbtSchema::list(element)
+                }
+                None => NbtSchema::any_list(),
+            },
+            "compound" => NbtSchema::Compound(self.read_compound(description)?),
+            "one_of"   => NbtSchema::OneOf(self.read_options(description)?),
+            _ => return Err(self.invalid(&format!("unknown schema type `{type_name}`"))),
+        })
+    }
+
+    fn read_compound(&mut self, description: &NbtCompound) -> Result<CompoundSchema, NbtSchemaError> {
+        let allow_unknown = self.read_bool(description, "allow_unknown", true)?;
+        let mut schema = CompoundSchema::new().allow_unknown(allow_unknown);
+
+        let fields = match description.0.get("fields") {
+            Some(NbtTag::Compound(fields)) => fields,
+            Some(_) => return Err(self.invalid_key("fields", "expected a compound")),
+            None    => return Ok(schema),
+        };
+
+        self.path.push_key("fields");
+        for (key, field) in fields {
+            self.path.push_key(key);
+            let (field_schema, optional) = self.read_schema(field)?;
+            self.path.pop();
+
+            schema = schema.with_field(FieldSchema {
+                key:      key.clone(),
+                schema:   field_schema,
+                required: !optional,
+            });
+        }
+        self.path.pop();
+
+        Ok(schema)
+    }
+
+    fn read_options(&mut self, description: &NbtCompound) -> Result<Vec<NbtSchema>, NbtSchemaError> {
+        let options = match description.0.get("options") {
+            Some(NbtTag::List(options)) if !options.is_empty() => options,
+            Some(_) => return Err(self.invalid_key("options", "expected a nonempty list")),
+            None    => return Err(self.invalid("missing the `options` key")),
+        };
+
+        self.path.push_key("options");
+        let mut schemas = Vec::with_capacity(options.len());
+        for (index, option) in options.iter().enumerate() {
+            self.path.push_index(i32::try_from(index).unwrap_or(i32::MAX));
+            schemas.push(self.read_schema(option)?.0);
+            self.path.pop();
+        }
+        self.path.pop();
+
+        Ok(schemas)
+    }
+
+    fn read_int_range(
+        &mut self,
+        description: &NbtCompound,
+        type_min:    i64,
+        type_max:    i64,
+    ) -> Result<RangeInclusive<i64>, NbtSchemaError> {
+        let mut read_bound = |key: &str, default: i64| match description.0.get(key) {
+            Some(NbtTag::Byte(value))  => Ok(i64::from(*value)),
+            Some(NbtTag::Short(value)) => Ok(i64::from(*value)),
+            Some(NbtTag::Int(value))   => Ok(i64::from(*value)),
+            Some(NbtTag::Long(value))  => Ok(*value),
+            Some(_) => Err(self.invalid_key(key, "expected an integer")),
+            None    => Ok(default),
+        };
+
+        Ok(read_bound("min", type_min)?..=read_bound("max", type_max)?)
+    }
+
+    fn read_float_range(
+        &mut self,
+        description: &NbtCompound,
+    ) -> Result<RangeInclusive<f64>, NbtSchemaError> {
+        let mut read_bound = |key: &str, default: f64| match description.0.get(key) {
+            Some(NbtTag::Byte(value))   => Ok(f64::from(*value)),
+            Some(NbtTag::Short(value))  => Ok(f64::from(*value)),
+            Some(NbtTag::Int(value))    => Ok(f64::from(*value)),
+            #[expect(clippy::cast_precision_loss, reason = "bounds this large are approximate anyway")]
+            Some(NbtTag::Long(value))   => Ok(*value as f64),
+            Some(NbtTag::Float(value))  => Ok(f64::from(*value)),
+            Some(NbtTag::Double(value)) => Ok(*value),
+            Some(_) => Err(self.invalid_key(key, "expected a number")),
+            None    => Ok(default),
+        };
+
+        Ok(read_bound("min", f64::NEG_INFINITY)?..=read_bound("max", f64::INFINITY)?)
+    }
+
+    fn read_bool(
+        &mut self,
+        description: &NbtCompound,
+        key:         &str,
+        default:     bool,
+    ) -> Result<bool, NbtSchemaError> {
+        match description.0.get(key) {
+            Some(NbtTag::Byte(0)) => Ok(false),
+            Some(NbtTag::Byte(1)) => Ok(true),
+            Some(_) => Err(self.invalid_key(key, "expected a boolean")),
+            None    => Ok(default),
+        }
+    }
+
+    fn invalid(&self, message: &str) -> NbtSchemaError {
+        NbtSchemaError::invalid(&self.path, message)
+    }
+
+    fn invalid_key(&mut self, key: &str, message: &str) -> NbtSchemaError {
+        self.path.push_key(key);
+        let error = self.invalid(message);
+        self.path.pop();
+        error
+    }
+}
+
+
+// ================================
+//  Errors
+// ================================
+
+/// A way in which NBT data does not match a schema, along with the path to the offending tag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaViolation {
+    pub path: NbtPath,
+    pub kind: SchemaViolationKind,
+}
+
+impl Display for SchemaViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.path.is_root() {
+            write!(f, "{}", self.kind)
+        } else {
+            write!(f, "{} at {}", self.kind, self.path)
+        }
+    }
+}
+
+impl std::error::Error for SchemaViolation {}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SchemaViolationKind {
+    /// A compound did not have a required key.
+    #[error("missing required key")]
+    MissingKey,
+    /// A compound had a key without a schema, and its schema does not allow unknown keys.
+    #[error("unknown key")]
+    UnknownKey,
+    /// A tag was not of the expected type.
+    #[error("expected {expected:?} tag, but found {found:?}")]
+    TypeMismatch {
+        expected: NbtType,
+        found:    NbtType,
+    },
+    #[error("{value} is outside the range {min}..={max}")]
+    IntOutOfRange {
+        value: i64,
+        min:   i64,
+        max:   i64,
+    },
+    #[error("{value} is outside the range {min}..={max}")]
+    FloatOutOfRange {
+        value: f64,
+        min:   f64,
+        max:   f64,
+    },
+    /// A tag did not match any of the options of a `OneOf` schema.
+    #[error("does not match any of the {options} permitted schemas")]
+    NoMatchingOption { options: usize },
+}
+
+/// An error from reading a schema from its description.
+#[derive(Error, Debug, Clone)]
+pub enum NbtSchemaError {
+    #[error(transparent)]
+    Snbt(#[from] SnbtError),
+    #[cfg(feature = "json")]
+    #[error(transparent)]
+    Json(#[from] NbtJsonError),
+    /// The description of a schema was invalid. The path is to the invalid part of the
+    /// description.
+    #[error("invalid schema description at {path}: {message}")]
+    InvalidDescription {
+        path:    String,
+        message: String,
+    },
+}
+
+impl NbtSchemaError {
+    fn invalid(path: &NbtPath, message: &str) -> Self {
+        let path = if path.is_root() {
+            "(root)".to_owned()
+        } else {
+            path.to_string()
+        };
+
+        Self::InvalidDescription {
+            path,
+            message: message.to_owned(),
+        }
+    }
+}
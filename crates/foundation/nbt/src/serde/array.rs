@@ -11,8 +11,7 @@ use crate::raw;
 pub(crate) const BYTE_ARRAY_NICHE:    &str = "b_nbt_array";
 pub(crate) const INT_ARRAY_NICHE:     &str = "i_nbt_array";
 pub(crate) const LONG_ARRAY_NICHE:    &str = "l_nbt_array";
-// TODO: try to figure out some way to support ByteStrings in the serde impl
-// pub(crate) const BSTRING_ARRAY_NICHE: &str = "s_nbt_array";
+pub(crate) const BYTE_STRING_NICHE:   &str = "s_nbt_array";
 pub(crate) const TYPE_HINT_NICHE:     &str = "__nbt_array_type_hint";
 
 
@@ -114,6 +113,177 @@ where
     }
 }
 
+/// A transparent wrapper around a sequence of bytes, which are stored in a `String` tag.
+///
+/// Bytes which are not a valid string become a [`ByteString`] tag, and writing them as binary
+/// NBT requires [`IoOptions::enable_byte_strings`]. When reading, either a `String` or
+/// `ByteString` tag is accepted. You can construct a byte string using `ByteString::from`.
+///
+/// [`ByteString`]: crate::tag::NbtTag::ByteString
+/// [`IoOptions::enable_byte_strings`]: crate::settings::IoOptions::enable_byte_strings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct ByteString<T>(T);
+
+impl<T> ByteString<T> {
+    /// Returns the inner value wrapped by this type.
+    #[inline]
+    pub fn into_inner(byte_string: Self) -> T {
+        byte_string.0
+    }
+}
+
+impl<T: AsRef<[u8]>> Serialize for ByteString<T> {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_newtype_struct(BYTE_STRING_NICHE, &Bytes(self.0.as_ref()))
+    }
+}
+
+impl<'de, T: From<Vec<u8>>> Deserialize<'de> for ByteString<T> {
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "A newtype struct type")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                deserializer.deserialize_byte_buf(ByteBufVisitor)
+            }
+        }
+
+        deserializer
+            .deserialize_newtype_struct(BYTE_STRING_NICHE, Visitor)
+            .map(|bytes| Self(T::from(bytes)))
+    }
+}
+
+impl<T> AsRef<T> for ByteString<T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> AsMut<T> for ByteString<T> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> From<T> for ByteString<T>
+where
+    T: AsRef<[u8]>,
+{
+    #[inline]
+    fn from(bytes: T) -> Self {
+        Self(bytes)
+    }
+}
+
+/// Serializes a byte slice with `serialize_bytes`, instead of as a sequence.
+struct Bytes<'a>(&'a [u8]);
+
+impl Serialize for Bytes<'_> {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+/// Reads bytes from bytes, strings, or sequences of bytes.
+pub(crate) struct ByteBufVisitor;
+
+impl<'de> Visitor<'de> for ByteBufVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "A sequence of bytes")
+    }
+
+    #[inline]
+    fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(v.to_vec())
+    }
+
+    #[inline]
+    fn visit_byte_buf<E: Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    #[inline]
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(v.as_bytes().to_vec())
+    }
+
+    #[inline]
+    fn visit_string<E: Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(v.into_bytes())
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element::<ByteElement>()? {
+            bytes.push(byte.0);
+        }
+        Ok(bytes)
+    }
+}
+
+/// A byte which may be deserialized from either an `i8` or a `u8`.
+struct ByteElement(u8);
+
+impl<'de> Deserialize<'de> for ByteElement {
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct ByteVisitor;
+
+        impl Visitor<'_> for ByteVisitor {
+            type Value = u8;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(formatter, "A byte")
+            }
+
+            #[inline]
+            fn visit_i8<E: Error>(self, v: i8) -> Result<Self::Value, E> {
+                Ok(v as u8)
+            }
+
+            #[inline]
+            fn visit_u8<E: Error>(self, v: u8) -> Result<Self::Value, E> {
+                Ok(v)
+            }
+        }
+
+        deserializer.deserialize_u8(ByteVisitor).map(Self)
+    }
+}
+
 pub trait ArrayNiche {
     type SerRepr: ?Sized + Serialize;
     const NICHE: &'static str;
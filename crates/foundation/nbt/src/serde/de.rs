@@ -11,12 +11,13 @@ use serde::de;
 use serde::forward_to_deserialize_any;
 use serde::de::{
     DeserializeSeed, EnumAccess, IntoDeserializer as _, MapAccess, SeqAccess,
-    value::CowStrDeserializer, VariantAccess, Visitor,
+    value::{BytesDeserializer, CowStrDeserializer}, VariantAccess, Visitor,
 };
 
-use crate::raw;
+use crate::raw::{self, BudgetTracker};
 use crate::{io::NbtIoError, settings::IoOptions};
-use super::array::TYPE_HINT_NICHE;
+use crate::tag::NbtTag;
+use super::array::{BYTE_STRING_NICHE, TYPE_HINT_NICHE};
 use crate::raw::{
     BYTE_ARRAY_ID, BYTE_ID, COMPOUND_ID, DOUBLE_ID, FLOAT_ID, INT_ARRAY_ID,
     INT_ID, LIST_ID, LONG_ID, LONG_ARRAY_ID, SHORT_ID, STRING_ID, TAG_END_ID,
@@ -27,6 +28,7 @@ use crate::raw::{
 pub struct Deserializer<'a, R, B> {
     reader:    &'a mut R,
     opts:      IoOptions,
+    budget:    BudgetTracker,
     _buffered: PhantomData<B>,
 }
 
@@ -45,11 +47,16 @@ impl<'a, R: Read> Deserializer<'a, R, Unbuffered> {
             return Err(NbtIoError::MissingRootTag);
         }
 
-        let root_name = raw::read_string(reader, opts)?;
+        let budget = BudgetTracker::new(opts.memory_budget);
+        let root_name_len = raw::read_string_len(reader, opts)?;
+        budget.charge(root_name_len, 0)?;
+        let root_name = raw::read_string_body(reader, opts, root_name_len)?;
+
         Ok((
             Deserializer {
                 reader,
                 opts,
+                budget,
                 _buffered: PhantomData,
             },
             root_name,
@@ -86,6 +93,7 @@ where
             Deserializer {
                 reader,
                 opts,
+                budget: BudgetTracker::new(opts.memory_budget),
                 _buffered: PhantomData,
             },
             root_name,
@@ -121,7 +129,7 @@ where
     where
         V: Visitor<'de>,
     {
-        DeserializeTag::<_, B, COMPOUND_ID>::new(self.reader, self.opts, 0)
+        DeserializeTag::<_, B, COMPOUND_ID>::new(self.reader, self.opts, &self.budget, 0)
             .deserialize_map(visitor)
     }
 
@@ -148,7 +156,7 @@ where
     where
         V: Visitor<'de>,
     {
-        DeserializeTag::<_, B, COMPOUND_ID>::new(self.reader, self.opts, 0)
+        DeserializeTag::<_, B, COMPOUND_ID>::new(self.reader, self.opts, &self.budget, 0)
             .deserialize_enum(name, variants, visitor)
     }
 
@@ -162,6 +170,7 @@ where
 fn drive_visitor_seq_const<'de, 'a, 'buffer, R, V, B, const TAG_ID: u8>(
     reader:        &'a mut R,
     opts:          IoOptions,
+    budget:        &'a BudgetTracker,
     current_depth: u32,
     visitor:       V,
 ) -> Result<V::Value, NbtIoError>
@@ -175,23 +184,28 @@ where
     match TAG_ID {
         BYTE_ARRAY_ID => {
             let len = raw::read_i32_as_usize(reader, opts)?;
+            budget.charge_array(BYTE_ID, len)?;
             visitor.visit_seq(DeserializeSeq::<_, _, BYTE_ID, TAG_ID>::new(
-                DeserializeTag::<_, B, BYTE_ID>::new(reader, opts, current_depth),
+                DeserializeTag::<_, B, BYTE_ID>::new(reader, opts, budget, current_depth),
                 len,
             ))
         }
-        LIST_ID => drive_visitor_seq_tag::<_, _, B>(reader, opts, current_depth, visitor),
+        LIST_ID => {
+            drive_visitor_seq_tag::<_, _, B>(reader, opts, budget, current_depth, visitor)
+        }
         INT_ARRAY_ID => {
             let len = raw::read_i32_as_usize(reader, opts)?;
+            budget.charge_array(INT_ID, len)?;
             visitor.visit_seq(DeserializeSeq::<_, _, INT_ID, TAG_ID>::new(
-                DeserializeTag::<_, B, INT_ID>::new(reader, opts, current_depth),
+                DeserializeTag::<_, B, INT_ID>::new(reader, opts, budget, current_depth),
                 len,
             ))
         }
         LONG_ARRAY_ID => {
             let len = raw::read_i32_as_usize(reader, opts)?;
+            budget.charge_array(LONG_ID, len)?;
             visitor.visit_seq(DeserializeSeq::<_, _, LONG_ID, TAG_ID>::new(
-                DeserializeTag::<_, B, LONG_ID>::new(reader, opts, current_depth),
+                DeserializeTag::<_, B, LONG_ID>::new(reader, opts, budget, current_depth),
                 len,
             ))
         }
@@ -202,6 +216,7 @@ where
 fn drive_visitor_seq_tag<'de, 'a, 'buffer, R, V, B>(
     reader:        &'a mut R,
     opts:          IoOptions,
+    budget:        &'a BudgetTracker,
     current_depth: u32,
     visitor:       V,
 ) -> Result<V::Value, NbtIoError>
@@ -221,6 +236,8 @@ where
         });
     }
 
+    budget.charge_tags(len)?;
+
     macro_rules! drive_visitor {
         ($($id:literal)*) => {
             match id {
@@ -230,6 +247,7 @@ where
                             DeserializeTag::<_, B, TAG_END_ID>::new(
                                 reader,
                                 opts,
+                                budget,
                                 current_depth + 1,
                             ),
                             len,
@@ -239,7 +257,7 @@ where
                     }
                 }
                 $( $id => visitor.visit_seq(DeserializeSeq::<_, _, $id, LIST_ID>::new(
-                    DeserializeTag::<_, B, $id>::new(reader, opts, current_depth + 1), len)
+                    DeserializeTag::<_, B, $id>::new(reader, opts, budget, current_depth + 1), len)
                 ), )*
                 _ => Err(NbtIoError::InvalidTagId(id))
             }
@@ -252,6 +270,7 @@ where
 struct DeserializeEnum<'a, R, B, const TAG_ID: u8> {
     reader:        &'a mut R,
     opts:          IoOptions,
+    budget:        &'a BudgetTracker,
     current_depth: u32,
     variant:       Cow<'a, str>,
     _buffered:     PhantomData<B>,
@@ -259,10 +278,17 @@ struct DeserializeEnum<'a, R, B, const TAG_ID: u8> {
 
 impl<'a, R, B, const TAG_ID: u8> DeserializeEnum<'a, R, B, TAG_ID> {
     #[inline]
-    fn new(reader: &'a mut R, opts: IoOptions, current_depth: u32, variant: Cow<'a, str>) -> Self {
+    fn new(
+        reader:        &'a mut R,
+        opts:          IoOptions,
+        budget:        &'a BudgetTracker,
+        current_depth: u32,
+        variant:       Cow<'a, str>,
+    ) -> Self {
         DeserializeEnum {
             reader,
             opts,
+            budget,
             current_depth,
             variant,
             _buffered: PhantomData,
@@ -289,7 +315,7 @@ where
         let de: CowStrDeserializer<'a, Self::Error> = self.variant.into_deserializer();
         Ok((
             seed.deserialize(de)?,
-            DeserializeVariant::new(self.reader, self.opts, self.current_depth),
+            DeserializeVariant::new(self.reader, self.opts, self.budget, self.current_depth),
         ))
     }
 }
@@ -297,6 +323,7 @@ where
 struct DeserializeVariant<'a, R, B, const TAG_ID: u8> {
     reader:        &'a mut R,
     opts:          IoOptions,
+    budget:        &'a BudgetTracker,
     current_depth: u32,
     _buffered:     PhantomData<B>,
 }
@@ -307,10 +334,16 @@ where
     B: BufferSpecialization<'buffer>,
 {
     #[inline]
-    fn new(reader: &'a mut R, opts: IoOptions, current_depth: u32) -> Self {
+    fn new(
+        reader:        &'a mut R,
+        opts:          IoOptions,
+        budget:        &'a BudgetTracker,
+        current_depth: u32,
+    ) -> Self {
         DeserializeVariant {
             reader,
             opts,
+            budget,
             current_depth,
             _buffered: PhantomData,
         }
@@ -344,6 +377,7 @@ where
         seed.deserialize(&mut DeserializeTag::<_, B, TAG_ID>::new(
             self.reader,
             self.opts,
+            self.budget,
             self.current_depth,
         ))
     }
@@ -356,6 +390,7 @@ where
         drive_visitor_seq_const::<_, _, B, TAG_ID>(
             self.reader,
             self.opts,
+            self.budget,
             self.current_depth,
             visitor,
         )
@@ -374,6 +409,7 @@ where
             visitor.visit_map(DeserializeMap::<_, B>::new(
                 self.reader,
                 self.opts,
+                self.budget,
                 self.current_depth,
             ))
         } else {
@@ -473,7 +509,7 @@ where
 }
 
 #[derive(PartialEq, Eq)]
-enum TypeHintDispatchState {
+pub(super) enum TypeHintDispatchState {
     Waiting,
     Ready,
     Sent,
@@ -482,6 +518,7 @@ enum TypeHintDispatchState {
 struct DeserializeMap<'a, R, B> {
     reader:        &'a mut R,
     opts:          IoOptions,
+    budget:        &'a BudgetTracker,
     current_depth: u32,
     tag_id:        u8,
     _buffered:     PhantomData<B>,
@@ -493,10 +530,16 @@ where
     B: BufferSpecialization<'buffer>,
 {
     #[inline]
-    fn new(reader: &'a mut R, opts: IoOptions, current_depth: u32) -> Self {
+    fn new(
+        reader:        &'a mut R,
+        opts:          IoOptions,
+        budget:        &'a BudgetTracker,
+        current_depth: u32,
+    ) -> Self {
         DeserializeMap {
             reader,
             opts,
+            budget,
             current_depth,
             tag_id: 0,
             _buffered: PhantomData,
@@ -517,7 +560,7 @@ where
             ($($id:literal)*) => {
                 match tag_id {
                     $( $id => seed.deserialize(&mut DeserializeTag::<_, B, $id>::new(
-                        self.reader, self.opts, self.current_depth + 1
+                        self.reader, self.opts, self.budget, self.current_depth + 1
                     )), )*
                     _ => Err(NbtIoError::InvalidTagId(tag_id))
                 }
@@ -552,9 +595,12 @@ where
             });
         }
 
+        self.budget.charge_tags(1)?;
+
         let mut de = DeserializeTag::<_, B, STRING_ID>::new(
             self.reader,
             self.opts,
+            self.budget,
             self.current_depth + 1,
         );
         seed.deserialize(&mut de).map(Some)
@@ -572,6 +618,7 @@ where
 pub struct DeserializeTag<'a, R, B, const TAG_ID: u8> {
     reader:        &'a mut R,
     opts:          IoOptions,
+    budget:        &'a BudgetTracker,
     current_depth: u32,
     _buffered:     PhantomData<B>,
 }
@@ -597,11 +644,13 @@ where
     fn new(
         reader:        &'a mut R,
         opts:          IoOptions,
+        budget:        &'a BudgetTracker,
         current_depth: u32,
     ) -> Self {
         DeserializeTag {
             reader,
             opts,
+            budget,
             current_depth,
             _buffered: PhantomData,
         }
@@ -636,33 +685,55 @@ where
             DOUBLE_ID => visitor.visit_f64(raw::read_f64(self.reader, self.opts)?),
             BYTE_ARRAY_ID => {
                 let len = raw::read_i32_as_usize(self.reader, self.opts)?;
+                self.budget.charge_array(BYTE_ID, len)?;
                 visitor.visit_seq(DeserializeSeq::<_, _, BYTE_ID, BYTE_ARRAY_ID>::new(
                     DeserializeTag::<_, B, BYTE_ID>::new(
                         self.reader,
                         self.opts,
+                        self.budget,
                         self.current_depth,
                     ),
                     len,
                 ))
             }
-            STRING_ID => visitor.visit_string(raw::read_string(self.reader, self.opts)?),
+            STRING_ID if self.opts.enable_byte_strings => {
+                let len = raw::read_string_len(self.reader, self.opts)?;
+                self.budget.charge(len, 0)?;
+
+                match raw::read_string_or_bytes_body(self.reader, self.opts, len)? {
+                    NbtTag::ByteString(bytes) => visitor.visit_newtype_struct(
+                        BytesDeserializer::new(&bytes),
+                    ),
+                    NbtTag::String(string) => visitor.visit_string(string),
+                    _ => unreachable!(),
+                }
+            }
+            STRING_ID => {
+                let len = raw::read_string_len(self.reader, self.opts)?;
+                self.budget.charge(len, 0)?;
+                visitor.visit_string(raw::read_string_body(self.reader, self.opts, len)?)
+            }
             LIST_ID => drive_visitor_seq_tag::<_, _, B>(
                 self.reader,
                 self.opts,
+                self.budget,
                 self.current_depth,
                 visitor,
             ),
             COMPOUND_ID => visitor.visit_map(DeserializeMap::<_, B>::new(
                 self.reader,
                 self.opts,
+                self.budget,
                 self.current_depth,
             )),
             INT_ARRAY_ID => {
                 let len = raw::read_i32_as_usize(self.reader, self.opts)?;
+                self.budget.charge_array(INT_ID, len)?;
                 visitor.visit_seq(DeserializeSeq::<_, _, INT_ID, INT_ARRAY_ID>::new(
                     DeserializeTag::<_, B, INT_ID>::new(
                         self.reader,
                         self.opts,
+                        self.budget,
                         self.current_depth,
                     ),
                     len,
@@ -670,10 +741,12 @@ where
             }
             LONG_ARRAY_ID => {
                 let len = raw::read_i32_as_usize(self.reader, self.opts)?;
+                self.budget.charge_array(LONG_ID, len)?;
                 visitor.visit_seq(DeserializeSeq::<_, _, LONG_ID, LONG_ARRAY_ID>::new(
                     DeserializeTag::<_, B, LONG_ID>::new(
                         self.reader,
                         self.opts,
+                        self.budget,
                         self.current_depth,
                     ),
                     len,
@@ -714,6 +787,7 @@ where
     {
         if TAG_ID == BYTE_ARRAY_ID {
             let len = raw::read_i32_as_usize(self.reader, self.opts)?;
+            self.budget.charge_array(BYTE_ID, len)?;
             let mut array = vec![0_u8; len];
             self.reader.read_exact(&mut array)?;
             visitor.visit_byte_buf(array)
@@ -735,6 +809,7 @@ where
                 // `BufferedCursor<'buffer>` by the constructor `Deserializer::from_cursor`
                 visitor.visit_borrowed_bytes(unsafe { B::read_bytes(self.reader, len) }?)
             } else {
+                self.budget.charge_array(BYTE_ID, len)?;
                 let mut array = vec![0_u8; len];
                 self.reader.read_exact(&mut array)?;
                 visitor.visit_bytes(&array)
@@ -764,7 +839,7 @@ where
                 }
             } else {
                 let mut dest = Vec::new();
-                match raw::read_string_into(self.reader, self.opts, &mut dest)? {
+                match raw::read_string_into(self.reader, self.opts, self.budget, &mut dest)? {
                     Cow::Borrowed(string) => visitor.visit_str(string),
                     Cow::Owned(string) => visitor.visit_string(string),
                 }
@@ -805,13 +880,22 @@ where
     #[inline]
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name:    &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self)
+        if TAG_ID == STRING_ID && name == BYTE_STRING_NICHE {
+            // The bytes are read as-is, whether or not they form a valid string.
+            let len = raw::read_string_len(self.reader, self.opts)?;
+            self.budget.charge(len, 0)?;
+            let mut bytes = vec![0_u8; len];
+            self.reader.read_exact(&mut bytes)?;
+            visitor.visit_newtype_struct(BytesDeserializer::new(&bytes))
+        } else {
+            visitor.visit_newtype_struct(self)
+        }
     }
 
     #[inline]
@@ -822,6 +906,7 @@ where
         drive_visitor_seq_const::<_, _, B, TAG_ID>(
             self.reader,
             self.opts,
+            self.budget,
             self.current_depth,
             visitor,
         )
@@ -835,6 +920,7 @@ where
         visitor.visit_map(DeserializeMap::<_, B>::new(
             self.reader,
             self.opts,
+            self.budget,
             self.current_depth,
         ))
     }
@@ -919,20 +1005,20 @@ where
             STRING_ID => {
                 let mut dest = Vec::new();
                 visitor.visit_enum(
-                    raw::read_string_into(self.reader, self.opts, &mut dest)?.into_deserializer(),
+                    raw::read_string_into(self.reader, self.opts, self.budget, &mut dest)?.into_deserializer(),
                 )
             }
             // Newtype, tuple, and struct variants
             COMPOUND_ID => {
                 let id = raw::read_u8(self.reader, self.opts)?;
                 let mut buf = Vec::new();
-                let variant = raw::read_string_into(self.reader, self.opts, &mut buf)?;
+                let variant = raw::read_string_into(self.reader, self.opts, self.budget, &mut buf)?;
 
                 macro_rules! drive_visitor {
                     ($($id:literal)*) => {
                         match id {
                             $( $id => visitor.visit_enum(DeserializeEnum::<_, B, $id>::new(
-                                self.reader, self.opts, self.current_depth, variant
+                                self.reader, self.opts, self.budget, self.current_depth, variant
                             )), )*
                             _ => Err(NbtIoError::InvalidTagId(id))
                         }
@@ -972,7 +1058,7 @@ where
     }
 }
 
-struct TypeHintDeserializer<const TAG_ID: u8>;
+pub(super) struct TypeHintDeserializer<const TAG_ID: u8>;
 
 impl<'de, const TAG_ID: u8> de::Deserializer<'de> for TypeHintDeserializer<TAG_ID> {
     type Error = TypeHintDeserializerError;
@@ -1,6 +1,8 @@
 mod array;
 mod de;
 mod ser;
+mod tag_de;
+mod tag_ser;
 mod util;
 
 
@@ -15,11 +17,14 @@ use flate2::{
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::io::NbtIoError;
-use crate::settings::{IoOptions, NbtCompression};
+use crate::settings::{DepthLimit, IoOptions, NbtCompression};
+use crate::tag::{NbtCompound, NbtTag};
+use self::{tag_de::{CompoundDeserializer, TagDeserializer}, tag_ser::TagSerializer};
+use self::util::DefaultSerializer as _;
 
 
-pub(crate) use self::array::TypeHint;
-pub use self::{array::Array, de::Deserializer, util::Ser};
+pub(crate) use self::array::{ByteBufVisitor, TypeHint};
+pub use self::{array::{Array, ByteString}, de::Deserializer, util::Ser};
 pub use self::ser::{Serializer, UncheckedSerializer};
 
 
@@ -172,3 +177,79 @@ fn deserialize_from_raw<'de: 'a, 'a, R: Read, T: Deserialize<'de>>(
     let (de, root_name) = Deserializer::new(reader, opts)?;
     Ok((T::deserialize(de)?, root_name))
 }
+
+/// Serializes the given value into an in-memory NBT tag.
+///
+/// Unlike [`serialize`], the value may be of any type which has an NBT representation, but
+/// values which are omitted from compounds, like `None` or `()`, result in a `MissingRootTag`
+/// error.
+///
+/// [`serialize`]: crate::serde::serialize
+pub fn to_tag<T: Serialize + ?Sized>(
+    value:       &T,
+    depth_limit: DepthLimit,
+) -> Result<NbtTag, NbtIoError> {
+    value
+        .serialize(TagSerializer::new(depth_limit).into_serializer())?
+        .ok_or(NbtIoError::MissingRootTag)
+}
+
+/// Serializes the given value into an in-memory NBT compound. The value must be a struct, map,
+/// or non-unit enum variant, else a `MissingRootTag` error is returned.
+pub fn to_compound<T: Serialize + ?Sized>(
+    value:       &T,
+    depth_limit: DepthLimit,
+) -> Result<NbtCompound, NbtIoError> {
+    match to_tag(value, depth_limit)? {
+        NbtTag::Compound(compound) => Ok(compound),
+        _ => Err(NbtIoError::MissingRootTag),
+    }
+}
+
+/// Deserializes the given type from an in-memory NBT tag, allowing for the type to borrow
+/// strings and byte strings from the tag.
+///
+/// The tag must represent the type `T` correctly, else the deserializer will return with an
+/// error.
+pub fn from_tag<'de, T: Deserialize<'de>>(
+    tag:         &'de NbtTag,
+    depth_limit: DepthLimit,
+) -> Result<T, NbtIoError> {
+    T::deserialize(TagDeserializer::new(Cow::Borrowed(tag), depth_limit))
+}
+
+/// Deserializes the given type from an in-memory NBT tag, moving strings and other data out
+/// of the tag instead of cloning them.
+///
+/// The tag must represent the type `T` correctly, else the deserializer will return with an
+/// error.
+pub fn from_owned_tag<T: DeserializeOwned>(
+    tag:         NbtTag,
+    depth_limit: DepthLimit,
+) -> Result<T, NbtIoError> {
+    T::deserialize(TagDeserializer::new(Cow::Owned(tag), depth_limit))
+}
+
+/// Deserializes the given type from an in-memory NBT compound, allowing for the type to borrow
+/// strings and byte strings from the compound.
+///
+/// The compound must represent the type `T` correctly, else the deserializer will return with an
+/// error.
+pub fn from_compound<'de, T: Deserialize<'de>>(
+    compound:    &'de NbtCompound,
+    depth_limit: DepthLimit,
+) -> Result<T, NbtIoError> {
+    T::deserialize(CompoundDeserializer::new(Cow::Borrowed(compound), depth_limit))
+}
+
+/// Deserializes the given type from an in-memory NBT compound, moving strings and other data
+/// out of the compound instead of cloning them.
+///
+/// The compound must represent the type `T` correctly, else the deserializer will return with an
+/// error.
+pub fn from_owned_compound<T: DeserializeOwned>(
+    compound:    NbtCompound,
+    depth_limit: DepthLimit,
+) -> Result<T, NbtIoError> {
+    T::deserialize(CompoundDeserializer::new(Cow::Owned(compound), depth_limit))
+}
@@ -1,4 +1,4 @@
-use std::fmt;
+use std::{fmt, str};
 use std::{cell::Cell, marker::PhantomData};
 use std::{
     fmt::{Debug, Formatter},
@@ -18,15 +18,11 @@ use crate::raw::{
     INT_ID, LIST_ID, LONG_ID, LONG_ARRAY_ID, SHORT_ID, STRING_ID, TAG_END_ID,
 };
 use super::{
-    array::{BYTE_ARRAY_NICHE, INT_ARRAY_NICHE, LONG_ARRAY_NICHE},
+    array::{BYTE_ARRAY_NICHE, BYTE_STRING_NICHE, INT_ARRAY_NICHE, LONG_ARRAY_NICHE},
     util::{DefaultSerializer, Ser},
 };
 
 
-// TODO: actually implement DepthLimit checks. I haven't managed to figure out the boundary
-// between compound or list tags and their elements, so I don't know where to add checks.
-// Some messing around with debug prints should reveal useful info, probably.
-
 // TODO: there are some things here that really, really, really need to be tested.
 // I think I trust the io and snbt code. This, not as much.
 
@@ -127,8 +123,9 @@ impl<'a, W: Write, C: TypeChecker> DefaultSerializer for SerializerImpl<'a, W, C
         len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
         self.root_name.write(self.writer, self.opts, COMPOUND_ID)?;
+        check_depth(0, self.opts)?;
         let prefix = BorrowedPrefix::new(variant);
-        SerializeCompoundEntry::new(self.writer, self.opts, 0, prefix)
+        SerializeCompoundEntry::new(self.writer, self.opts, 1, prefix)
             .serialize_seq(Some(len))
     }
 
@@ -156,10 +153,11 @@ impl<'a, W: Write, C: TypeChecker> DefaultSerializer for SerializerImpl<'a, W, C
         _len:           usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
         self.root_name.write(self.writer, self.opts, COMPOUND_ID)?;
+        check_depth(0, self.opts)?;
         raw::write_u8(self.writer, self.opts, COMPOUND_ID)?;
         raw::write_string(self.writer, self.opts, variant)?;
         // The extra closing tag is added by the SerializeStructVariant impl
-        Ok(SerializeCompound::new(self.writer, self.opts, 0))
+        Ok(SerializeCompound::new(self.writer, self.opts, 1))
     }
 
     #[inline]
@@ -366,12 +364,14 @@ where
     where
         T: Serialize + ?Sized,
     {
+        check_depth(self.current_depth, self.opts)?;
+
         match self.length.take() {
             None => value.serialize(
                 SerializeListElement::new(
                     self.writer,
                     self.opts,
-                    self.current_depth,
+                    self.current_depth + 1,
                     NoPrefix,
                     &self.type_checker,
                 )
@@ -381,7 +381,7 @@ where
                 SerializeListElement::new(
                     self.writer,
                     self.opts,
-                    self.current_depth,
+                    self.current_depth + 1,
                     LengthPrefix::new(length),
                     &self.type_checker,
                 )
@@ -665,6 +665,13 @@ where
                 self.type_checker.verify(LONG_ARRAY_ID)?;
                 self.prefix.write(self.writer, self.opts, LONG_ARRAY_ID)?;
             }
+            BYTE_STRING_NICHE => {
+                self.type_checker.verify(STRING_ID)?;
+                self.prefix.write(self.writer, self.opts, STRING_ID)?;
+                return value.serialize(
+                    SerializeByteString::new(self.writer, self.opts).into_serializer(),
+                );
+            }
             _ => return value.serialize(self.into_serializer()),
         }
         value.serialize(
@@ -685,11 +692,12 @@ where
     {
         self.type_checker.verify(COMPOUND_ID)?;
         self.prefix.write(self.writer, self.opts, COMPOUND_ID)?;
+        check_depth(self.current_depth, self.opts)?;
         value.serialize(
             SerializeCompoundEntry::<_, C, _>::new(
                 self.writer,
                 self.opts,
-                self.current_depth,
+                self.current_depth + 1,
                 BorrowedPrefix::new(variant),
             )
             .into_serializer(),
@@ -741,8 +749,9 @@ where
         self.prefix.write(self.writer, self.opts, COMPOUND_ID)?;
 
         // Write the compound
+        check_depth(self.current_depth, self.opts)?;
         let prefix = BorrowedPrefix::new(variant);
-        SerializeCompoundEntry::new(self.writer, self.opts, self.current_depth, prefix)
+        SerializeCompoundEntry::new(self.writer, self.opts, self.current_depth + 1, prefix)
             .serialize_seq(Some(len))
     }
 
@@ -776,13 +785,14 @@ where
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
         self.type_checker.verify(COMPOUND_ID)?;
         self.prefix.write(self.writer, self.opts, COMPOUND_ID)?;
+        check_depth(self.current_depth, self.opts)?;
         raw::write_u8(self.writer, self.opts, COMPOUND_ID)?;
         raw::write_string(self.writer, self.opts, variant)?;
         // The extra closing tag is added by the SerializeStructVariant impl
         Ok(SerializeCompound::new(
             self.writer,
             self.opts,
-            self.current_depth,
+            self.current_depth + 1,
         ))
     }
 
@@ -849,12 +859,13 @@ impl<W: Write, C: TypeChecker> SerializeMap for SerializeCompound<'_, W, C> {
             .key
             .take()
             .expect("serialize_value called before key was serialized.");
+        check_depth(self.current_depth, self.opts)?;
         let prefix = RawPrefix::new(key);
         value.serialize(
             SerializeCompoundEntry::<_, C, _>::new(
                 self.writer,
                 self.opts,
-                self.current_depth,
+                self.current_depth + 1,
                 prefix,
             )
             .into_serializer(),
@@ -871,12 +882,13 @@ impl<W: Write, C: TypeChecker> SerializeMap for SerializeCompound<'_, W, C> {
         K: Serialize + ?Sized,
         V: Serialize + ?Sized,
     {
+        check_depth(self.current_depth, self.opts)?;
         let prefix = BorrowedPrefix::new(key);
         value.serialize(
             SerializeCompoundEntry::<_, C, _>::new(
                 self.writer,
                 self.opts,
-                self.current_depth,
+                self.current_depth + 1,
                 prefix,
             )
             .into_serializer(),
@@ -903,12 +915,13 @@ impl<W: Write, C: TypeChecker> SerializeStruct for SerializeCompound<'_, W, C> {
     where
         T: Serialize + ?Sized,
     {
+        check_depth(self.current_depth, self.opts)?;
         let prefix = BorrowedPrefix::new(key);
         value.serialize(
             SerializeCompoundEntry::<_, C, _>::new(
                 self.writer,
                 self.opts,
-                self.current_depth,
+                self.current_depth + 1,
                 prefix,
             )
             .into_serializer(),
@@ -940,11 +953,10 @@ impl<W: Write, C: TypeChecker> SerializeStructVariant for SerializeCompound<'_,
 
     #[inline]
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        // Add an extra tag because struct variants are serialized as { name: { fields... } }
-        // self.writer.write_all(&[0, 0])?; <- not `&[0, 0, 0, 0, 0]` or just `&[0]`?
-        // TODO: this case really, really, really needs to be tested
+        // Struct variants are serialized as { name: { fields... } }, so both the compound of
+        // fields and the outer compound need to be closed.
+        raw::write_u8(self.writer, self.opts, TAG_END_ID)?;
         raw::write_u8(self.writer, self.opts, TAG_END_ID)?;
-        raw::write_usize_as_i32(self.writer, self.opts, 0)?; // not sure if this line is needed
         Ok(())
     }
 }
@@ -1114,6 +1126,12 @@ where
             LONG_ARRAY_NICHE => {
                 self.prefix.write(self.writer, self.opts, LONG_ARRAY_ID)?;
             }
+            BYTE_STRING_NICHE => {
+                self.prefix.write(self.writer, self.opts, STRING_ID)?;
+                return value.serialize(
+                    SerializeByteString::new(self.writer, self.opts).into_serializer(),
+                );
+            }
             _ => return value.serialize(self.into_serializer()),
         }
         value.serialize(
@@ -1133,11 +1151,12 @@ where
         T: Serialize + ?Sized,
     {
         self.prefix.write(self.writer, self.opts, COMPOUND_ID)?;
+        check_depth(self.current_depth, self.opts)?;
         value.serialize(
             SerializeCompoundEntry::<_, C, _>::new(
                 self.writer,
                 self.opts,
-                self.current_depth,
+                self.current_depth + 1,
                 BorrowedPrefix::new(variant),
             )
             .into_serializer(),
@@ -1182,8 +1201,9 @@ where
         len:            usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
         self.prefix.write(self.writer, self.opts, COMPOUND_ID)?;
+        check_depth(self.current_depth, self.opts)?;
         let prefix = BorrowedPrefix::new(variant);
-        SerializeCompoundEntry::new(self.writer, self.opts, self.current_depth, prefix)
+        SerializeCompoundEntry::new(self.writer, self.opts, self.current_depth + 1, prefix)
             .serialize_seq(Some(len))
     }
 
@@ -1215,13 +1235,14 @@ where
         _len:           usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
         self.prefix.write(self.writer, self.opts, COMPOUND_ID)?;
+        check_depth(self.current_depth, self.opts)?;
         raw::write_u8(self.writer, self.opts, COMPOUND_ID)?;
         raw::write_string(self.writer, self.opts, variant)?;
         // The extra closing tag is added by the SerializeStructVariant impl
         Ok(SerializeCompound::new(
             self.writer,
             self.opts,
-            self.current_depth,
+            self.current_depth + 1,
         ))
     }
 
@@ -1231,6 +1252,44 @@ where
     }
 }
 
+struct SerializeByteString<'a, W> {
+    writer: &'a mut W,
+    opts:   IoOptions,
+}
+
+impl<'a, W: Write> SerializeByteString<'a, W> {
+    #[inline]
+    fn new(writer: &'a mut W, opts: IoOptions) -> Self {
+        SerializeByteString { writer, opts }
+    }
+}
+
+impl<W: Write> DefaultSerializer for SerializeByteString<'_, W> {
+    type Error = NbtIoError;
+    type Ok    = ();
+    type SerializeMap           = Impossible<Self::Ok, Self::Error>;
+    type SerializeSeq           = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct        = Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple         = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct   = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant  = Impossible<Self::Ok, Self::Error>;
+
+    #[cold]
+    fn unimplemented(self, _ty: &'static str) -> Self::Error {
+        panic!("ByteString<T> wrapper incorrectly used on non-byte type")
+    }
+
+    #[inline]
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Self::Error> {
+        // Valid strings are written normally, in case the string encoding is not UTF-8.
+        match str::from_utf8(value) {
+            Ok(string) => raw::write_string(self.writer, self.opts, string),
+            Err(_)     => raw::write_byte_string(self.writer, self.opts, value),
+        }
+    }
+}
+
 struct SerializeKey<'a, W> {
     writer: &'a mut W,
     opts:   IoOptions,
@@ -1266,6 +1325,18 @@ impl<W: Write> DefaultSerializer for SerializeKey<'_, W> {
     }
 }
 
+/// Checks that a compound or list tag at the given depth may have elements.
+#[inline]
+fn check_depth(current_depth: u32, opts: IoOptions) -> Result<(), NbtIoError> {
+    if current_depth >= opts.depth_limit.0 {
+        Err(NbtIoError::ExceededDepthLimit {
+            limit: opts.depth_limit,
+        })
+    } else {
+        Ok(())
+    }
+}
+
 pub trait TypeChecker: Sized {
     fn new() -> Self;
 
@@ -0,0 +1,659 @@
+use std::borrow::Cow;
+
+use serde::de;
+use serde::forward_to_deserialize_any;
+use serde::de::{
+    DeserializeSeed, EnumAccess, IntoDeserializer, SeqAccess, VariantAccess, Visitor,
+    value::{BorrowedBytesDeserializer, BorrowedStrDeserializer, BytesDeserializer, MapDeserializer},
+};
+
+use crate::{io::NbtIoError, raw, settings::DepthLimit};
+use crate::raw::{BYTE_ARRAY_ID, COMPOUND_ID, INT_ARRAY_ID, LIST_ID, LONG_ARRAY_ID};
+use crate::tag::{NbtCompound, NbtTag};
+use super::array::BYTE_STRING_NICHE;
+use super::de::{TypeHintDeserializer, TypeHintDispatchState};
+
+
+/// Deserializes values from an in-memory NBT tag, which is either borrowed (allowing strings and
+/// byte strings to be borrowed from the tag) or owned (allowing them to be moved out of it).
+pub(super) struct TagDeserializer<'de> {
+    tag:           Cow<'de, NbtTag>,
+    current_depth: u32,
+    depth_limit:   DepthLimit,
+}
+
+impl<'de> TagDeserializer<'de> {
+    #[inline]
+    pub(super) fn new(tag: Cow<'de, NbtTag>, depth_limit: DepthLimit) -> Self {
+        Self::at_depth(tag, 0, depth_limit)
+    }
+
+    #[inline]
+    fn at_depth(tag: Cow<'de, NbtTag>, current_depth: u32, depth_limit: DepthLimit) -> Self {
+        Self {
+            tag,
+            current_depth,
+            depth_limit,
+        }
+    }
+
+    /// Visits the elements of an array or list tag.
+    fn visit_seq<V>(self, visitor: V) -> Result<V::Value, NbtIoError>
+    where
+        V: Visitor<'de>,
+    {
+        let (depth, limit) = (self.current_depth, self.depth_limit);
+        // Array elements are not nested tags, so their depth does not matter.
+        let element = |tag| Self::at_depth(Cow::Owned(tag), depth, limit);
+
+        match self.tag {
+            Cow::Borrowed(NbtTag::ByteArray(array)) => visitor.visit_seq(
+                TagSeqAccess::<_, BYTE_ARRAY_ID>::new(
+                    array.iter().map(|&value| element(NbtTag::Byte(value))),
+                ),
+            ),
+            Cow::Owned(NbtTag::ByteArray(array)) => visitor.visit_seq(
+                TagSeqAccess::<_, BYTE_ARRAY_ID>::new(
+                    array.into_iter().map(|value| element(NbtTag::Byte(value))),
+                ),
+            ),
+            Cow::Borrowed(NbtTag::IntArray(array)) => visitor.visit_seq(
+                TagSeqAccess::<_, INT_ARRAY_ID>::new(
+                    array.iter().map(|&value| element(NbtTag::Int(value))),
+                ),
+            ),
+            Cow::Owned(NbtTag::IntArray(array)) => visitor.visit_seq(
+                TagSeqAccess::<_, INT_ARRAY_ID>::new(
+                    array.into_iter().map(|value| element(NbtTag::Int(value))),
+                ),
+            ),
+            Cow::Borrowed(NbtTag::LongArray(array)) => visitor.visit_seq(
+                TagSeqAccess::<_, LONG_ARRAY_ID>::new(
+                    array.iter().map(|&value| element(NbtTag::Long(value))),
+                ),
+            ),
+            Cow::Owned(NbtTag::LongArray(array)) => visitor.visit_seq(
+                TagSeqAccess::<_, LONG_ARRAY_ID>::new(
+                    array.into_iter().map(|value| element(NbtTag::Long(value))),
+                ),
+            ),
+            Cow::Borrowed(NbtTag::List(list)) => {
+                check_depth(depth, limit, !list.is_empty())?;
+                visitor.visit_seq(TagSeqAccess::<_, LIST_ID>::new(
+                    list.iter().map(|tag| Self::at_depth(Cow::Borrowed(tag), depth + 1, limit)),
+                ))
+            }
+            Cow::Owned(NbtTag::List(list)) => {
+                check_depth(depth, limit, !list.is_empty())?;
+                visitor.visit_seq(TagSeqAccess::<_, LIST_ID>::new(
+                    list.into_iter().map(|tag| Self::at_depth(Cow::Owned(tag), depth + 1, limit)),
+                ))
+            }
+            _ => Err(NbtIoError::ExpectedSeq),
+        }
+    }
+
+    /// Converts a compound tag into a deserializer for the compound, or returns an error if
+    /// the tag is not a compound.
+    fn into_compound(self) -> Result<CompoundDeserializer<'de>, NbtIoError> {
+        let compound = match self.tag {
+            Cow::Borrowed(NbtTag::Compound(compound)) => Cow::Borrowed(compound),
+            Cow::Owned(NbtTag::Compound(compound))    => Cow::Owned(compound),
+            tag => {
+                return Err(NbtIoError::TagTypeMismatch {
+                    expected: COMPOUND_ID,
+                    found:    raw::id_for_tag(Some(&tag)),
+                });
+            }
+        };
+
+        Ok(CompoundDeserializer::at_depth(compound, self.current_depth, self.depth_limit))
+    }
+
+    /// Visits the bytes of a `String` or `ByteString` tag.
+    fn visit_byte_string<V>(self, visitor: V) -> Result<V::Value, NbtIoError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.tag {
+            Cow::Borrowed(NbtTag::String(string)) => visitor.visit_newtype_struct(
+                BorrowedBytesDeserializer::new(string.as_bytes()),
+            ),
+            Cow::Borrowed(NbtTag::ByteString(bytes)) => visitor.visit_newtype_struct(
+                BorrowedBytesDeserializer::new(bytes),
+            ),
+            Cow::Owned(NbtTag::String(string)) => visitor.visit_newtype_struct(
+                BytesDeserializer::new(string.as_bytes()),
+            ),
+            Cow::Owned(NbtTag::ByteString(bytes)) => visitor.visit_newtype_struct(
+                BytesDeserializer::new(&bytes),
+            ),
+            _ => visitor.visit_newtype_struct(self),
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for TagDeserializer<'de> {
+    type Error = NbtIoError;
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u16 u32 u64 u128 char f32 f64 str string identifier
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.tag {
+            Cow::Borrowed(NbtTag::String(string)) => visitor.visit_borrowed_str(string),
+            Cow::Owned(NbtTag::String(string))    => visitor.visit_string(string),
+            _ => match *self.tag {
+                NbtTag::Byte(value)   => visitor.visit_i8(value),
+                NbtTag::Short(value)  => visitor.visit_i16(value),
+                NbtTag::Int(value)    => visitor.visit_i32(value),
+                NbtTag::Long(value)   => visitor.visit_i64(value),
+                NbtTag::Float(value)  => visitor.visit_f32(value),
+                NbtTag::Double(value) => visitor.visit_f64(value),
+                NbtTag::ByteString(_) => self.visit_byte_string(visitor),
+                NbtTag::Compound(_)   => self.into_compound()?.visit_map(visitor),
+                _                     => self.visit_seq(visitor),
+            },
+        }
+    }
+
+    #[inline]
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match *self.tag {
+            NbtTag::Byte(value) => visitor.visit_bool(value != 0),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    #[inline]
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match *self.tag {
+            NbtTag::Byte(value) => visitor.visit_u8(value as u8),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    #[inline]
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.tag {
+            Cow::Borrowed(NbtTag::ByteArray(array)) => {
+                visitor.visit_borrowed_bytes(raw::cast_bytes_to_unsigned(array))
+            }
+            Cow::Owned(NbtTag::ByteArray(array)) => {
+                visitor.visit_byte_buf(raw::cast_byte_buf_to_unsigned(array))
+            }
+            Cow::Borrowed(NbtTag::ByteString(bytes)) => visitor.visit_borrowed_bytes(bytes),
+            Cow::Owned(NbtTag::ByteString(bytes))    => visitor.visit_byte_buf(bytes),
+            tag => Self::at_depth(tag, self.current_depth, self.depth_limit)
+                .deserialize_any(visitor),
+        }
+    }
+
+    #[inline]
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    #[inline]
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    #[inline]
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    #[inline]
+    fn deserialize_unit_struct<V>(
+        self,
+        _name:   &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_unit(visitor)
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V>(
+        self,
+        name:    &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if name == BYTE_STRING_NICHE {
+            self.visit_byte_string(visitor)
+        } else {
+            visitor.visit_newtype_struct(self)
+        }
+    }
+
+    #[inline]
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.visit_seq(visitor)
+    }
+
+    #[inline]
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.visit_seq(visitor)
+    }
+
+    #[inline]
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name:   &'static str,
+        _len:    usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.visit_seq(visitor)
+    }
+
+    #[inline]
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.into_compound()?.visit_map(visitor)
+    }
+
+    #[inline]
+    fn deserialize_struct<V>(
+        self,
+        _name:   &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name:    &'static str,
+        variants: &'static [&'static str],
+        visitor:  V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        // Unit variants
+        let index = match *self.tag {
+            NbtTag::Byte(value)  => Some(i64::from(value)),
+            NbtTag::Short(value) => Some(i64::from(value)),
+            NbtTag::Int(value)   => Some(i64::from(value)),
+            _ => None,
+        };
+        if let Some(index) = index {
+            let variant = usize::try_from(index)
+                .ok()
+                .and_then(|index| variants.get(index))
+                .ok_or(NbtIoError::InvalidEnumVariant)?;
+            return visitor.visit_enum((*variant).into_deserializer());
+        }
+
+        match self.tag {
+            Cow::Borrowed(NbtTag::String(string)) => {
+                visitor.visit_enum(BorrowedStrDeserializer::new(string))
+            }
+            Cow::Owned(NbtTag::String(string)) => visitor.visit_enum(string.into_deserializer()),
+            // Newtype, tuple, and struct variants
+            tag => Self::at_depth(tag, self.current_depth, self.depth_limit)
+                .into_compound()
+                .map_err(|_err| NbtIoError::ExpectedEnum)?
+                .visit_enum(visitor),
+        }
+    }
+
+    #[inline]
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<'de> IntoDeserializer<'de, NbtIoError> for TagDeserializer<'de> {
+    type Deserializer = Self;
+
+    #[inline]
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> VariantAccess<'de> for TagDeserializer<'de> {
+    type Error = NbtIoError;
+
+    /// Unit variants are stored as integers or strings, not compounds.
+    #[cold]
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Err(NbtIoError::ExpectedEnum)
+    }
+
+    #[inline]
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    #[inline]
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.visit_seq(visitor)
+    }
+
+    #[inline]
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.into_compound()?.visit_map(visitor)
+    }
+}
+
+/// Deserializes values from an in-memory NBT compound. Similar to the binary [`Deserializer`],
+/// the compound may represent a struct, map, or non-unit enum variant.
+///
+/// [`Deserializer`]: super::Deserializer
+pub(super) struct CompoundDeserializer<'de> {
+    compound:      Cow<'de, NbtCompound>,
+    current_depth: u32,
+    depth_limit:   DepthLimit,
+}
+
+impl<'de> CompoundDeserializer<'de> {
+    #[inline]
+    pub(super) fn new(compound: Cow<'de, NbtCompound>, depth_limit: DepthLimit) -> Self {
+        Self::at_depth(compound, 0, depth_limit)
+    }
+
+    #[inline]
+    fn at_depth(
+        compound:      Cow<'de, NbtCompound>,
+        current_depth: u32,
+        depth_limit:   DepthLimit,
+    ) -> Self {
+        Self {
+            compound,
+            current_depth,
+            depth_limit,
+        }
+    }
+
+    fn visit_map<V>(self, visitor: V) -> Result<V::Value, NbtIoError>
+    where
+        V: Visitor<'de>,
+    {
+        let (depth, limit) = (self.current_depth + 1, self.depth_limit);
+        check_depth(self.current_depth, limit, !self.compound.is_empty())?;
+
+        match self.compound {
+            Cow::Borrowed(compound) => {
+                let mut map = MapDeserializer::new(compound.iter().map(|(key, tag)| (
+                    TagKeyDeserializer(Cow::Borrowed(key.as_str())),
+                    TagDeserializer::at_depth(Cow::Borrowed(tag), depth, limit),
+                )));
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+            Cow::Owned(compound) => {
+                let mut map = MapDeserializer::new(compound.into_iter().map(|(key, tag)| (
+                    TagKeyDeserializer(Cow::Owned(key)),
+                    TagDeserializer::at_depth(Cow::Owned(tag), depth, limit),
+                )));
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    /// Visits a newtype, tuple, or struct variant, stored as a compound whose only key is the
+    /// variant's name.
+    fn visit_enum<V>(self, visitor: V) -> Result<V::Value, NbtIoError>
+    where
+        V: Visitor<'de>,
+    {
+        let (depth, limit) = (self.current_depth + 1, self.depth_limit);
+        if self.compound.len() != 1 {
+            return Err(NbtIoError::ExpectedEnum);
+        }
+        check_depth(self.current_depth, limit, true)?;
+
+        let entry = match self.compound {
+            Cow::Borrowed(compound) => compound.iter().next().map(|(key, tag)| (
+                Cow::Borrowed(key.as_str()),
+                TagDeserializer::at_depth(Cow::Borrowed(tag), depth, limit),
+            )),
+            Cow::Owned(compound) => compound.into_iter().next().map(|(key, tag)| (
+                Cow::Owned(key),
+                TagDeserializer::at_depth(Cow::Owned(tag), depth, limit),
+            )),
+        };
+        let (variant, value) = entry.ok_or(NbtIoError::ExpectedEnum)?;
+
+        visitor.visit_enum(TagEnumAccess { variant, value })
+    }
+}
+
+impl<'de> de::Deserializer<'de> for CompoundDeserializer<'de> {
+    type Error = NbtIoError;
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+
+    #[inline]
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.visit_map(visitor)
+    }
+
+    #[inline]
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name:   &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    #[inline]
+    fn deserialize_enum<V>(
+        self,
+        _name:     &'static str,
+        _variants: &'static [&'static str],
+        visitor:   V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.visit_enum(visitor)
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+struct TagEnumAccess<'de> {
+    variant: Cow<'de, str>,
+    value:   TagDeserializer<'de>,
+}
+
+impl<'de> EnumAccess<'de> for TagEnumAccess<'de> {
+    type Error   = NbtIoError;
+    type Variant = TagDeserializer<'de>;
+
+    #[inline]
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        Ok((seed.deserialize(TagKeyDeserializer(self.variant))?, self.value))
+    }
+}
+
+/// Elements of an array or list, followed by a type hint indicating which kind of tag the
+/// elements came from. See [`TypeHint`].
+///
+/// [`TypeHint`]: super::TypeHint
+struct TagSeqAccess<I, const LIST_ID: u8> {
+    elements:       I,
+    dispatch_state: TypeHintDispatchState,
+}
+
+impl<I, const LIST_ID: u8> TagSeqAccess<I, LIST_ID> {
+    #[inline]
+    fn new(elements: I) -> Self {
+        Self {
+            elements,
+            dispatch_state: TypeHintDispatchState::Waiting,
+        }
+    }
+}
+
+impl<'de, I, const LIST_ID: u8> SeqAccess<'de> for TagSeqAccess<I, LIST_ID>
+where
+    I: ExactSizeIterator<Item = TagDeserializer<'de>>,
+{
+    type Error = NbtIoError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if let Some(element) = self.elements.next() {
+            return seed.deserialize(element).map(Some);
+        }
+
+        // If this method gets called again after the end, we'll deserialize a type hint
+        match self.dispatch_state {
+            TypeHintDispatchState::Waiting => {
+                self.dispatch_state = TypeHintDispatchState::Ready;
+                Ok(None)
+            }
+            TypeHintDispatchState::Ready => {
+                self.dispatch_state = TypeHintDispatchState::Sent;
+                Ok(seed.deserialize(TypeHintDeserializer::<LIST_ID>).ok())
+            }
+            TypeHintDispatchState::Sent => Ok(None),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.elements.len())
+    }
+}
+
+/// Deserializes a compound key or enum variant name, which may be borrowed.
+struct TagKeyDeserializer<'de>(Cow<'de, str>);
+
+impl<'de> de::Deserializer<'de> for TagKeyDeserializer<'de> {
+    type Error = NbtIoError;
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+
+    #[inline]
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            Cow::Borrowed(key) => visitor.visit_borrowed_str(key),
+            Cow::Owned(key)    => visitor.visit_string(key),
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, NbtIoError> for TagKeyDeserializer<'de> {
+    type Deserializer = Self;
+
+    #[inline]
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+/// Checks that a compound or list tag at the given depth may have elements.
+#[inline]
+fn check_depth(current_depth: u32, depth_limit: DepthLimit, nonempty: bool) -> Result<(), NbtIoError> {
+    if nonempty && current_depth >= depth_limit.0 {
+        Err(NbtIoError::ExceededDepthLimit { limit: depth_limit })
+    } else {
+        Ok(())
+    }
+}
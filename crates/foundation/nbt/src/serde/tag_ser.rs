@@ -0,0 +1,723 @@
+use serde::Serialize;
+use serde::ser::{
+    Impossible, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+    SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+};
+
+use crate::{io::NbtIoError, raw, settings::DepthLimit};
+use crate::raw::{BYTE_ID, INT_ID, LONG_ID};
+use crate::tag::{NbtCompound, NbtList, NbtTag};
+use super::util::DefaultSerializer;
+use super::array::{BYTE_ARRAY_NICHE, BYTE_STRING_NICHE, INT_ARRAY_NICHE, LONG_ARRAY_NICHE};
+
+
+/// Serializes values into in-memory NBT tags.
+///
+/// Values which do not produce a tag, like `None` or `()`, result in `Ok(None)`, and are omitted
+/// from compounds. Such values are not permitted in lists.
+pub(super) struct TagSerializer {
+    current_depth: u32,
+    depth_limit:   DepthLimit,
+    in_list:       bool,
+}
+
+impl TagSerializer {
+    #[inline]
+    pub(super) fn new(depth_limit: DepthLimit) -> Self {
+        Self {
+            current_depth: 0,
+            depth_limit,
+            in_list: false,
+        }
+    }
+
+    #[inline]
+    fn array_element() -> Self {
+        Self {
+            current_depth: 0,
+            depth_limit:   DepthLimit::default(),
+            in_list:       true,
+        }
+    }
+
+    #[inline]
+    fn nested(&self, in_list: bool) -> Self {
+        Self {
+            current_depth: self.current_depth + 1,
+            depth_limit:   self.depth_limit,
+            in_list,
+        }
+    }
+
+    /// Checks that the compound or list tag being serialized may have elements.
+    #[inline]
+    fn check_depth(&self) -> Result<(), NbtIoError> {
+        if self.current_depth >= self.depth_limit.0 {
+            Err(NbtIoError::ExceededDepthLimit {
+                limit: self.depth_limit,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Wraps the tag of an enum variant's contents in a compound with the variant's name as its
+    /// only key.
+    fn wrap_variant(variant: &'static str, tag: NbtTag) -> NbtTag {
+        let mut compound = NbtCompound::with_capacity(1);
+        compound.insert(variant.to_owned(), tag);
+        NbtTag::Compound(compound)
+    }
+}
+
+impl DefaultSerializer for TagSerializer {
+    type Error = NbtIoError;
+    type Ok    = Option<NbtTag>;
+    type SerializeMap           = SerializeTagCompound;
+    type SerializeSeq           = SerializeTagList;
+    type SerializeStruct        = SerializeTagCompound;
+    type SerializeStructVariant = SerializeTagCompound;
+    type SerializeTuple         = SerializeTagList;
+    type SerializeTupleStruct   = SerializeTagList;
+    type SerializeTupleVariant  = SerializeTagList;
+
+    #[inline]
+    fn unimplemented(self, ty: &'static str) -> Self::Error {
+        NbtIoError::UnsupportedType(ty)
+    }
+
+    #[inline]
+    fn serialize_bool(self, value: bool) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(NbtTag::Byte(i8::from(value))))
+    }
+
+    #[inline]
+    fn serialize_i8(self, value: i8) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(NbtTag::Byte(value)))
+    }
+
+    #[inline]
+    fn serialize_u8(self, value: u8) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(NbtTag::Byte(value as i8)))
+    }
+
+    #[inline]
+    fn serialize_i16(self, value: i16) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(NbtTag::Short(value)))
+    }
+
+    #[inline]
+    fn serialize_i32(self, value: i32) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(NbtTag::Int(value)))
+    }
+
+    #[inline]
+    fn serialize_i64(self, value: i64) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(NbtTag::Long(value)))
+    }
+
+    #[inline]
+    fn serialize_f32(self, value: f32) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(NbtTag::Float(value)))
+    }
+
+    #[inline]
+    fn serialize_f64(self, value: f64) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(NbtTag::Double(value)))
+    }
+
+    #[inline]
+    fn serialize_str(self, value: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(NbtTag::String(value.to_owned())))
+    }
+
+    #[inline]
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(NbtTag::ByteArray(raw::cast_byte_buf_to_signed(value.to_vec()))))
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        if self.in_list {
+            Err(NbtIoError::OptionInList)
+        } else {
+            Ok(None)
+        }
+    }
+
+    #[inline]
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        if self.in_list {
+            Err(NbtIoError::OptionInList)
+        } else {
+            value.serialize(self.into_serializer())
+        }
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        if self.in_list {
+            Err(NbtIoError::UnsupportedType("unit"))
+        } else {
+            Ok(None)
+        }
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.serialize_unit()
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        _name:         &'static str,
+        variant_index: u32,
+        _variant:      &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(NbtTag::Int(variant_index as i32)))
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T>(
+        self,
+        name:  &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let array_type = match name {
+            BYTE_ARRAY_NICHE  => ArrayType::Byte,
+            INT_ARRAY_NICHE   => ArrayType::Int,
+            LONG_ARRAY_NICHE  => ArrayType::Long,
+            BYTE_STRING_NICHE => {
+                return value.serialize(SerializeTagByteString.into_serializer()).map(Some);
+            }
+            _ => return value.serialize(self.into_serializer()),
+        };
+
+        value
+            .serialize(SerializeTagArray::new(array_type).into_serializer())
+            .map(Some)
+    }
+
+    #[inline]
+    fn serialize_newtype_variant<T>(
+        self,
+        _name:          &'static str,
+        _variant_index: u32,
+        variant:        &'static str,
+        value:          &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.check_depth()?;
+        let tag = value.serialize(self.nested(false).into_serializer())?;
+
+        let mut compound = NbtCompound::with_capacity(1);
+        if let Some(tag) = tag {
+            compound.insert(variant.to_owned(), tag);
+        }
+        Ok(Some(NbtTag::Compound(compound)))
+    }
+
+    #[inline]
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(SerializeTagList::new(self, len, None))
+    }
+
+    #[inline]
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len:   usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(
+        self,
+        _name:          &'static str,
+        _variant_index: u32,
+        variant:        &'static str,
+        len:            usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        self.check_depth()?;
+        Ok(SerializeTagList::new(self.nested(false), Some(len), Some(variant)))
+    }
+
+    #[inline]
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(SerializeTagCompound::new(self, len, None))
+    }
+
+    #[inline]
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len:   usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+
+    #[inline]
+    fn serialize_struct_variant(
+        self,
+        _name:          &'static str,
+        _variant_index: u32,
+        variant:        &'static str,
+        len:            usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.check_depth()?;
+        Ok(SerializeTagCompound::new(self.nested(false), Some(len), Some(variant)))
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+pub(super) struct SerializeTagList {
+    serializer: TagSerializer,
+    list:       NbtList,
+    /// The variant to wrap the list in, for tuple variants.
+    variant:    Option<&'static str>,
+}
+
+impl SerializeTagList {
+    #[inline]
+    fn new(serializer: TagSerializer, len: Option<usize>, variant: Option<&'static str>) -> Self {
+        Self {
+            serializer,
+            list: NbtList::with_capacity(len.unwrap_or(0)),
+            variant,
+        }
+    }
+}
+
+impl SerializeSeq for SerializeTagList {
+    type Error = NbtIoError;
+    type Ok    = Option<NbtTag>;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.serializer.check_depth()?;
+
+        let tag = value
+            .serialize(self.serializer.nested(true).into_serializer())?
+            .ok_or(NbtIoError::UnsupportedType("unit"))?;
+
+        let list_type = raw::id_for_tag(self.list.0.first());
+        let encountered_type = raw::id_for_tag(Some(&tag));
+        if !self.list.is_empty() && list_type != encountered_type {
+            return Err(NbtIoError::NonHomogenousList {
+                list_type,
+                encountered_type,
+            });
+        }
+
+        self.list.push(tag);
+        Ok(())
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        let tag = NbtTag::List(self.list);
+        match self.variant {
+            Some(variant) => Ok(Some(TagSerializer::wrap_variant(variant, tag))),
+            None          => Ok(Some(tag)),
+        }
+    }
+}
+
+impl SerializeTuple for SerializeTagList {
+    type Error = NbtIoError;
+    type Ok    = Option<NbtTag>;
+
+    #[inline]
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        <Self as SerializeSeq>::serialize_element(self, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        <Self as SerializeSeq>::end(self)
+    }
+}
+
+impl SerializeTupleStruct for SerializeTagList {
+    type Error = NbtIoError;
+    type Ok    = Option<NbtTag>;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        <Self as SerializeSeq>::serialize_element(self, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        <Self as SerializeSeq>::end(self)
+    }
+}
+
+impl SerializeTupleVariant for SerializeTagList {
+    type Error = NbtIoError;
+    type Ok    = Option<NbtTag>;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        <Self as SerializeSeq>::serialize_element(self, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        <Self as SerializeSeq>::end(self)
+    }
+}
+
+pub(super) struct SerializeTagCompound {
+    serializer: TagSerializer,
+    compound:   NbtCompound,
+    key:        Option<String>,
+    /// The variant to wrap the compound in, for struct variants.
+    variant:    Option<&'static str>,
+}
+
+impl SerializeTagCompound {
+    #[inline]
+    fn new(serializer: TagSerializer, len: Option<usize>, variant: Option<&'static str>) -> Self {
+        Self {
+            serializer,
+            compound: NbtCompound::with_capacity(len.unwrap_or(0)),
+            key: None,
+            variant,
+        }
+    }
+
+    fn insert<T>(&mut self, key: String, value: &T) -> Result<(), NbtIoError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.serializer.check_depth()?;
+
+        if let Some(tag) = value.serialize(self.serializer.nested(false).into_serializer())? {
+            self.compound.insert(key, tag);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn finish(self) -> NbtTag {
+        let tag = NbtTag::Compound(self.compound);
+        match self.variant {
+            Some(variant) => TagSerializer::wrap_variant(variant, tag),
+            None          => tag,
+        }
+    }
+}
+
+impl SerializeMap for SerializeTagCompound {
+    type Error = NbtIoError;
+    type Ok    = Option<NbtTag>;
+
+    #[inline]
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.key = Some(key.serialize(SerializeTagKey.into_serializer())?);
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self.key.take().ok_or(NbtIoError::InvalidKey)?;
+        self.insert(key, value)
+    }
+
+    #[inline]
+    fn serialize_entry<K, V>(&mut self, key: &K, value: &V) -> Result<(), Self::Error>
+    where
+        K: Serialize + ?Sized,
+        V: Serialize + ?Sized,
+    {
+        let key = key.serialize(SerializeTagKey.into_serializer())?;
+        self.insert(key, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(self.finish()))
+    }
+}
+
+impl SerializeStruct for SerializeTagCompound {
+    type Error = NbtIoError;
+    type Ok    = Option<NbtTag>;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.insert(key.to_owned(), value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(self.finish()))
+    }
+}
+
+impl SerializeStructVariant for SerializeTagCompound {
+    type Error = NbtIoError;
+    type Ok    = Option<NbtTag>;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.insert(key.to_owned(), value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(self.finish()))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ArrayType {
+    Byte,
+    Int,
+    Long,
+}
+
+/// Serializes the contents of an `Array<T>` wrapper into a `ByteArray`, `IntArray`, or
+/// `LongArray` tag.
+struct SerializeTagArray {
+    array_type: ArrayType,
+    tag:        NbtTag,
+}
+
+impl SerializeTagArray {
+    #[inline]
+    fn new(array_type: ArrayType) -> Self {
+        let tag = match array_type {
+            ArrayType::Byte => NbtTag::ByteArray(Vec::new()),
+            ArrayType::Int  => NbtTag::IntArray(Vec::new()),
+            ArrayType::Long => NbtTag::LongArray(Vec::new()),
+        };
+        Self { array_type, tag }
+    }
+}
+
+impl DefaultSerializer for SerializeTagArray {
+    type Error = NbtIoError;
+    type Ok    = NbtTag;
+    type SerializeMap           = Impossible<Self::Ok, Self::Error>;
+    type SerializeSeq           = Self;
+    type SerializeStruct        = Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple         = Self::SerializeSeq;
+    type SerializeTupleStruct   = Self::SerializeSeq;
+    type SerializeTupleVariant  = Impossible<Self::Ok, Self::Error>;
+
+    #[cold]
+    fn unimplemented(self, _ty: &'static str) -> Self::Error {
+        panic!("Array<T> wrapper incorrectly used on non-sequential type")
+    }
+
+    #[inline]
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Self::Error> {
+        match self.array_type {
+            ArrayType::Byte => Ok(NbtTag::ByteArray(raw::cast_byte_buf_to_signed(value.to_vec()))),
+            ArrayType::Int  => Err(NbtIoError::TagTypeMismatch {
+                expected: INT_ID,
+                found:    BYTE_ID,
+            }),
+            ArrayType::Long => Err(NbtIoError::TagTypeMismatch {
+                expected: LONG_ID,
+                found:    BYTE_ID,
+            }),
+        }
+    }
+
+    #[inline]
+    fn serialize_seq(mut self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        let len = len.unwrap_or(0);
+        match &mut self.tag {
+            NbtTag::ByteArray(array) => array.reserve(len),
+            NbtTag::IntArray(array)  => array.reserve(len),
+            NbtTag::LongArray(array) => array.reserve(len),
+            _ => {}
+        }
+        Ok(self)
+    }
+
+    #[inline]
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len:   usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+}
+
+impl SerializeSeq for SerializeTagArray {
+    type Error = NbtIoError;
+    type Ok    = NbtTag;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let element = value
+            .serialize(TagSerializer::array_element().into_serializer())?
+            .ok_or(NbtIoError::UnsupportedType("unit"))?;
+
+        match (&mut self.tag, element) {
+            (NbtTag::ByteArray(array), NbtTag::Byte(value)) => array.push(value),
+            (NbtTag::IntArray(array),  NbtTag::Int(value))  => array.push(value),
+            (NbtTag::LongArray(array), NbtTag::Long(value)) => array.push(value),
+            (_, element) => {
+                return Err(NbtIoError::TagTypeMismatch {
+                    expected: match self.array_type {
+                        ArrayType::Byte => BYTE_ID,
+                        ArrayType::Int  => INT_ID,
+                        ArrayType::Long => LONG_ID,
+                    },
+                    found: raw::id_for_tag(Some(&element)),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.tag)
+    }
+}
+
+impl SerializeTuple for SerializeTagArray {
+    type Error = NbtIoError;
+    type Ok    = NbtTag;
+
+    #[inline]
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        <Self as SerializeSeq>::serialize_element(self, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        <Self as SerializeSeq>::end(self)
+    }
+}
+
+impl SerializeTupleStruct for SerializeTagArray {
+    type Error = NbtIoError;
+    type Ok    = NbtTag;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        <Self as SerializeSeq>::serialize_element(self, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        <Self as SerializeSeq>::end(self)
+    }
+}
+
+/// Serializes the contents of a `ByteString<T>` wrapper into a `String` tag if the bytes are
+/// valid UTF-8, and a `ByteString` tag otherwise, as when reading binary NBT.
+struct SerializeTagByteString;
+
+impl DefaultSerializer for SerializeTagByteString {
+    type Error = NbtIoError;
+    type Ok    = NbtTag;
+    type SerializeMap           = Impossible<Self::Ok, Self::Error>;
+    type SerializeSeq           = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct        = Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple         = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct   = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant  = Impossible<Self::Ok, Self::Error>;
+
+    #[cold]
+    fn unimplemented(self, _ty: &'static str) -> Self::Error {
+        panic!("ByteString<T> wrapper incorrectly used on non-byte type")
+    }
+
+    #[inline]
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Self::Error> {
+        match String::from_utf8(value.to_vec()) {
+            Ok(string) => Ok(NbtTag::String(string)),
+            Err(err)   => Ok(NbtTag::ByteString(err.into_bytes())),
+        }
+    }
+}
+
+struct SerializeTagKey;
+
+impl DefaultSerializer for SerializeTagKey {
+    type Error = NbtIoError;
+    type Ok    = String;
+    type SerializeMap           = Impossible<Self::Ok, Self::Error>;
+    type SerializeSeq           = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct        = Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple         = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct   = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant  = Impossible<Self::Ok, Self::Error>;
+
+    #[inline]
+    fn unimplemented(self, _ty: &'static str) -> Self::Error {
+        NbtIoError::InvalidKey
+    }
+
+    #[inline]
+    fn serialize_str(self, value: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(value.to_owned())
+    }
+}
@@ -48,21 +48,64 @@ impl DepthLimit {
     }
 }
 
-// This section could be expanded to add one or two more limits on parsing and writing data,
-// in particular in regards to length (of files/bytes/strings, multicharacter tokens).
-// It wouldn't be too hard to give functions here data that results in too much memory allocation
-// and ultimately a crash.
-// Is that truly important to fix? Compared to functional tasks, no,
-// and it would be time-consuming to implement, so it's left as an idea.
-// And too much heap memory usage isn't even likely to be *too* much of a problem
-// thanks to swap space, and catch_unwind is there if needed (if panic isn't set to abort).
-// Stack memory usage is worse, we can limit that with DepthLimit.
-// Might need to place a TotalLengthLimit on parsing files, though, or place a limit
-// on how many items can be in a compound tag or list tag and have an option to ignore ones
-// that are too long, in case a user is trying to handle some really bad NBT data,
-// trying to delete parts of it. Likewise, perhaps this library could use features to try to
-// recover corrupt NBT byte data as much as possible...
-// but until that becomes a problem someone has, it doesn't seem worth tackling.
+/// A limit on the memory which may be allocated while reading NBT or SNBT data, to protect
+/// against corrupted or malicious data whose length prefixes request enormous allocations.
+///
+/// Both limits apply to the total over a single read or parse. When reading binary NBT data,
+/// allocations are checked against the limits before they are made. When parsing SNBT, each
+/// tag is instead charged once it has been parsed; a tag parsed from SNBT is at most a small
+/// multiple of the size of its SNBT text, which is already in memory.
+///
+/// The number of bytes is an estimate of the memory used by the resulting tags: strings and
+/// arrays count their contents, and every tag in a list or compound counts the size of an
+/// [`NbtTag`]. Every tag, and every element of an array, counts as one element.
+///
+/// By default, there is no limit.
+///
+/// [`NbtTag`]: crate::tag::NbtTag
+#[cfg_attr(feature = "derive_serde",    derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "derive_standard", derive(PartialEq, Eq, PartialOrd, Ord, Hash))]
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryBudget {
+    /// The maximum number of bytes which may be allocated.
+    pub max_bytes:    usize,
+    /// The maximum number of tags and array elements which may be read.
+    pub max_elements: usize,
+}
+
+impl Default for MemoryBudget {
+    /// No limit on allocations.
+    #[inline]
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+impl MemoryBudget {
+    #[inline]
+    pub fn new(max_bytes: usize, max_elements: usize) -> Self {
+        Self { max_bytes, max_elements }
+    }
+
+    /// No limit on allocations.
+    #[inline]
+    pub fn unlimited() -> Self {
+        Self {
+            max_bytes:    usize::MAX,
+            max_elements: usize::MAX,
+        }
+    }
+
+    /// A budget suitable for untrusted input, such as data received over a network or values
+    /// read from a possibly-corrupted database: 64 MiB and 4 million elements.
+    #[inline]
+    pub fn untrusted() -> Self {
+        Self {
+            max_bytes:    64 << 20,
+            max_elements: 4 << 20,
+        }
+    }
+}
 
 
 // ================================
@@ -103,10 +146,28 @@ pub struct IoOptions {
     ///
     /// Default: false for Java, true for Bedrock.
     pub enable_byte_strings: bool,
+    /// Whether compound elements of lists whose only key is the empty string `""` should be
+    /// unwrapped into the value of that key when read, as done by Java 1.21.5 and later
+    /// to store heterogenous lists. When writing, compounds of that form are wrapped again
+    /// so that they are read back unchanged.
+    ///
+    /// Heterogenous lists are only written, by wrapping their elements, if this is true;
+    /// otherwise, they could not be read back unchanged, and writing them fails with
+    /// [`NbtIoError::NonHomogenousList`]. See [`NbtList::wrap_heterogenous`].
+    ///
+    /// Default: true for Java, false for Bedrock.
+    ///
+    /// [`NbtIoError::NonHomogenousList`]: crate::io::NbtIoError::NonHomogenousList
+    /// [`NbtList::wrap_heterogenous`]: crate::tag::NbtList::wrap_heterogenous
+    pub unwrap_heterogenous_lists: bool,
     /// The maximum depth that NBT compounds and tags can be recursively nested.
     ///
     /// Default: 512, the limit used by Minecraft.
     pub depth_limit: DepthLimit,
+    /// A limit on the memory allocated while reading NBT data. Has no effect on writing.
+    ///
+    /// Default: no limit.
+    pub memory_budget: MemoryBudget,
 }
 
 impl IoOptions {
@@ -114,11 +175,13 @@ impl IoOptions {
     #[inline]
     pub fn java() -> Self {
         Self {
-            endianness:          Endianness::BigEndian,
-            compression:         NbtCompression::GzipCompressed,
-            string_encoding:     StringEncoding::Cesu8,
-            enable_byte_strings: false,
-            depth_limit:         DepthLimit::default(),
+            endianness:                Endianness::BigEndian,
+            compression:               NbtCompression::GzipCompressed,
+            string_encoding:           StringEncoding::Cesu8,
+            enable_byte_strings:       false,
+            unwrap_heterogenous_lists: true,
+            depth_limit:               DepthLimit::default(),
+            memory_budget:             MemoryBudget::default(),
         }
     }
 
@@ -135,11 +198,13 @@ impl IoOptions {
     #[inline]
     pub fn bedrock() -> Self {
         Self {
-            endianness:          Endianness::LittleEndian,
-            compression:         NbtCompression::GzipCompressed,
-            string_encoding:     StringEncoding::Utf8,
-            enable_byte_strings: true,
-            depth_limit:         DepthLimit::default(),
+            endianness:                Endianness::LittleEndian,
+            compression:               NbtCompression::GzipCompressed,
+            string_encoding:           StringEncoding::Utf8,
+            enable_byte_strings:       true,
+            unwrap_heterogenous_lists: false,
+            depth_limit:               DepthLimit::default(),
+            memory_budget:             MemoryBudget::default(),
         }
     }
 
@@ -157,11 +222,13 @@ impl IoOptions {
     #[inline]
     pub fn bedrock_network_uncompressed() -> Self {
         Self {
-            endianness:          Endianness::NetworkLittleEndian,
-            compression:         NbtCompression::Uncompressed,
-            string_encoding:     StringEncoding::Utf8,
-            enable_byte_strings: true,
-            depth_limit:         DepthLimit::default(),
+            endianness:                Endianness::NetworkLittleEndian,
+            compression:               NbtCompression::Uncompressed,
+            string_encoding:           StringEncoding::Utf8,
+            enable_byte_strings:       true,
+            unwrap_heterogenous_lists: false,
+            depth_limit:               DepthLimit::default(),
+            memory_budget:             MemoryBudget::default(),
         }
     }
 }
@@ -265,6 +332,8 @@ pub enum SnbtVersion {
     /// and can otherwise have any characters in `[0-9a-zA-Z]` or `_`, `-`, `.`, `+`.
     /// Only finite float values (not an infinity or NaN) are allowed.
     /// Leading `0`'s are prohibited for integers.
+    /// Lists may be heterogenous, with elements of different tag types; see
+    /// [`NbtList::wrap_heterogenous`] for how they are stored in NBT bytes.
     /// Most other parsing rules are looser than in the original version; only these
     /// stricter exceptions are mentioned as care must be taken with them.
     /// See [minecraft.wiki] for full details.
@@ -285,6 +354,7 @@ pub enum SnbtVersion {
     ///
     /// [minecraft.wiki]: https://minecraft.wiki/w/Java_Edition_1.21.5#:~:text=SNBT%20format
     /// [`replace_non_finite`]: SnbtParseOptions::replace_non_finite
+    /// [`NbtList::wrap_heterogenous`]: crate::tag::NbtList::wrap_heterogenous
     UpdatedJava,
     /// For Java before 1.21.5, or Bedrock Edition.
     /// Fewer parsing features than the newer SNBT version. The specification for
@@ -373,6 +443,10 @@ pub struct SnbtParseOptions {
     ///
     /// Default: true.
     pub enable_byte_strings: bool,
+    /// A limit on the memory allocated while parsing SNBT data.
+    ///
+    /// Default: no limit.
+    pub memory_budget: MemoryBudget,
 }
 
 impl SnbtParseOptions {
@@ -388,6 +462,7 @@ impl SnbtParseOptions {
             enabled_escape_sequences: EnabledEscapeSequences::all_escapes(),
             handle_invalid_escape:    HandleInvalidEscape::Error,
             enable_byte_strings:      true,
+            memory_budget:            MemoryBudget::default(),
         }
     }
 
@@ -403,6 +478,7 @@ impl SnbtParseOptions {
             enabled_escape_sequences: EnabledEscapeSequences::no_escapes(),
             handle_invalid_escape:    HandleInvalidEscape::Error,
             enable_byte_strings:      true,
+            memory_budget:            MemoryBudget::default(),
         }
     }
 }
@@ -498,6 +574,34 @@ pub struct SnbtWriteOptions {
     /// some tool with stringent whitespace requirements might require those characters to
     /// be escaped, too, in which case you need to be aware of this exception.
     pub enabled_escape_sequences: EnabledEscapeSequences,
+    /// The width, in characters, which lines of pretty SNBT should fit in. A compound, list,
+    /// or array is written on a single line if it fits in the rest of the line, and
+    /// is otherwise expanded over multiple lines; arrays are expanded into rows of elements
+    /// aligned in columns. A width of zero expands everything. A line may still exceed
+    /// the width if it contains a long key or string.
+    ///
+    /// This only affects pretty SNBT, such as the output of [`to_pretty_snbt`].
+    ///
+    /// Default: 100
+    ///
+    /// [`to_pretty_snbt`]: crate::tag::NbtTag::to_pretty_snbt
+    pub line_width:               usize,
+    /// The order in which the entries of compounds are written.
+    ///
+    /// Default: `AsStored`
+    pub key_order:                KeyOrder,
+    /// The maximum number of elements written for each `ByteArray`, `IntArray`, or `LongArray`.
+    /// If an array is longer, then the elements past the limit are replaced by
+    /// `... N more`, where `N` is the number of omitted elements. Note that the resulting
+    /// SNBT cannot be parsed.
+    ///
+    /// This only affects pretty SNBT, such as the output of [`to_pretty_snbt_with_limit`];
+    /// compact SNBT always includes every element, so that it can be parsed.
+    ///
+    /// Default: `None`, which writes every element.
+    ///
+    /// [`to_pretty_snbt_with_limit`]: crate::tag::NbtTag::to_pretty_snbt_with_limit
+    pub array_limit:              Option<usize>,
 }
 
 impl SnbtWriteOptions {
@@ -514,6 +618,9 @@ impl SnbtWriteOptions {
                     EscapeSequence::N | EscapeSequence::R | EscapeSequence::S,
                 )
             }),
+            line_width:               100,
+            key_order:                KeyOrder::AsStored,
+            array_limit:              None,
         }
     }
 
@@ -525,10 +632,28 @@ impl SnbtWriteOptions {
             depth_limit:              DepthLimit::default(),
             non_finite:               WriteNonFinite::PrintFloats,
             enabled_escape_sequences: EnabledEscapeSequences::no_escapes(),
+            line_width:               100,
+            key_order:                KeyOrder::AsStored,
+            array_limit:              None,
         }
     }
 }
 
+/// The order in which the entries of compounds are written to SNBT.
+#[cfg_attr(feature = "derive_serde",    derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "derive_standard", derive(PartialEq, Eq, PartialOrd, Ord, Hash))]
+#[derive(Debug, Clone, Copy)]
+pub enum KeyOrder {
+    /// The order in which the compound iterates over its entries. This is insertion order
+    /// if the `preserve_order` feature is enabled, and is unspecified otherwise.
+    AsStored,
+    /// Sorted by key.
+    Alphabetical,
+    /// Entries whose values are not compounds or lists come first, and entries within
+    /// each of those two groups are sorted by key.
+    NestedLast,
+}
+
 /// Controls how non-finite floating-point values (which are NaN or infinite) are written.
 ///
 /// NBT and SNBT aren't meant to support infinite and NaN float or double values, but they
@@ -678,3 +803,86 @@ pub enum EscapeSequence {
     /// If the `named_escapes` feature is not enabled, this option will be ignored.
     UnicodeNamed = 9,
 }
+
+
+// ================================
+//      JSON Options
+// ================================
+
+/// Options for importing plain JSON, which carries no NBT type information, as NBT data.
+/// See the `json` module, enabled by the `json` feature, for more information.
+#[cfg_attr(feature = "derive_serde",    derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "derive_standard", derive(PartialEq, Eq, PartialOrd, Ord, Hash))]
+#[derive(Debug, Clone, Copy)]
+pub struct PlainJsonOptions {
+    /// The maximum depth that NBT compounds and tags can be recursively nested.
+    ///
+    /// Default: 512, the limit used by Minecraft.
+    pub depth_limit: DepthLimit,
+    /// Which tag variant is used for JSON numbers without a fractional part or exponent.
+    ///
+    /// Default: `IntOrLong`
+    pub integers: InferInteger,
+    /// Which tag variant is used for all other JSON numbers.
+    ///
+    /// Default: `Double`
+    pub floats: InferFloat,
+    /// Whether JSON `true` and `false` are converted to the bytes `1b` and `0b`, as is done by
+    /// SNBT. If not, booleans result in an error.
+    ///
+    /// Default: `true`
+    pub booleans_as_bytes: bool,
+    /// Whether compound entries whose value is JSON `null` are skipped. If not, or if `null`
+    /// occurs anywhere other than as the value of an object entry, an error is returned.
+    ///
+    /// Default: `true`
+    pub skip_nulls: bool,
+    /// Whether nonempty JSON arrays containing only integers are converted into `ByteArray`,
+    /// `IntArray`, or `LongArray` tags instead of `List` tags. The array type is chosen from
+    /// the `integers` setting and the largest integer, and `Short` values widen to `IntArray`.
+    ///
+    /// Default: `false`
+    pub typed_arrays: bool,
+}
+
+impl Default for PlainJsonOptions {
+    fn default() -> Self {
+        Self {
+            depth_limit:       DepthLimit::default(),
+            integers:          InferInteger::IntOrLong,
+            floats:            InferFloat::Double,
+            booleans_as_bytes: true,
+            skip_nulls:        true,
+            typed_arrays:      false,
+        }
+    }
+}
+
+/// Controls which tag variant JSON integers are converted into.
+///
+/// Within a JSON array, every integer uses the variant needed by the largest integer,
+/// since NBT lists are homogenous. If an array also contains a non-integer number,
+/// every number in it is converted according to [`InferFloat`].
+#[cfg_attr(feature = "derive_serde",    derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "derive_standard", derive(PartialEq, Eq, PartialOrd, Ord, Hash))]
+#[derive(Debug, Clone, Copy)]
+pub enum InferInteger {
+    /// Use the smallest of `Byte`, `Short`, `Int`, and `Long` which fits the value.
+    Smallest,
+    /// Use `Int` if the value fits, and `Long` otherwise. This matches how SNBT treats
+    /// numbers without a suffix.
+    IntOrLong,
+    /// Always use `Long`.
+    Long,
+}
+
+/// Controls which tag variant JSON numbers which are not integers are converted into.
+#[cfg_attr(feature = "derive_serde",    derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "derive_standard", derive(PartialEq, Eq, PartialOrd, Ord, Hash))]
+#[derive(Debug, Clone, Copy)]
+pub enum InferFloat {
+    /// Use `Float`, losing precision.
+    Float,
+    /// Use `Double`.
+    Double,
+}
@@ -0,0 +1,166 @@
+//! Locations of errors in SNBT input, and reports which point them out.
+
+use std::fmt;
+use std::fmt::{Display, Formatter, Write as _};
+
+
+/// A position in SNBT input. Lines and columns start at 1, and columns are counted in
+/// characters rather than bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SnbtPosition {
+    /// The byte index of the position in the input.
+    pub index:  usize,
+    pub line:   usize,
+    pub column: usize,
+}
+
+impl SnbtPosition {
+    /// Finds the line and column of the given byte index into the input.
+    pub(super) fn new(input: &str, index: usize) -> Self {
+        let index = floor_char_boundary(input, index);
+        let before = &input[..index];
+
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+
+        Self {
+            index,
+            line:   before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl Display for SnbtPosition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// The range of SNBT input in which an error occurred. The end is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SnbtSpan {
+    pub start: SnbtPosition,
+    pub end:   SnbtPosition,
+}
+
+impl SnbtSpan {
+    /// The span of `char_width` characters starting at the given byte index into the input.
+    pub(super) fn new(input: &str, index: usize, char_width: usize) -> Self {
+        let start = SnbtPosition::new(input, index);
+
+        let end_index = input[start.index..]
+            .char_indices()
+            .nth(char_width)
+            .map_or(input.len(), |(len, _)| start.index + len);
+
+        Self {
+            start,
+            end: SnbtPosition::new(input, end_index),
+        }
+    }
+
+    /// The empty span at the end of the input.
+    pub(super) fn end_of_input(input: &str) -> Self {
+        let end = SnbtPosition::new(input, input.len());
+        Self { start: end, end }
+    }
+
+    /// Returns true if the span contains no characters, false otherwise.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.start.index == self.end.index
+    }
+}
+
+impl Display for SnbtSpan {
+    /// Displays the start of the span.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.start, f)
+    }
+}
+
+/// The tokens, or sorts of tokens, which could have been valid where an error occurred,
+/// such as `"','"` or `"value"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExpectedTokens(pub(super) &'static [&'static str]);
+
+impl ExpectedTokens {
+    #[inline]
+    pub fn tokens(self) -> &'static [&'static str] {
+        self.0
+    }
+}
+
+impl Display for ExpectedTokens {
+    /// Lists the expected tokens, separated by commas, with `or` before the last one.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            [] => Ok(()),
+            [only] => f.write_str(only),
+            [first, second] => write!(f, "{first} or {second}"),
+            [rest @ .., last] => {
+                for token in rest {
+                    write!(f, "{token}, ")?;
+                }
+                write!(f, "or {last}")
+            }
+        }
+    }
+}
+
+/// Renders a report of an error with the given message, which shows the line of input
+/// containing the start of the span and underlines the span with carets.
+///
+/// For instance:
+/// ```text
+/// error: reached end of input but expected ',' or ']'
+///  --> line 1, column 5
+///   |
+/// 1 | [1,2
+///   |     ^
+/// ```
+pub(super) fn render_report(input: &str, message: &str, span: SnbtSpan) -> String {
+    let line_start = input[..span.start.index].rfind('\n').map_or(0, |newline| newline + 1);
+    let line_end = input[span.start.index..]
+        .find('\n')
+        .map_or(input.len(), |newline| span.start.index + newline);
+    let line = input[line_start..line_end].trim_end_matches('\r');
+
+    // Keep tabs, so that the carets line up with the line above them
+    let padding: String = input[line_start..span.start.index]
+        .chars()
+        .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+        .collect();
+
+    // Only the first line of a multi-line span is underlined
+    let carets = if span.end.line == span.start.line {
+        span.end.column - span.start.column
+    } else {
+        line.chars().count() + 1 - span.start.column
+    };
+
+    let line_number = span.start.line.to_string();
+    let gutter = " ".repeat(line_number.len());
+
+    let mut report = String::new();
+    // Writing to a `String` cannot fail
+    let _ = writeln!(report, "error: {message}");
+    let _ = writeln!(report, "{gutter}--> {}", span.start);
+    let _ = writeln!(report, "{gutter} |");
+    let _ = writeln!(report, "{line_number} | {line}");
+    let _ = write!(report, "{gutter} | {padding}{}", "^".repeat(carets.max(1)));
+    report
+}
+
+/// Rounds the index down to the nearest character boundary, so that it can be used to slice
+/// the input.
+fn floor_char_boundary(input: &str, index: usize) -> usize {
+    if index >= input.len() {
+        return input.len();
+    }
+
+    (0..=index)
+        .rev()
+        .find(|&index| input.is_char_boundary(index))
+        .unwrap_or(0)
+}
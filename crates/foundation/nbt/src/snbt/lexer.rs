@@ -6,7 +6,7 @@ mod other_utils;
 use std::mem;
 use std::{borrow::Cow, iter::Peekable, str::CharIndices};
 
-use crate::tag::NbtTag;
+use crate::{raw::BudgetTracker, tag::NbtTag};
 use crate::settings::{
     DepthLimit, ParseNonFinite, ParseTrueFalse, SnbtParseOptions, SnbtVersion,
 };
@@ -23,6 +23,9 @@ pub struct Lexer<'a> {
     index:      usize,
     peek_stack: Vec<Result<TokenData, SnbtError>>,
     opts:       SnbtParseOptions,
+    /// Errors which were recovered from, if in recovery mode.
+    errors:     Option<Vec<SnbtError>>,
+    budget:     BudgetTracker,
 }
 
 impl<'a> Lexer<'a> {
@@ -33,6 +36,85 @@ impl<'a> Lexer<'a> {
             index: 0,
             peek_stack: Vec::new(),
             opts,
+            errors: None,
+            budget: BudgetTracker::new(opts.memory_budget),
+        }
+    }
+
+    /// Enables recovery mode, in which the parser records errors with [`recover`] and continues
+    /// instead of halting.
+    ///
+    /// [`recover`]: Lexer::recover
+    #[inline]
+    pub fn recovering(mut self) -> Self {
+        self.errors = Some(Vec::new());
+        self
+    }
+
+    /// In recovery mode, records the error and returns `Ok`, unless an error at the same
+    /// position was already recorded, in which case the new error is discarded.
+    /// Otherwise, returns the error.
+    pub fn recover(&mut self, err: SnbtError) -> Result<(), SnbtError> {
+        let Some(errors) = &mut self.errors else {
+            return Err(err);
+        };
+
+        let duplicate = errors
+            .last()
+            .is_some_and(|last| last.span().start == err.span().start);
+        if !duplicate {
+            errors.push(err);
+        }
+
+        Ok(())
+    }
+
+    /// Takes the errors recorded in recovery mode.
+    #[inline]
+    pub fn take_errors(&mut self) -> Vec<SnbtError> {
+        self.errors.as_mut().map(mem::take).unwrap_or_default()
+    }
+
+    /// In recovery mode, returns a comma or closing bracket which caused an error to the token
+    /// stream, so that the enclosing list or compound can still find its end.
+    pub fn unread_delimiter(&mut self, td: TokenData) {
+        let is_delimiter = matches!(
+            td.token,
+            Token::Comma | Token::ClosedCurly | Token::ClosedSquare,
+        );
+        if self.errors.is_some() && is_delimiter {
+            self.peek_stack.push(Ok(td));
+        }
+    }
+
+    /// Records the memory used by a parsed tag which began at the given index, not including
+    /// the memory of any tags it contains, returning an error if the memory budget
+    /// is exceeded.
+    pub fn charge_tag(&self, index: usize, tag: &NbtTag) -> Result<(), SnbtError> {
+        let (bytes, elements) = match tag {
+            NbtTag::String(string)     => (string.len(), 0),
+            NbtTag::ByteString(bytes)  => (bytes.len(), bytes.len()),
+            NbtTag::ByteArray(array)   => (array.len(), array.len()),
+            NbtTag::IntArray(array)    => (mem::size_of_val(array.as_slice()), array.len()),
+            NbtTag::LongArray(array)   => (mem::size_of_val(array.as_slice()), array.len()),
+            _                          => (0, 0),
+        };
+
+        self.charge(index, bytes.saturating_add(mem::size_of::<NbtTag>()), elements + 1)
+    }
+
+    /// Records the memory used by a compound key which began at the given index,
+    /// returning an error if the memory budget is exceeded.
+    #[inline]
+    pub fn charge_key(&self, index: usize, key: &str) -> Result<(), SnbtError> {
+        self.charge(index, key.len(), 0)
+    }
+
+    fn charge(&self, index: usize, bytes: usize, elements: usize) -> Result<(), SnbtError> {
+        if self.budget.try_charge(bytes, elements) {
+            Ok(())
+        } else {
+            Err(SnbtError::exceeded_memory_budget(self.raw, index, self.budget.budget()))
         }
     }
 
@@ -130,15 +212,17 @@ impl<'a> Lexer<'a> {
                 if mem::discriminant(&td.token) == mem::discriminant(token) {
                     Ok(td)
                 } else {
-                    Err(SnbtError::unexpected_token(
+                    let err = SnbtError::unexpected_token(
                         self.raw,
                         Some(&td),
                         token.as_expectation(),
-                    ))
+                    );
+                    self.unread_delimiter(td);
+                    Err(err)
                 }
             }
             // No tokens were left so return an unexpected end of string error
-            None => Err(SnbtError::unexpected_eos(token.as_expectation())),
+            None => Err(SnbtError::unexpected_eos(self.raw, token.as_expectation())),
         }
     }
 
@@ -457,16 +541,16 @@ pub enum Token {
 }
 
 impl Token {
-    pub fn as_expectation(&self) -> &'static str {
+    pub fn as_expectation(&self) -> &'static [&'static str] {
         match self {
-            Self::OpenCurly    => "'{'",
-            Self::ClosedCurly  => "'}'",
-            Self::OpenSquare   => "'['",
-            Self::ClosedSquare => "']'",
-            Self::Comma        => "','",
-            Self::Colon        => "':'",
-            Self::Semicolon    => "';'",
-            _ => "value",
+            Self::OpenCurly    => &["'{'"],
+            Self::ClosedCurly  => &["'}'"],
+            Self::OpenSquare   => &["'['"],
+            Self::ClosedSquare => &["']'"],
+            Self::Comma        => &["','"],
+            Self::Colon        => &["':'"],
+            Self::Semicolon    => &["';'"],
+            _ => &["value"],
         }
     }
 
@@ -479,6 +563,11 @@ impl Token {
             Self::Long(value)          => Ok(NbtTag::Long(value)),
             Self::Float(value)         => Ok(NbtTag::Float(value)),
             Self::Double(value)        => Ok(NbtTag::Double(value)),
+            // An unsuffixed integer is an int, and is only kept as an i64 for use
+            // in numeric arrays.
+            tk @ Self::UnsuffixedInt(value) => i32::try_from(value)
+                .map(NbtTag::Int)
+                .or(Err(tk)),
             tk => Err(tk),
         }
     }
@@ -731,3 +820,28 @@ impl AmbiguousWord {
         }
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use crate::snbt::{parse_any_original, parse_any_updated, parse_compound_updated};
+    use crate::tag::{NbtList, NbtTag};
+
+
+    #[test]
+    fn unsuffixed_integers_are_ints() {
+        for parse in [parse_any_original::<str>, parse_any_updated::<str>] {
+            assert_eq!(parse("5").expect("valid SNBT"),  NbtTag::Int(5),  "positive integer");
+            assert_eq!(parse("-7").expect("valid SNBT"), NbtTag::Int(-7), "negative integer");
+            assert_eq!(
+                parse("[1, 2]").expect("valid SNBT"),
+                NbtTag::List(NbtList::from(vec![1_i32, 2])),
+                "list elements",
+            );
+            assert!(parse("3000000000").is_err(), "out of range of an int");
+        }
+
+        let compound = parse_compound_updated("{a: 5}").expect("valid SNBT");
+        assert_eq!(compound.get::<_, i32>("a").expect("an int"), 5, "compound entries");
+    }
+}
@@ -1,6 +1,6 @@
 //! Specialized lexing functions for parsing numeric tokens.
 
-use std::{iter::Peekable, str::Chars};
+use std::{iter::Peekable, str::{Chars, FromStr}};
 
 use thiserror::Error;
 
@@ -106,7 +106,7 @@ impl Lexer<'_> {
 
         result
             .map(|token| TokenData::new(token, index, char_width))
-            .map_err(|err| SnbtError::invalid_number(num_string, index, char_width, err))
+            .map_err(|err| SnbtError::invalid_number(self.raw, index, char_width, err))
     }
 
     /// Parses a numeric token, in the `Original` version. See numeric module source for details.
@@ -128,7 +128,7 @@ impl Lexer<'_> {
 
         result
             .map(|token| TokenData::new(token, index, char_width))
-            .map_err(|err| SnbtError::invalid_number(num_string, index, char_width, err))
+            .map_err(|err| SnbtError::invalid_number(self.raw, index, char_width, err))
     }
 }
 
@@ -584,62 +584,30 @@ fn float_value(
     replace_non_finite: bool,
     require_finite:     bool,
 ) -> Result<Token, NumericParseError> {
-    let mut num: f64 = 0.;
-
-    // No need to worry about panics, thanks to infinity.
-    for digit in integral_digits {
-        num = (num * 10.) + f64::from(digit);
-    }
-
-    let mut factor = 0.1;
-    for digit in fractional_digits {
-        num += f64::from(digit) * factor;
-        factor *= 0.1;
-    }
-
+    // Rebuild the number without underscores or a suffix, and let `std` do the (correctly
+    // rounded) conversion. Parsing straight to `f32` avoids rounding twice for floats.
+    let mut normalized = String::with_capacity(
+        integral_digits.len() + fractional_digits.len() + 4
+            + exp.as_ref().map_or(0, |(_, exp_digits)| exp_digits.len() + 2),
+    );
     if !positive_sign {
-        num = -num;
-    }
-
-    if num == 0. {
-        // Don't bother with the exponent stuff.
-        return Ok(if is_double {
-            Token::Double(num)
-        } else {
-            Token::Float(num as f32)
-        });
+        normalized.push('-');
     }
+    normalized.extend(integral_digits.into_iter().map(ascii_digit));
+    // At least one of the two is nonempty, so there is at least one digit.
+    normalized.push('.');
+    normalized.extend(fractional_digits.into_iter().map(ascii_digit));
 
     if let Some((exp_sign, exp_digits)) = exp {
-        // Allow leading zeroes in the exponent digits. Technically this is an
-        // "integral value", sort of, but the spec makes it seem like this is fine.
-        let digits = exp_digits.into_iter().skip_while(|d| d == &0);
-
-        // Parse the first six exponent digits after the leading zeroes into a i32. If the exponent
-        // has more than six digits,
-        // then it's more than enough to push any nonzero non-NaN value to an infinity.
-        // It might have zero nonzero digits, in which case the exponent is 0, which does nothing.
-
-        // Even if someone put `.0000000[...]00001e99999999[...]`, the fractional part would
-        // round to zero. Sure, that just means the analytically correct thing would require
-        // parsing the exponent and coefficient at the same time.
-        // NOTE: this would probably be a funny edge case for a test. But seriously, nobody
-        // should actually use `.000000[ 400 zeroes ]01e500` and expect it to work.
-
-        // Also, these calculations won't overflow since 999_999 is much less than 2^31
-        let mut exponent: i32 = 0;
-        for digit in digits.take(6) {
-            exponent = exponent * 10 + i32::from(digit);
-        }
-
+        normalized.push('e');
         if !exp_sign {
-            exponent = -exponent;
+            normalized.push('-');
         }
-
-        num = num.powi(exponent);
+        normalized.extend(exp_digits.into_iter().map(ascii_digit));
     }
 
     if is_double {
+        let num = parse_normalized::<f64>(&normalized);
         let num = if replace_non_finite {
             if num.is_finite() {
                 num
@@ -658,7 +626,7 @@ fn float_value(
         }
         Ok(Token::Double(num))
     } else {
-        let num = num as f32;
+        let num = parse_normalized::<f32>(&normalized);
         let num = if replace_non_finite {
             if num.is_finite() {
                 num
@@ -683,6 +651,18 @@ fn float_value(
 //      Helper functions
 // ================================================================
 
+/// Converts a digit value (less than 10) to its ASCII character.
+fn ascii_digit(digit: u8) -> char {
+    char::from(b'0' + digit)
+}
+
+/// Parses a string of the form `-?[0-9]*\.[0-9]*(e-?[0-9]+)?` with at least one digit before
+/// the exponent.
+fn parse_normalized<F: FromStr + Default>(normalized: &str) -> F {
+    // `std` accepts every such string, so the default is never used.
+    normalized.parse().unwrap_or_default()
+}
+
 /// Returns `Some(true)` if a plus sign or no sign was read,
 /// and `Some(false)` if a minus sign was read. Consumes a `+` or `-`, otherwise peeks.
 /// Returns `None` if the `chars` iterator ended.
@@ -712,9 +692,9 @@ enum ReadDigitsResult {
 
 /// Utility function for reading in characters as digits, possibly with underscores,
 /// until some condition causes the function to return.
-/// The first character is treated differently, and may be skipped. If it is not skipped
-/// and is not an underscore or character, then `FirstPeekedNone` or `FirstUnlistedChar`
-/// is returned as appropriate.
+/// The first character is treated differently, and may be skipped, in which case it is left
+/// unconsumed. If it is not skipped and is not an underscore or digit, then `FirstPeekedNone`
+/// or `FirstUnlistedChar` is returned as appropriate.
 ///
 /// Neither the first nor last character may be underscores. If `underscores_allowed` is false,
 /// an underscore in any position will result in `InvalidUnderscore` being returned.
@@ -742,11 +722,13 @@ fn read_digits<S: FnOnce(char) -> bool>(
     let mut digits = Vec::new();
 
     // The first has to be handled a bit differently.
-    match chars.next() {
-        // The callee can choose which first characters allow us to proceed to the loop
+    match chars.peek().copied() {
+        // The callee can choose which first characters allow us to proceed to the loop.
+        // The character is left for the callee to consume.
         Some(ch) if first_chars_to_skip(ch) => {}
         Some(ch) => {
-            if let Some(digit) = ch.to_digit(10) {
+            chars.next();
+            if let Some(digit) = ch.to_digit(radix) {
                 // Maximum digit value is far less than 255
                 digits.push(digit as u8);
             } else if ch == '_' {
@@ -765,7 +747,7 @@ fn read_digits<S: FnOnce(char) -> bool>(
 
     let mut last_is_underscore = false;
 
-    // Note that if the character read above is in first_chars_to_skip and not '_' or a digit,
+    // Note that if the character peeked above is in first_chars_to_skip and not '_' or a digit,
     // this loop will correctly not consume any characters.
     let halt_cause = loop {
         match chars.peek() {
@@ -897,3 +879,60 @@ pub enum NumericParseError {
     #[error("the floating-point value was infinite or NaN, but was required to be finite")]
     NonfiniteFloat,
 }
+
+
+#[cfg(test)]
+mod tests {
+    use crate::snbt::{parse_any_original, parse_any_updated};
+    use crate::tag::NbtTag;
+
+
+    fn updated(input: &str) -> NbtTag {
+        parse_any_updated(&input).expect("valid SNBT")
+    }
+
+    #[test]
+    fn exponents_and_suffixes() {
+        assert_eq!(updated("1e5f"),    NbtTag::Float(1e5),     "the exponent is not a fraction");
+        assert_eq!(updated("2.5E+2f"), NbtTag::Float(250.0),   "positive exponent");
+        assert_eq!(updated("12e2d"),   NbtTag::Double(1200.0), "suffix after an exponent");
+        assert_eq!(updated("1.e2"),    NbtTag::Double(100.0),  "empty fractional part");
+        assert_eq!(updated("1.5e-3"),  NbtTag::Double(1.5e-3), "negative exponent");
+    }
+
+    #[test]
+    fn leading_decimal_point() {
+        assert_eq!(updated(".5"),  NbtTag::Double(0.5), "no integer part");
+        assert_eq!(updated(".5f"), NbtTag::Float(0.5),  "no integer part, with a suffix");
+        assert_eq!(
+            parse_any_original(&".5").expect("valid SNBT"),
+            NbtTag::Double(0.5),
+            "no integer part, in the original version",
+        );
+    }
+
+    #[test]
+    fn radix_prefixes() {
+        assert_eq!(updated("0xFF"),   NbtTag::Int(255), "hexadecimal digits are not base 10");
+        assert_eq!(updated("0xff"),   NbtTag::Int(255), "lowercase hexadecimal digits");
+        assert_eq!(updated("0b101"),  NbtTag::Int(5),   "binary");
+        assert_eq!(updated("0xFFub"), NbtTag::Byte(-1), "unsigned byte");
+    }
+
+    #[test]
+    fn floats_are_correctly_rounded() {
+        assert_eq!(updated("0.1"),   NbtTag::Double(0.1), "0.1 is not exactly representable");
+        assert_eq!(updated("0.1f"),  NbtTag::Float(0.1),  "0.1 is not exactly representable");
+        assert_eq!(updated("1e-7f"), NbtTag::Float(1e-7), "small float with an exponent");
+        assert_eq!(
+            updated("9007199254740993d"),
+            NbtTag::Double(9_007_199_254_740_992.0),
+            "ties round to even",
+        );
+        assert_eq!(
+            updated("2.2250738585072014e-308"),
+            NbtTag::Double(f64::MIN_POSITIVE),
+            "smallest normal double",
+        );
+    }
+}
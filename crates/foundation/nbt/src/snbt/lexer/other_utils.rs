@@ -106,7 +106,7 @@ impl Lexer<'_> {
                 self.raw,
                 num_index,
                 1, // the character following arg, ')', has length 1
-                "a numeric value",
+                &["a numeric value"],
             ));
         };
 
@@ -333,7 +333,7 @@ impl Lexer<'_> {
                 HandleInvalidEscape::CopyVerbatim => Ok(Some((None, 0))),
                 HandleInvalidEscape::Ignore => Ok(None),
                 HandleInvalidEscape::Error => {
-                    Err(SnbtError::unexpected_eos("a character escape sequence"))
+                    Err(SnbtError::unexpected_eos(self.raw, &["a character escape sequence"]))
                 }
             };
         };
@@ -411,8 +411,14 @@ impl Lexer<'_> {
         // The function calls to create errors are cheap and will probably be inlined
         #[expect(clippy::or_fun_call)]
         let chars = [
-            self.next_ch().ok_or(SnbtError::unexpected_eos("two-character hex unicode value"))?,
-            self.next_ch().ok_or(SnbtError::unexpected_eos("two-character hex unicode value"))?,
+            self.next_ch().ok_or(SnbtError::unexpected_eos(
+                self.raw,
+                &["two-character hex unicode value"],
+            ))?,
+            self.next_ch().ok_or(SnbtError::unexpected_eos(
+                self.raw,
+                &["two-character hex unicode value"],
+            ))?,
         ];
 
         let utf_val = u32::from(chars_to_u8(chars).ok_or_else(|| {
@@ -420,7 +426,7 @@ impl Lexer<'_> {
                 self.raw,
                 index + 2, // Skip the '\\' and 'x', which are each byte length 1
                 2,
-                "two hexadecimal digits",
+                &["two hexadecimal digits"],
             )
         })?);
 
@@ -474,7 +480,8 @@ impl Lexer<'_> {
             // The function calls to create errors are cheap and will probably be inlined
             #[expect(clippy::or_fun_call)]
             self.next_ch().ok_or(SnbtError::unexpected_eos(
-                "four-character hex unicode value",
+                self.raw,
+                &["four-character hex unicode value"],
             ))
         };
 
@@ -485,7 +492,7 @@ impl Lexer<'_> {
                 self.raw,
                 index + 2, // Skip the '\\' and 'u', which are each byte length 1
                 4,
-                "four hexadecimal digits",
+                &["four hexadecimal digits"],
             )
         })?);
 
@@ -539,7 +546,8 @@ impl Lexer<'_> {
             // The function calls to create errors are cheap and will probably be inlined
             #[expect(clippy::or_fun_call)]
             self.next_ch().ok_or(SnbtError::unexpected_eos(
-                "eight-character hex unicode value",
+                self.raw,
+                &["eight-character hex unicode value"],
             ))
         };
 
@@ -553,7 +561,7 @@ impl Lexer<'_> {
                 self.raw,
                 index + 2, // Skip the '\\' and 'U', which are each byte length 1
                 8,
-                "eight hexadecimal digits",
+                &["eight hexadecimal digits"],
             )
         })?;
 
@@ -622,12 +630,13 @@ impl Lexer<'_> {
                     self.raw,
                     index,
                     1,
-                    "an opening curly bracket",
+                    &["an opening curly bracket"],
                 ));
             }
         } else {
             return Err(SnbtError::unexpected_eos(
-                "a named unicode character escape",
+                self.raw,
+                &["a named unicode character escape"],
             ));
         }
 
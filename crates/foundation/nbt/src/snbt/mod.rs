@@ -6,6 +6,7 @@
 )]
 // See the `pub use` and `pub(crate) use` below for exceptions
 mod lexer;
+mod diagnostic;
 
 
 use std::mem;
@@ -14,7 +15,7 @@ use thiserror::Error;
 
 use crate::raw;
 use crate::{
-    settings::{DepthLimit, SnbtParseOptions, SnbtVersion},
+    settings::{DepthLimit, MemoryBudget, SnbtParseOptions, SnbtVersion},
     tag::{NbtCompound, NbtList, NbtTag},
 };
 use self::lexer::{FromExact, FromLossless, Lexer, Token, TokenData};
@@ -22,6 +23,7 @@ use self::lexer::{FromExact, FromLossless, Lexer, Token, TokenData};
 
 pub(crate) use self::lexer::is_ambiguous;
 pub use self::lexer::{NumericParseError, allowed_unquoted, starts_unquoted_number};
+pub use self::diagnostic::{ExpectedTokens, SnbtPosition, SnbtSpan};
 
 
 // TODO: should add module-wide documentation about the specification and implementation.
@@ -63,6 +65,8 @@ impl VerifiedSnbt {
 
 /// Parses the given string into an NBT tag.
 /// See [`SnbtVersion`] for some specifics of the standard and this implementation.
+///
+/// Parsing halts at the first error; to collect every error, use [`parse_any_recovering`].
 #[inline]
 pub fn parse_any<T>(string_nbt: &T, opts: SnbtParseOptions) -> Result<NbtTag, SnbtError>
 where
@@ -160,6 +164,103 @@ where
     parse_compound_tag(&mut tokens, &open_curly, 0)
 }
 
+/// Parses the given string into an NBT tag like [`parse_any`], but continues past errors
+/// instead of halting at the first one, so that every error can be reported at once.
+///
+/// The returned tag is missing whichever parts of the input could not be parsed:
+/// invalid compound entries and list elements are left out, and compounds and lists
+/// which are not closed are ended where the error occurred.
+/// Only the first error at each position of the input is reported.
+///
+/// # Examples:
+/// ```
+/// use prismarine_anchor_nbt::{snbt, NbtTag, SnbtParseOptions};
+///
+/// let input = "{\n  a: 1,\n  b 2,\n  c: [3, 4}\n}";
+/// let recovered = snbt::parse_any_recovering(input, SnbtParseOptions::default_updated());
+///
+/// assert_eq!(recovered.errors.len(), 2);
+/// assert_eq!(recovered.errors[0].span().start.line, 3);
+/// assert_eq!(recovered.errors[1].span().start.line, 4);
+///
+/// let Some(NbtTag::Compound(compound)) = recovered.tag else { panic!() };
+/// assert_eq!(compound.len(), 2);
+/// assert!(compound.contains_key("c"));
+/// ```
+pub fn parse_any_recovering<T>(string_nbt: &T, opts: SnbtParseOptions) -> RecoveredSnbt
+where
+    T: AsRef<str> + ?Sized,
+{
+    let mut tokens = Lexer::new(string_nbt.as_ref(), opts).recovering();
+    let tag = parse_next_value(&mut tokens, false, 0);
+    let mut errors = tokens.take_errors();
+
+    let tag = match tag {
+        Ok(tag) => Some(tag),
+        Err(err) => {
+            errors.push(err);
+            None
+        }
+    };
+
+    RecoveredSnbt { tag, errors }
+}
+
+/// The result of parsing SNBT with [`parse_any_recovering`].
+#[derive(Debug, Clone)]
+pub struct RecoveredSnbt {
+    /// The parsed tag, without any parts of the input which could not be parsed, or `None`
+    /// if no tag could be parsed at all.
+    pub tag:    Option<NbtTag>,
+    /// Each error which was encountered, in the order they occurred.
+    pub errors: Vec<SnbtError>,
+}
+
+impl RecoveredSnbt {
+    /// Returns true if no errors were encountered, false otherwise.
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// In recovery mode, records the error and skips the rest of the current compound entry
+/// or list element. Otherwise, returns the error.
+macro_rules! recover {
+    ($tokens:expr, $err:expr) => {{
+        let err = $err;
+        $tokens.recover(err)?;
+        skip_to_delimiter($tokens, true);
+    }};
+}
+
+/// Skips tokens until reaching a comma (if `stop_at_comma` is true) or a closing bracket which
+/// is not nested inside the skipped tokens. The comma or closing bracket is not consumed.
+/// Errors from skipped tokens are ignored.
+fn skip_to_delimiter(tokens: &mut Lexer<'_>, stop_at_comma: bool) {
+    let mut depth = 0_usize;
+
+    loop {
+        match tokens.peek(false) {
+            Some(Ok(TokenData { token, .. })) => match token {
+                Token::OpenCurly | Token::OpenSquare => depth += 1,
+                Token::ClosedCurly | Token::ClosedSquare => {
+                    if depth == 0 {
+                        return;
+                    }
+                    depth -= 1;
+                }
+                Token::Comma if stop_at_comma && depth == 0 => return,
+                _ => {}
+            },
+            Some(Err(_)) => {}
+            None => return,
+        }
+
+        tokens.next(false);
+    }
+}
+
 // Parses the next value in the token stream
 fn parse_next_value(
     tokens:           &mut Lexer<'_>,
@@ -177,7 +278,8 @@ fn parse_value(
     current_depth: u32,
 ) -> Result<NbtTag, SnbtError> {
     if let Some(td) = token {
-        match td {
+        let index = td.index;
+        let tag = match td {
             // Open curly brace indicates a compound tag is present
             TokenData {
                 token: Token::OpenCurly,
@@ -191,14 +293,33 @@ fn parse_value(
             } => parse_list(tokens, &td, current_depth),
 
             // Could be a value token or delimiter token
-            _ => td
-                .into_tag()
-                .map_err(|td| SnbtError::unexpected_token(tokens.raw(), Some(&td), "value")),
-        }
+            _ => td.into_tag().map_err(|td| {
+                let err = SnbtError::unexpected_token(tokens.raw(), Some(&td), &["value"]);
+                // Let the enclosing compound or list see its closing bracket
+                tokens.unread_delimiter(td);
+                err
+            }),
+        }?;
+
+        tokens.charge_tag(index, &tag)?;
+        Ok(tag)
     } else {
         // We expected a value but ran out of data
-        Err(SnbtError::unexpected_eos("value"))
+        Err(SnbtError::unexpected_eos(tokens.raw(), &["value"]))
+    }
+}
+
+/// In recovery mode, records the error and skips the rest of the list, returning an empty
+/// list in its place. Otherwise, returns the error.
+fn recover_list(tokens: &mut Lexer<'_>, open_square: &TokenData, err: SnbtError) -> Result<NbtTag, SnbtError> {
+    tokens.recover(err)?;
+    skip_to_delimiter(tokens, false);
+
+    if tokens.next(false).is_none() {
+        tokens.recover(SnbtError::unmatched_brace(tokens.raw(), open_square.index))?;
     }
+
+    Ok(NbtList::new().into())
 }
 
 // Parses a list, which can be either a generic tag list or vector of primitives
@@ -207,7 +328,15 @@ fn parse_list(
     open_square:   &TokenData,
     current_depth: u32,
 ) -> Result<NbtTag, SnbtError> {
-    match tokens.next(false).transpose()? {
+    let first_token = match tokens.next(false).transpose() {
+        Ok(first_token) => first_token,
+        Err(err) => {
+            recover!(tokens, err);
+            return parse_tag_list(tokens, None, current_depth).map(Into::into);
+        }
+    };
+
+    match first_token {
         // Empty list ('[]') with no type specifier is treated as an empty NBT tag list
         Some(TokenData {
             token: Token::ClosedSquare,
@@ -234,18 +363,19 @@ fn parse_list(
                     token: Token::Semicolon,
                     ..
                 })) => {
+                    // Moves past the peeked semicolon
+                    tokens.next(false);
+
                     if quoted {
-                        return Err(SnbtError::unexpected_token_at(
+                        let err = SnbtError::unexpected_token_at(
                             tokens.raw(),
                             index,
                             char_width,
-                            "'B', 'I', or 'L'",
-                        ));
+                            &["'B'", "'I'", "'L'"],
+                        );
+                        return recover_list(tokens, open_square, err);
                     }
 
-                    // Moves past the peeked semicolon
-                    tokens.next(false);
-
                     // Determine the primitive type and parse it
                     match string.as_str() {
                         "b" | "B" => parse_prim_list(tokens, open_square, NbtTag::ByteArray),
@@ -257,32 +387,33 @@ fn parse_list(
                                     NbtTag::ByteString(raw::cast_byte_buf_to_unsigned(list))
                                 })
                             } else {
-                                Err(SnbtError::byte_string(
-                                    tokens.raw(),
-                                    index,
-                                    char_width,
-                                ))
+                                let err = SnbtError::byte_string(tokens.raw(), index, char_width);
+                                recover_list(tokens, open_square, err)
                             }
                         }
-                        _ => Err(SnbtError::unexpected_token_at(
-                            tokens.raw(),
-                            index,
-                            char_width,
-                            "'B', 'I', or 'L'",
-                        )),
+                        _ => {
+                            let err = SnbtError::unexpected_token_at(
+                                tokens.raw(),
+                                index,
+                                char_width,
+                                &["'B'", "'I'", "'L'"],
+                            );
+                            recover_list(tokens, open_square, err)
+                        }
                     }
                 }
 
                 _ => {
                     if current_depth >= tokens.depth_limit().0 {
-                        Err(SnbtError::exceeded_depth_limit(
+                        let err = SnbtError::exceeded_depth_limit(
                             tokens.raw(),
                             index,
                             tokens.depth_limit(),
-                        ))
+                        );
+                        recover_list(tokens, open_square, err)
                     } else {
                         // Parse as a tag list (token errors are delegated to this function)
-                        parse_tag_list(tokens, NbtTag::String(string), current_depth)
+                        parse_tag_list(tokens, Some(NbtTag::String(string)), current_depth)
                             .map(Into::into)
                     }
                 }
@@ -294,15 +425,22 @@ fn parse_list(
             // Check the depth limit
             if let Some(td) = &td {
                 if current_depth >= tokens.depth_limit().0 {
-                    return Err(SnbtError::exceeded_depth_limit(
+                    let err = SnbtError::exceeded_depth_limit(
                         tokens.raw(),
                         td.index,
                         tokens.depth_limit(),
-                    ));
+                    );
+                    return recover_list(tokens, open_square, err);
                 }
             }
 
-            let first_element = parse_value(tokens, td, current_depth + 1)?;
+            let first_element = match parse_value(tokens, td, current_depth + 1) {
+                Ok(first_element) => Some(first_element),
+                Err(err) => {
+                    recover!(tokens, err);
+                    None
+                }
+            };
             parse_tag_list(tokens, first_element, current_depth).map(Into::into)
         }
     }
@@ -322,7 +460,16 @@ where
     let mut comma: Option<usize> = Some(0);
 
     loop {
-        match tokens.next(false).transpose()? {
+        let td = match tokens.next(false).transpose() {
+            Ok(td) => td,
+            Err(err) => {
+                tokens.recover(err)?;
+                comma = None;
+                continue;
+            }
+        };
+
+        match td {
             // Finish off the list
             Some(TokenData {
                 token: Token::ClosedSquare,
@@ -331,7 +478,10 @@ where
                 Some(0) | None => return Ok(vec_to_tag(list)),
                 // For some reason, even in the updated version, trailing commas are
                 // still not allowed for numeric arrays, if I'm reading the spec correctly.
-                Some(index) => return Err(SnbtError::trailing_comma(tokens.raw(), index)),
+                Some(index) => {
+                    tokens.recover(SnbtError::trailing_comma(tokens.raw(), index))?;
+                    return Ok(vec_to_tag(list));
+                }
             },
 
             // Indicates another value should be parsed
@@ -341,77 +491,106 @@ where
                 ..
             }) => comma = Some(index),
 
+            // The wrong closing bracket; assume that this array should have been closed
+            Some(td @ TokenData {
+                token: Token::ClosedCurly,
+                ..
+            }) => {
+                tokens.recover(SnbtError::unexpected_token(
+                    tokens.raw(),
+                    Some(&td),
+                    &["value", "']'"],
+                ))?;
+                tokens.unread_delimiter(td);
+                return Ok(vec_to_tag(list));
+            }
+
             // Attempt to convert the token into a value
             Some(td) => {
                 // Make sure a value was expected
-                match comma {
-                    Some(_) => {
-                        match tokens.snbt_version() {
-                            // The numeric array can accept data of the same size or smaller
-                            SnbtVersion::UpdatedJava => match T::from_lossless(td) {
-                                Ok(value) => list.push(value),
-                                Err((td, Some(numeric_err))) => {
-                                    return Err(SnbtError::invalid_number(
-                                        tokens.raw(),
-                                        td.index,
-                                        td.char_width,
-                                        numeric_err,
-                                    ));
-                                }
-                                Err((td, None)) => {
-                                    return Err(SnbtError::non_homogenous_numeric_list(
-                                        tokens.raw(),
-                                        td.index,
-                                        td.char_width,
-                                    ));
-                                }
-                            },
-                            // The numeric array can accept data only of the same size
-                            SnbtVersion::Original => match T::from_exact(td) {
-                                Ok(value) => list.push(value),
-                                Err(td) => {
-                                    return Err(SnbtError::non_homogenous_numeric_list(
-                                        tokens.raw(),
-                                        td.index,
-                                        td.char_width,
-                                    ));
-                                }
-                            },
-                        }
+                if comma.is_none() {
+                    // If recovering, act as though the missing comma were present
+                    tokens.recover(SnbtError::unexpected_token(
+                        tokens.raw(),
+                        Some(&td),
+                        Token::Comma.as_expectation(),
+                    ))?;
+                }
+                comma = None;
 
-                        comma = None;
-                    }
+                // Nested lists and compounds are never valid here
+                let nested = matches!(td.token, Token::OpenCurly | Token::OpenSquare);
 
-                    None => {
-                        return Err(SnbtError::unexpected_token(
+                let result = match tokens.snbt_version() {
+                    // The numeric array can accept data of the same size or smaller
+                    SnbtVersion::UpdatedJava => match T::from_lossless(td) {
+                        Ok(value) => Ok(value),
+                        Err((td, Some(numeric_err))) => Err(SnbtError::invalid_number(
+                            tokens.raw(),
+                            td.index,
+                            td.char_width,
+                            numeric_err,
+                        )),
+                        Err((td, None)) => Err(SnbtError::non_homogenous_numeric_list(
+                            tokens.raw(),
+                            td.index,
+                            td.char_width,
+                        )),
+                    },
+                    // The numeric array can accept data only of the same size
+                    SnbtVersion::Original => T::from_exact(td).map_err(|td| {
+                        SnbtError::non_homogenous_numeric_list(
                             tokens.raw(),
-                            Some(&td),
-                            Token::Comma.as_expectation(),
-                        ));
+                            td.index,
+                            td.char_width,
+                        )
+                    }),
+                };
+
+                match result {
+                    Ok(value) => list.push(value),
+                    Err(err) => {
+                        tokens.recover(err)?;
+                        if nested {
+                            // Skip the contents and closing bracket of the nested value
+                            skip_to_delimiter(tokens, false);
+                            tokens.next(false);
+                        }
                     }
                 }
             }
 
-            None => return Err(SnbtError::unmatched_brace(tokens.raw(), open_square.index)),
+            None => {
+                tokens.recover(SnbtError::unmatched_brace(tokens.raw(), open_square.index))?;
+                return Ok(vec_to_tag(list));
+            }
         }
     }
 }
 
-// Depth limit should be checked before entering this function
+// Depth limit should be checked before entering this function.
+// The first element is `None` only if it could not be parsed, in recovery mode.
 fn parse_tag_list(
     tokens:        &mut Lexer<'_>,
-    first_element: NbtTag,
+    first_element: Option<NbtTag>,
     current_depth: u32,
 ) -> Result<NbtList, SnbtError> {
     // Construct the list and use the first element to determine the list's type
     let mut list = NbtList::new();
-    let mut descrim = mem::discriminant(&first_element);
-    let expecting_strings = matches!(&first_element, &NbtTag::String(_));
-    let mut list_holds_compounds = matches!(&first_element, &NbtTag::Compound { .. });
-    list.push(first_element);
+    let mut descrim = first_element.as_ref().map(mem::discriminant);
+    let mut expecting_strings = matches!(&first_element, Some(NbtTag::String(_)));
+    list.extend(first_element);
 
     loop {
-        match tokens.next(expecting_strings).transpose()? {
+        let td = match tokens.next(expecting_strings).transpose() {
+            Ok(td) => td,
+            Err(err) => {
+                recover!(tokens, err);
+                continue;
+            }
+        };
+
+        let (index, char_width, element) = match td {
             // Finish off the list
             Some(TokenData {
                 token: Token::ClosedSquare,
@@ -429,69 +608,87 @@ fn parse_tag_list(
                         index,
                         token: Token::ClosedSquare,
                         ..
-                    })) => match tokens.snbt_version() {
-                        SnbtVersion::UpdatedJava => return Ok(list),
-                        SnbtVersion::Original => {
-                            return Err(SnbtError::trailing_comma(tokens.raw(), index));
+                    })) => {
+                        if matches!(tokens.snbt_version(), SnbtVersion::Original) {
+                            tokens.recover(SnbtError::trailing_comma(tokens.raw(), index))?;
                         }
-                    },
+                        // Moves past the peeked square bracket
+                        tokens.next(expecting_strings);
+                        return Ok(list);
+                    }
                     Some(&Ok(TokenData {
                         index, char_width, ..
                     })) => (index, char_width),
                     _ => (0, 0),
                 };
-                let element = parse_next_value(tokens, expecting_strings, current_depth + 1)?;
-
-                if mem::discriminant(&element) == descrim {
-                    list.push(element);
-                } else {
-                    match tokens.snbt_version() {
-                        SnbtVersion::UpdatedJava => {
-                            // In order to preserve list homogeneity, convert everything
-                            // to a compound tag.
-
-                            #[inline]
-                            fn to_compound(tag: NbtTag) -> NbtTag {
-                                if let NbtTag::Compound { .. } = tag {
-                                    tag
-                                } else {
-                                    let mut compound = NbtCompound::with_capacity(1);
-                                    compound.insert("", tag);
-                                    NbtTag::Compound(compound)
-                                }
-                            }
-
-                            let compounded_element = to_compound(element);
 
-                            if !list_holds_compounds {
-                                // Convert the rest of the list to compound tags
-                                list = list.into_iter().map(to_compound).collect();
+                (
+                    index,
+                    char_width,
+                    parse_next_value(tokens, expecting_strings, current_depth + 1),
+                )
+            }
 
-                                descrim = mem::discriminant(&compounded_element);
-                                list_holds_compounds = true;
-                            }
+            // The wrong closing bracket; assume that this list should have been closed
+            Some(td @ TokenData {
+                token: Token::ClosedCurly,
+                ..
+            }) => {
+                tokens.recover(SnbtError::unexpected_token(
+                    tokens.raw(),
+                    Some(&td),
+                    &["','", "']'"],
+                ))?;
+                tokens.unread_delimiter(td);
+                return Ok(list);
+            }
 
-                            list.push(compounded_element);
-                        }
+            // If recovering, act as though a missing comma were present
+            Some(td) => {
+                tokens.recover(SnbtError::unexpected_token(
+                    tokens.raw(),
+                    Some(&td),
+                    &["','", "']'"],
+                ))?;
+
+                (
+                    td.index,
+                    td.char_width,
+                    parse_value(tokens, Some(td), current_depth + 1),
+                )
+            }
 
-                        SnbtVersion::Original => {
-                            return Err(SnbtError::non_homogenous_tag_list(
-                                tokens.raw(),
-                                index,
-                                char_width,
-                            ));
-                        }
-                    };
-                }
+            None => {
+                tokens.recover(SnbtError::unexpected_eos(tokens.raw(), &["','", "']'"]))?;
+                return Ok(list);
             }
+        };
 
-            // Some invalid token
-            td => {
-                return Err(SnbtError::unexpected_token(
+        let element = match element {
+            Ok(element) => element,
+            Err(err) => {
+                recover!(tokens, err);
+                continue;
+            }
+        };
+
+        match descrim {
+            // Heterogenous lists are permitted in the updated version. They are wrapped
+            // in compounds when written to NBT bytes; see `NbtList::wrap_heterogenous`.
+            Some(descrim) if descrim != mem::discriminant(&element)
+                && matches!(tokens.snbt_version(), SnbtVersion::Original) =>
+            {
+                tokens.recover(SnbtError::non_homogenous_tag_list(
                     tokens.raw(),
-                    td.as_ref(),
-                    "',' or ']'",
-                ));
+                    index,
+                    char_width,
+                ))?;
+            }
+            Some(_) => list.push(element),
+            None => {
+                descrim = Some(mem::discriminant(&element));
+                expecting_strings = matches!(&element, NbtTag::String(_));
+                list.push(element);
             }
         }
     }
@@ -507,218 +704,338 @@ fn parse_compound_tag(
     let mut comma: Option<usize> = Some(0);
 
     loop {
-        if let Some(td) = tokens.next(true).transpose()? {
-            match td {
-                // Finish off the compound tag
-                TokenData {
-                    token: Token::ClosedCurly,
-                    ..
-                } => {
-                    match comma {
-                        // First loop iteration or no comma
-                        Some(0) | None => return Ok((compound, tokens.index())),
-                        // Later iteration with a trailing comma
-                        Some(index) => {
-                            if matches!(tokens.snbt_version(), SnbtVersion::Original) {
-                                return Err(SnbtError::trailing_comma(tokens.raw(), index));
-                            }
-                        }
+        let td = match tokens.next(true).transpose() {
+            Ok(Some(td)) => td,
+            Err(err) => {
+                recover!(tokens, err);
+                comma = None;
+                continue;
+            }
+            Ok(None) => {
+                // End of input / unmatched brace
+                tokens.recover(SnbtError::unmatched_brace(tokens.raw(), open_curly.index))?;
+                return Ok((compound, tokens.index()));
+            }
+        };
+
+        match td {
+            // Finish off the compound tag
+            TokenData {
+                token: Token::ClosedCurly,
+                ..
+            } => {
+                // A trailing comma is only permitted after a later iteration of the loop,
+                // and only in the updated version
+                if let Some(index) = comma.filter(|&index| index != 0) {
+                    if matches!(tokens.snbt_version(), SnbtVersion::Original) {
+                        tokens.recover(SnbtError::trailing_comma(tokens.raw(), index))?;
                     }
                 }
+                return Ok((compound, tokens.index()));
+            }
 
-                // Parse a new key-value pair
-                TokenData {
-                    token: Token::String { value: key, .. },
-                    index,
-                    char_width,
-                } => {
-                    match comma {
-                        // First loop iteration or a comma indicated that more data is present
-                        Some(_) => {
-                            // Check current_depth. If we're at the limit, then this is
-                            // an error.
-                            if current_depth >= tokens.depth_limit().0 {
-                                return Err(SnbtError::exceeded_depth_limit(
-                                    tokens.raw(),
-                                    index,
-                                    tokens.depth_limit(),
-                                ));
-                            }
+            // Parse a new key-value pair
+            TokenData {
+                token: Token::String { value: key, .. },
+                index,
+                char_width,
+            } => {
+                // If there was not a comma before this string, then the token is unexpected.
+                // If recovering, act as though the missing comma were present.
+                if comma.is_none() {
+                    tokens.recover(SnbtError::unexpected_token_at(
+                        tokens.raw(),
+                        index,
+                        char_width,
+                        Token::Comma.as_expectation(),
+                    ))?;
+                }
+                comma = None;
 
-                            tokens.assert_next(&Token::Colon, false)?;
-                            compound.insert(
-                                key,
-                                parse_next_value(tokens, false, current_depth + 1)?,
-                            );
-                            comma = None;
-                        }
+                // Check current_depth. If we're at the limit, then this is
+                // an error.
+                if current_depth >= tokens.depth_limit().0 {
+                    recover!(
+                        tokens,
+                        SnbtError::exceeded_depth_limit(tokens.raw(), index, tokens.depth_limit())
+                    );
+                    continue;
+                }
 
-                        // There was not a comma before this string
-                        // so therefore the token is unexpected
-                        None => {
-                            return Err(SnbtError::unexpected_token_at(
-                                tokens.raw(),
-                                index,
-                                char_width,
-                                Token::Comma.as_expectation(),
-                            ));
-                        }
+                let value = tokens
+                    .assert_next(&Token::Colon, false)
+                    .and_then(|_| parse_next_value(tokens, false, current_depth + 1))
+                    .and_then(|value| tokens.charge_key(index, &key).map(|()| value));
+
+                match value {
+                    Ok(value) => {
+                        compound.insert(key, value);
                     }
+                    Err(err) => recover!(tokens, err),
                 }
+            }
 
-                // Denote that another key-value pair is anticipated
-                TokenData {
-                    token: Token::Comma,
-                    index,
-                    ..
-                } => match comma {
-                    None => comma = Some(index),
-                    // This comma came before any valid element, or after another comma;
-                    // this is not valid in either version.
-                    Some(_) => {
-                        return Err(SnbtError::unexpected_token_at(
-                            tokens.raw(),
-                            index,
-                            1,
-                            "compound key or '}'",
-                        ));
-                    }
-                },
+            // Denote that another key-value pair is anticipated
+            TokenData {
+                token: Token::Comma,
+                index,
+                ..
+            } => match comma {
+                None => comma = Some(index),
+                // This comma came before any valid element, or after another comma;
+                // this is not valid in either version.
+                Some(_) => {
+                    tokens.recover(SnbtError::unexpected_token_at(
+                        tokens.raw(),
+                        index,
+                        1,
+                        &["compound key", "'}'"],
+                    ))?;
+                }
+            },
+
+            // The wrong closing bracket; assume that this compound should have been closed
+            td @ TokenData {
+                token: Token::ClosedSquare,
+                ..
+            } => {
+                tokens.recover(SnbtError::unexpected_token(
+                    tokens.raw(),
+                    Some(&td),
+                    &["compound key", "'}'", "','"],
+                ))?;
+                tokens.unread_delimiter(td);
+                return Ok((compound, tokens.index()));
+            }
 
-                // Catch-all for unexpected tokens
-                td => {
-                    return Err(SnbtError::unexpected_token(
+            // Catch-all for unexpected tokens
+            td => {
+                recover!(
+                    tokens,
+                    SnbtError::unexpected_token(
                         tokens.raw(),
                         Some(&td),
-                        "compound key, '}', or ','",
-                    ));
-                }
+                        &["compound key", "'}'", "','"],
+                    )
+                );
+                comma = None;
             }
-        } else {
-            // End of input / unmatched brace
-            return Err(SnbtError::unmatched_brace(tokens.raw(), open_curly.index));
         }
     }
 }
 
-/// An error that occurs during the parsing process. Most errors contain a copy of a segment
-/// of the input where the error occurred, and each has metadata about the specific error.
+/// An error that occurs during the parsing process.
+///
+/// Most errors contain a copy of a segment of the input where the error occurred, and each
+/// has metadata about the specific error, including the [span] of input where it occurred.
+/// [`report`] renders an error along with the line of input where it occurred.
+///
+/// [span]: SnbtError::span
+/// [`report`]: SnbtError::report
 #[derive(Error, Debug, Clone)]
 pub enum SnbtError {
     /// The limit on recursive nesting depth of NBT lists and compounds was exceeded.
     #[error(
-        "exceeded depth limit {} for nested compound and list tags at column {} near '{}'",
-        limit.0, index, segment,
+        "exceeded depth limit {} for nested compound and list tags at {} near '{}'",
+        limit.0, span, segment,
     )]
     ExceededDepthLimit {
         segment: String,
-        index:   usize,
+        span:    SnbtSpan,
         /// The limit which was exceeded.
         limit:   DepthLimit,
     },
+    /// Parsing the input would have allocated more memory than permitted by the
+    /// [`MemoryBudget`] in the [`SnbtParseOptions`].
+    #[error(
+        "exceeded memory budget of {} bytes and {} elements at {} near '{}'",
+        budget.max_bytes, budget.max_elements, span, segment,
+    )]
+    ExceededMemoryBudget {
+        segment: String,
+        span:    SnbtSpan,
+        /// The budget which was exceeded.
+        budget:  MemoryBudget,
+    },
     /// The end of the string (EOS) was encountered before it was expected.
     #[error("reached end of input but expected {expected}")]
     UnexpectedEOS {
-        /// The expected token or sequence of tokens.
-        expected: &'static str,
+        span:     SnbtSpan,
+        /// The expected tokens.
+        expected: ExpectedTokens,
     },
     /// An unexpected token was encountered.
-    #[error("unexpected token at column {index} near '{segment}', expected {expected}")]
+    #[error("unexpected token at {span} near '{segment}', expected {expected}")]
     UnexpectedToken {
         segment:  String,
-        index:    usize,
-        /// The expected token or sequence of tokens.
-        expected: &'static str,
+        span:     SnbtSpan,
+        /// The expected tokens.
+        expected: ExpectedTokens,
     },
     /// An escape sequence supported in some SNBT version, but not the one selected.
     #[error(
-        "escape sequence only supported in a different SNBT version at column {index}: '{segment}'",
+        "escape sequence only supported in a different SNBT version at {span}: '{segment}'",
     )]
-    UnsupportedEscapeSequence { segment: String, index: usize },
+    UnsupportedEscapeSequence { segment: String, span: SnbtSpan },
     /// A named escape sequence was encountered, but named escape sequence support wasn't enabled.
     #[error(
-        "named sequence support is not enabled; could not parse escape sequence '{}' at column {}",
-        segment, index,
+        "named sequence support is not enabled; could not parse escape sequence '{}' at {}",
+        segment, span,
     )]
-    NamedEscapeSequence { segment: String, index: usize },
+    NamedEscapeSequence { segment: String, span: SnbtSpan },
     /// An unknown or invalid escape sequence.
-    #[error("unknown escape sequence at column {index}: '{segment}'")]
-    UnknownEscapeSequence { segment: String, index: usize },
+    #[error("unknown escape sequence at {span}: '{segment}'")]
+    UnknownEscapeSequence { segment: String, span: SnbtSpan },
     /// A non-alphanumeric character other than `_`, `-`, `.`, or `+`
     /// appeared in an unquoted string.
-    #[error("character '{ch}' disallowed in unquoted strings at column {index} near '{segment}'")]
+    #[error("character '{ch}' disallowed in unquoted strings at {span} near '{segment}'")]
     InvalidUnquotedCharacter {
         segment: String,
-        index:   usize,
+        span:    SnbtSpan,
         /// The encountered character which should not appear in unquoted strings.
         ch:      char,
     },
     /// A `ByteString` was encountered but they were not enabled
     #[error(
-        "the enable_byte_strings setting was false, but a ByteString occurred at column {}, \
+        "the enable_byte_strings setting was false, but a ByteString occurred at {}, \
         beginning with {}",
-        index, segment,
+        span, segment,
     )]
     ByteString {
         segment: String,
-        index:   usize,
+        span:    SnbtSpan,
     },
     /// An invalid number.
     #[error(
-        "numeric literal at column {} was invalid because {}. Literal began with '{}'",
-        index, cause, segment,
+        "numeric literal at {} was invalid because {}. Literal began with '{}'",
+        span, cause, segment,
     )]
     InvalidNumber {
         segment: String,
-        index:   usize,
+        span:    SnbtSpan,
         cause:   NumericParseError,
     },
     /// An invalid string representation of a UUID.
-    #[error("invalid string representation of a UUID at column {index}: '{segment}'")]
-    InvalidUUID { segment: String, index: usize },
+    #[error("invalid string representation of a UUID at {span}: '{segment}'")]
+    InvalidUUID { segment: String, span: SnbtSpan },
     /// An unquoted token which could be numeric or a string,
     /// which was prohibited in parsing options.
-    #[error("ambiguous token '{segment}' at column {index}")]
-    AmbiguousToken { segment: String, index: usize },
+    #[error("ambiguous token '{segment}' at {span}")]
+    AmbiguousToken { segment: String, span: SnbtSpan },
     /// A trailing comma was encountered in a list or compound when it shouldn't have been.
-    #[error("forbidden trailing comma at column {index}: '{segment}'")]
-    TrailingComma { segment: String, index: usize },
+    #[error("forbidden trailing comma at {span}: '{segment}'")]
+    TrailingComma { segment: String, span: SnbtSpan },
     /// An unmatched single or double quote was encountered.
-    #[error("unmatched quote at column {index} near '{segment}'")]
-    UnmatchedQuote { segment: String, index: usize },
+    #[error("unmatched quote at {span} near '{segment}'")]
+    UnmatchedQuote { segment: String, span: SnbtSpan },
     /// An unmatched curly bracket, square bracket, or parenthesis was encountered.
-    #[error("unmatched brace at column {index} near '{segment}'")]
-    UnmatchedBrace { segment: String, index: usize },
+    #[error("unmatched brace at {span} near '{segment}'")]
+    UnmatchedBrace { segment: String, span: SnbtSpan },
     /// A non-homogenous array of numbers was encountered.
-    #[error("non-homogenous typed array of numbers at column {index} near '{segment}'")]
-    NonHomogenousNumericList { segment: String, index: usize },
+    #[error("non-homogenous typed array of numbers at {span} near '{segment}'")]
+    NonHomogenousNumericList { segment: String, span: SnbtSpan },
     /// A non-homogenous array of NBT tags was encountered.
     #[error(
-        "non-homogenous tag list (only supported in new SNBT version) at column {} near '{}'",
-        index, segment,
+        "non-homogenous tag list (only supported in new SNBT version) at {} near '{}'",
+        span, segment,
     )]
-    NonHomogenousTagList { segment: String, index: usize },
+    NonHomogenousTagList { segment: String, span: SnbtSpan },
 }
 
 impl SnbtError {
+    /// The span of input where the error occurred.
+    pub fn span(&self) -> SnbtSpan {
+        match self {
+            Self::ExceededDepthLimit { span, .. }
+            | Self::ExceededMemoryBudget { span, .. }
+            | Self::UnexpectedEOS { span, .. }
+            | Self::UnexpectedToken { span, .. }
+            | Self::UnsupportedEscapeSequence { span, .. }
+            | Self::NamedEscapeSequence { span, .. }
+            | Self::UnknownEscapeSequence { span, .. }
+            | Self::InvalidUnquotedCharacter { span, .. }
+            | Self::ByteString { span, .. }
+            | Self::InvalidNumber { span, .. }
+            | Self::InvalidUUID { span, .. }
+            | Self::AmbiguousToken { span, .. }
+            | Self::TrailingComma { span, .. }
+            | Self::UnmatchedQuote { span, .. }
+            | Self::UnmatchedBrace { span, .. }
+            | Self::NonHomogenousNumericList { span, .. }
+            | Self::NonHomogenousTagList { span, .. } => *span,
+        }
+    }
+
+    /// The byte index into the input where the error occurred, which is the start of
+    /// its [`span`].
+    ///
+    /// [`span`]: SnbtError::span
+    #[deprecated(note = "the `index` field of each variant was replaced by `span`; \
+                         use `span().start.index` instead")]
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.span().start.index
+    }
+
+    /// The tokens which could have been valid where the error occurred, if the error
+    /// is from an unexpected token or the input ending early.
+    pub fn expected(&self) -> Option<ExpectedTokens> {
+        match self {
+            Self::UnexpectedEOS { expected, .. } | Self::UnexpectedToken { expected, .. } => {
+                Some(*expected)
+            }
+            _ => None,
+        }
+    }
+
+    /// Renders a report of this error, which shows the line of the given input where the error
+    /// occurred and underlines the error's span. The input should be the same string which
+    /// was parsed.
+    ///
+    /// # Examples:
+    /// ```
+    /// use prismarine_anchor_nbt::snbt;
+    ///
+    /// let err = snbt::parse_any_updated("{\n  a: 1,\n  b 2\n}").unwrap_err();
+    /// assert!(err.report("{\n  a: 1,\n  b 2\n}").ends_with("3 |   b 2\n  |     ^"));
+    /// ```
+    pub fn report(&self, input: &str) -> String {
+        diagnostic::render_report(input, &self.to_string(), self.span())
+    }
+
     fn exceeded_depth_limit(input: &str, index: usize, limit: DepthLimit) -> Self {
         Self::ExceededDepthLimit {
             segment: Self::segment(input, index, 1, 4, 4),
-            index,
+            span:    SnbtSpan::new(input, index, 1),
             limit,
         }
     }
 
-    fn unexpected_eos(expected: &'static str) -> Self {
-        Self::UnexpectedEOS { expected }
+    fn exceeded_memory_budget(input: &str, index: usize, budget: MemoryBudget) -> Self {
+        Self::ExceededMemoryBudget {
+            segment: Self::segment(input, index, 1, 4, 4),
+            span:    SnbtSpan::new(input, index, 1),
+            budget,
+        }
+    }
+
+    fn unexpected_eos(input: &str, expected: &'static [&'static str]) -> Self {
+        Self::UnexpectedEOS {
+            span:     SnbtSpan::end_of_input(input),
+            expected: ExpectedTokens(expected),
+        }
     }
 
-    fn unexpected_token(input: &str, token: Option<&TokenData>, expected: &'static str) -> Self {
+    fn unexpected_token(
+        input:    &str,
+        token:    Option<&TokenData>,
+        expected: &'static [&'static str],
+    ) -> Self {
         match token {
             Some(token) => {
                 Self::unexpected_token_at(input, token.index, token.char_width, expected)
             }
-            None => Self::unexpected_eos(expected),
+            None => Self::unexpected_eos(input, expected),
         }
     }
 
@@ -726,19 +1043,19 @@ impl SnbtError {
         input:      &str,
         index:      usize,
         char_width: usize,
-        expected:   &'static str,
+        expected:   &'static [&'static str],
     ) -> Self {
         Self::UnexpectedToken {
-            segment: Self::segment(input, index, char_width, 15, 0),
-            index,
-            expected,
+            segment:  Self::segment(input, index, char_width, 15, 0),
+            span:     SnbtSpan::new(input, index, char_width),
+            expected: ExpectedTokens(expected),
         }
     }
 
     fn unsupported_escape_sequence(input: &str, index: usize, char_width: usize) -> Self {
         Self::UnsupportedEscapeSequence {
             segment: Self::segment(input, index, char_width, 0, 0),
-            index,
+            span:    SnbtSpan::new(input, index, char_width),
         }
     }
 
@@ -746,21 +1063,21 @@ impl SnbtError {
     fn named_escape_sequence(input: &str, index: usize, char_width: usize) -> Self {
         Self::NamedEscapeSequence {
             segment: Self::segment(input, index, char_width, 0, 0),
-            index,
+            span:    SnbtSpan::new(input, index, char_width),
         }
     }
 
     fn unknown_escape_sequence(input: &str, index: usize, char_width: usize) -> Self {
         Self::UnknownEscapeSequence {
             segment: Self::segment(input, index, char_width, 0, 0),
-            index,
+            span:    SnbtSpan::new(input, index, char_width),
         }
     }
 
     fn invalid_unquoted_character(input: &str, index: usize, char_width: usize, ch: char) -> Self {
         Self::InvalidUnquotedCharacter {
             segment: Self::segment(input, index, char_width, 10, 5),
-            index,
+            span:    SnbtSpan::new(input, index, char_width),
             ch,
         }
     }
@@ -768,7 +1085,7 @@ impl SnbtError {
     fn byte_string(input: &str, index: usize, char_width: usize) -> Self {
         Self::ByteString {
             segment: Self::segment(input, index, char_width, 1, 10),
-            index,
+            span:    SnbtSpan::new(input, index, char_width),
         }
     }
 
@@ -780,7 +1097,7 @@ impl SnbtError {
     ) -> Self {
         Self::InvalidNumber {
             segment: Self::segment(input, index, char_width, 0, 0),
-            index,
+            span:    SnbtSpan::new(input, index, char_width),
             cause,
         }
     }
@@ -788,49 +1105,49 @@ impl SnbtError {
     fn invalid_uuid(input: &str, index: usize, char_width: usize) -> Self {
         Self::InvalidUUID {
             segment: Self::segment(input, index, char_width, 0, 0),
-            index,
+            span:    SnbtSpan::new(input, index, char_width),
         }
     }
 
     fn ambiguous_token(input: &str, index: usize, char_width: usize) -> Self {
         Self::AmbiguousToken {
             segment: Self::segment(input, index, char_width, 0, 0),
-            index,
+            span:    SnbtSpan::new(input, index, char_width),
         }
     }
 
     fn trailing_comma(input: &str, index: usize) -> Self {
         Self::TrailingComma {
             segment: Self::segment(input, index, 1, 15, 1),
-            index,
+            span:    SnbtSpan::new(input, index, 1),
         }
     }
 
     fn unmatched_quote(input: &str, index: usize) -> Self {
         Self::UnmatchedQuote {
             segment: Self::segment(input, index, 1, 7, 7),
-            index,
+            span:    SnbtSpan::new(input, index, 1),
         }
     }
 
     fn unmatched_brace(input: &str, index: usize) -> Self {
         Self::UnmatchedBrace {
             segment: Self::segment(input, index, 1, 0, 15),
-            index,
+            span:    SnbtSpan::new(input, index, 1),
         }
     }
 
     fn non_homogenous_numeric_list(input: &str, index: usize, char_width: usize) -> Self {
         Self::NonHomogenousNumericList {
             segment: Self::segment(input, index, char_width, 15, 0),
-            index,
+            span:    SnbtSpan::new(input, index, char_width),
         }
     }
 
     fn non_homogenous_tag_list(input: &str, index: usize, char_width: usize) -> Self {
         Self::NonHomogenousTagList {
             segment: Self::segment(input, index, char_width, 15, 0),
-            index,
+            span:    SnbtSpan::new(input, index, char_width),
         }
     }
 
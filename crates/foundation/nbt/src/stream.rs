@@ -0,0 +1,753 @@
+//! Event-based reading of binary NBT data, without materializing an entire NBT tree.
+//!
+//! An [`NbtReader`] yields [`NbtEvent`]s one at a time, and allows callers to skip over
+//! tags (including entire compound and list subtrees) without allocating them. This is useful
+//! when only a few fields are needed from a large NBT file.
+//!
+//! The tree readers of the [`io`] module, such as [`io::read_compound`], are built on
+//! [`NbtReader::read_value`].
+//!
+//! [`io`]: crate::io
+//! [`io::read_compound`]: crate::io::read_compound
+
+#[cfg(feature = "async_io")]
+pub(crate) mod scan;
+
+
+use std::io;
+use std::io::Read;
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+
+use crate::{io::read_string, raw::{self, BudgetTracker}};
+use crate::{
+    io::NbtIoError,
+    raw::{
+        BYTE_ARRAY_ID, BYTE_ID, COMPOUND_ID, DOUBLE_ID, FLOAT_ID, INT_ARRAY_ID,
+        INT_ID, LIST_ID, LONG_ID, LONG_ARRAY_ID, SHORT_ID, STRING_ID, TAG_END_ID,
+    },
+    settings::{IoOptions, NbtCompression},
+    tag::{NbtCompound, NbtList, NbtTag, NbtType},
+};
+
+
+// ================================
+//  Events
+// ================================
+
+/// A single event produced while reading NBT data with an [`NbtReader`].
+///
+/// The events for a compound are a `CompoundStart`, followed by a `Name` event and
+/// the events of the named tag for each entry, followed by a `CompoundEnd`. The events for a list
+/// are a `ListStart`, followed by the events of each element, followed by a `ListEnd`.
+/// Any other tag is a single `Value` event.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtEvent {
+    /// The name of the next tag, which is either a named root tag or an entry of a compound.
+    Name {
+        name:     String,
+        /// The type of the named tag.
+        tag_type: NbtType,
+    },
+    /// The start of a compound tag.
+    CompoundStart,
+    /// The end of the most recently started compound tag.
+    CompoundEnd,
+    /// The start of a list tag.
+    ListStart {
+        /// The type of the elements in the list, or `None` if the list is empty
+        /// and has the `TAG_End` element type.
+        element_type: Option<NbtType>,
+        /// The number of elements in the list.
+        len:          usize,
+    },
+    /// The end of the most recently started list tag.
+    ListEnd,
+    /// A tag which is neither a compound nor a list, such as a numeric tag, string,
+    /// or numeric array.
+    Value(NbtTag),
+}
+
+// ================================
+//  Reader
+// ================================
+
+/// A pull-parser for binary NBT data, which yields [`NbtEvent`]s.
+///
+/// The reader honors every field of the provided [`IoOptions`], including compression,
+/// endianness, string encoding, `ByteString` support, the depth limit, and the memory budget.
+/// Since the reader keeps track of nesting with an explicit stack, it does not recurse,
+/// even when skipping deeply-nested subtrees.
+///
+/// The memory budget covers every name and value read by the reader over the whole stream,
+/// rather than each value separately. Skipped tags are not charged to the budget.
+///
+/// An error from the underlying reader or from invalid data ends the stream of events;
+/// any further calls to [`next_event`] return `Ok(None)`.
+///
+/// [`next_event`]: NbtReader::next_event
+#[derive(Debug)]
+pub struct NbtReader<R> {
+    reader:     Decompressor<R>,
+    opts:       IoOptions,
+    state:      ReaderState,
+    stack:      Vec<Frame>,
+    budget:     BudgetTracker,
+    /// The depth of the tag which the reader starts at, which is nonzero only for readers
+    /// constructed by `skip_tag_body`.
+    base_depth: u32,
+}
+
+impl<R: Read> NbtReader<R> {
+    /// Creates a reader for NBT data with a named root compound tag,
+    /// as read by [`io::read_compound`].
+    ///
+    /// The first event is the `Name` of the root tag.
+    ///
+    /// [`io::read_compound`]: crate::io::read_compound
+    pub fn new(reader: R, opts: IoOptions) -> Self {
+        Self::with_root(reader, opts, RootKind::NamedCompound)
+    }
+
+    /// Creates a reader for NBT data with a named root tag of any type,
+    /// as read by [`io::read_any_nbt`].
+    ///
+    /// The first event is the `Name` of the root tag.
+    ///
+    /// [`io::read_any_nbt`]: crate::io::read_any_nbt
+    #[cfg(feature = "allow_any_root")]
+    pub fn new_any_root(reader: R, opts: IoOptions) -> Self {
+        Self::with_root(reader, opts, RootKind::Named)
+    }
+
+    /// Creates a reader for NBT data with a named root list tag,
+    /// as read by [`io::read_list`].
+    ///
+    /// The first event is the `Name` of the root tag.
+    ///
+    /// [`io::read_list`]: crate::io::read_list
+    #[cfg(feature = "allow_any_root")]
+    pub fn new_list(reader: R, opts: IoOptions) -> Self {
+        Self::with_root(reader, opts, RootKind::NamedList)
+    }
+
+    /// Creates a reader for NBT data with an unnamed root tag of any type,
+    /// as read by [`io::read_any_unnamed_nbt`].
+    ///
+    /// [`io::read_any_unnamed_nbt`]: crate::io::read_any_unnamed_nbt
+    #[cfg(feature = "allow_any_root")]
+    pub fn new_unnamed(reader: R, opts: IoOptions) -> Self {
+        Self::with_root(reader, opts, RootKind::Unnamed)
+    }
+
+    fn with_root(reader: R, opts: IoOptions, root: RootKind) -> Self {
+        Self {
+            reader:     Decompressor::new(reader, opts.compression),
+            opts,
+            state:      ReaderState::Root(root),
+            stack:      Vec::new(),
+            budget:     BudgetTracker::new(opts.memory_budget),
+            base_depth: 0,
+        }
+    }
+
+    /// The nesting depth of the innermost compound or list tag which has been started
+    /// but not ended, with the root tag having a depth of zero.
+    /// Returns `None` if no compound or list tag is open.
+    #[inline]
+    pub fn depth(&self) -> Option<u32> {
+        // The stack can't be longer than the depth limit, which is a u32
+        self.stack
+            .len()
+            .checked_sub(1)
+            .map(|depth| depth as u32)
+    }
+
+    /// Reads the next event, or returns `None` if the root tag has been entirely read.
+    pub fn next_event(&mut self) -> Result<Option<NbtEvent>, NbtIoError> {
+        let step = self.step(StepMode::Read);
+        self.finish_on_error(step).map(|step| step.into_event())
+    }
+
+    /// Skips the next tag without allocating it. If the next event would be a `Name`,
+    /// the name and its tag are both skipped.
+    ///
+    /// Returns `false` if nothing was skipped, because the next event would be the end
+    /// of a compound or list, or because the root tag has already been read.
+    pub fn skip_value(&mut self) -> Result<bool, NbtIoError> {
+        let skipped = self.skip_value_impl();
+        self.finish_on_error(skipped)
+    }
+
+    /// Skips the remaining contents of the innermost compound or list tag which has been
+    /// started but not ended, including its `CompoundEnd` or `ListEnd` event.
+    ///
+    /// Does nothing if no compound or list tag is open.
+    pub fn skip_to_end(&mut self) -> Result<(), NbtIoError> {
+        let skipped = self.skip_to_depth(self.stack.len().saturating_sub(1));
+        self.finish_on_error(skipped)
+    }
+
+    /// Reads the next tag, including any compound or list subtree, into an owned [`NbtTag`].
+    /// If the next event would be a `Name`, the name is read and discarded;
+    /// use [`next_event`] first if the name is needed.
+    ///
+    /// Returns `None` if the next event would be the end of a compound or list, or if the
+    /// root tag has already been read.
+    ///
+    /// As in [`io`], heterogenous lists are unwrapped if `unwrap_heterogenous_lists` is enabled
+    /// in the `IoOptions`, whereas events describe lists exactly as they are stored.
+    ///
+    /// [`next_event`]: NbtReader::next_event
+    /// [`io`]: crate::io
+    pub fn read_value(&mut self) -> Result<Option<NbtTag>, NbtIoError> {
+        let tag = self.read_value_impl();
+        self.finish_on_error(tag)
+    }
+
+    fn finish_on_error<T>(&mut self, result: Result<T, NbtIoError>) -> Result<T, NbtIoError> {
+        if result.is_err() {
+            self.state = ReaderState::Finished;
+            self.stack.clear();
+        }
+        result
+    }
+
+    fn skip_value_impl(&mut self) -> Result<bool, NbtIoError> {
+        loop {
+            match self.step(StepMode::Skip)? {
+                Step::Name { .. } => {}
+                Step::CompoundStart | Step::ListStart { .. } => {
+                    self.skip_to_depth(self.stack.len() - 1)?;
+                    return Ok(true);
+                }
+                Step::Value(_) => return Ok(true),
+                Step::CompoundEnd | Step::ListEnd => {
+                    // The end event should not be consumed
+                    self.unread_end();
+                    return Ok(false);
+                }
+                Step::Finished => return Ok(false),
+            }
+        }
+    }
+
+    /// Skips events until the stack has the given length after a container has ended.
+    fn skip_to_depth(&mut self, target_len: usize) -> Result<(), NbtIoError> {
+        while self.stack.len() > target_len {
+            if matches!(self.step(StepMode::Skip)?, Step::Finished) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_value_impl(&mut self) -> Result<Option<NbtTag>, NbtIoError> {
+        // Each open container, paired with the name of the entry it will be inserted into
+        // if the container is a compound, and whether the container is a list whose
+        // elements should be unwrapped with `NbtList::unwrap_heterogenous`.
+        let mut containers: Vec<(Option<String>, NbtTag, bool)> = Vec::new();
+        let mut pending_name = None;
+
+        loop {
+            let tag = match self.step(StepMode::Read)? {
+                Step::Name { name, .. } => {
+                    pending_name = name;
+                    continue;
+                }
+                Step::CompoundStart => {
+                    let compound = NbtTag::Compound(NbtCompound::new());
+                    containers.push((pending_name.take(), compound, false));
+                    continue;
+                }
+                Step::ListStart { element_type, len } => {
+                    self.budget.charge_tags(len)?;
+                    let list = NbtTag::List(NbtList::with_capacity(len));
+                    // Heterogenous lists are stored as lists of compounds, as in `io`
+                    let unwrap = self.opts.unwrap_heterogenous_lists
+                        && element_type == Some(NbtType::Compound);
+                    containers.push((pending_name.take(), list, unwrap));
+                    continue;
+                }
+                Step::Value(tag) => tag.expect("values are read in StepMode::Read"),
+                Step::CompoundEnd | Step::ListEnd => {
+                    if let Some((name, mut container, unwrap)) = containers.pop() {
+                        if unwrap {
+                            if let NbtTag::List(list) = &mut container {
+                                list.unwrap_heterogenous();
+                            }
+                        }
+                        pending_name = name;
+                        container
+                    } else {
+                        // We were at the end of a container before reading anything.
+                        // Put the end event back.
+                        self.unread_end();
+                        return Ok(None);
+                    }
+                }
+                Step::Finished => return Ok(None),
+            };
+
+            match containers.last_mut() {
+                Some((_, NbtTag::Compound(compound), _)) => {
+                    self.budget.charge_tags(1)?;
+                    compound.insert(pending_name.take().unwrap_or_default(), tag);
+                }
+                Some((_, NbtTag::List(list), _)) => list.push(tag),
+                Some(_) => unreachable!("only compounds and lists are pushed as containers"),
+                None => return Ok(Some(tag)),
+            }
+        }
+    }
+
+    /// Undo the most recent `CompoundEnd` or `ListEnd` step, whose frame is recorded in
+    /// `self.state` by `step`.
+    fn unread_end(&mut self) {
+        if let ReaderState::AfterEnd(frame) = self.state {
+            self.state = match frame {
+                Frame::Compound    => ReaderState::CompoundEndRead,
+                Frame::List { .. } => ReaderState::Container,
+            };
+            self.stack.push(frame);
+        }
+    }
+
+    fn step(&mut self, mode: StepMode) -> Result<Step, NbtIoError> {
+        loop {
+            match self.state {
+                ReaderState::Finished => return Ok(Step::Finished),
+                ReaderState::Root(root) => {
+                    let tag_id = raw::read_u8(&mut self.reader, self.opts)?;
+
+                    let expected_id = match root {
+                        RootKind::NamedCompound => Some(COMPOUND_ID),
+                        #[cfg(feature = "allow_any_root")]
+                        RootKind::NamedList => Some(LIST_ID),
+                        #[cfg(feature = "allow_any_root")]
+                        RootKind::Named | RootKind::Unnamed => None,
+                    };
+                    if let Some(expected) = expected_id {
+                        if tag_id != expected {
+                            return Err(NbtIoError::TagTypeMismatch {
+                                expected,
+                                found: tag_id,
+                            });
+                        }
+                    }
+
+                    let tag_type = raw::type_for_id(tag_id)
+                        .ok_or(NbtIoError::InvalidTagId(tag_id))?;
+                    self.state = ReaderState::Value(tag_id);
+
+                    #[cfg(feature = "allow_any_root")]
+                    if matches!(root, RootKind::Unnamed) {
+                        continue;
+                    }
+
+                    let name = self.read_or_skip_name(mode)?;
+                    return Ok(Step::Name { name, tag_type });
+                }
+                ReaderState::Value(tag_id) => {
+                    self.state = ReaderState::Container;
+                    return self.read_value_body(tag_id, mode);
+                }
+                ReaderState::CompoundEndRead => {
+                    self.stack.pop();
+                    self.state = ReaderState::AfterEnd(Frame::Compound);
+                    return Ok(Step::CompoundEnd);
+                }
+                ReaderState::Container | ReaderState::AfterEnd(_) => {
+                    let current_depth = self.stack.len().saturating_sub(1);
+
+                    match self.stack.last_mut() {
+                        None => {
+                            self.state = ReaderState::Finished;
+                            return Ok(Step::Finished);
+                        }
+                        Some(Frame::Compound) => {
+                            let tag_id = raw::read_u8(&mut self.reader, self.opts)?;
+
+                            if tag_id == TAG_END_ID {
+                                self.stack.pop();
+                                self.state = ReaderState::AfterEnd(Frame::Compound);
+                                return Ok(Step::CompoundEnd);
+                            }
+
+                            self.check_depth(current_depth)?;
+                            let tag_type = raw::type_for_id(tag_id)
+                                .ok_or(NbtIoError::InvalidTagId(tag_id))?;
+
+                            let name = self.read_or_skip_name(mode)?;
+                            self.state = ReaderState::Value(tag_id);
+                            return Ok(Step::Name { name, tag_type });
+                        }
+                        Some(&mut Frame::List { remaining: 0, element_id }) => {
+                            self.stack.pop();
+                            self.state = ReaderState::AfterEnd(Frame::List {
+                                element_id,
+                                remaining: 0,
+                            });
+                            return Ok(Step::ListEnd);
+                        }
+                        Some(Frame::List { element_id, remaining }) => {
+                            *remaining -= 1;
+                            self.state = ReaderState::Value(*element_id);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reads or skips the body of a tag whose ID has already been read,
+    /// pushing a frame onto the stack if the tag is a compound or list.
+    fn read_value_body(&mut self, tag_id: u8, mode: StepMode) -> Result<Step, NbtIoError> {
+        match tag_id {
+            COMPOUND_ID => {
+                self.stack.push(Frame::Compound);
+                Ok(Step::CompoundStart)
+            }
+            LIST_ID => {
+                let element_id = raw::read_u8(&mut self.reader, self.opts)?;
+                let len = raw::read_i32_as_usize(&mut self.reader, self.opts)?;
+
+                // Make sure we don't have an invalid type or a nonempty list of TAG_End
+                if element_id > LONG_ARRAY_ID || (element_id == TAG_END_ID && len > 0) {
+                    return Err(NbtIoError::InvalidTagId(element_id));
+                }
+
+                if len > 0 {
+                    self.check_depth(self.stack.len())?;
+                }
+
+                self.stack.push(Frame::List {
+                    element_id,
+                    remaining: len,
+                });
+                Ok(Step::ListStart {
+                    element_type: raw::type_for_id(element_id),
+                    len,
+                })
+            }
+            _ => match mode {
+                StepMode::Read => Ok(Step::Value(Some(self.read_value_tag(tag_id)?))),
+                StepMode::Skip => {
+                    self.skip_value_body(tag_id)?;
+                    Ok(Step::Value(None))
+                }
+            },
+        }
+    }
+
+    /// Reads the body of a tag which is neither a compound nor a list.
+    fn read_value_tag(&mut self, tag_id: u8) -> Result<NbtTag, NbtIoError> {
+        let reader = &mut self.reader;
+        let opts = self.opts;
+        let budget = &self.budget;
+
+        Ok(match tag_id {
+            BYTE_ID   => NbtTag::Byte(  raw::read_i8( reader, opts)?),
+            SHORT_ID  => NbtTag::Short( raw::read_i16(reader, opts)?),
+            INT_ID    => NbtTag::Int(   raw::read_i32(reader, opts)?),
+            LONG_ID   => NbtTag::Long(  raw::read_i64(reader, opts)?),
+            FLOAT_ID  => NbtTag::Float( raw::read_f32(reader, opts)?),
+            DOUBLE_ID => NbtTag::Double(raw::read_f64(reader, opts)?),
+            BYTE_ARRAY_ID => {
+                let len = raw::read_i32_as_usize(reader, opts)?;
+                budget.charge_array(BYTE_ID, len)?;
+                let mut array = vec![0_u8; len];
+
+                reader.read_exact(&mut array)?;
+
+                NbtTag::ByteArray(raw::cast_byte_buf_to_signed(array))
+            }
+            STRING_ID => {
+                let len = raw::read_string_len(reader, opts)?;
+                budget.charge(len, 0)?;
+
+                if opts.enable_byte_strings {
+                    raw::read_string_or_bytes_body(reader, opts, len)?
+                } else {
+                    NbtTag::String(raw::read_string_body(reader, opts, len)?)
+                }
+            }
+            INT_ARRAY_ID => {
+                let len = raw::read_i32_as_usize(reader, opts)?;
+                budget.charge_array(INT_ID, len)?;
+                NbtTag::IntArray(raw::read_i32_array(reader, opts, len)?)
+            }
+            LONG_ARRAY_ID => {
+                let len = raw::read_i32_as_usize(reader, opts)?;
+                budget.charge_array(LONG_ID, len)?;
+                NbtTag::LongArray(raw::read_i64_array(reader, opts, len)?)
+            }
+            _ => return Err(NbtIoError::InvalidTagId(tag_id)),
+        })
+    }
+
+    fn skip_value_body(&mut self, tag_id: u8) -> Result<(), NbtIoError> {
+        let reader = &mut self.reader;
+        let opts = self.opts;
+
+        match tag_id {
+            BYTE_ID   => raw::skip_bytes(reader, 1)?,
+            SHORT_ID  => raw::skip_bytes(reader, 2)?,
+            INT_ID    => {
+                raw::read_i32(reader, opts)?;
+            }
+            LONG_ID   => {
+                raw::read_i64(reader, opts)?;
+            }
+            FLOAT_ID  => raw::skip_bytes(reader, 4)?,
+            DOUBLE_ID => raw::skip_bytes(reader, 8)?,
+            BYTE_ARRAY_ID => {
+                let len = raw::read_i32_as_usize(reader, opts)?;
+                raw::skip_bytes(reader, len)?;
+            }
+            STRING_ID => {
+                let len = raw::read_string_len(reader, opts)?;
+                raw::skip_bytes(reader, len)?;
+            }
+            INT_ARRAY_ID => {
+                let len = raw::read_i32_as_usize(reader, opts)?;
+                raw::skip_i32_array(reader, opts, len)?;
+            }
+            LONG_ARRAY_ID => {
+                let len = raw::read_i32_as_usize(reader, opts)?;
+                raw::skip_i64_array(reader, opts, len)?;
+            }
+            _ => return Err(NbtIoError::InvalidTagId(tag_id)),
+        }
+
+        Ok(())
+    }
+
+    fn read_or_skip_name(&mut self, mode: StepMode) -> Result<Option<String>, NbtIoError> {
+        match mode {
+            StepMode::Read => read_string(&mut self.reader, self.opts, &self.budget).map(Some),
+            StepMode::Skip => {
+                let len = raw::read_string_len(&mut self.reader, self.opts)?;
+                raw::skip_bytes(&mut self.reader, len)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Check whether a compound or list at the given depth (relative to `self.base_depth`)
+    /// may have elements.
+    #[inline]
+    fn check_depth(&self, container_depth: usize) -> Result<(), NbtIoError> {
+        let container_depth = container_depth + self.base_depth as usize;
+        if container_depth >= self.opts.depth_limit.0 as usize {
+            Err(NbtIoError::ExceededDepthLimit {
+                limit: self.opts.depth_limit,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Reads the body of a tag with the given ID into an owned tag, charging its allocations to
+/// the given budget. The `current_depth` is the nesting depth of that tag, with the root tag
+/// having a depth of zero.
+///
+/// This is the tree reader which the functions of `io` are built on.
+pub(crate) fn read_tag_body<R: Read>(
+    reader:        R,
+    opts:          IoOptions,
+    budget:        &BudgetTracker,
+    tag_id:        u8,
+    current_depth: u32,
+) -> Result<NbtTag, NbtIoError> {
+    let mut tree_reader = NbtReader {
+        reader:     Decompressor::Uncompressed(reader),
+        opts,
+        state:      ReaderState::Value(tag_id),
+        stack:      Vec::new(),
+        budget:     budget.clone(),
+        base_depth: current_depth,
+    };
+    let tag = tree_reader.read_value_impl();
+    budget.record_usage(&tree_reader.budget);
+
+    // The first step reads the body of the tag, so the tag can't be missing.
+    Ok(tag?.expect("NbtReader reads the body of a tag"))
+}
+
+/// Reads past the body of a tag with the given ID, without allocating it. The `current_depth`
+/// is the nesting depth of that tag, as in `read_tag_body`.
+pub(crate) fn skip_tag_body<R: Read>(
+    reader:        R,
+    opts:          IoOptions,
+    tag_id:        u8,
+    current_depth: u32,
+) -> Result<(), NbtIoError> {
+    let mut skipper = NbtReader {
+        reader:     Decompressor::Uncompressed(reader),
+        opts,
+        state:      ReaderState::Value(tag_id),
+        stack:      Vec::new(),
+        budget:     BudgetTracker::new(opts.memory_budget),
+        base_depth: current_depth,
+    };
+    skipper.skip_value_impl().map(|_| ())
+}
+
+impl<R: Read> Iterator for NbtReader<R> {
+    type Item = Result<NbtEvent, NbtIoError>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+// ================================
+//  Internal state
+// ================================
+
+#[derive(Debug, Clone, Copy)]
+enum RootKind {
+    NamedCompound,
+    #[cfg(feature = "allow_any_root")]
+    NamedList,
+    #[cfg(feature = "allow_any_root")]
+    Named,
+    #[cfg(feature = "allow_any_root")]
+    Unnamed,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ReaderState {
+    /// The root tag's header has not yet been read.
+    Root(RootKind),
+    /// The next data to read is the body of a tag with the given ID.
+    Value(u8),
+    /// The next data to read is determined by the innermost open container, if any.
+    Container,
+    /// Same as `Container`, but the last step ended the given container.
+    AfterEnd(Frame),
+    /// The `TAG_End` of the innermost open compound was read,
+    /// but its `CompoundEnd` event has not been emitted.
+    CompoundEndRead,
+    Finished,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Frame {
+    Compound,
+    List {
+        element_id: u8,
+        remaining:  usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum StepMode {
+    Read,
+    Skip,
+}
+
+/// Similar to `NbtEvent`, but names and values are `None` when skipped.
+enum Step {
+    Name {
+        name:     Option<String>,
+        tag_type: NbtType,
+    },
+    CompoundStart,
+    CompoundEnd,
+    ListStart {
+        element_type: Option<NbtType>,
+        len:          usize,
+    },
+    ListEnd,
+    Value(Option<NbtTag>),
+    Finished,
+}
+
+impl Step {
+    fn into_event(self) -> Option<NbtEvent> {
+        Some(match self {
+            Self::Name { name, tag_type } => NbtEvent::Name {
+                name: name.unwrap_or_default(),
+                tag_type,
+            },
+            Self::CompoundStart => NbtEvent::CompoundStart,
+            Self::CompoundEnd   => NbtEvent::CompoundEnd,
+            Self::ListStart { element_type, len } => NbtEvent::ListStart { element_type, len },
+            Self::ListEnd       => NbtEvent::ListEnd,
+            Self::Value(tag)    => NbtEvent::Value(tag?),
+            Self::Finished      => return None,
+        })
+    }
+}
+
+/// Applies the decompression indicated by [`NbtCompression`] to a reader.
+#[derive(Debug)]
+enum Decompressor<R> {
+    Uncompressed(R),
+    Zlib(ZlibDecoder<R>),
+    Gzip(GzDecoder<R>),
+}
+
+impl<R: Read> Decompressor<R> {
+    fn new(reader: R, compression: NbtCompression) -> Self {
+        match compression {
+            NbtCompression::Uncompressed => Self::Uncompressed(reader),
+            NbtCompression::ZlibCompressed | NbtCompression::ZlibCompressedWith(_) => {
+                Self::Zlib(ZlibDecoder::new(reader))
+            }
+            NbtCompression::GzipCompressed | NbtCompression::GzipCompressedWith(_) => {
+                Self::Gzip(GzDecoder::new(reader))
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for Decompressor<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Uncompressed(reader) => reader.read(buf),
+            Self::Zlib(reader)         => reader.read(buf),
+            Self::Gzip(reader)         => reader.read(buf),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{io, settings::IoOptions};
+    use crate::tag::{NbtCompound, NbtList, NbtTag};
+    use super::NbtReader;
+
+
+    #[test]
+    fn heterogenous_list_matches_io() {
+        let mut element = NbtCompound::new();
+        element.insert("k", 2_i32);
+        let mut list = NbtList::new();
+        list.push(1_i8);
+        list.push("x");
+        list.push(element);
+        let mut compound = NbtCompound::new();
+        compound.insert("list", list);
+
+        let opts = IoOptions::java_uncompressed();
+        let mut bytes = Vec::new();
+        io::write_compound(&mut bytes, opts, None, &compound).expect("writing to a Vec succeeds");
+
+        let (from_io, _) = io::read_compound(&mut bytes.as_slice(), opts).expect("valid NBT");
+        let from_stream = NbtReader::new(bytes.as_slice(), opts).read_value().expect("valid NBT");
+
+        assert_eq!(from_io, compound, "io should unwrap heterogenous lists");
+        assert_eq!(
+            from_stream,
+            Some(NbtTag::Compound(from_io)),
+            "the stream reader should agree with io",
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Finding the end of NBT data in a buffer which is filled incrementally, as used by `async_io`.
+
+use std::{io, mem};
+use std::io::Read;
+
+use crate::{raw::BudgetTracker, settings::IoOptions};
+use super::{Decompressor, Frame, NbtReader, ReaderState, RootKind, StepMode};
+
+
+/// The minimum number of zeroes which a [`ScanInput`] produces past the end of its data.
+const MIN_OVERRUN: usize = 1024;
+
+/// Data whose end is found by a [`TagScanner`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Scanned {
+    /// The ID and name of a root compound tag.
+    Header,
+    /// The ID, name (if named), and body of a root tag.
+    #[cfg(feature = "allow_any_root")]
+    Root {
+        named: bool,
+    },
+    /// An entry of a compound, or its `TAG_End`, whose tag is at the given depth.
+    Entry {
+        depth: u32,
+    },
+}
+
+/// The result of scanning partially-buffered data.
+pub(crate) enum Scan {
+    /// The scanned data has the given length.
+    Complete(usize),
+    /// At least `needed` bytes of data are needed to find the end of the scanned data.
+    Incomplete {
+        needed: usize,
+    },
+    /// The data is not valid NBT data, or its end cannot be found.
+    Invalid,
+}
+
+/// Finds the end of NBT data in a buffer which is filled incrementally, by skipping the data with
+/// the state machine of an [`NbtReader`]. Each step of the reader is only committed once all of
+/// the data it reads is available, so no committed step is scanned again.
+///
+/// Nothing is allocated apart from the reader's stack; the data is checked only as far as is
+/// needed to find its end, and is otherwise checked when it is parsed.
+pub(crate) struct TagScanner {
+    opts:       IoOptions,
+    scanned:    Scanned,
+    state:      ReaderState,
+    stack:      Vec<Frame>,
+    base_depth: u32,
+    /// Whether a step has been committed.
+    started:    bool,
+    /// The length of the data scanned by committed steps.
+    len:        usize,
+}
+
+impl TagScanner {
+    pub(crate) fn new(opts: IoOptions, scanned: Scanned) -> Self {
+        let (state, stack, base_depth) = match scanned {
+            Scanned::Header => (ReaderState::Root(RootKind::NamedCompound), Vec::new(), 0),
+            #[cfg(feature = "allow_any_root")]
+            Scanned::Root { named: true }  => (ReaderState::Root(RootKind::Named), Vec::new(), 0),
+            #[cfg(feature = "allow_any_root")]
+            Scanned::Root { named: false } => (ReaderState::Root(RootKind::Unnamed), Vec::new(), 0),
+            // The entry's compound is one level shallower than the entry
+            Scanned::Entry { depth } => {
+                (ReaderState::Container, vec![Frame::Compound], depth.saturating_sub(1))
+            }
+        };
+
+        Self {
+            opts,
+            scanned,
+            state,
+            stack,
+            base_depth,
+            started: false,
+            len:     0,
+        }
+    }
+
+    /// Continues scanning the data, which must start with the data previously scanned.
+    pub(crate) fn scan(&mut self, data: &[u8]) -> Scan {
+        while !self.is_done() {
+            let mut input = ScanInput {
+                data,
+                pos:   self.len,
+                limit: data.len().saturating_add(data.len().max(MIN_OVERRUN)),
+            };
+            let state = self.state;
+            let stack_len = self.stack.len();
+            let top = self.stack.last().copied();
+
+            let mut reader = NbtReader {
+                reader:     Decompressor::Uncompressed(&mut input),
+                opts:       self.opts,
+                state,
+                stack:      mem::take(&mut self.stack),
+                budget:     BudgetTracker::new(self.opts.memory_budget),
+                base_depth: self.base_depth,
+            };
+            let result = reader.step(StepMode::Skip);
+            self.state = reader.state;
+            self.stack = reader.stack;
+
+            if input.pos > data.len() {
+                // Undo the step. A step pushes or pops at most one frame,
+                // and otherwise changes at most the innermost frame.
+                self.state = state;
+                self.stack.truncate(stack_len);
+                if let Some(top) = top {
+                    if self.stack.len() < stack_len {
+                        self.stack.push(top);
+                    } else if let Some(last) = self.stack.last_mut() {
+                        *last = top;
+                    }
+                }
+
+                return Scan::Incomplete { needed: input.pos };
+            }
+
+            if result.is_err() {
+                return Scan::Invalid;
+            }
+            self.started = true;
+            self.len = input.pos;
+        }
+
+        Scan::Complete(self.len)
+    }
+
+    fn is_done(&self) -> bool {
+        match self.scanned {
+            Scanned::Header => !matches!(self.state, ReaderState::Root(_)),
+            // Done once the root tag ends
+            #[cfg(feature = "allow_any_root")]
+            Scanned::Root { .. } => self.started && self.at_depth(0),
+            // Done once the entry ends, or once its compound ends
+            Scanned::Entry { .. } => self.started && self.at_depth(1),
+        }
+    }
+
+    /// Whether the reader is not in the middle of a tag, and at most `stack_len` containers
+    /// are open.
+    fn at_depth(&self, stack_len: usize) -> bool {
+        let between_tags = matches!(
+            self.state,
+            ReaderState::Container | ReaderState::AfterEnd(_) | ReaderState::Finished,
+        );
+        between_tags && self.stack.len() <= stack_len
+    }
+}
+
+/// The buffered data given to a [`TagScanner`]. Reading past the end of the data produces
+/// zeroes, up to `limit`, so that the position reached by a step is known even if its data
+/// is incomplete.
+///
+/// For valid NBT data, that position is never past the end of the step's data: every length
+/// partially or wholly read from the zeroes is at most the true length, so the step reads at
+/// most as much data as it would have had the data been complete. Since the limit grows with
+/// the data, a step whose data is large is rescanned only a few times before it is complete.
+struct ScanInput<'a> {
+    data:  &'a [u8],
+    pos:   usize,
+    limit: usize,
+}
+
+impl Read for ScanInput<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.data.get(self.pos..).unwrap_or_default();
+        let len = buf.len().min(self.limit.saturating_sub(self.pos));
+        let buffered = len.min(available.len());
+
+        buf[..buffered].copy_from_slice(&available[..buffered]);
+        buf[buffered..len].fill(0);
+        self.pos += len;
+
+        Ok(len)
+    }
+}
@@ -1,14 +1,18 @@
 use std::fmt;
-use std::{borrow::Borrow, fmt::Formatter, hash::Hash, ops::Index};
+use std::{borrow::{Borrow, Cow}, fmt::Formatter, hash::Hash, ops::Index};
 
 use crate::snbt;
 use crate::snbt::SnbtError;
+use crate::coerce::{CoercingCompound, Coercion, NbtCoercionError};
+use crate::hash::{self, CompoundOrder};
+use crate::matching::{self, NumericMatching};
 use crate::{
     repr::{NbtReprError, NbtStructureError},
-    settings::{SnbtParseOptions, SnbtWriteOptions},
+    schema::{CompoundSchema, SchemaViolation},
+    settings::{KeyOrder, SnbtParseOptions, SnbtWriteOptions},
 };
 
-use super::{CompoundWithOptions, Map, NbtTag};
+use super::{CompoundWithOptions, Map, NbtTag, layout::Layout};
 
 
 /// The NBT tag compound type which is essentially just a wrapper for a hash map of string keys
@@ -275,6 +279,99 @@ impl NbtCompound {
         snbt::parse_compound(input, opts)
     }
 
+    /// Checks whether this compound matches the given schema, returning every violation if not.
+    #[inline]
+    pub fn validate(&self, schema: &CompoundSchema) -> Result<(), Vec<SchemaViolation>> {
+        schema.validate(self)
+    }
+
+    /// Computes the canonical hash of this compound, which ignores the order of entries.
+    /// See the [`hash`] module for more information.
+    ///
+    /// [`hash`]: crate::hash
+    #[inline]
+    pub fn canonical_hash(&self) -> u64 {
+        hash::canonical_compound_hash(self, CompoundOrder::Ignored)
+    }
+
+    /// Computes the canonical hash of this compound, with the given treatment of the order
+    /// of entries. See the [`hash`] module for more information.
+    ///
+    /// [`hash`]: crate::hash
+    #[inline]
+    pub fn canonical_hash_with(&self, order: CompoundOrder) -> u64 {
+        hash::canonical_compound_hash(self, order)
+    }
+
+    /// Checks whether this compound contains every entry of `pattern`, as done by Minecraft's
+    /// `nbt={...}` arguments. See the [`matching`] module for more information.
+    ///
+    /// [`matching`]: crate::matching
+    #[inline]
+    pub fn matches(&self, pattern: &Self) -> bool {
+        matching::compound_matches(self, pattern, NumericMatching::Exact)
+    }
+
+    /// Checks whether this compound matches `pattern`, comparing numbers as specified.
+    /// See the [`matching`] module for more information.
+    ///
+    /// [`matching`]: crate::matching
+    #[inline]
+    pub fn matches_with(&self, pattern: &Self, numeric: NumericMatching) -> bool {
+        matching::compound_matches(self, pattern, numeric)
+    }
+
+    /// Returns a view of this compound from which values are read with the given coercion.
+    /// See the [`coerce`] module for more information.
+    ///
+    /// [`coerce`]: crate::coerce
+    #[inline]
+    pub fn coerce(&self, coercion: Coercion) -> CoercingCompound<'_> {
+        CoercingCompound::new(self, coercion)
+    }
+
+    /// Returns the value of the integer tag with the given name, which may be of any integer type
+    /// or an integral floating-point number, if it fits in an `i32`.
+    #[inline]
+    pub fn get_int_lenient(&self, name: &str) -> Result<i32, NbtCoercionError> {
+        self.coerce(Coercion::Lenient).get_int(name)
+    }
+
+    /// Returns the value of the integer tag with the given name, which may be of any integer type
+    /// or an integral floating-point number, if it fits in an `i64`.
+    #[inline]
+    pub fn get_long_lenient(&self, name: &str) -> Result<i64, NbtCoercionError> {
+        self.coerce(Coercion::Lenient).get_long(name)
+    }
+
+    /// Returns the value of the numeric tag with the given name as an `f32`, possibly losing
+    /// precision.
+    #[inline]
+    pub fn get_float_lenient(&self, name: &str) -> Result<f32, NbtCoercionError> {
+        self.coerce(Coercion::Lenient).get_float(name)
+    }
+
+    /// Returns the value of the numeric tag with the given name as an `f64`, possibly losing
+    /// precision.
+    #[inline]
+    pub fn get_double_lenient(&self, name: &str) -> Result<f64, NbtCoercionError> {
+        self.coerce(Coercion::Lenient).get_double(name)
+    }
+
+    /// Returns whether the integer tag with the given name is nonzero, or the value of a
+    /// `"true"` or `"false"` string tag.
+    #[inline]
+    pub fn get_bool(&self, name: &str) -> Result<bool, NbtCoercionError> {
+        self.coerce(Coercion::Lenient).get_bool(name)
+    }
+
+    /// Returns the value of the `String` or `ByteString` tag with the given name, replacing any
+    /// invalid UTF-8 of a `ByteString`.
+    #[inline]
+    pub fn get_str_lenient(&self, name: &str) -> Result<Cow<'_, str>, NbtCoercionError> {
+        self.coerce(Coercion::Lenient).get_str(name)
+    }
+
     /// Used in the `display_and_debug` macro in the tag module
     #[inline]
     pub(super) fn to_formatted_snbt(
@@ -282,13 +379,12 @@ impl NbtCompound {
         f:    &mut Formatter<'_>,
         opts: SnbtWriteOptions,
     ) -> fmt::Result {
-        self.recursively_format_snbt(&mut String::new(), f, 0, opts)
+        self.recursively_format_snbt(&mut Layout::new(f), f, 0, opts)
     }
 
-    #[expect(clippy::write_with_newline)]
     pub(super) fn recursively_format_snbt(
         &self,
-        indent:        &mut String,
+        layout:        &mut Layout,
         f:             &mut Formatter<'_>,
         current_depth: u32,
         opts:          SnbtWriteOptions,
@@ -297,40 +393,50 @@ impl NbtCompound {
             return write!(f, "{{}}");
         }
 
-        if f.alternate() {
-            indent.push_str("    ");
-            write!(f, "{{\n")?;
-        } else {
-            write!(f, "{{")?;
+        if let Some(result) = layout.try_inline(f, opts, |layout, f| {
+            self.recursively_format_snbt(layout, f, current_depth, opts)
+        }) {
+            return result;
+        }
+
+        let mut entries: Vec<(&String, &NbtTag)> = self.0.iter().collect();
+        match opts.key_order {
+            KeyOrder::AsStored     => {}
+            KeyOrder::Alphabetical => entries.sort_unstable_by_key(|&(key, _)| key),
+            KeyOrder::NestedLast   => entries.sort_unstable_by_key(|&(key, value)| {
+                (matches!(value, NbtTag::Compound(_) | NbtTag::List(_)), key)
+            }),
+        }
+
+        let expanded = layout.is_expanded();
+        if expanded {
+            layout.push_indent();
         }
+        write!(f, "{{")?;
 
-        let last_index = self.len() - 1;
-        for (index, (key, value)) in self.0.iter().enumerate() {
+        for (index, (key, value)) in entries.into_iter().enumerate() {
             let key = NbtTag::string_to_snbt(key, opts);
 
-            if f.alternate() {
-                write!(f, "{indent}{key}: ")?;
-            } else {
-                write!(f, "{key}:")?;
+            if index != 0 {
+                write!(f, ",{}", layout.comma_spacing())?;
             }
+            if expanded {
+                write!(f, "\n{}", layout.indent())?;
+            }
+            write!(f, "{key}:{}", layout.colon_spacing())?;
+
+            // The key and `: ` precede the value on its line
+            layout.set_prefix_width(key.chars().count() + 2);
 
             // Conceptually, current_depth is the depth of this Compound itself;
             // its elements are one recursive tag deeper.
             // Note that depth limits are checked in `NbtTag::recursively_format_snbt`
-            value.recursively_format_snbt(indent, f, current_depth + 1, opts)?;
-
-            if index != last_index {
-                if f.alternate() {
-                    write!(f, ",\n")?;
-                } else {
-                    write!(f, ",")?;
-                }
-            }
+            value.recursively_format_snbt(layout, f, current_depth + 1, opts)?;
         }
 
-        if f.alternate() {
-            indent.truncate(indent.len() - 4);
-            write!(f, "\n{indent}}}")
+        if expanded {
+            layout.pop_indent();
+            write!(f, "\n{}}}", layout.indent())
         } else {
             write!(f, "}}")
         }
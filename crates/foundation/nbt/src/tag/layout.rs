@@ -0,0 +1,159 @@
+use std::fmt;
+use std::fmt::{Display, Formatter, Write as _};
+
+use crate::settings::SnbtWriteOptions;
+
+
+/// The number of spaces added to the indentation of each nested line in pretty SNBT.
+const INDENT_WIDTH: usize = 4;
+
+/// Layout state threaded through the functions which format tags as SNBT.
+///
+/// Compact SNBT has no extraneous spacing. Pretty SNBT expands compounds, lists, and arrays
+/// over multiple lines, unless they fit within the remaining line width when written inline.
+#[derive(Debug, Clone)]
+pub(super) struct Layout {
+    mode:   LayoutMode,
+    indent: String,
+    /// The width of whatever precedes the tag being written on its line, other than
+    /// indentation; for instance, the key of a compound entry.
+    prefix_width: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LayoutMode {
+    /// `{a:1,b:[I;1,2]}`
+    Compact,
+    /// `{a: 1, b: [I; 1, 2]}`
+    Inline,
+    /// One compound entry, list element, or row of array elements per line.
+    Expanded,
+}
+
+impl Layout {
+    /// The layout used to begin formatting a tag: pretty if the `#` flag was provided
+    /// to the formatter, and compact otherwise.
+    pub(super) fn new(f: &Formatter<'_>) -> Self {
+        Self {
+            mode:         if f.alternate() { LayoutMode::Expanded } else { LayoutMode::Compact },
+            indent:       String::new(),
+            prefix_width: 0,
+        }
+    }
+
+    fn inline() -> Self {
+        Self {
+            mode:         LayoutMode::Inline,
+            indent:       String::new(),
+            prefix_width: 0,
+        }
+    }
+
+    #[inline]
+    pub(super) fn is_expanded(&self) -> bool {
+        self.mode == LayoutMode::Expanded
+    }
+
+    /// Whether this layout is part of pretty SNBT, as opposed to compact SNBT.
+    #[inline]
+    pub(super) fn is_pretty(&self) -> bool {
+        self.mode != LayoutMode::Compact
+    }
+
+    #[inline]
+    pub(super) fn indent(&self) -> &str {
+        &self.indent
+    }
+
+    #[inline]
+    pub(super) fn push_indent(&mut self) {
+        self.indent.push_str(&" ".repeat(INDENT_WIDTH));
+    }
+
+    #[inline]
+    pub(super) fn pop_indent(&mut self) {
+        self.indent.truncate(self.indent.len().saturating_sub(INDENT_WIDTH));
+    }
+
+    #[inline]
+    pub(super) fn set_prefix_width(&mut self, prefix_width: usize) {
+        self.prefix_width = prefix_width;
+    }
+
+    /// Written after the `:` of a compound entry.
+    #[inline]
+    pub(super) fn colon_spacing(&self) -> &'static str {
+        if self.mode == LayoutMode::Compact { "" } else { " " }
+    }
+
+    /// Written after the `,` between elements, except in expanded layout, in which
+    /// a newline and indentation are written instead.
+    #[inline]
+    pub(super) fn comma_spacing(&self) -> &'static str {
+        if self.mode == LayoutMode::Inline { " " } else { "" }
+    }
+
+    /// The width left on the current line for a tag and a trailing comma.
+    pub(super) fn remaining_width(&self, opts: SnbtWriteOptions) -> usize {
+        opts.line_width
+            .saturating_sub(self.indent.chars().count() + self.prefix_width + 1)
+    }
+
+    /// If the layout is expanded, checks whether the tag written by `write` fits on the rest
+    /// of the current line when written inline. If so, it is written inline and `Some` is
+    /// returned. Otherwise, nothing is written, and `None` is returned.
+    pub(super) fn try_inline<W>(
+        &self,
+        f:     &mut Formatter<'_>,
+        opts:  SnbtWriteOptions,
+        write: W,
+    ) -> Option<fmt::Result>
+    where
+        W: Fn(&mut Self, &mut Formatter<'_>) -> fmt::Result,
+    {
+        if !self.is_expanded() {
+            return None;
+        }
+
+        let mut counter = WidthCounter {
+            width: 0,
+            limit: self.remaining_width(opts),
+        };
+        let inline = FormatWith(|f: &mut Formatter<'_>| write(&mut Self::inline(), f));
+
+        // Writing fails as soon as the limit is exceeded
+        write!(counter, "{inline}").ok()?;
+
+        Some(write(&mut Self::inline(), f))
+    }
+}
+
+/// Counts the characters written to it, and fails once more than `limit` characters
+/// have been written.
+struct WidthCounter {
+    width: usize,
+    limit: usize,
+}
+
+impl fmt::Write for WidthCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.width += s.chars().count();
+        if self.width > self.limit {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Displays the output of the wrapped function.
+struct FormatWith<W>(W);
+
+impl<W> Display for FormatWith<W>
+where
+    W: Fn(&mut Formatter<'_>) -> fmt::Result,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        (self.0)(f)
+    }
+}
@@ -1,14 +1,15 @@
-use std::fmt;
+use std::{fmt, mem};
 use std::fmt::Formatter;
 use std::{
     borrow::{Borrow, BorrowMut},
     ops::{Deref, DerefMut, Index, IndexMut},
 };
 
+use crate::raw;
 use crate::settings::SnbtWriteOptions;
 use crate::repr::{NbtReprError, NbtStructureError};
 
-use super::{ListWithOptions, NbtTag};
+use super::{ListWithOptions, NbtCompound, NbtTag, layout::Layout};
 
 
 /// The NBT tag list type which is essentially just a wrapper for a vec of NBT tags.
@@ -217,6 +218,72 @@ impl NbtList {
         self.0.push(value.into());
     }
 
+    /// Returns true if every element of this list has the same tag type, false otherwise.
+    /// Empty lists are homogenous.
+    ///
+    /// Before Java 1.21.5, all lists needed to be homogenous. Heterogenous lists are stored
+    /// in NBT bytes by wrapping their elements in compounds; see [`wrap_heterogenous`].
+    ///
+    /// [`wrap_heterogenous`]: NbtList::wrap_heterogenous
+    #[inline]
+    pub fn is_homogenous(&self) -> bool {
+        self.element_id().is_some()
+    }
+
+    /// Converts this list into the form used by Java 1.21.5 to store heterogenous lists
+    /// in NBT bytes, which is a list of compounds.
+    ///
+    /// If this list is homogenous and its elements are not compounds, it is left unchanged.
+    /// Otherwise, every element other than a compound is wrapped in a compound, as the value
+    /// of the empty-string key `""`. Compounds whose only key is `""` are wrapped as well,
+    /// so that [`unwrap_heterogenous`] reverses this conversion.
+    ///
+    /// [`unwrap_heterogenous`]: NbtList::unwrap_heterogenous
+    pub fn wrap_heterogenous(&mut self) {
+        if self.element_id().is_some_and(|tag_id| tag_id != raw::COMPOUND_ID) {
+            return;
+        }
+
+        for element in &mut self.0 {
+            if needs_wrapper(element) {
+                let tag = mem::replace(element, NbtTag::Byte(0));
+                let mut wrapper = NbtCompound::with_capacity(1);
+                wrapper.insert("", tag);
+                *element = NbtTag::Compound(wrapper);
+            }
+        }
+    }
+
+    /// Reverses [`wrap_heterogenous`], replacing each compound element whose only key
+    /// is the empty-string key `""` with the value of that key.
+    ///
+    /// [`wrap_heterogenous`]: NbtList::wrap_heterogenous
+    pub fn unwrap_heterogenous(&mut self) {
+        for element in &mut self.0 {
+            if let NbtTag::Compound(compound) = element {
+                if is_wrapper(compound) {
+                    if let Some(tag) = compound.swap_remove_tag("") {
+                        *element = tag;
+                    }
+                }
+            }
+        }
+    }
+
+    /// The ID of the tag type of every element in this list, or `None` if the list is not
+    /// homogenous. Empty lists have the ID of `TAG_End`.
+    pub(crate) fn element_id(&self) -> Option<u8> {
+        let mut elements = self.0.iter();
+        let Some(first) = elements.next() else {
+            return Some(raw::TAG_END_ID);
+        };
+
+        let list_type = raw::id_for_tag(Some(first));
+        elements
+            .all(|element| raw::id_for_tag(Some(element)) == list_type)
+            .then_some(list_type)
+    }
+
     /// Used in the `display_and_debug` macro in the tag module
     #[inline]
     pub(super) fn to_formatted_snbt(
@@ -224,13 +291,12 @@ impl NbtList {
         f:    &mut Formatter<'_>,
         opts: SnbtWriteOptions,
     ) -> fmt::Result {
-        self.recursively_format_snbt(&mut String::new(), f, 0, opts)
+        self.recursively_format_snbt(&mut Layout::new(f), f, 0, opts)
     }
 
-    #[expect(clippy::write_with_newline)]
     pub(super) fn recursively_format_snbt(
         &self,
-        indent:        &mut String,
+        layout:        &mut Layout,
         f:             &mut Formatter<'_>,
         current_depth: u32,
         opts:          SnbtWriteOptions,
@@ -239,41 +305,70 @@ impl NbtList {
             return write!(f, "[]");
         }
 
-        if f.alternate() {
-            indent.push_str("    ");
-            write!(f, "[\n")?;
-        } else {
-            write!(f, "[")?;
+        if let Some(result) = layout.try_inline(f, opts, |layout, f| {
+            self.recursively_format_snbt(layout, f, current_depth, opts)
+        }) {
+            return result;
+        }
+
+        let expanded = layout.is_expanded();
+        if expanded {
+            layout.push_indent();
         }
+        write!(f, "[")?;
 
-        let last_index = self.len() - 1;
         for (index, element) in self.0.iter().enumerate() {
-            if f.alternate() {
-                write!(f, "{indent}")?;
+            if index != 0 {
+                write!(f, ",{}", layout.comma_spacing())?;
+            }
+            if expanded {
+                write!(f, "\n{}", layout.indent())?;
+                layout.set_prefix_width(0);
             }
 
             // Conceptually, current_depth is the depth of this List itself;
             // its elements are one recursive tag deeper.
-            element.recursively_format_snbt(indent, f, current_depth + 1, opts)?;
-
-            if index != last_index {
-                if f.alternate() {
-                    write!(f, ",\n")?;
-                } else {
-                    write!(f, ",")?;
-                }
-            }
+            element.recursively_format_snbt(layout, f, current_depth + 1, opts)?;
         }
 
-        if f.alternate() {
-            indent.truncate(indent.len() - 4);
-            write!(f, "\n{indent}]")
+        if expanded {
+            layout.pop_indent();
+            write!(f, "\n{}]", layout.indent())
         } else {
             write!(f, "]")
         }
     }
 }
 
+/// Whether the given element of a list whose elements are stored as compounds needs to be
+/// wrapped in a compound.
+#[inline]
+fn needs_wrapper(element: &NbtTag) -> bool {
+    match element {
+        NbtTag::Compound(compound) => is_wrapper(compound),
+        _ => true,
+    }
+}
+
+/// Whether the given compound has the empty-string key `""` as its only key.
+#[inline]
+pub(crate) fn is_wrapper(compound: &NbtCompound) -> bool {
+    compound.len() == 1 && compound.contains_key("")
+}
+
+/// Java 1.21.5 unwraps the elements of heterogenous lists when reading them, so compounds
+/// whose only key is `""` are treated as the value of that key.
+pub(crate) fn unwrap_element(element: &NbtTag) -> &NbtTag {
+    if let NbtTag::Compound(compound) = element {
+        if is_wrapper(compound) {
+            if let Some(inner) = compound.get_tag("") {
+                return inner;
+            }
+        }
+    }
+    element
+}
+
 impl Default for NbtList {
     #[inline]
     fn default() -> Self {
@@ -1,6 +1,7 @@
 mod compound;
 mod list;
 mod comparable;
+mod layout;
 
 #[cfg(feature = "serde")]
 mod serde_impl;
@@ -12,13 +13,18 @@ use std::fmt::{Debug, Display, Formatter};
 
 use crate::{raw, snbt};
 use crate::repr::NbtStructureError;
+use crate::hash::{self, CompoundOrder};
+use crate::matching::{self, NumericMatching};
 use crate::{
     settings::{EscapeSequence, SnbtParseOptions, SnbtWriteOptions, WriteNonFinite},
     snbt::{SnbtError, allowed_unquoted, is_ambiguous, starts_unquoted_number},
 };
 
+use self::layout::Layout;
+
 
 pub use self::{compound::NbtCompound, list::NbtList};
+pub(crate) use self::list::{is_wrapper, unwrap_element};
 pub use self::comparable::*;
 
 
@@ -196,6 +202,43 @@ impl NbtTag {
         snbt::parse_any(input, opts)
     }
 
+    /// Computes the canonical hash of this tag, which ignores the order of the entries of
+    /// compounds. See the [`hash`] module for more information.
+    ///
+    /// [`hash`]: crate::hash
+    #[inline]
+    pub fn canonical_hash(&self) -> u64 {
+        hash::canonical_hash(self, CompoundOrder::Ignored)
+    }
+
+    /// Computes the canonical hash of this tag, with the given treatment of the order of the
+    /// entries of compounds. See the [`hash`] module for more information.
+    ///
+    /// [`hash`]: crate::hash
+    #[inline]
+    pub fn canonical_hash_with(&self, order: CompoundOrder) -> u64 {
+        hash::canonical_hash(self, order)
+    }
+
+    /// Checks whether this tag matches `pattern`, as done by Minecraft's `nbt={...}` arguments:
+    /// compounds need only contain the entries of the pattern, and each element of a pattern
+    /// list need only be found in the list. See the [`matching`] module for more information.
+    ///
+    /// [`matching`]: crate::matching
+    #[inline]
+    pub fn matches(&self, pattern: &Self) -> bool {
+        matching::tag_matches(self, pattern, NumericMatching::Exact)
+    }
+
+    /// Checks whether this tag matches `pattern`, comparing numbers as specified.
+    /// See the [`matching`] module for more information.
+    ///
+    /// [`matching`]: crate::matching
+    #[inline]
+    pub fn matches_with(&self, pattern: &Self, numeric: NumericMatching) -> bool {
+        matching::tag_matches(self, pattern, numeric)
+    }
+
     /// Converts this NBT tag into a valid, parsable SNBT string with no extraneous spacing. This
     /// method should not be used to generate user-facing text, rather [`to_pretty_snbt`] should
     /// be used instead. Additionally, this function uses the default `SnbtWriteOptions`
@@ -237,6 +280,8 @@ impl NbtTag {
     }
 
     /// Converts this NBT tag into a valid, parsable SNBT string with extra spacing for readability.
+    /// However, if `opts.array_limit` is set, then arrays longer than the limit are truncated,
+    /// and the resulting SNBT cannot be parsed.
     ///
     /// If a more compact SNBT representation is desired, then use [`to_snbt`].
     ///
@@ -374,52 +419,117 @@ impl NbtTag {
     /// Used in the `display_and_debug` macro below
     #[inline]
     fn to_formatted_snbt(&self, f: &mut Formatter<'_>, opts: SnbtWriteOptions) -> fmt::Result {
-        self.recursively_format_snbt(&mut String::new(), f, 0, opts)
+        self.recursively_format_snbt(&mut Layout::new(f), f, 0, opts)
     }
 
     /// Helper function for `Self::recursively_format_snbt`
-    #[expect(clippy::write_with_newline, reason = "clarity")]
     fn write_prim_list<D: Display>(
         f:                   &mut Formatter<'_>,
         list:                &[D],
-        indent:              &mut String,
+        layout:              &mut Layout,
         list_header:         &str,
         element_type_suffix: &str,
+        opts:                SnbtWriteOptions,
     ) -> fmt::Result {
         if list.is_empty() {
             return write!(f, "[{list_header};]");
         }
 
-        if f.alternate() {
-            indent.push_str("    ");
-            write!(f, "[\n{indent}{list_header};\n")?;
-        } else {
-            write!(f, "[{list_header};")?;
+        // Compact SNBT should always be parsable, so the limit only applies to pretty SNBT
+        let array_limit = opts.array_limit.filter(|_| layout.is_pretty());
+        let shown = array_limit.map_or(list.len(), |limit| limit.min(list.len()));
+        let (shown, omitted) = (&list[..shown], list.len() - shown);
+
+        if let Some(result) = layout.try_inline(f, opts, |layout, f| {
+            Self::write_prim_list(f, list, layout, list_header, element_type_suffix, opts)
+        }) {
+            return result;
         }
 
-        let last_index = list.len() - 1;
-        for (index, element) in list.iter().enumerate() {
-            if f.alternate() {
-                write!(f, "{indent}")?;
+        if layout.is_expanded() {
+            return Self::write_prim_list_rows(
+                f,
+                shown,
+                omitted,
+                layout,
+                list_header,
+                element_type_suffix,
+                opts,
+            );
+        }
+
+        let spacing = layout.comma_spacing();
+        write!(f, "[{list_header};{spacing}")?;
+
+        for (index, element) in shown.iter().enumerate() {
+            if index != 0 {
+                write!(f, ",{spacing}")?;
             }
             Display::fmt(element, f)?;
             write!(f, "{element_type_suffix}")?;
+        }
 
-            if index != last_index {
-                if f.alternate() {
-                    write!(f, ",\n")?;
-                } else {
-                    write!(f, ",")?;
+        if omitted != 0 {
+            if !shown.is_empty() {
+                write!(f, ",{spacing}")?;
+            }
+            write!(f, "... {omitted} more")?;
+        }
+
+        write!(f, "]")
+    }
+
+    /// Helper function for `Self::write_prim_list`, which writes the elements in rows,
+    /// with each element right-aligned in a column as wide as the widest element.
+    fn write_prim_list_rows<D: Display>(
+        f:                   &mut Formatter<'_>,
+        shown:               &[D],
+        omitted:             usize,
+        layout:              &mut Layout,
+        list_header:         &str,
+        element_type_suffix: &str,
+        opts:                SnbtWriteOptions,
+    ) -> fmt::Result {
+        let elements: Vec<String> = shown
+            .iter()
+            .map(|element| format!("{element}{element_type_suffix}"))
+            .collect();
+        let column_width = elements
+            .iter()
+            .map(|element| element.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        layout.push_indent();
+        layout.set_prefix_width(0);
+
+        // Each element takes up its column, followed by `, ` or the trailing `,` of a row
+        let per_row = ((layout.remaining_width(opts) + 2) / (column_width + 2)).max(1);
+
+        write!(f, "[{list_header};")?;
+        for (row_index, row) in elements.chunks(per_row).enumerate() {
+            if row_index != 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "\n{}", layout.indent())?;
+
+            for (index, element) in row.iter().enumerate() {
+                if index != 0 {
+                    write!(f, ", ")?;
                 }
+                write!(f, "{element:>column_width$}")?;
             }
         }
 
-        if f.alternate() {
-            indent.truncate(indent.len() - 4);
-            write!(f, "\n{indent}]")
-        } else {
-            write!(f, "]")
+        if omitted != 0 {
+            if !elements.is_empty() {
+                write!(f, ",")?;
+            }
+            write!(f, "\n{}... {omitted} more", layout.indent())?;
         }
+
+        layout.pop_indent();
+        write!(f, "\n{}]", layout.indent())
     }
 
     /// Helper function for `Self::recursively_format_snbt`
@@ -472,7 +582,7 @@ impl NbtTag {
 
     fn recursively_format_snbt(
         &self,
-        indent:        &mut String,
+        layout:        &mut Layout,
         f:             &mut Formatter<'_>,
         current_depth: u32,
         opts:          SnbtWriteOptions,
@@ -524,7 +634,7 @@ impl NbtTag {
             Self::Long(value)       => Self::write(f, value, ts),
             Self::Float(value)      => write_floating_point!(f, opts, value, ts, "f", f32),
             Self::Double(value)     => write_floating_point!(f, opts, value, ts, "d", f64),
-            Self::ByteArray(value)  => Self::write_prim_list(f, value, indent, ts, ts),
+            Self::ByteArray(value)  => Self::write_prim_list(f, value, layout, ts, ts, opts),
             Self::String(value)     => write!(f, "{}", Self::string_to_snbt(value, opts)),
             Self::ByteString(value) => {
                 if let Ok(string) = String::from_utf8(value.clone()) {
@@ -534,7 +644,7 @@ impl NbtTag {
                     // has to be a valid string. This isn't valid SNBT, but it should be
                     // useful for debugging, I think.
                     // This is printed as `[ByteString; 1B, 2B, 3B, 4B]`, for instance
-                    Self::write_prim_list(f, value, indent, "ByteString", "B")
+                    Self::write_prim_list(f, value, layout, "ByteString", "B", opts)
                 }
             }
             Self::List(value) => {
@@ -545,14 +655,14 @@ impl NbtTag {
                 // so incrementing it here would be a logic error.
                 // Conceptually, current_depth is the depth of that list tag,
                 // and that list tag *is* the current NbtTag, more or less.
-                value.recursively_format_snbt(indent, f, current_depth, opts)
+                value.recursively_format_snbt(layout, f, current_depth, opts)
             }
             Self::Compound(value) => {
                 Self::check_depth_limit(f, current_depth, opts, "Compound")?;
-                value.recursively_format_snbt(indent, f, current_depth, opts)
+                value.recursively_format_snbt(layout, f, current_depth, opts)
             }
-            Self::IntArray(value)  => Self::write_prim_list(f, value, indent, ts, ts),
-            Self::LongArray(value) => Self::write_prim_list(f, value, indent, ts, ts),
+            Self::IntArray(value)  => Self::write_prim_list(f, value, layout, ts, ts, opts),
+            Self::LongArray(value) => Self::write_prim_list(f, value, layout, ts, ts, opts),
         }
     }
 }
@@ -8,7 +8,8 @@ use serde::de::{MapAccess, Visitor};
 use crate::raw;
 use crate::{
     raw::{BYTE_ARRAY_ID, INT_ARRAY_ID, LIST_ID, LONG_ARRAY_ID},
-    serde::{Array, TypeHint},
+    serde::{Array, ByteString, TypeHint},
+    serde::ByteBufVisitor,
 };
 use super::{Map, NbtCompound, NbtList, NbtTag};
 
@@ -26,7 +27,7 @@ impl Serialize for NbtTag {
             &Self::Float(value)      => serializer.serialize_f32(value),
             &Self::Double(value)     => serializer.serialize_f64(value),
             Self::ByteArray(array)   => Array::from(array).serialize(serializer),
-            Self::ByteString(array)  => Array::from(array).serialize(serializer),
+            Self::ByteString(array)  => ByteString::from(array).serialize(serializer),
             Self::String(value)      => serializer.serialize_str(value),
             Self::List(list)         => list.serialize(serializer),
             Self::Compound(compound) => compound.serialize(serializer),
@@ -115,6 +116,15 @@ impl<'de> Visitor<'de> for NbtTagVisitor {
         Ok(NbtTag::String(v))
     }
 
+    /// Byte strings are the only newtype structs produced by NBT deserializers.
+    #[inline]
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_byte_buf(ByteBufVisitor).map(NbtTag::ByteString)
+    }
+
     fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
     where
         A: MapAccess<'de>,
@@ -0,0 +1,961 @@
+//! Borrowed, lazily-indexed views of uncompressed binary NBT data.
+//!
+//! Creating a view only reads the header of the root tag. The entries of a compound or the
+//! elements of a list are indexed the first time they are accessed, which reads past their
+//! values without allocating them; nested compounds and lists are in turn only indexed if
+//! they are accessed. Strings and arrays borrow from the original bytes where possible,
+//! and a view can be converted into an owned tag on demand.
+//!
+//! Since NBT data is only checked as it is indexed, the accessors of a view return any error
+//! in the data which they encounter.
+
+use std::{borrow::Cow, cell::OnceCell, io, mem, ops::Range};
+
+use crate::{raw::{self, BudgetTracker}, stream::{read_tag_body, skip_tag_body}};
+use crate::{
+    io::NbtIoError,
+    raw::{
+        BYTE_ARRAY_ID, BYTE_ID, COMPOUND_ID, DOUBLE_ID, FLOAT_ID, INT_ARRAY_ID,
+        INT_ID, LIST_ID, LONG_ID, LONG_ARRAY_ID, SHORT_ID, STRING_ID, TAG_END_ID,
+    },
+    settings::{Endianness, IoOptions, NbtCompression},
+    tag::{NbtCompound, NbtList, NbtTag, NbtType},
+};
+
+
+type NbtResult<T> = Result<T, NbtIoError>;
+
+
+// ================================
+//  Reading functions
+// ================================
+
+/// Creates a view of NBT data with a named root compound tag, returning the view and the
+/// root name.
+///
+/// This is the borrowed equivalent of [`io::read_compound`], and the same `IoOptions` are
+/// honored, except that the data must be uncompressed.
+///
+/// Only the header of the root tag is read, and the `bytes` slice is advanced past it.
+/// The body of the root compound, followed by any trailing data, remains in `bytes`;
+/// [`NbtCompoundRef::raw_bytes`] finds where the body ends.
+///
+/// [`io::read_compound`]: crate::io::read_compound
+pub fn read_compound_ref<'a>(
+    bytes: &mut &'a [u8],
+    opts:  IoOptions,
+) -> Result<(NbtCompoundRef<'a>, Cow<'a, str>), NbtIoError> {
+    if !matches!(opts.compression, NbtCompression::Uncompressed) {
+        return Err(NbtIoError::CompressedView);
+    }
+
+    let root_id = raw::read_u8(bytes, opts)?;
+    if root_id != COMPOUND_ID {
+        return Err(NbtIoError::TagTypeMismatch {
+            expected: COMPOUND_ID,
+            found:    root_id,
+        });
+    }
+
+    let name_len = raw::read_string_len(bytes, opts)?;
+    let root_name = raw::string_from_bytes(take(bytes, name_len)?, opts)?;
+
+    Ok((NbtCompoundRef::new(bytes, opts, 0), root_name))
+}
+
+/// Creates a view of NBT data with an unnamed root tag of any type.
+///
+/// This is the borrowed equivalent of [`io::read_any_unnamed_nbt`], and the same `IoOptions`
+/// are honored, except that the data must be uncompressed.
+///
+/// The `bytes` slice is advanced past the ID of the root tag and, unless the root tag is a
+/// compound or list, past its body. The body of a root compound or list, followed by any
+/// trailing data, remains in `bytes`; the `raw_bytes` methods of [`NbtCompoundRef`] and
+/// [`NbtListRef`] find where the body ends.
+///
+/// [`io::read_any_unnamed_nbt`]: crate::io::read_any_unnamed_nbt
+#[cfg(feature = "allow_any_root")]
+pub fn read_any_unnamed_ref<'a>(
+    bytes: &mut &'a [u8],
+    opts:  IoOptions,
+) -> Result<NbtTagRef<'a>, NbtIoError> {
+    if !matches!(opts.compression, NbtCompression::Uncompressed) {
+        return Err(NbtIoError::CompressedView);
+    }
+
+    let root_id = raw::read_u8(bytes, opts)?;
+    if root_id == COMPOUND_ID || root_id == LIST_ID {
+        NbtTagRef::new(root_id, bytes, opts, 0)
+    } else {
+        let body = take_tag_body(bytes, opts, root_id, 0)?;
+        NbtTagRef::new(root_id, body, opts, 0)
+    }
+}
+
+/// Splits off the first `len` bytes of `bytes`.
+#[inline]
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> NbtResult<&'a [u8]> {
+    let (taken, rest) = bytes
+        .split_at_checked(len)
+        .ok_or_else(|| NbtIoError::StdIo(io::ErrorKind::UnexpectedEof.into()))?;
+    *bytes = rest;
+    Ok(taken)
+}
+
+/// Splits off the body of a tag with the given ID, after checking that it is valid.
+#[inline]
+fn take_tag_body<'a>(
+    bytes:         &mut &'a [u8],
+    opts:          IoOptions,
+    tag_id:        u8,
+    current_depth: u32,
+) -> NbtResult<&'a [u8]> {
+    let start = *bytes;
+    skip_tag_body(&mut *bytes, opts, tag_id, current_depth)?;
+    Ok(&start[..start.len() - bytes.len()])
+}
+
+/// Check whether a compound or list at the given depth may have elements.
+#[inline]
+fn check_depth(opts: IoOptions, current_depth: u32) -> NbtResult<()> {
+    if current_depth >= opts.depth_limit.0 {
+        Err(NbtIoError::ExceededDepthLimit {
+            limit: opts.depth_limit,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+// ================================
+//  Tags
+// ================================
+
+/// A borrowed view of any NBT tag. See [`NbtTag`] for information about the variants.
+#[derive(Debug, Clone)]
+pub enum NbtTagRef<'a> {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(&'a [i8]),
+    /// A string, which is only borrowed if no conversion from the string encoding
+    /// to UTF-8 was necessary.
+    String(Cow<'a, str>),
+    /// A "string" which is not valid in the string encoding; only returned if `ByteString`s
+    /// are enabled in the `IoOptions`.
+    ByteString(&'a [u8]),
+    List(NbtListRef<'a>),
+    Compound(NbtCompoundRef<'a>),
+    IntArray(NbtIntArrayRef<'a>),
+    LongArray(NbtLongArrayRef<'a>),
+}
+
+impl<'a> NbtTagRef<'a> {
+    /// `body` should start with the body of the tag. Any data after the body is ignored,
+    /// except that compounds and lists only find the end of their body when indexed.
+    fn new(
+        tag_id:        u8,
+        mut body:      &'a [u8],
+        opts:          IoOptions,
+        current_depth: u32,
+    ) -> NbtResult<Self> {
+        let bytes = &mut body;
+
+        Ok(match tag_id {
+            BYTE_ID   => Self::Byte(  raw::read_i8( bytes, opts)?),
+            SHORT_ID  => Self::Short( raw::read_i16(bytes, opts)?),
+            INT_ID    => Self::Int(   raw::read_i32(bytes, opts)?),
+            LONG_ID   => Self::Long(  raw::read_i64(bytes, opts)?),
+            FLOAT_ID  => Self::Float( raw::read_f32(bytes, opts)?),
+            DOUBLE_ID => Self::Double(raw::read_f64(bytes, opts)?),
+            BYTE_ARRAY_ID => {
+                let len = raw::read_i32_as_usize(bytes, opts)?;
+                Self::ByteArray(raw::cast_bytes_to_signed(take(bytes, len)?))
+            }
+            STRING_ID => {
+                let len = raw::read_string_len(bytes, opts)?;
+                let string = take(bytes, len)?;
+
+                match raw::string_from_bytes(string, opts) {
+                    Ok(string) => Self::String(string),
+                    Err(_) if opts.enable_byte_strings => Self::ByteString(string),
+                    Err(err) => return Err(err),
+                }
+            }
+            LIST_ID => Self::List(NbtListRef::new(body, opts, current_depth)?),
+            COMPOUND_ID => Self::Compound(NbtCompoundRef::new(body, opts, current_depth)),
+            INT_ARRAY_ID => {
+                let len = raw::read_i32_as_usize(bytes, opts)?;
+                let elements = *bytes;
+                raw::skip_i32_array(bytes, opts, len)?;
+                let elements = &elements[..elements.len() - bytes.len()];
+                Self::IntArray(NbtIntArrayRef::new(elements, len, opts))
+            }
+            LONG_ARRAY_ID => {
+                let len = raw::read_i32_as_usize(bytes, opts)?;
+                let elements = *bytes;
+                raw::skip_i64_array(bytes, opts, len)?;
+                let elements = &elements[..elements.len() - bytes.len()];
+                Self::LongArray(NbtLongArrayRef::new(elements, len, opts))
+            }
+            _ => return Err(NbtIoError::InvalidTagId(tag_id)),
+        })
+    }
+
+    /// Returns this tag's type.
+    #[inline]
+    pub fn tag_type(&self) -> NbtType {
+        match self {
+            Self::Byte(_)       => NbtType::Byte,
+            Self::Short(_)      => NbtType::Short,
+            Self::Int(_)        => NbtType::Int,
+            Self::Long(_)       => NbtType::Long,
+            Self::Float(_)      => NbtType::Float,
+            Self::Double(_)     => NbtType::Double,
+            Self::ByteArray(_)  => NbtType::ByteArray,
+            #[expect(clippy::match_same_arms)]
+            Self::String(_)     => NbtType::String,
+            Self::ByteString(_) => NbtType::String,
+            Self::List(_)       => NbtType::List,
+            Self::Compound(_)   => NbtType::Compound,
+            Self::IntArray(_)   => NbtType::IntArray,
+            Self::LongArray(_)  => NbtType::LongArray,
+        }
+    }
+
+    /// Converts this view into an owned tag.
+    pub fn to_tag(&self) -> Result<NbtTag, NbtIoError> {
+        Ok(match self {
+            &Self::Byte(value)      => NbtTag::Byte(value),
+            &Self::Short(value)     => NbtTag::Short(value),
+            &Self::Int(value)       => NbtTag::Int(value),
+            &Self::Long(value)      => NbtTag::Long(value),
+            &Self::Float(value)     => NbtTag::Float(value),
+            &Self::Double(value)    => NbtTag::Double(value),
+            Self::ByteArray(value)  => NbtTag::ByteArray(value.to_vec()),
+            Self::String(value)     => NbtTag::String(value.clone().into_owned()),
+            Self::ByteString(value) => NbtTag::ByteString(value.to_vec()),
+            Self::List(value)       => NbtTag::List(value.to_list()?),
+            Self::Compound(value)   => NbtTag::Compound(value.to_compound()?),
+            Self::IntArray(value)   => NbtTag::IntArray(value.to_vec()?),
+            Self::LongArray(value)  => NbtTag::LongArray(value.to_vec()?),
+        })
+    }
+}
+
+// ================================
+//  Compounds
+// ================================
+
+/// A borrowed view of an NBT compound tag. The compound's entries are indexed the first time
+/// they are needed.
+///
+/// Views of the compound's tags are created on each access, and each view of a compound or
+/// list indexes its own entries or elements, so a view should be kept rather than accessed
+/// again if its contents are needed more than once.
+#[derive(Debug, Clone)]
+pub struct NbtCompoundRef<'a> {
+    /// The entries of the compound, followed by `TAG_End`, and possibly followed by
+    /// trailing data if the extent of the compound is not yet known.
+    body:          &'a [u8],
+    opts:          IoOptions,
+    current_depth: u32,
+    index:         OnceCell<CompoundIndex>,
+}
+
+#[derive(Debug, Clone)]
+struct CompoundIndex {
+    entries:  Vec<CompoundEntry>,
+    /// The indices of `entries`, sorted by name. Entries with equal names remain in the order
+    /// they appear in the NBT data.
+    by_name:  Vec<usize>,
+    /// The length of the compound's body, including its `TAG_End`.
+    body_len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct CompoundEntry {
+    tag_id: u8,
+    name:   Range<usize>,
+    value:  Range<usize>,
+}
+
+impl<'a> NbtCompoundRef<'a> {
+    #[inline]
+    fn new(body: &'a [u8], opts: IoOptions, current_depth: u32) -> Self {
+        Self {
+            body,
+            opts,
+            current_depth,
+            index: OnceCell::new(),
+        }
+    }
+
+    /// The raw bytes of the body of this compound, excluding the root tag header if this
+    /// is a root compound.
+    pub fn raw_bytes(&self) -> Result<&'a [u8], NbtIoError> {
+        let body_len = self.index()?.body_len;
+        Ok(&self.body[..body_len])
+    }
+
+    /// Returns the number of tags in this compound.
+    pub fn len(&self) -> Result<usize, NbtIoError> {
+        Ok(self.index()?.entries.len())
+    }
+
+    /// Returns true if the length of this compound is zero, false otherwise.
+    ///
+    /// This only checks the first byte of the compound, so it does not index the compound.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        // The body starts with TAG_End only if the compound is empty.
+        self.body.first().is_none_or(|&tag_id| tag_id == TAG_END_ID)
+    }
+
+    /// Returns whether or not this compound has a tag with the given name.
+    pub fn contains_key(&self, name: &str) -> Result<bool, NbtIoError> {
+        Ok(self.find(name)?.is_some())
+    }
+
+    /// Returns a view of the tag with the given name, or `None` if no tag exists with the
+    /// given name. If the name appears more than once, the last tag with that name is used,
+    /// as in [`to_compound`].
+    ///
+    /// [`to_compound`]: NbtCompoundRef::to_compound
+    pub fn get(&self, name: &str) -> Result<Option<NbtTagRef<'a>>, NbtIoError> {
+        self.find(name)?
+            .map(|entry| self.entry_value(entry))
+            .transpose()
+    }
+
+    /// Iterates over the names and views of the tags in this compound, in the order they
+    /// appear in the NBT data.
+    pub fn entries(&self) -> Result<NbtCompoundRefIter<'_, 'a>, NbtIoError> {
+        Ok(NbtCompoundRefIter {
+            compound: self,
+            entries:  self.index()?.entries.iter(),
+        })
+    }
+
+    /// Converts this view into an owned compound.
+    pub fn to_compound(&self) -> Result<NbtCompound, NbtIoError> {
+        let mut body = self.body;
+        let budget = BudgetTracker::new(self.opts.memory_budget);
+        match read_tag_body(&mut body, self.opts, &budget, COMPOUND_ID, self.current_depth)? {
+            NbtTag::Compound(compound) => Ok(compound),
+            _ => unreachable!("read_tag_body with COMPOUND_ID returns a compound"),
+        }
+    }
+
+    fn find(&self, name: &str) -> NbtResult<Option<&CompoundEntry>> {
+        let name = raw::bytes_from_string(name, self.opts);
+        let index = self.index()?;
+        let entry_name = |position: usize| &self.body[index.entries[position].name.clone()];
+
+        // The last of the entries with the given name is just before the first entry
+        // whose name is greater.
+        let end = index.by_name.partition_point(|&position| entry_name(position) <= &*name);
+
+        Ok(end
+            .checked_sub(1)
+            .map(|last| index.by_name[last])
+            .filter(|&position| entry_name(position) == &*name)
+            .map(|position| &index.entries[position]))
+    }
+
+    /// If this compound wraps an element of a heterogenous list, returns a view of the
+    /// wrapped element. See [`NbtList::unwrap_heterogenous`].
+    fn wrapped_value(&self) -> NbtResult<Option<NbtTagRef<'a>>> {
+        match self.index()?.entries.as_slice() {
+            [entry] if entry.name.is_empty() => self.entry_value(entry).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    #[inline]
+    fn entry_name(&self, entry: &CompoundEntry) -> NbtResult<Cow<'a, str>> {
+        raw::string_from_bytes(&self.body[entry.name.clone()], self.opts)
+    }
+
+    #[inline]
+    fn entry_value(&self, entry: &CompoundEntry) -> NbtResult<NbtTagRef<'a>> {
+        NbtTagRef::new(
+            entry.tag_id,
+            &self.body[entry.value.clone()],
+            self.opts,
+            self.current_depth + 1,
+        )
+    }
+
+    fn index(&self) -> NbtResult<&CompoundIndex> {
+        if let Some(index) = self.index.get() {
+            return Ok(index);
+        }
+
+        let mut entries = Vec::new();
+        let mut rest = self.body;
+        let offset = |rest: &[u8]| self.body.len() - rest.len();
+
+        loop {
+            let tag_id = raw::read_u8(&mut rest, self.opts)?;
+            if tag_id == TAG_END_ID {
+                break;
+            }
+            check_depth(self.opts, self.current_depth)?;
+
+            let name_len = raw::read_string_len(&mut rest, self.opts)?;
+            let name_start = offset(rest);
+            take(&mut rest, name_len)?;
+
+            let value_start = offset(rest);
+            take_tag_body(&mut rest, self.opts, tag_id, self.current_depth + 1)?;
+
+            entries.push(CompoundEntry {
+                tag_id,
+                name:  name_start..name_start + name_len,
+                value: value_start..offset(rest),
+            });
+        }
+
+        // Note that the sort is stable.
+        let mut by_name = (0..entries.len()).collect::<Vec<_>>();
+        by_name.sort_by_key(|&position| &self.body[entries[position].name.clone()]);
+
+        Ok(self.index.get_or_init(|| CompoundIndex {
+            entries,
+            by_name,
+            body_len: offset(rest),
+        }))
+    }
+}
+
+/// An iterator over the names and views of the tags in an [`NbtCompoundRef`].
+#[derive(Debug, Clone)]
+pub struct NbtCompoundRefIter<'c, 'a> {
+    compound: &'c NbtCompoundRef<'a>,
+    entries:  std::slice::Iter<'c, CompoundEntry>,
+}
+
+impl<'a> Iterator for NbtCompoundRefIter<'_, 'a> {
+    type Item = Result<(Cow<'a, str>, NbtTagRef<'a>), NbtIoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.entries.next()?;
+
+        Some(
+            self.compound
+                .entry_name(entry)
+                .and_then(|name| Ok((name, self.compound.entry_value(entry)?))),
+        )
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+// ================================
+//  Lists
+// ================================
+
+/// A borrowed view of an NBT list tag. If the elements of the list do not have a fixed size,
+/// their offsets are computed the first time they are needed.
+///
+/// As with [`NbtCompoundRef`], views of the list's elements are created on each access.
+#[derive(Debug, Clone)]
+pub struct NbtListRef<'a> {
+    element_id:    u8,
+    len:           usize,
+    /// The list's header and elements, possibly followed by trailing data if the extent
+    /// of the list is not yet known.
+    body:          &'a [u8],
+    /// The elements of the list, excluding the list's header
+    elements:      &'a [u8],
+    opts:          IoOptions,
+    current_depth: u32,
+    offsets:       OnceCell<ListOffsets>,
+}
+
+#[derive(Debug, Clone)]
+struct ListOffsets {
+    /// The offset of each element in `NbtListRef::elements`.
+    offsets:      Vec<usize>,
+    /// The total length of the elements.
+    elements_len: usize,
+}
+
+impl<'a> NbtListRef<'a> {
+    fn new(body: &'a [u8], opts: IoOptions, current_depth: u32) -> NbtResult<Self> {
+        let mut elements = body;
+        let element_id = raw::read_u8(&mut elements, opts)?;
+        let len = raw::read_i32_as_usize(&mut elements, opts)?;
+
+        // Make sure we don't have an invalid type or a nonempty list of TAG_End
+        if element_id > LONG_ARRAY_ID || (element_id == TAG_END_ID && len > 0) {
+            return Err(NbtIoError::InvalidTagId(element_id));
+        }
+        if len > 0 {
+            check_depth(opts, current_depth)?;
+        }
+
+        let list = Self {
+            element_id,
+            len,
+            body,
+            elements,
+            opts,
+            current_depth,
+            offsets: OnceCell::new(),
+        };
+
+        if let Some(size) = list.element_size() {
+            let elements_len = len.checked_mul(size).ok_or(NbtIoError::ExcessiveLength)?;
+            if elements_len > elements.len() {
+                return Err(NbtIoError::StdIo(io::ErrorKind::UnexpectedEof.into()));
+            }
+        }
+
+        Ok(list)
+    }
+
+    /// The raw bytes of the body of this list, including its element type and length.
+    pub fn raw_bytes(&self) -> Result<&'a [u8], NbtIoError> {
+        let elements_len = match self.element_size() {
+            // This was checked to not overflow when the view was created
+            Some(size) => self.len * size,
+            None       => self.offsets()?.elements_len,
+        };
+        let header_len = self.body.len() - self.elements.len();
+
+        Ok(&self.body[..header_len + elements_len])
+    }
+
+    /// Returns the length of this list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if this list has a length of zero, false otherwise.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The type of the elements in the list, or `None` if the list is empty
+    /// and has the `TAG_End` element type.
+    ///
+    /// This is the element type in the NBT data, so a heterogenous list has the
+    /// `Compound` element type.
+    #[inline]
+    pub fn element_type(&self) -> Option<NbtType> {
+        raw::type_for_id(self.element_id)
+    }
+
+    /// Returns a view of the tag at the given index, or `None` if the index is out of bounds.
+    ///
+    /// If `unwrap_heterogenous_lists` is enabled in the `IoOptions`, an element of a
+    /// heterogenous list is unwrapped, as in [`NbtList::unwrap_heterogenous`].
+    pub fn get(&self, index: usize) -> Result<Option<NbtTagRef<'a>>, NbtIoError> {
+        if index >= self.len {
+            return Ok(None);
+        }
+
+        let range = if let Some(size) = self.element_size() {
+            index * size .. (index + 1) * size
+        } else {
+            let offsets = self.offsets()?;
+            let end = offsets.offsets
+                .get(index + 1)
+                .copied()
+                .unwrap_or(offsets.elements_len);
+            offsets.offsets[index]..end
+        };
+
+        let element = NbtTagRef::new(
+            self.element_id,
+            &self.elements[range],
+            self.opts,
+            self.current_depth + 1,
+        )?;
+
+        if self.unwraps_elements() {
+            if let NbtTagRef::Compound(compound) = &element {
+                if let Some(wrapped) = compound.wrapped_value()? {
+                    return Ok(Some(wrapped));
+                }
+            }
+        }
+
+        Ok(Some(element))
+    }
+
+    /// Iterates over views of the tags in this list.
+    #[inline]
+    pub fn iter(&self) -> NbtListRefIter<'_, 'a> {
+        NbtListRefIter {
+            list:  self,
+            index: 0,
+        }
+    }
+
+    /// Converts this view into an owned list.
+    pub fn to_list(&self) -> Result<NbtList, NbtIoError> {
+        let budget = BudgetTracker::new(self.opts.memory_budget);
+        budget.charge_tags(self.len)?;
+
+        let mut list = NbtList::with_capacity(self.len);
+        let mut elements = self.elements;
+
+        for _ in 0..self.len {
+            list.push(read_tag_body(
+                &mut elements,
+                self.opts,
+                &budget,
+                self.element_id,
+                self.current_depth + 1,
+            )?);
+        }
+
+        if self.unwraps_elements() {
+            list.unwrap_heterogenous();
+        }
+
+        Ok(list)
+    }
+
+    /// Whether the elements of this list should be unwrapped, as in `io`.
+    #[inline]
+    fn unwraps_elements(&self) -> bool {
+        self.element_id == COMPOUND_ID && self.opts.unwrap_heterogenous_lists
+    }
+
+    /// The size of each element, if every element has the same size.
+    fn element_size(&self) -> Option<usize> {
+        match self.element_id {
+            BYTE_ID   => Some(1),
+            SHORT_ID  => Some(2),
+            FLOAT_ID  => Some(4),
+            DOUBLE_ID => Some(8),
+            INT_ID  if !matches!(self.opts.endianness, Endianness::NetworkLittleEndian) => Some(4),
+            LONG_ID if !matches!(self.opts.endianness, Endianness::NetworkLittleEndian) => Some(8),
+            _ => None,
+        }
+    }
+
+    fn offsets(&self) -> NbtResult<&ListOffsets> {
+        if let Some(offsets) = self.offsets.get() {
+            return Ok(offsets);
+        }
+
+        let budget = BudgetTracker::new(self.opts.memory_budget);
+        budget.charge(self.len.saturating_mul(mem::size_of::<usize>()), self.len)?;
+
+        let mut offsets = Vec::with_capacity(self.len);
+        let mut rest = self.elements;
+
+        for _ in 0..self.len {
+            offsets.push(self.elements.len() - rest.len());
+            take_tag_body(&mut rest, self.opts, self.element_id, self.current_depth + 1)?;
+        }
+
+        Ok(self.offsets.get_or_init(|| ListOffsets {
+            offsets,
+            elements_len: self.elements.len() - rest.len(),
+        }))
+    }
+}
+
+impl<'c, 'a> IntoIterator for &'c NbtListRef<'a> {
+    type IntoIter = NbtListRefIter<'c, 'a>;
+    type Item = Result<NbtTagRef<'a>, NbtIoError>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over views of the tags in an [`NbtListRef`].
+#[derive(Debug, Clone)]
+pub struct NbtListRefIter<'c, 'a> {
+    list:  &'c NbtListRef<'a>,
+    index: usize,
+}
+
+impl<'a> Iterator for NbtListRefIter<'_, 'a> {
+    type Item = Result<NbtTagRef<'a>, NbtIoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let element = self.list.get(self.index).transpose()?;
+        self.index += 1;
+        Some(element)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.list.len.saturating_sub(self.index);
+        (remaining, Some(remaining))
+    }
+}
+
+
+// ================================
+//  Numeric arrays
+// ================================
+
+macro_rules! array_ref {
+    ($name:ident, $iter:ident, $elem:ty, $size:literal, $read_fn:ident, $read_array_fn:ident) => {
+        #[doc = concat!("A borrowed view of an array of `", stringify!($elem), "` values.")]
+        ///
+        /// The array's data was validated when the view was created, so its elements
+        /// can be read infallibly.
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name<'a> {
+            /// The elements of the array, excluding the length prefix
+            bytes: &'a [u8],
+            len:   usize,
+            opts:  IoOptions,
+        }
+
+        impl<'a> $name<'a> {
+            #[inline]
+            fn new(bytes: &'a [u8], len: usize, opts: IoOptions) -> Self {
+                Self { bytes, len, opts }
+            }
+
+            /// Returns the length of this array.
+            #[inline]
+            pub fn len(&self) -> usize {
+                self.len
+            }
+
+            /// Returns true if this array has a length of zero, false otherwise.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.len == 0
+            }
+
+            /// Returns the element at the given index, or `None` if the index is out of bounds.
+            /// If the `NetworkLittleEndian` encoding is used, this takes linear time.
+            pub fn get(&self, index: usize) -> Option<$elem> {
+                if index >= self.len {
+                    return None;
+                }
+
+                if matches!(self.opts.endianness, Endianness::NetworkLittleEndian) {
+                    self.iter().nth(index)
+                } else {
+                    let mut bytes = &self.bytes[index * $size..];
+                    raw::$read_fn(&mut bytes, self.opts).ok()
+                }
+            }
+
+            /// Iterates over the elements of this array.
+            #[inline]
+            pub fn iter(&self) -> $iter<'a> {
+                $iter {
+                    bytes:     self.bytes,
+                    remaining: self.len,
+                    opts:      self.opts,
+                }
+            }
+
+            /// Copies the elements of this array into a `Vec`.
+            pub fn to_vec(&self) -> Result<Vec<$elem>, NbtIoError> {
+                let mut bytes = self.bytes;
+                Ok(raw::$read_array_fn(&mut bytes, self.opts, self.len)?)
+            }
+        }
+
+        impl<'a> IntoIterator for $name<'a> {
+            type IntoIter = $iter<'a>;
+            type Item = $elem;
+
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+
+        impl<'a> IntoIterator for &$name<'a> {
+            type IntoIter = $iter<'a>;
+            type Item = $elem;
+
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+
+        #[doc = concat!("An iterator over the elements of an [`", stringify!($name), "`].")]
+        #[derive(Debug, Clone)]
+        pub struct $iter<'a> {
+            bytes:     &'a [u8],
+            remaining: usize,
+            opts:      IoOptions,
+        }
+
+        impl Iterator for $iter<'_> {
+            type Item = $elem;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                if self.remaining == 0 {
+                    return None;
+                }
+                self.remaining -= 1;
+                raw::$read_fn(&mut self.bytes, self.opts).ok()
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (self.remaining, Some(self.remaining))
+            }
+        }
+    };
+}
+
+array_ref!(NbtIntArrayRef,  NbtIntArrayRefIter,  i32, 4, read_i32, read_i32_array);
+array_ref!(NbtLongArrayRef, NbtLongArrayRefIter, i64, 8, read_i64, read_i64_array);
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{io, settings::IoOptions};
+    use crate::tag::{NbtCompound, NbtList, NbtTag};
+    use super::{read_compound_ref, NbtTagRef};
+
+
+    fn write(compound: &NbtCompound) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        io::write_compound(&mut bytes, IoOptions::java_uncompressed(), None, compound)
+            .expect("writing to a Vec succeeds");
+        bytes
+    }
+
+    #[test]
+    fn view_access() {
+        let mut inner = NbtCompound::new();
+        inner.insert("name", "inner");
+        inner.insert("ints", NbtTag::IntArray(vec![1, -2, 3]));
+        let mut strings = NbtList::new();
+        strings.push("a");
+        strings.push("bc");
+        let mut compound = NbtCompound::new();
+        compound.insert("inner", inner);
+        compound.insert("strings", strings);
+        compound.insert("short", 7_i16);
+
+        let mut bytes = write(&compound);
+        bytes.extend_from_slice(b"trailing");
+        let mut remaining = bytes.as_slice();
+        let (view, _) = read_compound_ref(&mut remaining, IoOptions::java_uncompressed())
+            .expect("valid NBT");
+
+        assert!(!view.is_empty(), "the compound has entries");
+        assert_eq!(view.len().expect("valid NBT"), 3, "the compound has 3 entries");
+        assert!(view.get("missing").expect("valid NBT").is_none(), "no such entry");
+        assert!(
+            matches!(view.get("short").expect("valid NBT"), Some(NbtTagRef::Short(7))),
+            "the short should be read",
+        );
+
+        let Some(NbtTagRef::Compound(inner)) = view.get("inner").expect("valid NBT") else {
+            panic!("expected a compound");
+        };
+        assert!(
+            matches!(
+                inner.get("name").expect("valid NBT"),
+                Some(NbtTagRef::String(name)) if name == "inner",
+            ),
+            "the nested string should be read",
+        );
+        let Some(NbtTagRef::IntArray(ints)) = inner.get("ints").expect("valid NBT") else {
+            panic!("expected an int array");
+        };
+        assert_eq!(ints.to_vec().expect("valid NBT"), [1, -2, 3], "the int array should be read");
+
+        let Some(NbtTagRef::List(strings)) = view.get("strings").expect("valid NBT") else {
+            panic!("expected a list");
+        };
+        assert_eq!(strings.len(), 2, "the list has 2 elements");
+        assert!(
+            matches!(strings.get(1).expect("valid NBT"), Some(NbtTagRef::String(s)) if s == "bc"),
+            "the second element should be read",
+        );
+        assert!(strings.get(2).expect("valid NBT").is_none(), "the index is out of bounds");
+
+        let body = view.raw_bytes().expect("valid NBT");
+        let trailing = &remaining[body.len()..];
+        assert_eq!(trailing, b"trailing", "the body should end before trailing data");
+        assert_eq!(view.to_compound().expect("valid NBT"), compound, "the view should round-trip");
+    }
+
+    #[test]
+    fn malformed_input_is_an_error() {
+        let mut compound = NbtCompound::new();
+        compound.insert("long", 1_i64);
+        compound.insert("list", NbtList::from(vec!["x"]));
+        compound.insert("longs", NbtTag::LongArray(vec![1, 2]));
+        let bytes = write(&compound);
+        let opts = IoOptions::java_uncompressed();
+
+        // Only the root header is read when the view is created, so any truncation of the body
+        // is reported by accessors.
+        for len in 3..bytes.len() {
+            let (view, _) = read_compound_ref(&mut &bytes[..len], opts).expect("intact header");
+            assert!(view.len().is_err(), "truncating to {len} bytes should be an error");
+            assert!(view.raw_bytes().is_err(), "truncating to {len} bytes should be an error");
+            assert!(view.to_compound().is_err(), "truncating to {len} bytes should be an error");
+        }
+        assert!(
+            read_compound_ref(&mut &bytes[..2], opts).is_err(),
+            "a truncated header should be an error",
+        );
+
+        // Replace the ID of the first entry with an invalid ID
+        let mut invalid = bytes.clone();
+        invalid[3] = 0xFF;
+        let (view, _) = read_compound_ref(&mut invalid.as_slice(), opts).expect("intact header");
+        assert!(view.get("long").is_err(), "an invalid tag ID should be an error");
+        assert!(view.entries().is_err(), "an invalid tag ID should be an error");
+    }
+
+    #[test]
+    fn heterogenous_list_matches_io() {
+        let mut element = NbtCompound::new();
+        element.insert("k", 2_i32);
+        let mut list = NbtList::new();
+        list.push(1_i8);
+        list.push("x");
+        list.push(element);
+        let mut compound = NbtCompound::new();
+        compound.insert("list", list.clone());
+
+        let opts = IoOptions::java_uncompressed();
+        let mut bytes = Vec::new();
+        io::write_compound(&mut bytes, opts, None, &compound).expect("writing to a Vec succeeds");
+
+        let (view, _) = read_compound_ref(&mut bytes.as_slice(), opts).expect("valid NBT");
+        let list_view = match view.get("list").expect("valid NBT") {
+            Some(NbtTagRef::List(list_view)) => list_view,
+            other => panic!("expected a list, found {other:?}"),
+        };
+
+        assert_eq!(list_view.to_list().expect("valid NBT"), list, "to_list should unwrap");
+        assert_eq!(
+            NbtTagRef::List(list_view.clone()).to_tag().expect("valid NBT"),
+            NbtTag::List(list.clone()),
+            "to_tag should unwrap",
+        );
+        for (index, element) in list.iter().enumerate() {
+            let element_view = list_view.get(index).expect("valid NBT").expect("in bounds");
+            assert_eq!(&element_view.to_tag().expect("valid NBT"), element, "get should unwrap");
+        }
+        assert_eq!(view.to_compound().expect("valid NBT"), compound, "to_compound should unwrap");
+    }
+}
@@ -0,0 +1,656 @@
+use std::collections::HashSet;
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+use syn::{Attribute, Data, DeriveInput, Fields, GenericArgument, Generics, LitStr, Path};
+use syn::{parse_quote, PathArguments, Type};
+use syn::{ext::IdentExt as _, spanned::Spanned as _};
+
+
+/// The parsed input of a `ToNbt` or `FromNbt` derive.
+pub(crate) enum Container {
+    /// A struct with named fields, stored as a compound.
+    Struct(Vec<Field>),
+    /// An enum whose variants are all unit variants, stored as a string.
+    UnitEnum(Vec<Variant>),
+    /// An enum stored as a compound, with the variant's name stored in the given key.
+    TaggedEnum {
+        tag:      String,
+        variants: Vec<Variant>,
+    },
+}
+
+pub(crate) struct Variant {
+    ident:  Ident,
+    name:   String,
+    fields: Vec<Field>,
+}
+
+pub(crate) struct Field {
+    ident:    Ident,
+    key:      String,
+    /// The type of the field, or the type inside the `Option` for optional fields.
+    ty:       Type,
+    optional: bool,
+    default:  Option<FieldDefault>,
+    skip:     bool,
+    flatten:  bool,
+    encoding: Encoding,
+}
+
+enum FieldDefault {
+    Trait,
+    Function(Path),
+}
+
+#[derive(Clone, Copy)]
+enum Encoding {
+    /// Use the type's `ToNbtTag` and `FromNbtTag` implementations.
+    Standard,
+    Int(&'static str),
+    Array(&'static str),
+    ByteString,
+}
+
+
+// ================================
+//  Parsing
+// ================================
+
+pub(crate) fn parse_container(input: &DeriveInput) -> syn::Result<Container> {
+    let mut tag = None;
+    for attr in nbt_attrs(&input.attrs) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("tag") {
+                tag = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("expected `tag`"))
+            }
+        })?;
+    }
+
+    match &input.data {
+        Data::Struct(data) => {
+            if tag.is_some() {
+                return Err(syn::Error::new(input.ident.span(), "`tag` is only allowed on enums"));
+            }
+            parse_fields(&data.fields).map(Container::Struct)
+        }
+        Data::Enum(data) => {
+            let mut names = HashSet::new();
+            let variants = data.variants
+                .iter()
+                .map(|variant| {
+                    let mut name = variant.ident.unraw().to_string();
+                    for attr in nbt_attrs(&variant.attrs) {
+                        attr.parse_nested_meta(|meta| {
+                            if meta.path.is_ident("rename") {
+                                name = meta.value()?.parse::<LitStr>()?.value();
+                                Ok(())
+                            } else {
+                                Err(meta.error("expected `rename`"))
+                            }
+                        })?;
+                    }
+
+                    if !names.insert(name.clone()) {
+                        return Err(syn::Error::new(
+                            variant.ident.span(),
+                            format!("duplicate variant name \"{name}\""),
+                        ));
+                    }
+
+                    Ok(Variant {
+                        ident:  variant.ident.clone(),
+                        name,
+                        fields: parse_fields(&variant.fields)?,
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+
+            if let Some(tag) = tag {
+                if let Some(field) = variants
+                    .iter()
+                    .flat_map(|variant| &variant.fields)
+                    .find(|field| field.key == tag)
+                {
+                    return Err(syn::Error::new(
+                        field.ident.span(),
+                        format!("the key \"{tag}\" is already used for the enum's tag"),
+                    ));
+                }
+                Ok(Container::TaggedEnum { tag, variants })
+
+            } else if let Some(variant) = variants.iter().find(|variant| !variant.fields.is_empty()) {
+                Err(syn::Error::new(
+                    variant.ident.span(),
+                    "enums with fields must be given a `#[nbt(tag = \"key\")]` attribute",
+                ))
+            } else {
+                Ok(Container::UnitEnum(variants))
+            }
+        }
+        Data::Union(_) => Err(syn::Error::new(input.ident.span(), "unions are not supported")),
+    }
+}
+
+fn parse_fields(fields: &Fields) -> syn::Result<Vec<Field>> {
+    let fields = match fields {
+        Fields::Named(fields) => &fields.named,
+        Fields::Unit          => return Ok(Vec::new()),
+        Fields::Unnamed(_)    => {
+            return Err(syn::Error::new(fields.span(), "only named fields are supported"));
+        }
+    };
+
+    let fields = fields
+        .iter()
+        .map(parse_field)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let mut keys = HashSet::new();
+    let mut flatten = None;
+    for field in &fields {
+        if field.flatten {
+            if flatten.replace(&field.ident).is_some() {
+                return Err(syn::Error::new(
+                    field.ident.span(),
+                    "at most one field may be flattened",
+                ));
+            }
+        } else if !field.skip && !keys.insert(&field.key) {
+            return Err(syn::Error::new(
+                field.ident.span(),
+                format!("duplicate key \"{}\"", field.key),
+            ));
+        }
+    }
+
+    Ok(fields)
+}
+
+fn parse_field(field: &syn::Field) -> syn::Result<Field> {
+    #[expect(clippy::unwrap_used, reason = "only named fields are parsed")]
+    let ident = field.ident.clone().unwrap();
+
+    let mut key = ident.unraw().to_string();
+    let mut default = None;
+    let mut skip = false;
+    let mut flatten = false;
+    let mut encoding = Encoding::Standard;
+
+    for attr in nbt_attrs(&field.attrs) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                key = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("default") {
+                default = Some(if meta.input.peek(syn::Token![=]) {
+                    FieldDefault::Function(meta.value()?.parse::<LitStr>()?.parse()?)
+                } else {
+                    FieldDefault::Trait
+                });
+            } else if meta.path.is_ident("skip") {
+                skip = true;
+            } else if meta.path.is_ident("flatten") {
+                flatten = true;
+            } else if meta.path.is_ident("encoding") {
+                let lit = meta.value()?.parse::<LitStr>()?;
+                encoding = match lit.value().as_str() {
+                    "byte"        => Encoding::Int("Byte"),
+                    "short"       => Encoding::Int("Short"),
+                    "int"         => Encoding::Int("Int"),
+                    "long"        => Encoding::Int("Long"),
+                    "byte_array"  => Encoding::Array("ByteArray"),
+                    "int_array"   => Encoding::Array("IntArray"),
+                    "long_array"  => Encoding::Array("LongArray"),
+                    "byte_string" => Encoding::ByteString,
+                    _ => return Err(syn::Error::new(
+                        lit.span(),
+                        "expected one of `byte`, `short`, `int`, `long`, `byte_array`, \
+                         `int_array`, `long_array`, or `byte_string`",
+                    )),
+                };
+            } else {
+                return Err(meta.error(
+                    "expected `rename`, `default`, `skip`, `flatten`, or `encoding`",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+
+    if flatten && (default.is_some() || skip || !matches!(encoding, Encoding::Standard)) {
+        return Err(syn::Error::new(
+            ident.span(),
+            "a flattened field cannot have other `nbt` attributes",
+        ));
+    }
+
+    let (ty, optional) = match option_inner(&field.ty) {
+        Some(inner) if !skip && !flatten => (inner.clone(), true),
+        _                                => (field.ty.clone(), false),
+    };
+
+    Ok(Field {
+        ident,
+        key,
+        ty,
+        optional,
+        default,
+        skip,
+        flatten,
+        encoding,
+    })
+}
+
+fn nbt_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|attr| attr.path().is_ident("nbt"))
+}
+
+/// Returns `T` if the type is `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if path.qself.is_some() || segment.ident != "Option" {
+        return None;
+    }
+
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+
+// ================================
+//  Code generation
+// ================================
+
+pub(crate) fn generate_to_nbt(input: &DeriveInput, container: &Container) -> TokenStream {
+    let name = &input.ident;
+    let generics = bound_generics(&input.generics, &Shorthand::ToNbtTag);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let to_nbt      = Shorthand::ToNbt;
+    let to_nbt_tag  = Shorthand::ToNbtTag;
+    let nbt_tag     = Shorthand::NbtTag;
+    let compound    = Shorthand::Compound;
+    let string      = Shorthand::String;
+
+    let compound_impl = |body: TokenStream| quote! {
+        impl #impl_generics #to_nbt for #name #ty_generics #where_clause {
+            fn to_nbt(&self) -> #compound {
+                #body
+            }
+        }
+
+        impl #impl_generics #to_nbt_tag for #name #ty_generics #where_clause {
+            fn to_nbt_tag(&self) -> #nbt_tag {
+                #nbt_tag::Compound(#to_nbt::to_nbt(self))
+            }
+        }
+    };
+
+    match container {
+        Container::Struct(fields) => {
+            let idents = fields.iter().map(|field| &field.ident);
+            let write = write_fields(fields, None);
+            compound_impl(quote! {
+                let Self { #(#idents),* } = self;
+                #write
+            })
+        }
+        Container::TaggedEnum { tag, variants } => {
+            let arms = variants.iter().map(|variant| {
+                let ident = &variant.ident;
+                let idents = variant.fields.iter().map(|field| &field.ident);
+                let write = write_fields(&variant.fields, Some((tag, &variant.name)));
+                quote! {
+                    Self::#ident { #(#idents),* } => { #write }
+                }
+            });
+            compound_impl(quote! {
+                match self {
+                    #(#arms)*
+                }
+            })
+        }
+        Container::UnitEnum(variants) => {
+            let idents = variants.iter().map(|variant| &variant.ident);
+            let names = variants.iter().map(|variant| &variant.name);
+            quote! {
+                impl #impl_generics #to_nbt_tag for #name #ty_generics #where_clause {
+                    fn to_nbt_tag(&self) -> #nbt_tag {
+                        #nbt_tag::String(#string::from(match self {
+                            #( Self::#idents => #names, )*
+                        }))
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn generate_from_nbt(input: &DeriveInput, container: &Container) -> TokenStream {
+    let name = &input.ident;
+    let generics = bound_generics(&input.generics, &Shorthand::FromNbtTag);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let from_nbt     = Shorthand::FromNbt;
+    let from_nbt_tag = Shorthand::FromNbtTag;
+    let nbt_tag      = Shorthand::NbtTag;
+    let compound     = Shorthand::Compound;
+    let error        = Shorthand::ConvertError;
+    let error_kind   = Shorthand::ConvertErrorKind;
+    let result       = Shorthand::Result;
+    let ok           = Shorthand::Ok;
+    let err          = Shorthand::Err;
+    let take         = Shorthand::Take;
+
+    let compound_var = Ident::new("compound", Span::mixed_site());
+    let tag_var = Ident::new("tag", Span::mixed_site());
+
+    let compound_impl = |body: TokenStream| quote! {
+        impl #impl_generics #from_nbt for #name #ty_generics #where_clause {
+            fn from_nbt(#compound_var: #compound) -> #result<Self, #error> {
+                #[allow(unused_mut, unused_variables)]
+                let mut #compound_var = #compound_var;
+                #body
+            }
+        }
+
+        impl #impl_generics #from_nbt_tag for #name #ty_generics #where_clause {
+            fn from_nbt_tag(#tag_var: #nbt_tag) -> #result<Self, #error> {
+                match #tag_var {
+                    #nbt_tag::Compound(#compound_var) => #from_nbt::from_nbt(#compound_var),
+                    _ => #err(#error::type_mismatch("Compound", &#tag_var)),
+                }
+            }
+        }
+    };
+
+    match container {
+        Container::Struct(fields) => {
+            let read = read_fields(fields, &quote! {Self});
+            compound_impl(read)
+        }
+        Container::TaggedEnum { tag, variants } => {
+            let variant_var = Ident::new("variant", Span::mixed_site());
+            let arms = variants.iter().map(|variant| {
+                let ident = &variant.ident;
+                let name = &variant.name;
+                let read = read_fields(&variant.fields, &quote! {Self::#ident});
+                quote! { #name => { #read } }
+            });
+
+            compound_impl(quote! {
+                let #variant_var = match #take(&mut #compound_var, #tag) {
+                    ::std::option::Option::Some(#nbt_tag::String(#variant_var)) => #variant_var,
+                    ::std::option::Option::Some(#tag_var) => {
+                        return #err(#error::type_mismatch("String", &#tag_var).at_key(#tag));
+                    }
+                    ::std::option::Option::None => return #err(#error::missing_key(#tag)),
+                };
+
+                match #variant_var.as_str() {
+                    #(#arms)*
+                    _ => #err(
+                        #error::new(#error_kind::UnknownVariant { variant: #variant_var })
+                            .at_key(#tag)
+                    ),
+                }
+            })
+        }
+        Container::UnitEnum(variants) => {
+            let string_var = Ident::new("string", Span::mixed_site());
+            let idents = variants.iter().map(|variant| &variant.ident);
+            let names = variants.iter().map(|variant| &variant.name);
+            quote! {
+                impl #impl_generics #from_nbt_tag for #name #ty_generics #where_clause {
+                    fn from_nbt_tag(#tag_var: #nbt_tag) -> #result<Self, #error> {
+                        let #string_var = match #tag_var {
+                            #nbt_tag::String(#string_var) => #string_var,
+                            _ => return #err(#error::type_mismatch("String", &#tag_var)),
+                        };
+
+                        match #string_var.as_str() {
+                            #( #names => #ok(Self::#idents), )*
+                            _ => #err(#error::new(
+                                #error_kind::UnknownVariant { variant: #string_var },
+                            )),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Requires every type parameter to implement the given trait.
+fn bound_generics(generics: &Generics, bound: &Shorthand) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote! { #bound });
+    }
+    generics
+}
+
+/// Produces a block which evaluates to an `NbtCompound` containing the given fields, which must
+/// be bound to references with the fields' names. If `tag` is given, the first entry of the
+/// compound is the tag key and variant name.
+fn write_fields(fields: &[Field], tag: Option<(&String, &String)>) -> TokenStream {
+    let compound = Shorthand::Compound;
+    let nbt_tag = Shorthand::NbtTag;
+    let string = Shorthand::String;
+    let compound_var = Ident::new("compound", Span::mixed_site());
+
+    let capacity = fields.iter().filter(|field| !field.skip && !field.flatten).count()
+        + usize::from(tag.is_some());
+
+    let tag = tag.map(|(key, name)| quote! {
+        #compound_var.insert(#key, #nbt_tag::String(#string::from(#name)));
+    });
+
+    // The flattened field is written last, so that unknown keys cannot replace known fields.
+    let flatten = fields.iter().find(|field| field.flatten).map(|field| {
+        let ident = &field.ident;
+        let key_var = Ident::new("key", Span::mixed_site());
+        let value_var = Ident::new("value", Span::mixed_site());
+        quote! {
+            for (#key_var, #value_var) in #ident {
+                if !#compound_var.contains_key(#key_var) {
+                    #compound_var.insert(#string::clone(#key_var), #nbt_tag::clone(#value_var));
+                }
+            }
+        }
+    });
+
+    let writes = fields
+        .iter()
+        .filter(|field| !field.skip && !field.flatten)
+        .map(|field| {
+            let ident = &field.ident;
+            let key = &field.key;
+            if field.optional {
+                let value_var = Ident::new("value", Span::mixed_site());
+                let encode = encode(field.encoding, &value_var.to_token_stream());
+                quote! {
+                    if let ::std::option::Option::Some(#value_var) = #ident {
+                        #compound_var.insert(#key, #encode);
+                    }
+                }
+            } else {
+                let encode = encode(field.encoding, &ident.to_token_stream());
+                quote! { #compound_var.insert(#key, #encode); }
+            }
+        });
+
+    // Silence warnings about skipped fields, which are bound but unused.
+    let skipped = fields
+        .iter()
+        .filter(|field| field.skip)
+        .map(|field| &field.ident);
+
+    quote! {
+        #( let _ = #skipped; )*
+        let mut #compound_var = #compound::with_capacity(#capacity);
+        #tag
+        #( #writes )*
+        #flatten
+        #compound_var
+    }
+}
+
+/// Produces an expression of type `NbtTag` from a reference to a field's value.
+fn encode(encoding: Encoding, value: &TokenStream) -> TokenStream {
+    let to_nbt_tag = Shorthand::ToNbtTag;
+    let convert = Shorthand::Convert;
+
+    match encoding {
+        Encoding::Standard => quote! { #to_nbt_tag::to_nbt_tag(#value) },
+        Encoding::Int(variant) => {
+            let variant = Ident::new(variant, Span::call_site());
+            quote! { #convert::encode_int(*#value, #convert::IntEncoding::#variant) }
+        }
+        Encoding::Array(variant) => {
+            let variant = Ident::new(variant, Span::call_site());
+            quote! { #convert::encode_array(#value, #convert::ArrayEncoding::#variant) }
+        }
+        Encoding::ByteString => quote! { #convert::encode_byte_string(#value) },
+    }
+}
+
+/// Produces a block which removes the fields from the `compound` variable and evaluates to a
+/// `Result` of the given struct or variant.
+fn read_fields(fields: &[Field], constructor: &TokenStream) -> TokenStream {
+    let error = Shorthand::ConvertError;
+    let take = Shorthand::Take;
+    let ok = Shorthand::Ok;
+    let err = Shorthand::Err;
+    let compound_var = Ident::new("compound", Span::mixed_site());
+    let tag_var = Ident::new("tag", Span::mixed_site());
+
+    let locals: Vec<Ident> = fields
+        .iter()
+        .map(|field| format_ident!("field_{}", field.ident, span = Span::mixed_site()))
+        .collect();
+
+    let reads = fields
+        .iter()
+        .zip(&locals)
+        .filter(|(field, _)| !field.flatten)
+        .map(|(field, local)| {
+            let key = &field.key;
+
+            let missing = match &field.default {
+                _ if field.optional                     => quote! { ::std::option::Option::None },
+                Some(FieldDefault::Trait)               => quote! { ::std::default::Default::default() },
+                Some(FieldDefault::Function(function))  => quote! { #function() },
+                None => quote! { return #err(#error::missing_key(#key)) },
+            };
+
+            if field.skip {
+                let default = if let Some(FieldDefault::Function(function)) = &field.default {
+                    quote! { #function() }
+                } else {
+                    quote! { ::std::default::Default::default() }
+                };
+                return quote! { let #local = #default; };
+            }
+
+            let decode = decode(field.encoding, &field.ty, &tag_var);
+            let error_var = Ident::new("error", Span::mixed_site());
+            let mut present = quote! { #decode.map_err(|#error_var| #error_var.at_key(#key))? };
+            if field.optional {
+                present = quote! { ::std::option::Option::Some(#present) };
+            }
+
+            quote! {
+                let #local = match #take(&mut #compound_var, #key) {
+                    ::std::option::Option::Some(#tag_var) => #present,
+                    ::std::option::Option::None => #missing,
+                };
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // The flattened field receives whatever is left after every other field is removed.
+    let flatten = fields
+        .iter()
+        .zip(&locals)
+        .find(|(field, _)| field.flatten)
+        .map(|(_, local)| quote! { let #local = #compound_var; });
+
+    let idents = fields.iter().map(|field| &field.ident);
+
+    quote! {
+        #( #reads )*
+        #flatten
+        #ok(#constructor {
+            #( #idents: #locals, )*
+        })
+    }
+}
+
+/// Produces an expression of type `Result<T, NbtConvertError>` from an owned `NbtTag`.
+/// The array and byte string encodings produce a `Vec`, so they require `Vec` fields.
+fn decode(encoding: Encoding, ty: &Type, tag: &Ident) -> TokenStream {
+    let from_nbt_tag = Shorthand::FromNbtTag;
+    let convert = Shorthand::Convert;
+
+    match encoding {
+        Encoding::Standard => quote! { <#ty as #from_nbt_tag>::from_nbt_tag(#tag) },
+        Encoding::Int(variant) => {
+            let variant = Ident::new(variant, Span::call_site());
+            quote! { #convert::decode_int::<#ty>(&#tag, #convert::IntEncoding::#variant) }
+        }
+        Encoding::Array(variant) => {
+            let variant = Ident::new(variant, Span::call_site());
+            quote! { #convert::decode_array(&#tag, #convert::ArrayEncoding::#variant) }
+        }
+        Encoding::ByteString => quote! { #convert::decode_byte_string(#tag) },
+    }
+}
+
+enum Shorthand {
+    Result,
+    Ok,
+    Err,
+    String,
+    NbtTag,
+    Compound,
+    Convert,
+    ToNbt,
+    FromNbt,
+    ToNbtTag,
+    FromNbtTag,
+    ConvertError,
+    ConvertErrorKind,
+    Take,
+}
+
+impl ToTokens for Shorthand {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match *self {
+            Self::Result           => quote! {::std::result::Result},
+            Self::Ok               => quote! {::std::result::Result::Ok},
+            Self::Err              => quote! {::std::result::Result::Err},
+            Self::String           => quote! {::std::string::String},
+            // As in the `CustomTranslator` derive, these are left without the leading `::`.
+            Self::NbtTag           => quote! {prismarine_anchor_nbt::NbtTag},
+            Self::Compound         => quote! {prismarine_anchor_nbt::NbtCompound},
+            Self::Convert          => quote! {prismarine_anchor_nbt::convert},
+            Self::ToNbt            => quote! {prismarine_anchor_nbt::convert::ToNbt},
+            Self::FromNbt          => quote! {prismarine_anchor_nbt::convert::FromNbt},
+            Self::ToNbtTag         => quote! {prismarine_anchor_nbt::convert::ToNbtTag},
+            Self::FromNbtTag       => quote! {prismarine_anchor_nbt::convert::FromNbtTag},
+            Self::ConvertError     => quote! {prismarine_anchor_nbt::convert::NbtConvertError},
+            Self::ConvertErrorKind => quote! {prismarine_anchor_nbt::convert::NbtConvertErrorKind},
+            Self::Take             => quote! {prismarine_anchor_nbt::convert::take},
+        });
+    }
+}
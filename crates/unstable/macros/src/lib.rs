@@ -1,13 +1,165 @@
+mod convert;
 mod generate;
+mod snbt;
 
 use proc_macro::TokenStream;
+use quote::ToTokens as _;
 use syn::{parse_macro_input, Token};
 use syn::{Attribute, DeriveInput, punctuated::Punctuated, spanned::Spanned as _, Type};
 
 use self::generate::generate_impl;
 
 
-// TODO: compound! macro, and more convenient deserialization with error handling
+// TODO: more convenient deserialization with error handling
+
+/// Builds an `NbtCompound` from SNBT-like syntax, which is checked at compile time.
+///
+/// The input is the body of an SNBT compound: comma-separated `key: value` pairs, where
+/// each key is a string literal or an identifier. Values may be:
+/// - numbers, which follow the rules of the `UpdatedJava` SNBT version; for instance, `1b`,
+///   `-2.5f`, and `0x1Fs`. Note that Rust does not permit `0b` (as it starts a binary literal),
+///   so use `0B` or `false` instead.
+/// - `true` and `false`, which become bytes.
+/// - string literals, with Rust's escape sequences.
+/// - compounds in braces, lists in brackets, and `B`, `I`, and `L` arrays such as `[I; 1, 2]`.
+/// - any other Rust expression, up to the next comma, which is converted into an `NbtTag`
+///   with `Into`. Expressions containing a comma that is not inside brackets, braces,
+///   or parentheses need to be wrapped in parentheses. Expressions in arrays are instead
+///   converted into `i8`, `i32`, or `i64`.
+///
+/// Lists are checked to be homogenous at compile time, except for interpolated expressions,
+/// whose types are not known. Generated code refers to the `prismarine_anchor_nbt` crate,
+/// which must be a dependency of the crate invoking the macro.
+///
+/// # Examples:
+/// ```
+/// # use prismarine_anchor_macros::compound;
+/// # use prismarine_anchor_nbt::NbtTag;
+/// let name = "Steve";
+/// let (x, y, z) = (1.5, 64.0, -3.25);
+///
+/// let tag = compound! {
+///     "Count": 1b,
+///     "Name":  name,
+///     "Pos":   [x, y, z],
+///     Data:    { flags: [B; 1, 0, true], uuid: [I; 1, 2, 3, 4] },
+/// };
+/// assert_eq!(tag.get_tag("Count"), Some(&NbtTag::Byte(1)));
+/// assert_eq!(tag.get_tag("Name"), Some(&NbtTag::String("Steve".to_owned())));
+/// assert_eq!(tag["Pos"].to_snbt(), "[1.5D,64D,-3.25D]");
+/// ```
+#[proc_macro]
+pub fn compound(tokens: TokenStream) -> TokenStream {
+    parse_macro_input!(tokens as snbt::Compound)
+        .into_token_stream()
+        .into()
+}
+
+/// Builds an `NbtTag` from a single SNBT-like value, which is checked at compile time.
+/// See [`compound!`] for the accepted syntax.
+///
+/// # Examples:
+/// ```
+/// # use prismarine_anchor_macros::nbt;
+/// # use prismarine_anchor_nbt::NbtTag;
+/// assert_eq!(nbt!(3s), NbtTag::Short(3));
+/// assert_eq!(nbt!([L; -1, 2]), NbtTag::LongArray(vec![-1, 2]));
+/// assert_eq!(nbt!({a: "b"}).to_snbt(), "{a:b}");
+/// ```
+#[proc_macro]
+pub fn nbt(tokens: TokenStream) -> TokenStream {
+    parse_macro_input!(tokens as snbt::Value)
+        .into_token_stream()
+        .into()
+}
+
+/// Implements `ToNbt` for a struct, converting it into an `NbtCompound`.
+///
+/// Structs must have named fields, each of which is stored under its name. `ToNbtTag` is
+/// implemented as well, so that the struct can be a field of other derived types. Enums are
+/// supported in two forms:
+/// - enums with only unit variants are stored as a `String` tag holding the variant's name.
+///   Only `ToNbtTag` is implemented for them.
+/// - enums with `#[nbt(tag = "key")]` are stored as compounds, with the variant's name in a
+///   `String` tag under `key` and the variant's named fields alongside it.
+///
+/// Fields accept `#[nbt(...)]` attributes:
+/// - `rename = "key"` stores the field under a different key. This is also accepted on variants.
+/// - `default` or `default = "path::to::function"` uses `Default::default()` or the given function
+///   when the key is missing, instead of failing.
+/// - `skip` neither writes nor reads the field, which is always set to its default.
+/// - `flatten`, on an `NbtCompound` field, collects every key not used by another field when
+///   reading, and writes those keys back after the other fields. This preserves unknown data.
+///   Keys of other fields take precedence over keys in the flattened compound.
+/// - `encoding = "..."` picks the tag variant of an integer or `bool` field (`byte`, `short`,
+///   `int`, or `long`), of a `Vec` of integers (`byte_array`, `int_array`, or `long_array`), or
+///   of a `Vec<u8>` (`byte_string`, which writes a `String` tag if the bytes are valid UTF-8).
+///
+/// Fields of type `Option<T>` are optional: `None` is not written, and a missing key is read as
+/// `None`. Without an `encoding`, fields are converted with `ToNbtTag`, under which integers use
+/// the tag of the same width (with unsigned integers reinterpreted as signed), `bool` is a byte,
+/// and `Vec<T>` is a list.
+///
+/// Generated code refers to the `prismarine_anchor_nbt` crate, which must be a dependency of
+/// the crate using the derive.
+///
+/// # Examples:
+/// ```
+/// # use prismarine_anchor_macros::{FromNbt, ToNbt};
+/// # use prismarine_anchor_nbt::{NbtCompound, NbtTag};
+/// # use prismarine_anchor_nbt::convert::{FromNbt as _, ToNbt as _};
+/// #[derive(FromNbt, ToNbt, Debug, PartialEq)]
+/// struct Item {
+///     #[nbt(rename = "Name")]
+///     name:   String,
+///     #[nbt(rename = "Count", encoding = "byte")]
+///     count:  u32,
+///     #[nbt(rename = "Damage", default)]
+///     damage: i16,
+///     #[nbt(flatten)]
+///     extra:  NbtCompound,
+/// }
+///
+/// let mut compound = NbtCompound::new();
+/// compound.insert("Name", "minecraft:stick");
+/// compound.insert("Count", NbtTag::Byte(64));
+/// compound.insert("CustomTag", 1_i32);
+///
+/// let item = Item::from_nbt(compound).unwrap();
+/// assert_eq!(item.count, 64);
+/// assert_eq!(item.damage, 0);
+/// assert_eq!(item.extra.get_tag("CustomTag"), Some(&NbtTag::Int(1)));
+///
+/// let compound = item.to_nbt();
+/// assert_eq!(compound.get_tag("Damage"), Some(&NbtTag::Short(0)));
+/// assert_eq!(compound.get_tag("CustomTag"), Some(&NbtTag::Int(1)));
+/// ```
+#[proc_macro_derive(ToNbt, attributes(nbt))]
+pub fn to_nbt(tokens: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(tokens as DeriveInput);
+
+    match convert::parse_container(&input) {
+        Ok(container) => convert::generate_to_nbt(&input, &container),
+        Err(err) => err.into_compile_error(),
+    }
+    .into()
+}
+
+/// Implements `FromNbt` for a struct, converting it from an `NbtCompound`. See [`ToNbt`]
+/// for the supported types and attributes.
+///
+/// Errors include the path to the tag which could not be converted. Unless a field is
+/// flattened, keys not used by any field are ignored.
+#[proc_macro_derive(FromNbt, attributes(nbt))]
+pub fn from_nbt(tokens: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(tokens as DeriveInput);
+
+    match convert::parse_container(&input) {
+        Ok(container) => convert::generate_from_nbt(&input, &container),
+        Err(err) => err.into_compile_error(),
+    }
+    .into()
+}
 
 #[proc_macro_derive(CustomTranslator, attributes(translator_types))]
 pub fn custom_translator(tokens: TokenStream) -> TokenStream {
@@ -0,0 +1,351 @@
+use std::collections::HashSet;
+
+use proc_macro2::{Delimiter, Literal, Span, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{braced, bracketed, token, Ident, Lit, LitStr, Token};
+use syn::{ext::IdentExt as _, parse::{Parse, ParseStream}};
+
+use prismarine_anchor_nbt::{snbt, NbtTag, NbtType, SnbtParseOptions};
+
+
+/// The body of a compound, without the surrounding braces: comma-separated `key: value` pairs,
+/// where each key is a string literal or identifier.
+pub(crate) struct Compound {
+    entries: Vec<(String, Value)>,
+}
+
+/// A single SNBT-like value.
+pub(crate) enum Value {
+    /// A number, string, or boolean, already validated and converted into tokens which
+    /// construct a tag of the given type.
+    Literal(TokenStream, NbtType),
+    Compound(Compound),
+    List(Vec<Self>),
+    Array(ArrayType, Vec<TokenStream>),
+    /// A Rust expression, which is converted into an `NbtTag` with `Into`.
+    Expr(TokenStream),
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum ArrayType {
+    Byte,
+    Int,
+    Long,
+}
+
+impl Value {
+    /// Returns the type of tag this value constructs, if it is known at compile time.
+    fn nbt_type(&self) -> Option<NbtType> {
+        match self {
+            Self::Literal(_, nbt_type)      => Some(*nbt_type),
+            Self::Compound(_)               => Some(NbtType::Compound),
+            Self::List(_)                   => Some(NbtType::List),
+            Self::Array(ArrayType::Byte, _) => Some(NbtType::ByteArray),
+            Self::Array(ArrayType::Int, _)  => Some(NbtType::IntArray),
+            Self::Array(ArrayType::Long, _) => Some(NbtType::LongArray),
+            Self::Expr(_)                   => None,
+        }
+    }
+}
+
+
+// ================================
+//  Parsing
+// ================================
+
+impl Parse for Compound {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut entries = Vec::new();
+        let mut keys = HashSet::new();
+
+        while !input.is_empty() {
+            let key_span = input.span();
+            let key = if input.peek(LitStr) {
+                input.parse::<LitStr>()?.value()
+            } else if input.peek(Ident::peek_any) {
+                Ident::parse_any(input)?.unraw().to_string()
+            } else {
+                return Err(input.error("expected a string literal or identifier as a key"));
+            };
+
+            if !keys.insert(key.clone()) {
+                return Err(syn::Error::new(key_span, format!("duplicate key \"{key}\"")));
+            }
+
+            input.parse::<Token![:]>()?;
+            entries.push((key, input.parse()?));
+
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+impl Parse for Value {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        if input.peek(token::Brace) {
+            let content;
+            braced!(content in input);
+            Ok(Self::Compound(content.parse()?))
+
+        } else if input.peek(token::Bracket) {
+            let content;
+            bracketed!(content in input);
+            parse_sequence(&content)
+
+        } else if input.peek(LitStr) {
+            // The SNBT parser is not used for strings, so that Rust's escapes apply.
+            let string = input.parse::<LitStr>()?.value();
+            Ok(Self::Literal(
+                quote! {prismarine_anchor_nbt::NbtTag::String(::std::string::String::from(#string))},
+                NbtType::String,
+            ))
+
+        } else if peek_literal(input) {
+            let (text, span) = parse_literal_text(input)?;
+            parse_number(&text, span)
+
+        } else {
+            parse_expr(input).map(Self::Expr)
+        }
+    }
+}
+
+/// Parses the contents of a list or array.
+fn parse_sequence(input: ParseStream<'_>) -> syn::Result<Value> {
+    let array_type = if input.peek(Ident) && input.peek2(Token![;]) {
+        let ident: Ident = input.parse()?;
+        input.parse::<Token![;]>()?;
+
+        Some(match ident.to_string().as_str() {
+            "B" => ArrayType::Byte,
+            "I" => ArrayType::Int,
+            "L" => ArrayType::Long,
+            _ => return Err(syn::Error::new(ident.span(), "expected `B`, `I`, or `L`")),
+        })
+    } else {
+        None
+    };
+
+    if let Some(array_type) = array_type {
+        let mut elements = Vec::new();
+        while !input.is_empty() {
+            elements.push(parse_array_element(input, array_type)?);
+
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        return Ok(Value::Array(array_type, elements));
+    }
+
+    let mut values = Vec::new();
+    let mut list_type = None;
+
+    while !input.is_empty() {
+        let span = input.span();
+        let value: Value = input.parse()?;
+
+        // Only literals and nested structures can be checked here; the types of
+        // interpolated expressions are unknown.
+        if let Some(nbt_type) = value.nbt_type() {
+            match list_type {
+                None => list_type = Some(nbt_type),
+                Some(list_type) if list_type != nbt_type => {
+                    return Err(syn::Error::new(
+                        span,
+                        format!(
+                            "NBT lists must be homogenous, but found a {nbt_type:?} tag \
+                             in a list of {list_type:?} tags",
+                        ),
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        values.push(value);
+
+        if input.is_empty() {
+            break;
+        }
+        input.parse::<Token![,]>()?;
+    }
+
+    Ok(Value::List(values))
+}
+
+/// Parses an element of a `B`, `I`, or `L` array, returning tokens for the element's value.
+fn parse_array_element(input: ParseStream<'_>, array_type: ArrayType) -> syn::Result<TokenStream> {
+    if !peek_literal(input) {
+        let expr = parse_expr(input)?;
+        let element_type = match array_type {
+            ArrayType::Byte => quote! {::std::primitive::i8},
+            ArrayType::Int  => quote! {::std::primitive::i32},
+            ArrayType::Long => quote! {::std::primitive::i64},
+        };
+        return Ok(quote! {::std::convert::Into::<#element_type>::into(#expr)});
+    }
+
+    let (text, span) = parse_literal_text(input)?;
+    let prefix = match array_type {
+        ArrayType::Byte => "B",
+        ArrayType::Int  => "I",
+        ArrayType::Long => "L",
+    };
+
+    // Parse a single-element array, so that the element is checked exactly as SNBT would.
+    let parsed = snbt::parse_any(&format!("[{prefix};{text}]"), parse_options())
+        .map_err(|err| syn::Error::new(span, format!("invalid array element `{text}`: {err}")))?;
+
+    let literal = match parsed {
+        NbtTag::ByteArray(array) => array.first().copied().map(Literal::i8_suffixed),
+        NbtTag::IntArray(array)  => array.first().copied().map(Literal::i32_suffixed),
+        NbtTag::LongArray(array) => array.first().copied().map(Literal::i64_suffixed),
+        _ => None,
+    };
+
+    literal
+        .map(Literal::into_token_stream)
+        .ok_or_else(|| syn::Error::new(span, format!("invalid array element `{text}`")))
+}
+
+fn peek_literal(input: ParseStream<'_>) -> bool {
+    input.peek(Lit) || (input.peek(Token![-]) && input.peek2(Lit))
+}
+
+/// Reads a literal with an optional leading minus sign, returning the literal's source text.
+fn parse_literal_text(input: ParseStream<'_>) -> syn::Result<(String, Span)> {
+    let minus: Option<Token![-]> = input.parse()?;
+    let lit: Lit = input.parse()?;
+    let sign = if minus.is_some() { "-" } else { "" };
+
+    Ok((format!("{sign}{}", lit.to_token_stream()), lit.span()))
+}
+
+/// Converts the source text of a number or boolean into a value, using the SNBT parser.
+fn parse_number(text: &str, span: Span) -> syn::Result<Value> {
+    let tag = snbt::parse_any(text, parse_options())
+        .map_err(|err| syn::Error::new(span, format!("invalid SNBT value `{text}`: {err}")))?;
+
+    let (variant, literal) = match tag {
+        NbtTag::Byte(value)   => (quote! {Byte},   Literal::i8_suffixed(value)),
+        NbtTag::Short(value)  => (quote! {Short},  Literal::i16_suffixed(value)),
+        NbtTag::Int(value)    => (quote! {Int},    Literal::i32_suffixed(value)),
+        NbtTag::Long(value)   => (quote! {Long},   Literal::i64_suffixed(value)),
+        // Non-finite values are rejected by the parser, so these won't panic.
+        NbtTag::Float(value)  => (quote! {Float},  Literal::f32_suffixed(value)),
+        NbtTag::Double(value) => (quote! {Double}, Literal::f64_suffixed(value)),
+        _ => return Err(syn::Error::new(span, format!("`{text}` is not a valid SNBT number"))),
+    };
+
+    Ok(Value::Literal(
+        quote! {prismarine_anchor_nbt::NbtTag::#variant(#literal)},
+        tag.tag_type(),
+    ))
+}
+
+/// Takes every token up to the next comma (or the end of the input) as a Rust expression.
+fn parse_expr(input: ParseStream<'_>) -> syn::Result<TokenStream> {
+    let span = input.span();
+    let tokens = input.step(|cursor| {
+        let mut rest = *cursor;
+        let mut tokens = TokenStream::new();
+
+        while let Some((token, next)) = rest.token_tree() {
+            if matches!(&token, TokenTree::Punct(punct) if punct.as_char() == ',') {
+                break;
+            }
+            tokens.extend([token]);
+            rest = next;
+        }
+
+        Ok((tokens, rest))
+    })?;
+
+    if tokens.is_empty() {
+        return Err(syn::Error::new(span, "expected an SNBT value or a Rust expression"));
+    }
+
+    // The expression is placed in parentheses of a function call, so strip any parentheses
+    // around it to avoid `unused_parens` warnings. Tuples keep their parentheses.
+    let mut trees = tokens.clone().into_iter();
+    if let (Some(TokenTree::Group(group)), None) = (trees.next(), trees.next()) {
+        let is_tuple = group.stream().into_iter().any(|token| {
+            matches!(&token, TokenTree::Punct(punct) if punct.as_char() == ',')
+        });
+
+        if group.delimiter() == Delimiter::Parenthesis && !is_tuple {
+            return Ok(group.stream());
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_options() -> SnbtParseOptions {
+    // Infinite literals like `1e999` become an error instead of `MAX` or `MIN`.
+    SnbtParseOptions {
+        replace_non_finite: false,
+        ..SnbtParseOptions::default_updated()
+    }
+}
+
+
+// ================================
+//  Code generation
+// ================================
+
+impl ToTokens for Compound {
+    /// Produces an expression of type `NbtCompound`.
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        // The mixed-site span keeps this variable from shadowing any in interpolated expressions
+        let compound = Ident::new("compound", Span::mixed_site());
+        let capacity = self.entries.len();
+        let keys = self.entries.iter().map(|(key, _)| key);
+        let values = self.entries.iter().map(|(_, value)| value);
+
+        tokens.extend(quote! {{
+            let mut #compound = prismarine_anchor_nbt::NbtCompound::with_capacity(#capacity);
+            #( #compound.insert(#keys, #values); )*
+            #compound
+        }});
+    }
+}
+
+impl ToTokens for Value {
+    /// Produces an expression of type `NbtTag`.
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match self {
+            Self::Literal(literal, _) => literal.clone(),
+            Self::Compound(compound) => quote! {
+                prismarine_anchor_nbt::NbtTag::Compound(#compound)
+            },
+            Self::List(values) if values.is_empty() => quote! {
+                prismarine_anchor_nbt::NbtTag::List(prismarine_anchor_nbt::NbtList::new())
+            },
+            Self::List(values) => quote! {
+                prismarine_anchor_nbt::NbtTag::List(
+                    prismarine_anchor_nbt::NbtList::from(::std::vec![#(#values),*])
+                )
+            },
+            Self::Array(array_type, elements) => {
+                let variant = match array_type {
+                    ArrayType::Byte => quote! {ByteArray},
+                    ArrayType::Int  => quote! {IntArray},
+                    ArrayType::Long => quote! {LongArray},
+                };
+                quote! {
+                    prismarine_anchor_nbt::NbtTag::#variant(::std::vec![#(#elements),*])
+                }
+            }
+            Self::Expr(expr) => quote! {
+                ::std::convert::Into::<prismarine_anchor_nbt::NbtTag>::into(#expr)
+            },
+        });
+    }
+}
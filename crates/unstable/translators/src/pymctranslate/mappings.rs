@@ -304,7 +304,7 @@ fn parse_new_nbt(
 
         let key = json.key.into();
         let value = VerifiedSnbt::new(json.value, opts.snbt_options)
-            .map_err(MappingParseError::InvalidSnbt)?;
+            .map_err(|err| MappingParseError::InvalidSnbt(Box::new(err)))?;
 
         new_nbt_options.push(NewNbtOptions {
             outer_name,
@@ -382,7 +382,7 @@ fn parse_map_nbt(
             .into_iter()
             .map(|(key, function_vec)| {
                 let key = snbt::parse_any(&key, opts.snbt_options)
-                    .map_err(MappingParseError::InvalidSnbtKey)?;
+                    .map_err(|err| MappingParseError::InvalidSnbtKey(Box::new(err)))?;
                 let key = ComparableNbtTag::new(key);
 
                 Ok((Some(key), FunctionJson::parse_multiple(function_vec, opts)?))
@@ -495,7 +495,7 @@ fn parse_carry_properties(
                 .into_iter()
                 .map(|snbt| {
                     let tag = snbt::parse_any(&snbt, opts.snbt_options)
-                        .map_err(MappingParseError::InvalidSnbt)?;
+                        .map_err(|err| MappingParseError::InvalidSnbt(Box::new(err)))?;
 
                     Ok(ComparableNbtTag::new(tag))
                 })
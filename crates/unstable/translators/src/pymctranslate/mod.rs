@@ -82,12 +82,12 @@ pub enum MappingParseError {
     #[error["the value for property '{property}' was invalid SNBT: {error}"]]
     InvalidPropertySnbt {
         property: PropertyName,
-        error:    SnbtError,
+        error:    Box<SnbtError>,
     },
     #[error("a key had invalid SNBT data: {0}")]
-    InvalidSnbtKey(SnbtError),
+    InvalidSnbtKey(Box<SnbtError>),
     #[error("invalid SNBT data: {0}")]
-    InvalidSnbt(SnbtError),
+    InvalidSnbt(Box<SnbtError>),
     #[error("a property must be a Byte, Short, Int, Long, or String tag, but was {0}")]
     InvalidProperty(&'static str),
     #[error("a code function, '{0}', had unexpected inputs specified")]
@@ -130,7 +130,7 @@ pub fn block_property_from_str(
         .map_err(|error| {
             MappingParseError::InvalidPropertySnbt {
                 property: property_name.to_owned(),
-                error,
+                error:    Box::new(error),
             }
         })?;
 
@@ -131,7 +131,7 @@ fn parse_specification_file(
             let mut identifier = identifier.into_iter();
 
             let snbt = VerifiedSnbt::new(snbt, opts.snbt_options)
-                .map_err(MappingParseError::InvalidSnbt)?;
+                .map_err(|err| MappingParseError::InvalidSnbt(Box::new(err)))?;
 
             // We know we can call next() exactly twice before getting None
             #[expect(
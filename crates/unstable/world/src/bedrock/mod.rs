@@ -9,13 +9,14 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use rusty_leveldb::{DB as LevelDB, env::Env, Status};
+use rusty_leveldb::{DB as LevelDB, DBIterator, env::Env, LdbIterator as _, Status};
 use thiserror::Error;
 
 use prismarine_anchor_leveldb_entries::{
     DBEntry, DBKey, EntryBytes, EntryParseOptions, EntryToBytesOptions,
     KeyToBytesOptions, ValueToBytesError,
 };
+use prismarine_anchor_leveldb_entries::entries::helpers::{ChunkKeyRanges, DimensionedChunkPos};
 use prismarine_anchor_nbt::io as nbt_io;
 use prismarine_anchor_nbt::{NbtCompound, IoOptions};
 use prismarine_anchor_nbt::io::{NbtIoError, write_compound};
@@ -183,6 +184,38 @@ impl BedrockWorldFiles {
             .map(|value| DBEntry::parse_value_vec(key, value, parse_opts))
     }
 
+    /// Read every record of the chunk at the provided position, including every subchunk,
+    /// with the provided key serialization options.
+    ///
+    /// The chunk's records are read from a single LevelDB iterator, which seeks to the
+    /// first key of the chunk once, and are then parsed into `DBEntry` values lazily.
+    /// The chunk's `ActorDigest` record, whose key is elsewhere in the database,
+    /// is read immediately and returned last.
+    pub fn chunk_records(
+        &mut self,
+        chunk_pos:  DimensionedChunkPos,
+        opts:       KeyToBytesOptions,
+        parse_opts: EntryParseOptions,
+    ) -> Result<ChunkRecords, BedrockWorldFileError> {
+        let ranges = ChunkKeyRanges::new(chunk_pos, opts);
+        let actor_digest = self.db.get(&ranges.actor_digest);
+
+        let mut iter = self.db.new_iter().map_err(|err| {
+            BedrockWorldFileError::StatusCode(
+                Cow::Borrowed("iterating over the records of a chunk"),
+                err,
+            )
+        })?;
+        iter.seek(ranges.records.prefix());
+
+        Ok(ChunkRecords {
+            iter,
+            ranges,
+            actor_digest,
+            parse_opts,
+        })
+    }
+
     /// Write the provided entry into the LevelDB using the provided serialization options.
     pub fn put(
         &mut self,
@@ -223,6 +256,56 @@ impl Debug for BedrockWorldFiles {
     }
 }
 
+/// An iterator over every record of one chunk in a Bedrock world's LevelDB,
+/// returned by [`BedrockWorldFiles::chunk_records`].
+pub struct ChunkRecords {
+    iter:         DBIterator,
+    ranges:       ChunkKeyRanges,
+    actor_digest: Option<Vec<u8>>,
+    parse_opts:   EntryParseOptions,
+}
+
+impl ChunkRecords {
+    /// The keys which are covered by this iterator.
+    #[inline]
+    pub fn key_ranges(&self) -> &ChunkKeyRanges {
+        &self.ranges
+    }
+}
+
+impl Iterator for ChunkRecords {
+    type Item = DBEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut key = Vec::new();
+        let mut value = Vec::new();
+
+        // The iterator is positioned at the next key which has not been returned,
+        // or is invalid once every key has been passed.
+        while self.iter.current(&mut key, &mut value) && self.ranges.records.contains(&key) {
+            self.iter.advance();
+
+            // The range may include chunks at the same position in other dimensions.
+            if self.ranges.is_record_key(&key) {
+                return Some(DBEntry::parse_entry_vec(key, value, self.parse_opts));
+            }
+        }
+
+        self.actor_digest.take().map(|actor_digest| {
+            DBEntry::parse_entry_vec(self.ranges.actor_digest.clone(), actor_digest, self.parse_opts)
+        })
+    }
+}
+
+impl Debug for ChunkRecords {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChunkRecords")
+            .field("ranges",     &self.ranges)
+            .field("parse_opts", &self.parse_opts)
+            .finish_non_exhaustive()
+    }
+}
+
 /// Partially parsed `level.dat` file
 #[derive(Debug)]
 pub struct LevelDatFile {
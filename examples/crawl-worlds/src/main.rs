@@ -76,9 +76,7 @@ fn parse_world(world_num: usize, world_path: String) {
         value_fidelity: DataFidelity::BitPerfect,
         ..EntryToBytesOptions::for_version(version)
     };
-    let parse_opts = EntryParseOptions {
-        value_fidelity: DataFidelity::BitPerfect,
-    };
+    let parse_opts = EntryParseOptions::new(DataFidelity::BitPerfect);
 
     let buffers = Arc::new(Pool::new(
         ENTRY_PARSING_THREADS_PER_WORLD,